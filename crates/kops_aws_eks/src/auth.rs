@@ -0,0 +1,179 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Bearer token of EKS clusters, refreshed in the background.
+//!
+//! A presigned token is only accepted for a few minutes, while a kube
+//! client lives as long as the cluster is watched. The token is kept
+//! behind a layer that sets it on every request, and replaced before it
+//! expires. A token rejected by the API server drops the cached endpoint
+//! of the cluster, see `cache`.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, PoisonError, RwLock, Weak};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use anyhow::Result;
+use aws_config::SdkConfig;
+use http::HeaderValue;
+use http::StatusCode;
+use http::header::AUTHORIZATION;
+use tower::{Layer, Service};
+use tracing::warn;
+
+use crate::cache::{self, ClusterKey};
+use crate::create_cluster_token;
+
+/// How long a token is used before a new one is presigned; EKS accepts
+/// one for 15 minutes.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Delay before retrying a failed refresh.
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+type SharedToken = Arc<RwLock<HeaderValue>>;
+
+/// Sets the current token of a cluster as the bearer of every request.
+#[derive(Clone)]
+pub(crate) struct TokenLayer {
+    token: SharedToken,
+    key: Option<ClusterKey>,
+}
+
+impl TokenLayer {
+    /// Use `token` for cluster `cluster_name` and refresh it with the
+    /// credentials of `sdk_config`, until every client built with the
+    /// layer is dropped.
+    pub(crate) fn start(
+        sdk_config: &SdkConfig,
+        cluster_name: &str,
+        token: &str,
+        key: Option<ClusterKey>,
+    ) -> Result<Self> {
+        let token = Arc::new(RwLock::new(bearer(token)?));
+
+        tokio::spawn(refresh(
+            sdk_config.clone(),
+            cluster_name.to_string(),
+            Arc::downgrade(&token),
+        ));
+
+        Ok(Self { token, key })
+    }
+}
+
+impl<S> Layer<S> for TokenLayer {
+    type Service = TokenService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TokenService {
+            inner,
+            token: self.token.clone(),
+            key: self.key.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct TokenService<S> {
+    inner: S,
+    token: SharedToken,
+    key: Option<ClusterKey>,
+}
+
+impl<S, B, R> Service<http::Request<B>> for TokenService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<R>>,
+    S::Future: Send + 'static,
+    S::Error: 'static,
+    B: 'static,
+    R: 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        let token =
+            self.token.read().unwrap_or_else(PoisonError::into_inner).clone();
+        req.headers_mut().insert(AUTHORIZATION, token);
+
+        let response = self.inner.call(req);
+        let key = self.key.clone();
+        Box::pin(async move {
+            let response = response.await?;
+            if response.status() == StatusCode::UNAUTHORIZED
+                && let Some(key) = &key
+            {
+                cache::invalidate(key);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Replace `token` periodically while a client still holds it.
+async fn refresh(
+    sdk_config: SdkConfig,
+    cluster_name: String,
+    token: Weak<RwLock<HeaderValue>>,
+) {
+    let mut wait = REFRESH_INTERVAL;
+
+    loop {
+        tokio::time::sleep(wait).await;
+        if token.strong_count() == 0 {
+            return;
+        }
+
+        let next = create_cluster_token(&sdk_config, &cluster_name)
+            .await
+            .and_then(|t| bearer(&t));
+
+        wait = match next {
+            Ok(value) => {
+                let Some(token) = token.upgrade() else {
+                    return;
+                };
+                *token.write().unwrap_or_else(PoisonError::into_inner) = value;
+                REFRESH_INTERVAL
+            }
+            Err(err) => {
+                // The current token still works for a while.
+                warn!(
+                    "failed to refresh token of cluster {cluster_name}: {err:#}"
+                );
+                RETRY_INTERVAL
+            }
+        };
+    }
+}
+
+fn bearer(token: &str) -> Result<HeaderValue> {
+    let mut value = HeaderValue::from_str(&format!("Bearer {token}"))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
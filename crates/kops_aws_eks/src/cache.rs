@@ -0,0 +1,89 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Endpoint and CA of EKS clusters, so that rebuilding a client (a new
+//! login, a reload, a kubeconfig export) does not call DescribeCluster
+//! every time.
+//!
+//! Entries are keyed by account, region and cluster name, expire after
+//! `TTL`, and are dropped once the API server rejects a token of the
+//! cluster. Credentials not naming their account are not cached for.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use aws_config::SdkConfig;
+use aws_credential_types::provider::ProvideCredentials;
+
+use crate::EksEndpoint;
+
+const TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) struct ClusterKey {
+    account_id: String,
+    region: String,
+    cluster: String,
+}
+
+impl ClusterKey {
+    /// Key of `cluster` in the account and region of `sdk_config`.
+    pub(crate) async fn new(
+        sdk_config: &SdkConfig,
+        cluster: &str,
+    ) -> Option<Self> {
+        let credentials = sdk_config
+            .credentials_provider()?
+            .provide_credentials()
+            .await
+            .ok()?;
+
+        Some(Self {
+            account_id: credentials.account_id()?.as_str().to_string(),
+            region: sdk_config.region()?.to_string(),
+            cluster: cluster.to_string(),
+        })
+    }
+}
+
+type Entries = HashMap<ClusterKey, (Instant, EksEndpoint)>;
+
+static ENTRIES: LazyLock<Mutex<Entries>> = LazyLock::new(Mutex::default);
+
+fn entries() -> std::sync::MutexGuard<'static, Entries> {
+    ENTRIES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub(crate) fn get(key: &ClusterKey) -> Option<EksEndpoint> {
+    let mut entries = entries();
+    match entries.get(key) {
+        Some((at, endpoint)) if at.elapsed() < TTL => Some(endpoint.clone()),
+        Some(_) => {
+            entries.remove(key);
+            None
+        }
+        None => None,
+    }
+}
+
+pub(crate) fn insert(key: ClusterKey, endpoint: EksEndpoint) {
+    entries().insert(key, (Instant::now(), endpoint));
+}
+
+pub(crate) fn invalidate(key: &ClusterKey) {
+    entries().remove(key);
+}
@@ -14,12 +14,15 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
+use std::path::Path;
 use std::time::{Duration, SystemTime};
 
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
 use aws_config::SdkConfig;
 use aws_credential_types::provider::ProvideCredentials;
 use aws_sdk_eks as eks;
+use aws_sdk_sts as sts;
+use aws_sdk_sts::config::endpoint::ResolveEndpoint;
 use aws_sigv4::http_request::{
     SignableBody, SignableRequest, SignatureLocation, SigningSettings,
 };
@@ -30,65 +33,232 @@ use base64::{
 };
 use rustls::crypto::aws_lc_rs;
 
+use crate::auth::TokenLayer;
+use crate::cache::ClusterKey;
+
+mod auth;
+mod cache;
+
+/// Settings of the kube client of a cluster.
+#[derive(Debug, Clone)]
+pub struct KubeClientOptions {
+    /// Namespace of the requests not naming one.
+    pub namespace: String,
+    pub connect_timeout: Option<Duration>,
+    pub read_timeout: Option<Duration>,
+
+    /// HTTP proxy the API server is reached through.
+    pub proxy_url: Option<http::Uri>,
+
+    /// Name the certificate of the API server is checked against, instead
+    /// of the host of its URL.
+    pub tls_server_name: Option<String>,
+
+    /// API server URL, instead of the one of EKS or of the kubeconfig,
+    /// e.g. a private endpoint reached over a VPN.
+    pub endpoint: Option<http::Uri>,
+
+    /// CA certificates (DER) trusted besides the one of the cluster, e.g.
+    /// of a re-encrypting proxy, see `read_ca_bundle`.
+    pub extra_root_certs: Vec<Vec<u8>>,
+}
+
+impl Default for KubeClientOptions {
+    fn default() -> Self {
+        Self {
+            namespace: "default".to_string(),
+            connect_timeout: Some(Duration::from_secs(30)),
+            read_timeout: Some(Duration::from_secs(295)),
+            proxy_url: None,
+            tls_server_name: None,
+            endpoint: None,
+            extra_root_certs: Vec::new(),
+        }
+    }
+}
+
+impl KubeClientOptions {
+    /// The options `config` was built with, e.g. from a kubeconfig.
+    pub fn from_config(config: &kube::Config) -> Self {
+        Self {
+            namespace: config.default_namespace.clone(),
+            connect_timeout: config.connect_timeout,
+            read_timeout: config.read_timeout,
+            proxy_url: config.proxy_url.clone(),
+            tls_server_name: config.tls_server_name.clone(),
+            endpoint: None,
+            extra_root_certs: Vec::new(),
+        }
+    }
+
+    pub fn apply(&self, config: &mut kube::Config) {
+        config.default_namespace = self.namespace.clone();
+        config.connect_timeout = self.connect_timeout;
+        config.read_timeout = self.read_timeout;
+        config.proxy_url = self.proxy_url.clone();
+        config.tls_server_name = self.tls_server_name.clone();
+
+        if let Some(endpoint) = &self.endpoint {
+            config.cluster_url = endpoint.clone();
+        }
+        if !self.extra_root_certs.is_empty() {
+            config
+                .root_cert
+                .get_or_insert_with(Vec::new)
+                .extend(self.extra_root_certs.iter().cloned());
+        }
+    }
+}
+
+/// Certificates (DER) of the PEM bundle at `path`.
+pub fn read_ca_bundle(path: &Path) -> Result<Vec<Vec<u8>>> {
+    let content = std::fs::read(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let certs: Vec<Vec<u8>> = pem::parse_many(content)
+        .with_context(|| format!("invalid PEM in {}", path.display()))?
+        .into_iter()
+        .filter(|p| p.tag() == "CERTIFICATE")
+        .map(pem::Pem::into_contents)
+        .collect();
+
+    if certs.is_empty() {
+        bail!("no certificate in {}", path.display());
+    }
+    Ok(certs)
+}
+
 pub async fn create_kube_client(
     sdk_config: &SdkConfig,
     cluster_name: &str,
+    options: &KubeClientOptions,
 ) -> Result<kube::Client> {
-    aws_lc_rs::default_provider()
-        .install_default()
-        .expect("failed to install aws-lc provider");
+    // Fails once a provider is installed, i.e. from the second client on.
+    let _ = aws_lc_rs::default_provider().install_default();
 
     let (eks_cluster_url, eks_cluster_cert) =
         eks_k8s_cluster_info(sdk_config, cluster_name).await?;
 
     let token = create_cluster_token(sdk_config, cluster_name).await?;
+    let key = ClusterKey::new(sdk_config, cluster_name).await;
+    let auth = TokenLayer::start(sdk_config, cluster_name, &token, key)?;
 
-    let kubeconfig = kube::Config {
+    let mut kubeconfig = kube::Config {
         cluster_url: eks_cluster_url,
-        default_namespace: "observability".to_string(),
-        auth_info: kube::config::AuthInfo {
-            token: Some(token.clone().into()),
-            ..Default::default()
-        },
+        default_namespace: String::new(),
+        // Set by `auth`, which keeps the token fresh.
+        auth_info: kube::config::AuthInfo::default(),
         root_cert: Some(eks_cluster_cert),
         accept_invalid_certs: false,
-        connect_timeout: Some(Duration::from_secs(30)),
-        read_timeout: Some(Duration::from_secs(295)),
+        connect_timeout: None,
+        read_timeout: None,
         write_timeout: None,
         proxy_url: None,
         tls_server_name: None,
         disable_compression: false,
         headers: Vec::new(),
     };
+    options.apply(&mut kubeconfig);
 
-    let client = kube::Client::try_from(kubeconfig)?;
+    let client = kube::client::ClientBuilder::try_from(kubeconfig)?
+        .with_layer(&auth)
+        .build();
     Ok(client)
 }
 
-pub async fn eks_k8s_cluster_info(
+/// API server of an EKS cluster, as written in a kubeconfig.
+#[derive(Debug, Clone)]
+pub struct EksEndpoint {
+    pub server: String,
+
+    /// Base64 of the PEM of the cluster CA, `certificate-authority-data`.
+    pub certificate_authority_data: String,
+}
+
+/// Endpoint of EKS cluster `cluster_name`, from the cache when it was
+/// described lately.
+pub async fn eks_cluster_endpoint(
     sdk_config: &SdkConfig,
     cluster_name: &str,
-) -> Result<(http::Uri, Vec<Vec<u8>>)> {
-    let client = eks::Client::new(&sdk_config);
+) -> Result<EksEndpoint> {
+    let key = ClusterKey::new(sdk_config, cluster_name).await;
+    if let Some(endpoint) = key.as_ref().and_then(cache::get) {
+        return Ok(endpoint);
+    }
+
+    let endpoint = describe_cluster(sdk_config, cluster_name).await?;
+    if let Some(key) = key {
+        cache::insert(key, endpoint.clone());
+    }
+
+    Ok(endpoint)
+}
+
+/// Names of the EKS clusters in the region of `sdk_config`.
+pub async fn list_eks_clusters(sdk_config: &SdkConfig) -> Result<Vec<String>> {
+    let client = eks::Client::new(sdk_config);
+
+    let mut names = Vec::new();
+    let mut pages = client.list_clusters().into_paginator().send();
+    while let Some(page) = pages.next().await {
+        names.extend(page?.clusters().iter().cloned());
+    }
+
+    Ok(names)
+}
+
+async fn describe_cluster(
+    sdk_config: &SdkConfig,
+    cluster_name: &str,
+) -> Result<EksEndpoint> {
+    let client = eks::Client::new(sdk_config);
 
     let resp = client.describe_cluster().name(cluster_name).send().await?;
 
-    let cluster = resp.cluster().context("Unable to find cluster")?.to_owned();
-    let b64_cert = cluster
+    let cluster = resp.cluster().context("Unable to find cluster")?;
+    let certificate_authority_data = cluster
         .certificate_authority()
-        .context("Unable to find certificate authority")?
-        .data()
-        .context("Unable to find certificate data")?;
-    let cert = pem::parse(STANDARD.decode(b64_cert)?)?.into_contents();
-    let endpoint = cluster
-        .endpoint()
-        .context("Unable to find endpoint")?
-        .parse::<http::Uri>()?;
-
-    Ok((endpoint, [cert].to_vec()))
+        .and_then(|ca| ca.data())
+        .context("Unable to find certificate data")?
+        .to_string();
+    let server =
+        cluster.endpoint().context("Unable to find endpoint")?.to_string();
+
+    Ok(EksEndpoint { server, certificate_authority_data })
+}
+
+pub async fn eks_k8s_cluster_info(
+    sdk_config: &SdkConfig,
+    cluster_name: &str,
+) -> Result<(http::Uri, Vec<Vec<u8>>)> {
+    let endpoint = eks_cluster_endpoint(sdk_config, cluster_name).await?;
+
+    let cert = STANDARD.decode(&endpoint.certificate_authority_data)?;
+    let cert = pem::parse(cert)?.into_contents();
+    let server = endpoint.server.parse::<http::Uri>()?;
+
+    Ok((server, [cert].to_vec()))
+}
+
+/// Regional STS endpoint of `region`, in its partition (China, GovCloud,
+/// ...) and with the FIPS and dual-stack settings of `sdk_config`.
+async fn sts_endpoint(sdk_config: &SdkConfig, region: &str) -> Result<String> {
+    let params = sts::config::endpoint::Params::builder()
+        .region(region)
+        .use_fips(sdk_config.use_fips().unwrap_or(false))
+        .use_dual_stack(sdk_config.use_dual_stack().unwrap_or(false))
+        .use_global_endpoint(false)
+        .build()?;
+    let endpoint = sts::config::endpoint::DefaultResolver::new()
+        .resolve_endpoint(&params)
+        .await
+        .map_err(|err| anyhow!("failed to resolve the STS endpoint: {err}"))?;
+
+    Ok(endpoint.url().to_string())
 }
 
-async fn create_cluster_token(
+/// Bearer token of EKS cluster `cluster_name`, a presigned STS
+/// GetCallerIdentity URL as `aws eks get-token` makes them.
+pub async fn create_cluster_token(
     sdk_config: &SdkConfig,
     cluster_name: &str,
 ) -> Result<String> {
@@ -120,8 +290,10 @@ async fn create_cluster_token(
         }
     };
 
+    let sts = sts_endpoint(sdk_config, region.as_ref()).await?;
     let url = format!(
-        "https://sts.{region}.amazonaws.com/?Action=GetCallerIdentity&Version=2011-06-15"
+        "{}/?Action=GetCallerIdentity&Version=2011-06-15",
+        sts.trim_end_matches('/')
     );
     let headers = vec![("x-k8s-aws-id", cluster_name)];
     let signable_request = SignableRequest::new(
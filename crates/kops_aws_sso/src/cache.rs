@@ -0,0 +1,230 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! The SSO token cache of the AWS CLI, `~/.aws/sso/cache`, shared both
+//! ways: a token of `aws sso login` skips the device flow, and the tokens
+//! of kops are usable by the AWS CLI.
+//!
+//! Files are named after the SHA-1 of their key, like botocore does: the
+//! sso-session name (or the start URL) for tokens, the registration
+//! parameters for OIDC client registrations.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::SsoLoginConfig;
+
+/// Scopes registered for sso-sessions, the default of the AWS CLI.
+pub(crate) const SSO_SESSION_SCOPES: [&str; 1] = ["sso:account:access"];
+
+/// Tokens and registrations expiring sooner are not reused.
+const EXPIRY_MARGIN: Duration = Duration::minutes(1);
+
+/// A cached SSO access token, in the format of the AWS CLI.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CachedToken {
+    pub start_url: String,
+    pub region: String,
+    pub access_token: String,
+    #[serde(with = "cli_time")]
+    pub expires_at: DateTime<Utc>,
+
+    /// Registration the token was created with, set by the AWS CLI for
+    /// sso-sessions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration_expires_at: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+/// A cached OIDC client registration.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Registration {
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(with = "cli_time")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+}
+
+/// Directory of the AWS CLI token cache.
+pub(crate) struct TokenCache {
+    dir: PathBuf,
+}
+
+impl TokenCache {
+    /// The cache under the home directory, `None` without one.
+    pub(crate) fn new() -> Option<Self> {
+        let home = std::env::var_os("HOME").filter(|h| !h.is_empty())?;
+        Some(Self { dir: PathBuf::from(home).join(".aws/sso/cache") })
+    }
+
+    /// Unexpired token for the start URL of `config`.
+    pub(crate) fn token(
+        &self,
+        config: &SsoLoginConfig,
+    ) -> Option<CachedToken> {
+        let token: CachedToken = self.read(&token_key(config))?;
+        (token.start_url == config.start_url
+            && token.expires_at > Utc::now() + EXPIRY_MARGIN)
+            .then_some(token)
+    }
+
+    pub(crate) fn store_token(
+        &self,
+        config: &SsoLoginConfig,
+        token: &CachedToken,
+    ) -> Result<()> {
+        self.write(&token_key(config), token)
+    }
+
+    /// Unexpired client registration for the parameters of `config`.
+    pub(crate) fn registration(
+        &self,
+        config: &SsoLoginConfig,
+    ) -> Option<Registration> {
+        let registration: Registration =
+            self.read(&registration_key(config))?;
+        (registration.expires_at > Utc::now() + EXPIRY_MARGIN)
+            .then_some(registration)
+    }
+
+    pub(crate) fn store_registration(
+        &self,
+        config: &SsoLoginConfig,
+        registration: &Registration,
+    ) -> Result<()> {
+        self.write(&registration_key(config), registration)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let digest = Sha1::digest(key.as_bytes());
+        self.dir.join(format!("{digest:x}.json"))
+    }
+
+    /// Content of the file of `key`; a missing or foreign file is no hit.
+    fn read<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Option<T> {
+        let data = fs::read(self.path(key)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Write the file of `key`, readable by the user only like the AWS
+    /// CLI does.
+    fn write<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&self.dir)
+            .with_context(|| {
+                format!("failed to create {}", self.dir.display())
+            })?;
+
+        let path = self.path(key);
+        let data = serde_json::to_vec(value)?;
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&data))
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// Scopes registered for `config`, none outside sso-sessions.
+pub(crate) fn scopes(config: &SsoLoginConfig) -> Option<Vec<String>> {
+    config
+        .session_name
+        .as_ref()
+        .map(|_| SSO_SESSION_SCOPES.iter().map(|s| s.to_string()).collect())
+}
+
+fn token_key(config: &SsoLoginConfig) -> String {
+    config.session_name.clone().unwrap_or_else(|| config.start_url.clone())
+}
+
+/// `json.dumps(args, sort_keys=True)` of the registration arguments, as
+/// botocore hashes them.
+fn registration_key(config: &SsoLoginConfig) -> String {
+    format!(
+        "{{\"region\": {}, \"scopes\": {}, \"session_name\": {}, \
+         \"startUrl\": {}, \"tool\": \"botocore\"}}",
+        json(&config.region),
+        json(&scopes(config)),
+        json(&config.session_name),
+        json(&config.start_url),
+    )
+}
+
+fn json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Timestamps as the AWS CLI writes them, `2025-01-31T12:00:00Z`; the
+/// `UTC` suffix of older versions is read too.
+pub(crate) mod cli_time {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    const FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+    pub(crate) fn serialize<S: Serializer>(
+        time: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&time.format(FORMAT).to_string())
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse(&s).ok_or_else(|| de::Error::custom(format!("invalid time {s}")))
+    }
+
+    pub(crate) fn parse(s: &str) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(s)
+            .map(|t| t.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%SUTC")
+                    .ok()
+                    .map(|t| t.and_utc())
+            })
+    }
+
+    pub(crate) fn format(time: &DateTime<Utc>) -> String {
+        time.format(FORMAT).to_string()
+    }
+}
@@ -24,14 +24,24 @@ use aws_sdk_sso::error::ProvideErrorMetadata;
 use aws_sdk_ssooidc as ssooidc;
 use chrono::{DateTime, Duration, Utc};
 use tokio::time::sleep;
+use tracing::warn;
+
+use crate::cache::{CachedToken, Registration, TokenCache, cli_time};
+
+mod cache;
+mod profile;
+
+pub use profile::AwsProfile;
 
 #[derive(Debug, Clone)]
 pub struct SsoLoginConfig {
     pub region: String,
     pub start_url: String,
-    pub account_id: String,
-    pub role_name: String,
     pub client_name: String,
+
+    /// sso-session of the AWS config the login belongs to, keying the
+    /// token cache like the AWS CLI does; the start URL keys it otherwise.
+    pub session_name: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +50,52 @@ pub struct AwsSsoSession {
     pub account_id: String,
     pub role_name: String,
     pub expires_at: DateTime<Utc>,
+
+    /// Renews the session with `refresh_session`, when the SSO login
+    /// returned a refresh token.
+    pub refresh: Option<RefreshGrant>,
+}
+
+/// Refresh token of an SSO login with the client registration it was
+/// issued to.
+#[derive(Debug, Clone)]
+pub struct RefreshGrant {
+    pub region: String,
+    pub start_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub refresh_token: String,
+}
+
+impl RefreshGrant {
+    fn from_token(token: &CachedToken) -> Option<Self> {
+        Some(Self {
+            region: token.region.clone(),
+            start_url: token.start_url.clone(),
+            client_id: token.client_id.clone()?,
+            client_secret: token.client_secret.clone()?,
+            refresh_token: token.refresh_token.clone()?,
+        })
+    }
+}
+
+/// Access token of an SSO login, opening the accounts and roles of the
+/// user.
+#[derive(Debug, Clone)]
+pub struct SsoToken(CachedToken);
+
+impl SsoToken {
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.0.expires_at
+    }
+}
+
+/// An AWS account the SSO user has access to.
+#[derive(Debug, Clone)]
+pub struct SsoAccount {
+    pub account_id: String,
+    pub account_name: Option<String>,
+    pub email_address: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -50,20 +106,217 @@ pub struct DeviceVerificationInfo {
     pub expires_in: u64,
 }
 
+/// Log in to AWS SSO with the portal of `config`.
+///
+/// An unexpired token of the AWS CLI cache, from `aws sso login` or an
+/// earlier login, is used when the portal still accepts it; otherwise the
+/// device flow runs, calling `on_verification` for the user to approve
+/// it, and its token is cached for the next logins and the AWS CLI.
+pub async fn authenticate<F>(
+    sdk_config: &SdkConfig,
+    config: &SsoLoginConfig,
+    on_verification: F,
+) -> Result<SsoToken>
+where
+    F: Fn(&DeviceVerificationInfo) + Send + Sync,
+{
+    let cache = TokenCache::new();
+
+    if let Some(token) = cache.as_ref().and_then(|c| c.token(config)) {
+        // e.g. revoked by a logout of the portal meanwhile
+        let accepted = sso::Client::new(sdk_config)
+            .list_accounts()
+            .access_token(token.access_token.clone())
+            .max_results(1)
+            .send()
+            .await;
+        match accepted {
+            Ok(_) => return Ok(SsoToken(token)),
+            Err(e) => warn!(
+                "cached SSO token rejected, logging in again: {}",
+                e.message().unwrap_or("unknown error")
+            ),
+        }
+    }
+
+    let oidc_client = ssooidc::Client::new(sdk_config);
+    let registration = register(&oidc_client, config, cache.as_ref()).await?;
+    let token = device_authorization(
+        &oidc_client,
+        config,
+        &registration,
+        on_verification,
+    )
+    .await?;
+
+    if let Some(cache) = &cache
+        && let Err(e) = cache.store_token(config, &token)
+    {
+        warn!("failed to cache SSO token: {e:#}");
+    }
+
+    Ok(SsoToken(token))
+}
+
+/// Log in to AWS SSO and get the credentials of `role_name` in
+/// `account_id`, see `authenticate`.
 pub async fn login_device_flow<F>(
     sdk_config: &SdkConfig,
     config: &SsoLoginConfig,
+    account_id: &str,
+    role_name: &str,
     on_verification: F,
 ) -> Result<AwsSsoSession>
 where
     F: Fn(&DeviceVerificationInfo) + Send + Sync,
 {
-    let oidc_client = ssooidc::Client::new(sdk_config);
+    let token = authenticate(sdk_config, config, on_verification).await?;
+    role_session(sdk_config, &token, account_id, role_name).await
+}
+
+/// Accounts the user of `token` has access to, sorted by name.
+pub async fn list_accounts(
+    sdk_config: &SdkConfig,
+    token: &SsoToken,
+) -> Result<Vec<SsoAccount>> {
+    let client = sso::Client::new(sdk_config);
+
+    let mut accounts = Vec::new();
+    let mut next_token = None;
+    loop {
+        let out = client
+            .list_accounts()
+            .access_token(token.0.access_token.clone())
+            .set_next_token(next_token)
+            .send()
+            .await
+            .context("failed to list SSO accounts")?;
+
+        accounts.extend(out.account_list().iter().filter_map(|a| {
+            Some(SsoAccount {
+                account_id: a.account_id()?.to_string(),
+                account_name: a.account_name().map(|s| s.to_string()),
+                email_address: a.email_address().map(|s| s.to_string()),
+            })
+        }));
 
+        next_token = out.next_token().map(|s| s.to_string());
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    accounts.sort_by(|a, b| {
+        (&a.account_name, &a.account_id).cmp(&(&b.account_name, &b.account_id))
+    });
+    Ok(accounts)
+}
+
+/// Roles the user of `token` may assume in `account_id`, sorted.
+pub async fn list_account_roles(
+    sdk_config: &SdkConfig,
+    token: &SsoToken,
+    account_id: &str,
+) -> Result<Vec<String>> {
+    let client = sso::Client::new(sdk_config);
+
+    let mut roles = Vec::new();
+    let mut next_token = None;
+    loop {
+        let out = client
+            .list_account_roles()
+            .access_token(token.0.access_token.clone())
+            .account_id(account_id)
+            .set_next_token(next_token)
+            .send()
+            .await
+            .with_context(|| {
+                format!("failed to list SSO roles of account {account_id}")
+            })?;
+
+        roles.extend(
+            out.role_list()
+                .iter()
+                .filter_map(|r| r.role_name().map(|s| s.to_string())),
+        );
+
+        next_token = out.next_token().map(|s| s.to_string());
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    roles.sort();
+    Ok(roles)
+}
+
+/// Credentials of `role_name` in `account_id`, with the access token of
+/// `token`.
+pub async fn role_session(
+    sdk_config: &SdkConfig,
+    token: &SsoToken,
+    account_id: &str,
+    role_name: &str,
+) -> Result<AwsSsoSession> {
+    role_credentials(sdk_config, &token.0, account_id, role_name).await
+}
+
+/// Renew the role credentials of `account_id` and `role_name` with the
+/// refresh token of an earlier login, without the device flow.
+pub async fn refresh_session(
+    sdk_config: &SdkConfig,
+    grant: &RefreshGrant,
+    account_id: &str,
+    role_name: &str,
+) -> Result<AwsSsoSession> {
+    let out = ssooidc::Client::new(sdk_config)
+        .create_token()
+        .client_id(grant.client_id.clone())
+        .client_secret(grant.client_secret.clone())
+        .grant_type("refresh_token")
+        .refresh_token(grant.refresh_token.clone())
+        .send()
+        .await
+        .map_err(|e| {
+            let code = e.code().unwrap_or("Unknown");
+            let msg = e.message().unwrap_or("");
+            anyhow!("failed to refresh SSO token: {code}: {msg}")
+        })?;
+
+    let token = CachedToken {
+        start_url: grant.start_url.clone(),
+        region: grant.region.clone(),
+        access_token: must(out.access_token(), "access_token")?,
+        expires_at: Utc::now() + Duration::seconds(out.expires_in().into()),
+        client_id: Some(grant.client_id.clone()),
+        client_secret: Some(grant.client_secret.clone()),
+        registration_expires_at: None,
+        // the refresh token may be rotated
+        refresh_token: Some(
+            out.refresh_token().unwrap_or(&grant.refresh_token).to_string(),
+        ),
+    };
+
+    role_credentials(sdk_config, &token, account_id, role_name).await
+}
+
+/// OIDC client registration of `config`, from the cache when it holds an
+/// unexpired one.
+async fn register(
+    oidc_client: &ssooidc::Client,
+    config: &SsoLoginConfig,
+    cache: Option<&TokenCache>,
+) -> Result<Registration> {
+    if let Some(registration) = cache.and_then(|c| c.registration(config)) {
+        return Ok(registration);
+    }
+
+    let scopes = cache::scopes(config);
     let register_out = oidc_client
         .register_client()
         .client_name(config.client_name.clone())
         .client_type("public")
+        .set_scopes(scopes.clone())
         .send()
         .await
         .context("failed to register OIDC client")?;
@@ -78,6 +331,35 @@ where
         .ok_or_else(|| anyhow!("missing client_secret from register_client"))?
         .to_string();
 
+    let expires_at = DateTime::<Utc>::from(SystemTime::UNIX_EPOCH)
+        + Duration::seconds(register_out.client_secret_expires_at());
+
+    let registration =
+        Registration { client_id, client_secret, expires_at, scopes };
+
+    if let Some(cache) = cache
+        && let Err(e) = cache.store_registration(config, &registration)
+    {
+        warn!("failed to cache OIDC client registration: {e:#}");
+    }
+
+    Ok(registration)
+}
+
+/// Run the device flow, returning the access token once the user approved
+/// it.
+async fn device_authorization<F>(
+    oidc_client: &ssooidc::Client,
+    config: &SsoLoginConfig,
+    registration: &Registration,
+    on_verification: F,
+) -> Result<CachedToken>
+where
+    F: Fn(&DeviceVerificationInfo) + Send + Sync,
+{
+    let client_id = registration.client_id.clone();
+    let client_secret = registration.client_secret.clone();
+
     let device_auth = oidc_client
         .start_device_authorization()
         .client_id(client_id.clone())
@@ -113,8 +395,8 @@ where
     on_verification(&verification_info);
 
     let max_attempts = expires_in / interval_secs + 1;
-    let access_token = {
-        let mut access_token: Option<String> = None;
+    let created = {
+        let mut created = None;
 
         for _ in 0..max_attempts {
             let res = oidc_client
@@ -128,7 +410,7 @@ where
 
             match res {
                 Ok(out) => {
-                    access_token = out.access_token().map(|s| s.to_string());
+                    created = Some(out);
                     break;
                 }
                 Err(e) => {
@@ -166,17 +448,38 @@ where
             }
         }
 
-        access_token.ok_or_else(|| {
+        created.ok_or_else(|| {
             anyhow!("did not obtain access_token before timeout")
         })?
     };
 
+    Ok(CachedToken {
+        start_url: config.start_url.clone(),
+        region: config.region.clone(),
+        access_token: must(created.access_token(), "access_token")?,
+        expires_at: Utc::now()
+            + Duration::seconds(created.expires_in().into()),
+        client_id: Some(client_id),
+        client_secret: Some(client_secret),
+        registration_expires_at: Some(cli_time::format(
+            &registration.expires_at,
+        )),
+        refresh_token: created.refresh_token().map(|s| s.to_string()),
+    })
+}
+
+async fn role_credentials(
+    sdk_config: &SdkConfig,
+    token: &CachedToken,
+    account_id: &str,
+    role_name: &str,
+) -> Result<AwsSsoSession> {
     let sso_client = sso::Client::new(sdk_config);
     let out = sso_client
         .get_role_credentials()
-        .access_token(access_token.clone())
-        .account_id(config.account_id.clone())
-        .role_name(config.role_name.clone())
+        .access_token(token.access_token.clone())
+        .account_id(account_id)
+        .role_name(role_name)
         .send()
         .await
         .context("get_role_credentials failed")?;
@@ -204,9 +507,10 @@ where
 
     Ok(AwsSsoSession {
         credentials: creds,
-        account_id: config.account_id.clone(),
-        role_name: config.role_name.clone(),
+        account_id: account_id.to_string(),
+        role_name: role_name.to_string(),
         expires_at,
+        refresh: RefreshGrant::from_token(token),
     })
 }
 
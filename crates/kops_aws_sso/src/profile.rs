@@ -0,0 +1,128 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! SSO profiles of the AWS CLI config, `AWS_CONFIG_FILE` or
+//! `~/.aws/config`, both the legacy form with `sso_start_url` in the
+//! profile and the one referring to an `[sso-session]` section.
+
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+/// SSO settings of a profile of the AWS config.
+#[derive(Clone, Debug, Default)]
+pub struct AwsProfile {
+    pub start_url: Option<String>,
+    pub sso_region: Option<String>,
+    pub account_id: Option<String>,
+    pub role_name: Option<String>,
+    pub session_name: Option<String>,
+}
+
+type Section = HashMap<String, String>;
+
+impl AwsProfile {
+    /// Reads profile `name`, merged with its sso-session if any.
+    pub fn load(name: &str) -> Result<Self> {
+        let path = config_path()
+            .context("cannot locate the AWS config, HOME is not set")?;
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let sections = parse(&content);
+
+        let key = if name == "default" {
+            "default".to_string()
+        } else {
+            format!("profile {name}")
+        };
+        let Some(profile) = sections.get(&key) else {
+            bail!("profile {name} not found in {}", path.display());
+        };
+        let get = |section: &Section, key: &str| section.get(key).cloned();
+
+        let session_name = get(profile, "sso_session");
+        let session = match &session_name {
+            Some(session) => sections
+                .get(&format!("sso-session {session}"))
+                .with_context(|| {
+                format!(
+                    "sso-session {session} of profile {name} not found \
+                         in {}",
+                    path.display()
+                )
+            })?,
+            None => profile,
+        };
+
+        let aws = Self {
+            start_url: get(session, "sso_start_url"),
+            sso_region: get(session, "sso_region"),
+            account_id: get(profile, "sso_account_id"),
+            role_name: get(profile, "sso_role_name"),
+            session_name,
+        };
+        if aws.start_url.is_none() {
+            bail!(
+                "profile {name} of {} is not an SSO profile",
+                path.display()
+            );
+        }
+
+        Ok(aws)
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+
+    var("AWS_CONFIG_FILE").map(PathBuf::from).or_else(|| {
+        var("HOME").map(|home| PathBuf::from(home).join(".aws/config"))
+    })
+}
+
+/// Sections of an INI file by header; indented lines continue a nested
+/// value (e.g. `s3 =`) and are skipped.
+fn parse(content: &str) -> HashMap<String, Section> {
+    let mut sections: HashMap<String, Section> = HashMap::new();
+    let mut current = None;
+
+    for line in content.lines() {
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+
+        if let Some(header) =
+            line.strip_prefix('[').and_then(|l| l.strip_suffix(']'))
+        {
+            let header = header.split_whitespace().collect::<Vec<_>>();
+            current = Some(header.join(" "));
+        } else if let Some((key, value)) = line.split_once('=')
+            && let Some(header) = &current
+        {
+            let value = value.split(" #").next().unwrap_or_default();
+            sections
+                .entry(header.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    sections
+}
@@ -0,0 +1,455 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Alerts about pods going unhealthy, sent to webhooks, Slack or the local
+//! desktop.
+//!
+//! `run` follows the pod notifications of the daemon and alerts once when
+//! a pod starts failing (`CrashLoopBackOff`, `ImagePullBackOff`, `Failed`
+//! ...), again only after it recovered or changed failure. Alerts are
+//! filtered by namespace and rate limited, per pod and overall; one held
+//! back is sent on a later change of the pod, once the limits allow it.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use kops_protocol::{Notification, PodSummary};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    time::interval,
+};
+use tracing::{debug, warn};
+
+use crate::sink::Sinks;
+
+mod sink;
+
+pub use sink::SinkConfig;
+
+/// Time before the same pod is alerted about again when `cooldown_secs`
+/// is unset.
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Alerts per minute when `max_per_minute` is unset.
+const DEFAULT_MAX_PER_MINUTE: usize = 10;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// How often the pods of clusters no longer running are forgotten.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The `[notify]` section of the daemon config.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct NotifyConfig {
+    /// Where alerts are sent, `[[notify.sink]]` in the config.
+    #[serde(default, rename = "sink")]
+    pub sinks: Vec<SinkConfig>,
+
+    /// Only alert about pods of these namespaces (default: all of them).
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+
+    /// Never alert about pods of these namespaces.
+    #[serde(default)]
+    pub exclude_namespaces: Vec<String>,
+
+    /// Seconds before the same pod is alerted about again (default 300).
+    pub cooldown_secs: Option<u64>,
+
+    /// Alerts sent per minute at most, across every pod (default 10).
+    pub max_per_minute: Option<usize>,
+}
+
+impl NotifyConfig {
+    fn cooldown(&self) -> Duration {
+        self.cooldown_secs.map_or(DEFAULT_COOLDOWN, Duration::from_secs)
+    }
+
+    fn max_per_minute(&self) -> usize {
+        self.max_per_minute.unwrap_or(DEFAULT_MAX_PER_MINUTE)
+    }
+
+    fn watches(&self, namespace: &str) -> bool {
+        (self.namespaces.is_empty()
+            || self.namespaces.iter().any(|n| n == namespace))
+            && !self.exclude_namespaces.iter().any(|n| n == namespace)
+    }
+}
+
+/// A pod that went unhealthy.
+#[derive(Clone, Debug, Serialize)]
+pub struct Alert {
+    pub cluster: String,
+    pub namespace: String,
+    pub pod: String,
+
+    /// `Failed`, or the waiting reason of a container that cannot start,
+    /// e.g. `CrashLoopBackOff`; see `PodSummary::failure`.
+    pub status: String,
+    pub reason: Option<String>,
+    pub message: Option<String>,
+    pub restart_count: i32,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pod {}/{} of cluster {} is {}",
+            self.namespace, self.pod, self.cluster, self.status
+        )?;
+        if let Some(message) = self.message.as_ref().or(self.reason.as_ref()) {
+            write!(f, ": {message}")?;
+        }
+        Ok(())
+    }
+}
+
+/// (cluster, namespace, name) of a pod.
+type PodKey = (String, String, String);
+
+fn pod_key(pod: &PodSummary) -> PodKey {
+    (pod.cluster.clone(), pod.namespace.clone(), pod.name.clone())
+}
+
+/// Decides which pod changes are alerted about.
+#[derive(Debug)]
+pub struct Notifier {
+    config: NotifyConfig,
+
+    /// Failure of the pods last seen failing.
+    failing: HashMap<PodKey, String>,
+    last_alert: HashMap<PodKey, Instant>,
+
+    /// When the alerts of the last minute were sent.
+    sent: VecDeque<Instant>,
+}
+
+impl Notifier {
+    pub fn new(config: NotifyConfig) -> Self {
+        Self {
+            config,
+            failing: HashMap::new(),
+            last_alert: HashMap::new(),
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// Alert to send about the change of `pod` seen at `now`, when it went
+    /// into a failure.
+    pub fn observe(
+        &mut self,
+        pod: &PodSummary,
+        now: Instant,
+    ) -> Option<Alert> {
+        if !self.config.watches(&pod.namespace) {
+            return None;
+        }
+
+        let key = pod_key(pod);
+        let Some(status) = pod.failure() else {
+            self.failing.remove(&key);
+            return None;
+        };
+        if self.failing.get(&key).is_some_and(|s| s == status) {
+            return None;
+        }
+        // Held back alerts are retried on the next change of the pod.
+        if !self.allow(key.clone(), now) {
+            debug!(
+                cluster = %pod.cluster,
+                namespace = %pod.namespace,
+                pod = %pod.name,
+                status,
+                "alert rate limited"
+            );
+            return None;
+        }
+        self.failing.insert(key, status.to_string());
+
+        Some(Alert {
+            cluster: pod.cluster.clone(),
+            namespace: pod.namespace.clone(),
+            pod: pod.name.clone(),
+            status: status.to_string(),
+            reason: pod.reason.clone(),
+            message: pod.message.clone(),
+            restart_count: pod.restart_count,
+        })
+    }
+
+    /// Forget `pod`, deleted.
+    pub fn forget(&mut self, pod: &PodSummary) {
+        let key = pod_key(pod);
+        self.failing.remove(&key);
+        self.last_alert.remove(&key);
+    }
+
+    /// Forget the pods of the clusters not in `running`, removed or
+    /// stopped, whose deletions are never seen.
+    pub fn retain_clusters(&mut self, running: &HashSet<String>) {
+        self.failing.retain(|(cluster, ..), _| running.contains(cluster));
+        self.last_alert.retain(|(cluster, ..), _| running.contains(cluster));
+    }
+
+    /// Whether an alert about `key` may be sent at `now`, recording it
+    /// when it may.
+    fn allow(&mut self, key: PodKey, now: Instant) -> bool {
+        if let Some(last) = self.last_alert.get(&key)
+            && now.duration_since(*last) < self.config.cooldown()
+        {
+            return false;
+        }
+
+        while let Some(sent) = self.sent.front()
+            && now.duration_since(*sent) >= MINUTE
+        {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= self.config.max_per_minute() {
+            return false;
+        }
+
+        self.sent.push_back(now);
+        self.last_alert.insert(key, now);
+        true
+    }
+}
+
+/// Alert on the pod notifications of `rx` until its sender is gone.
+///
+/// `running` lists the clusters of the daemon; every `PRUNE_INTERVAL`
+/// the pods of the others are forgotten.
+pub async fn run<F>(
+    config: NotifyConfig,
+    mut rx: broadcast::Receiver<Notification>,
+    running: F,
+) where
+    F: Fn() -> HashSet<String>,
+{
+    let sinks = Arc::new(Sinks::new(config.sinks.clone()));
+    let mut notifier = Notifier::new(config);
+    let mut prune = interval(PRUNE_INTERVAL);
+
+    loop {
+        let received = tokio::select! {
+            received = rx.recv() => received,
+            _ = prune.tick() => {
+                notifier.retain_clusters(&running());
+                continue;
+            }
+        };
+        let notification = match received {
+            Ok(notification) => notification,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(skipped, "notifier fell behind pod changes");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        match notification {
+            Notification::PodApplied(pod) => {
+                let Some(alert) = notifier.observe(&pod, Instant::now())
+                else {
+                    continue;
+                };
+                // slow sinks must not hold back the next changes
+                let sinks = sinks.clone();
+                tokio::spawn(async move { sinks.send(&alert).await });
+            }
+            Notification::PodDeleted(pod) => notifier.forget(&pod),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pod(namespace: &str, name: &str, status: &str) -> PodSummary {
+        PodSummary {
+            cluster: "dev".to_string(),
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            phase: Some("Running".to_string()),
+            reason: None,
+            message: None,
+            ready: status == "Running",
+            restart_count: 0,
+            status: status.to_string(),
+            ready_containers: 0,
+            total_containers: 1,
+            created_at_epoch_ms: None,
+            node_name: None,
+            pod_ip: None,
+            start_time_epoch_ms: None,
+            last_warning: None,
+            ownership: None,
+        }
+    }
+
+    fn crashing(name: &str) -> PodSummary {
+        pod("default", name, "CrashLoopBackOff")
+    }
+
+    fn healthy(name: &str) -> PodSummary {
+        pod("default", name, "Running")
+    }
+
+    fn notifier(cooldown_secs: u64, max_per_minute: usize) -> Notifier {
+        Notifier::new(NotifyConfig {
+            cooldown_secs: Some(cooldown_secs),
+            max_per_minute: Some(max_per_minute),
+            ..Default::default()
+        })
+    }
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn alerts_once_per_failure() {
+        let mut n = notifier(0, 100);
+        let t = Instant::now();
+
+        let alert = n.observe(&crashing("web"), t).unwrap();
+        assert_eq!(alert.status, "CrashLoopBackOff");
+        assert!(n.observe(&crashing("web"), t + secs(1)).is_none());
+
+        // A different failure is alerted about again.
+        let pulling = pod("default", "web", "ImagePullBackOff");
+        assert!(n.observe(&pulling, t + secs(2)).is_some());
+
+        let mut failed = healthy("web");
+        failed.phase = Some("Failed".to_string());
+        let alert = n.observe(&failed, t + secs(3)).unwrap();
+        assert_eq!(alert.status, "Failed");
+    }
+
+    #[test]
+    fn cooldown_holds_back_a_pod_that_recovered() {
+        let mut n = notifier(300, 100);
+        let t = Instant::now();
+
+        assert!(n.observe(&crashing("web"), t).is_some());
+        assert!(n.observe(&healthy("web"), t + secs(10)).is_none());
+        assert!(n.observe(&crashing("web"), t + secs(20)).is_none());
+
+        // The cooldown ends 300s after the last alert, not the last change.
+        assert!(n.observe(&healthy("web"), t + secs(299)).is_none());
+        assert!(n.observe(&crashing("web"), t + secs(299)).is_none());
+        assert!(n.observe(&healthy("web"), t + secs(300)).is_none());
+        assert!(n.observe(&crashing("web"), t + secs(300)).is_some());
+    }
+
+    #[test]
+    fn cooldown_is_per_pod() {
+        let mut n = notifier(300, 100);
+        let t = Instant::now();
+
+        assert!(n.observe(&crashing("web"), t).is_some());
+        assert!(n.observe(&crashing("api"), t + secs(1)).is_some());
+    }
+
+    #[test]
+    fn alerts_per_minute_are_capped_over_a_sliding_window() {
+        let mut n = notifier(0, 2);
+        let t = Instant::now();
+
+        assert!(n.observe(&crashing("a"), t).is_some());
+        assert!(n.observe(&crashing("b"), t + secs(30)).is_some());
+        assert!(n.observe(&crashing("c"), t + secs(59)).is_none());
+
+        // The first alert leaves the window a minute after it was sent.
+        assert!(n.observe(&crashing("d"), t + secs(60)).is_some());
+        assert!(n.observe(&crashing("e"), t + secs(89)).is_none());
+        assert!(n.observe(&crashing("f"), t + secs(90)).is_some());
+    }
+
+    #[test]
+    fn rate_limited_alerts_take_no_slot() {
+        let mut n = notifier(0, 1);
+        let t = Instant::now();
+
+        assert!(n.observe(&crashing("a"), t).is_some());
+        for i in 1..10 {
+            assert!(n.observe(&crashing(&format!("p{i}")), t).is_none());
+        }
+        assert!(n.observe(&crashing("b"), t + MINUTE).is_some());
+    }
+
+    #[test]
+    fn namespaces_are_filtered() {
+        let mut n = Notifier::new(NotifyConfig {
+            namespaces: vec!["prod".to_string(), "staging".to_string()],
+            exclude_namespaces: vec!["staging".to_string()],
+            ..Default::default()
+        });
+        let t = Instant::now();
+
+        let crash = |ns| pod(ns, "web", "CrashLoopBackOff");
+        assert!(n.observe(&crash("prod"), t).is_some());
+        assert!(n.observe(&crash("staging"), t).is_none());
+        assert!(n.observe(&crash("default"), t).is_none());
+    }
+
+    #[test]
+    fn forgotten_pods_start_afresh() {
+        let mut n = notifier(300, 100);
+        let t = Instant::now();
+
+        assert!(n.observe(&crashing("web"), t).is_some());
+        n.forget(&crashing("web"));
+        assert!(n.observe(&crashing("web"), t + secs(1)).is_some());
+    }
+
+    #[test]
+    fn rate_limited_pods_are_alerted_about_later() {
+        let mut n = notifier(0, 1);
+        let t = Instant::now();
+
+        assert!(n.observe(&crashing("a"), t).is_some());
+        assert!(n.observe(&crashing("b"), t + secs(1)).is_none());
+
+        // The next change of the pod once the window has room.
+        assert!(n.observe(&crashing("b"), t + secs(30)).is_none());
+        assert!(n.observe(&crashing("b"), t + secs(61)).is_some());
+        assert!(n.observe(&crashing("b"), t + secs(62)).is_none());
+    }
+
+    #[test]
+    fn pods_of_stopped_clusters_are_forgotten() {
+        let mut n = notifier(300, 100);
+        let t = Instant::now();
+
+        assert!(n.observe(&crashing("web"), t).is_some());
+        n.retain_clusters(&HashSet::from(["dev".to_string()]));
+        assert!(n.observe(&crashing("web"), t + secs(1)).is_none());
+
+        n.retain_clusters(&HashSet::new());
+        assert!(n.failing.is_empty());
+        assert!(n.last_alert.is_empty());
+        assert!(n.observe(&crashing("web"), t + secs(2)).is_some());
+    }
+}
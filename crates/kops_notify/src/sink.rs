@@ -0,0 +1,116 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Destinations of alerts.
+
+use std::{error::Error, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::Alert;
+
+/// How long a webhook may take to accept an alert.
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+type SendResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// Where alerts are sent, a `[[notify.sink]]` of the config told apart by
+/// its `kind`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SinkConfig {
+    /// POST the alert as JSON to `url`.
+    Webhook { url: String },
+
+    /// Slack incoming webhook.
+    Slack { url: String },
+
+    /// Notification on the desktop of the user running the daemon, through
+    /// `notify-send`, or `osascript` on macOS.
+    Desktop,
+}
+
+impl SinkConfig {
+    fn kind(&self) -> &'static str {
+        match self {
+            SinkConfig::Webhook { .. } => "webhook",
+            SinkConfig::Slack { .. } => "slack",
+            SinkConfig::Desktop => "desktop",
+        }
+    }
+}
+
+/// Every sink alerts are sent to.
+pub(crate) struct Sinks {
+    sinks: Vec<SinkConfig>,
+    http: reqwest::Client,
+}
+
+impl Sinks {
+    pub(crate) fn new(sinks: Vec<SinkConfig>) -> Self {
+        Self { sinks, http: reqwest::Client::new() }
+    }
+
+    /// Send `alert` to every sink, logging the ones failing.
+    pub(crate) async fn send(&self, alert: &Alert) {
+        for sink in &self.sinks {
+            let res = match sink {
+                SinkConfig::Webhook { url } => self.post(url, alert).await,
+                SinkConfig::Slack { url } => {
+                    let text = json!({ "text": alert.to_string() });
+                    self.post(url, &text).await
+                }
+                SinkConfig::Desktop => desktop(alert).await,
+            };
+            if let Err(e) = res {
+                warn!(sink = sink.kind(), "failed to send alert: {e}");
+            }
+        }
+    }
+
+    async fn post(&self, url: &str, body: &impl Serialize) -> SendResult {
+        self.http
+            .post(url)
+            .json(body)
+            .timeout(SEND_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+async fn desktop(alert: &Alert) -> SendResult {
+    let title = format!("kops: {} {}", alert.pod, alert.status);
+    let body = alert.to_string();
+
+    let status = if cfg!(target_os = "macos") {
+        // Debug quoting escapes quotes and backslashes like AppleScript
+        let script =
+            format!("display notification {body:?} with title {title:?}");
+        Command::new("osascript").arg("-e").arg(script).status().await?
+    } else {
+        Command::new("notify-send").arg(title).arg(body).status().await?
+    };
+
+    if !status.success() {
+        return Err(format!("notifier exited with {status}").into());
+    }
+    Ok(())
+}
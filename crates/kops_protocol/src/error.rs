@@ -0,0 +1,115 @@
+//
+// Copyright (c) 2025 murilo ijanc <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Structured failure of a request, so that clients can tell a missing
+//! cluster from expired credentials from a Kubernetes API error.
+
+use std::fmt;
+
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// What went wrong, for clients to react on.
+#[derive(
+    Clone, Copy, Debug, Decode, Encode, Eq, PartialEq, Serialize, Deserialize,
+)]
+pub enum ErrorCode {
+    /// The cluster is not running in the daemon (not configured, or its
+    /// profile is not logged in).
+    ClusterNotFound,
+
+    /// A pod, container, key or other object does not exist.
+    NotFound,
+
+    /// The AWS session behind the cluster expired; log in again.
+    CredentialsExpired,
+
+    /// RBAC denied the call, or the daemon refused the client.
+    PermissionDenied,
+
+    /// The Kubernetes API server failed the call.
+    KubeApi,
+
+    /// The request itself is invalid: bad selector, regex, duration...
+    InvalidRequest,
+
+    /// A failure inside the daemon.
+    Internal,
+}
+
+/// Failure of a request.
+#[derive(Clone, Debug, Decode, Encode, Serialize, Deserialize)]
+pub struct KopsError {
+    pub code: ErrorCode,
+    pub message: String,
+
+    /// Cluster the failure relates to, when there is one.
+    pub cluster: Option<String>,
+
+    /// Whether sending the same request again may succeed.
+    pub retryable: bool,
+
+    /// Extra context as key/value pairs, e.g. the HTTP status of a failed
+    /// API call.
+    pub details: Vec<(String, String)>,
+}
+
+impl KopsError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            cluster: None,
+            retryable: false,
+            details: Vec::new(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidRequest, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    pub fn with_cluster(mut self, cluster: &str) -> Self {
+        self.cluster = Some(cluster.to_string());
+        self
+    }
+
+    pub fn with_detail(mut self, key: &str, value: impl ToString) -> Self {
+        self.details.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn retryable(mut self) -> Self {
+        self.retryable = true;
+        self
+    }
+}
+
+impl fmt::Display for KopsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for KopsError {}
@@ -14,73 +14,810 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
+pub mod error;
+pub mod mux;
+pub mod socket;
 pub mod types;
 pub mod wire;
 
-pub use types::VersionInfo;
+pub use error::{ErrorCode, KopsError};
+pub use types::{
+    ClientTraffic, ClusterAccess, ClusterStatus, ClusterSummary,
+    ConfigMapData, ConfigMapSummary, ConnectionStatus, ContainerDetail,
+    CronJobSummary, DaemonSetSummary, DaemonStatus, DeploymentSummary,
+    EventSummary, ExpiringSession, HpaSummary, ImageUsage, IngressRoute,
+    IngressSummary, JobSummary, NamespaceSummary, NodeSummary, Ownership,
+    PodConditionDetail, PodDetail, PvcSummary, ReflectorStatus, ResourceUsage,
+    RestartEntry, RestartSummary, SecretSummary, ServiceSummary,
+    SessionCredentials, SessionStatus, StatefulSetSummary, SyncState,
+    UserTraffic, VersionInfo, VolumeDetail,
+};
+
+use std::fmt;
 
 use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
 
 /// High-level request from `kopsctl` to `kopsd`.
-#[derive(Debug, Encode, Decode)]
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
 pub enum Request {
     /// Health-check: the daemon must reply with `Response::Pong`.
     Ping,
 
     Login(LoginRequest),
 
+    /// Stored AWS sessions, replied with `Response::Sessions`.
+    Sessions,
+
+    /// Forget the AWS session of `profile` and stop the clusters started
+    /// with it.
+    Logout {
+        profile: String,
+    },
+
+    /// Credentials of the AWS session of `profile`, replied with
+    /// `Response::SessionCredentials`.
+    SessionCredentials {
+        profile: String,
+    },
+
+    /// Sessions about to expire, replied with `Response::SessionHealth`;
+    /// kopsctl asks after each command to warn about them.
+    SessionHealth,
+
+    /// Start a cluster in the running daemon with the session of a
+    /// logged in profile; it is forgotten when the daemon stops.
+    ClusterAdd(ClusterAddRequest),
+
+    /// Stop a running cluster and drop its stores.
+    ClusterRemove {
+        name: String,
+    },
+
+    /// API server and CA of an EKS cluster, the default one when `None`,
+    /// replied with `Response::ClusterAccess`.
+    ClusterAccess {
+        cluster: Option<String>,
+    },
+
+    /// Bearer token of an EKS cluster, the default one when `None`,
+    /// replied with `Response::ClusterToken`.
+    ClusterToken {
+        cluster: Option<String>,
+    },
+
+    /// Configured and running clusters, replied with `Response::Clusters`.
+    Clusters,
+
+    /// Re-read the daemon config and start or stop clusters to match its
+    /// cluster list, like SIGHUP does.
+    Reload,
+
+    /// Stop the daemon, like SIGTERM does; replied with
+    /// `Response::ShuttingDown` before the daemon exits.
+    Shutdown,
+
     Pods(PodsRequest),
+
+    /// Watch pods of a cluster.
+    ///
+    /// Replied with `Response::Pods` holding the matching pods, then a
+    /// `Response::PodEvent` per change until the client disconnects. A
+    /// later `Response::Pods` replaces the whole set, sent when the daemon
+    /// could not keep up with the changes.
+    WatchPods(PodsRequest),
+
+    /// Subscribe to changes about `topic`, across every running cluster.
+    ///
+    /// Replied with a `Response::Notification` per change as it happens,
+    /// until the client disconnects or cancels. Only changes are pushed;
+    /// the current state comes from the matching listing.
+    Subscribe {
+        topic: Topic,
+    },
+    DescribePod(DescribePodRequest),
+
+    /// Full object of a cached kind, replied with `Response::Manifest`.
+    Get(GetRequest),
+    Deployments(DeploymentsRequest),
+    StatefulSets(WorkloadsRequest),
+    DaemonSets(WorkloadsRequest),
+    Events(EventsRequest),
+
+    /// Container restarts recorded by the daemon's pod reflector.
+    Restarts(RestartsRequest),
+
+    /// Every recorded restart of one pod, with its causes.
+    RestartHistory(RestartHistoryRequest),
+
+    Nodes(NodesRequest),
+    Services(ServicesRequest),
+    Ingresses(IngressesRequest),
+    Jobs(JobsRequest),
+    Hpas(WorkloadsRequest),
+    Pvcs(WorkloadsRequest),
+
+    /// Images of running containers, aggregated from the pod store.
+    Images(WorkloadsRequest),
+    CronJobs(CronJobsRequest),
+    Namespaces(NamespacesRequest),
+
+    /// Restart the pods of a deployment, like `kubectl rollout restart`.
+    RolloutRestart(RolloutRestartRequest),
+
+    DeletePod(DeletePodRequest),
+
+    /// Change the replica count of a deployment via its scale subresource.
+    Scale(ScaleRequest),
+
+    ConfigMaps(ConfigMapsRequest),
+    ConfigMapData(ConfigMapDataRequest),
+
+    /// List secrets; the reply never contains values.
+    Secrets(SecretsRequest),
+
+    /// Read the decoded value of a single secret key.
+    SecretValue(SecretValueRequest),
+
+    /// Resource usage from the metrics.k8s.io API.
+    Metrics(MetricsRequest),
     Env(EnvRequest),
 
+    /// Env vars of every pod of a cluster matching a regex, replied with
+    /// `Response::EnvMatches`.
+    EnvGrep(EnvGrepRequest),
+
+    /// Stream container logs.
+    ///
+    /// Replied with any number of `Response::LogChunk` followed by a single
+    /// `Response::LogEnd` (or `Response::Error`).
+    Logs(LogsRequest),
+
+    /// Run a command in a container.
+    ///
+    /// On success the connection switches to `wire::ExecFrame`s in both
+    /// directions; no `Response` is sent.
+    Exec(ExecRequest),
+
+    /// Forward connections to a pod port.
+    ///
+    /// The connection switches to `wire::ForwardFrame`s in both directions;
+    /// no `Response` is sent.
+    PortForward(PortForwardRequest),
+
+    /// Serve `request` as a stream of `wire::Frame`s tagged with `id`.
+    ///
+    /// Replied with `Start`, the responses to `request` as `Chunk`s (large
+    /// listings are split over several), then `End` or `Error`. When it is
+    /// the first request, the connection becomes multiplexed: it carries
+    /// only `Stream` requests, served concurrently with their frames
+    /// interleaved. Closing the connection cancels them all.
+    Stream {
+        id: u32,
+        request: Box<Request>,
+    },
+
+    /// Stop the `Stream` request `request_id` of a multiplexed connection.
+    ///
+    /// Nothing is replied to it; the cancelled request ends with `End` as
+    /// soon as the daemon has torn it down. Unknown or finished ids are
+    /// ignored.
+    Cancel {
+        request_id: u32,
+    },
+
     /// Version
     Version,
+
+    /// Daemon health overview, replied with `Response::Status`.
+    Status,
+
+    /// Keepalive of a multiplexed connection, sent every
+    /// `wire::HEARTBEAT_INTERVAL`; nothing is replied to it.
+    Heartbeat,
 }
 
 /// Response from `kopsd` to `kopsctl`.
-#[derive(Debug, Encode, Decode)]
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
 pub enum Response {
     /// Response for `Request::Ping`,
     Pong,
 
-    /// Login successfully registered in daemon
-    LoginOk,
+    /// Login successfully registered in daemon, with the clusters of its
+    /// profile sorted by name.
+    LoginOk {
+        started: Vec<String>,
+
+        /// Clusters already running, switched to the new session.
+        running: Vec<String>,
+
+        /// Clusters that failed to start, as `name: error`.
+        failed: Vec<String>,
+    },
+
+    /// Stored AWS sessions, sorted by profile.
+    Sessions {
+        sessions: Vec<SessionStatus>,
+    },
+
+    LoggedOut {
+        /// Clusters stopped along with the session, sorted.
+        stopped_clusters: Vec<String>,
+    },
+
+    SessionCredentials(SessionCredentials),
+
+    /// Sessions about to expire, sorted by profile; empty when all are
+    /// healthy.
+    SessionHealth {
+        expiring: Vec<ExpiringSession>,
+    },
+
+    ClusterAdded,
+    ClusterRemoved,
+    ClusterAccess(ClusterAccess),
+
+    ClusterToken {
+        token: String,
+
+        /// Expiry as Unix epoch milliseconds (UTC).
+        expires_at_epoch_ms: i64,
+    },
+
+    /// Clusters sorted by name.
+    Clusters {
+        clusters: Vec<ClusterSummary>,
+    },
+
+    /// Clusters the reload started and stopped, sorted by name.
+    Reloaded {
+        started: Vec<String>,
+        stopped: Vec<String>,
+
+        /// Clusters that failed to start, as `name: error`.
+        failed: Vec<String>,
+    },
+
+    /// The daemon stops once open connections close, or after a grace
+    /// period.
+    ShuttingDown {
+        pid: u32,
+    },
 
     Version(VersionInfo),
 
+    Status(DaemonStatus),
+
+    /// Pods sorted by `PodSummary::sort_key`. `continue_token`, set on
+    /// the last chunk of a limited listing, fetches the next page.
     Pods {
         pods: Vec<PodSummary>,
+        continue_token: Option<String>,
+    },
+
+    /// Incremental change for a `Request::WatchPods` client.
+    PodEvent(PodEvent),
+
+    /// Change pushed to a `Request::Subscribe` client.
+    Notification(Notification),
+
+    PodDetail(Box<PodDetail>),
+
+    /// JSON of the object asked with `Request::Get`, without its managed
+    /// fields.
+    Manifest {
+        object: String,
+    },
+
+    Deployments {
+        deployments: Vec<DeploymentSummary>,
+    },
+
+    StatefulSets {
+        stateful_sets: Vec<StatefulSetSummary>,
+    },
+
+    DaemonSets {
+        daemon_sets: Vec<DaemonSetSummary>,
+    },
+
+    Events {
+        events: Vec<EventSummary>,
+    },
+
+    Restarts {
+        restarts: Vec<RestartSummary>,
+    },
+
+    RestartHistory {
+        restarts: Vec<RestartEntry>,
+    },
+
+    Nodes {
+        nodes: Vec<NodeSummary>,
+    },
+
+    Services {
+        services: Vec<ServiceSummary>,
+    },
+
+    Ingresses {
+        ingresses: Vec<IngressSummary>,
+    },
+
+    Jobs {
+        jobs: Vec<JobSummary>,
+    },
+
+    Hpas {
+        hpas: Vec<HpaSummary>,
+    },
+
+    Pvcs {
+        pvcs: Vec<PvcSummary>,
+    },
+
+    Images {
+        images: Vec<ImageUsage>,
+    },
+
+    CronJobs {
+        cron_jobs: Vec<CronJobSummary>,
+    },
+
+    Namespaces {
+        namespaces: Vec<NamespaceSummary>,
+    },
+
+    /// Deployment patched (and rolled out, if waiting was requested).
+    RolloutRestarted {
+        /// Value written to the `restartedAt` annotation (RFC 3339).
+        restarted_at: String,
+    },
+
+    PodDeleted {
+        /// The pod is gone already, rather than still terminating.
+        deleted: bool,
+    },
+
+    Scaled {
+        old_replicas: i32,
+        new_replicas: i32,
+    },
+
+    ConfigMaps {
+        config_maps: Vec<ConfigMapSummary>,
+    },
+
+    ConfigMapData(ConfigMapData),
+
+    Secrets {
+        secrets: Vec<SecretSummary>,
+    },
+
+    SecretValue {
+        /// Decoded value, as raw bytes.
+        value: Vec<u8>,
+    },
+
+    Metrics {
+        usage: Vec<ResourceUsage>,
     },
 
     EnvVars {
         vars: Vec<EnvEntry>,
     },
 
-    /// Error
-    Error {
-        message: String,
+    EnvMatches {
+        matches: Vec<EnvMatch>,
+    },
+
+    /// Raw bytes of a log stream, as read from the API server.
+    LogChunk {
+        data: Vec<u8>,
     },
+
+    /// Log stream finished (container exited or not following).
+    LogEnd,
+
+    /// The request failed.
+    Error(KopsError),
 }
 
-#[derive(Debug, Decode, Encode)]
+#[derive(Debug, Decode, Encode, Serialize, Deserialize)]
 pub struct EnvRequest {
     pub cluster: Option<String>,
     pub namespace: String,
     pub pod: String,
+
+    /// Container to read; the pod's default container when unset.
     pub container: Option<String>,
+
+    /// Every container of the pod, ignoring `container`.
+    pub all_containers: bool,
+
+    /// Only variables whose name matches this regex.
     pub filter_regex: Option<String>,
+
+    /// Read the values of `valueFrom` references and expand `envFrom`.
+    pub resolve: bool,
+
+    /// Return resolved Secret values instead of masking them.
+    pub reveal_secrets: bool,
 }
 
-#[derive(Debug, Decode, Encode, Ord, Eq, PartialOrd, PartialEq)]
+#[derive(
+    Debug,
+    Decode,
+    Encode,
+    Ord,
+    Eq,
+    PartialOrd,
+    PartialEq,
+    Serialize,
+    Deserialize,
+)]
 pub struct EnvEntry {
+    /// Container the variable is defined in.
+    pub container: String,
     pub name: String,
     pub value: Option<String>,
+
+    /// Where the variable comes from in the container spec.
+    pub source: EnvSource,
+
+    /// The value comes from a Secret and was withheld.
+    pub masked: bool,
+}
+
+#[derive(Debug, Decode, Encode, Serialize, Deserialize)]
+pub struct EnvGrepRequest {
+    pub cluster: Option<String>,
+
+    /// Only this namespace; every namespace when unset.
+    pub namespace: Option<String>,
+
+    /// Regex matched against the name, literal value and referenced
+    /// ConfigMap or Secret of each variable. `envFrom` sources are not
+    /// expanded.
+    pub pattern: String,
+}
+
+/// Variable found by `Request::EnvGrep`, with the pod defining it.
+#[derive(
+    Debug,
+    Decode,
+    Encode,
+    Ord,
+    Eq,
+    PartialOrd,
+    PartialEq,
+    Serialize,
+    Deserialize,
+)]
+pub struct EnvMatch {
+    pub namespace: String,
+    pub pod: String,
+    pub var: EnvEntry,
+}
+
+/// Origin of an environment variable.
+#[derive(
+    Clone,
+    Debug,
+    Decode,
+    Encode,
+    Ord,
+    Eq,
+    PartialOrd,
+    PartialEq,
+    Serialize,
+    Deserialize,
+)]
+pub enum EnvSource {
+    /// Literal `value` in the container spec.
+    Literal,
+
+    /// `valueFrom.configMapKeyRef`, or a key of an `envFrom` ConfigMap.
+    ConfigMap { name: String, key: String },
+
+    /// `valueFrom.secretKeyRef`, or a key of an `envFrom` Secret.
+    Secret { name: String, key: String },
+
+    /// `valueFrom.fieldRef` or `valueFrom.resourceFieldRef`.
+    DownwardApi { path: String },
+}
+
+#[derive(Debug, Decode, Encode, Serialize, Deserialize)]
+pub struct LogsRequest {
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub pod: String,
+    pub container: Option<String>,
+
+    /// Keep the stream open and send new lines as they are written.
+    pub follow: bool,
+
+    /// Number of lines from the end of the log to start with.
+    pub tail_lines: Option<i64>,
+
+    /// Only return logs newer than this many seconds.
+    pub since_seconds: Option<i64>,
+}
+
+#[derive(Debug, Decode, Encode, Serialize, Deserialize)]
+pub struct ExecRequest {
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub pod: String,
+    pub container: Option<String>,
+
+    /// Command and arguments, not interpreted by a shell.
+    pub command: Vec<String>,
+
+    /// Attach the client stdin to the process.
+    pub stdin: bool,
+
+    /// Allocate a TTY; stderr is merged into stdout by the kubelet.
+    pub tty: bool,
+}
+
+#[derive(Debug, Decode, Encode, Serialize, Deserialize)]
+pub struct PortForwardRequest {
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub pod: String,
+
+    /// Port on the pod every forwarded stream connects to.
+    pub port: u16,
 }
 
-#[derive(Debug, Encode, Decode)]
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
 pub struct PodsRequest {
     pub cluster: Option<String>,
     pub namespace: Option<String>,
     pub failed_only: bool,
+
+    /// Every running cluster, ignoring `cluster`.
+    pub all_clusters: bool,
+
+    /// Label selector, e.g. `app=payments,tier in (web,api)`.
+    pub label_selector: Option<String>,
+
+    /// Field selector, e.g. `status.phase=Running`.
+    pub field_selector: Option<String>,
+
+    /// At most this many pods; the reply carries a `continue_token` when
+    /// more are left. Ignored when watching.
+    pub limit: Option<u32>,
+
+    /// `continue_token` of the previous page: list the pods sorted after
+    /// its last one.
+    pub continue_token: Option<String>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct DescribePodRequest {
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub pod: String,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct GetRequest {
+    pub cluster: Option<String>,
+    pub kind: ResourceKind,
+
+    /// Namespace of the object; unset for nodes and namespaces.
+    pub namespace: Option<String>,
+    pub name: String,
+}
+
+/// Kinds kept in the daemon stores.
+#[derive(Clone, Copy, Debug, Encode, Decode, Serialize, Deserialize)]
+pub enum ResourceKind {
+    Pod,
+    Deployment,
+    StatefulSet,
+    DaemonSet,
+    Service,
+    Ingress,
+    Job,
+    CronJob,
+    Hpa,
+    Pvc,
+    Node,
+    Namespace,
+}
+
+impl ResourceKind {
+    /// Lowercase name, as `kopsctl get` takes it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pod => "pod",
+            Self::Deployment => "deployment",
+            Self::StatefulSet => "statefulset",
+            Self::DaemonSet => "daemonset",
+            Self::Service => "service",
+            Self::Ingress => "ingress",
+            Self::Job => "job",
+            Self::CronJob => "cronjob",
+            Self::Hpa => "hpa",
+            Self::Pvc => "pvc",
+            Self::Node => "node",
+            Self::Namespace => "namespace",
+        }
+    }
+
+    /// Whether objects of this kind live in a namespace.
+    pub fn namespaced(self) -> bool {
+        !matches!(self, Self::Node | Self::Namespace)
+    }
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct DeploymentsRequest {
+    pub cluster: Option<String>,
+    pub namespace: Option<String>,
+
+    /// Every running cluster, ignoring `cluster`.
+    pub all_clusters: bool,
+}
+
+/// Listing of a namespaced kind that only filters by namespace.
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct WorkloadsRequest {
+    pub cluster: Option<String>,
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct EventsRequest {
+    pub cluster: Option<String>,
+    pub namespace: Option<String>,
+
+    /// Only events about this object, as `<name>` or `<kind>/<name>`.
+    pub involved_object: Option<String>,
+
+    /// Only events seen in the last this many seconds.
+    pub since_seconds: Option<i64>,
+
+    /// Every running cluster, ignoring `cluster`.
+    pub all_clusters: bool,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct RestartsRequest {
+    /// None means every cluster.
+    pub cluster: Option<String>,
+    pub namespace: Option<String>,
+
+    /// Only restarts in the last this many seconds.
+    pub since_seconds: i64,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct RestartHistoryRequest {
+    /// None means every cluster.
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub pod: String,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct NodesRequest {
+    /// Only this cluster; every running cluster when unset.
+    pub cluster: Option<String>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ServicesRequest {
+    pub cluster: Option<String>,
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct IngressesRequest {
+    pub cluster: Option<String>,
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct JobsRequest {
+    pub cluster: Option<String>,
+    pub namespace: Option<String>,
+    pub failed_only: bool,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct CronJobsRequest {
+    pub cluster: Option<String>,
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct NamespacesRequest {
+    pub cluster: Option<String>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct RolloutRestartRequest {
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub deployment: String,
+
+    /// Wait up to this many seconds for the rollout to complete before
+    /// replying; reply right after the patch when unset.
+    pub wait_timeout_seconds: Option<u64>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct DeletePodRequest {
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub pod: String,
+
+    /// Override the pod `terminationGracePeriodSeconds`.
+    pub grace_period_seconds: Option<u32>,
+
+    /// Delete immediately, without waiting for the kubelet to confirm the
+    /// containers were stopped. Implies a grace period of zero.
+    pub force: bool,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ScaleRequest {
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub deployment: String,
+    pub replicas: i32,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ConfigMapsRequest {
+    pub cluster: Option<String>,
+
+    /// Only this namespace; every namespace when unset.
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ConfigMapDataRequest {
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub name: String,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct SecretsRequest {
+    pub cluster: Option<String>,
+
+    /// Only this namespace; every namespace when unset.
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct SecretValueRequest {
+    pub cluster: Option<String>,
+    pub namespace: String,
+    pub name: String,
+    pub key: String,
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct MetricsRequest {
+    /// Only this cluster; every running cluster when unset.
+    pub cluster: Option<String>,
+
+    /// Only pods in this namespace; ignored for nodes.
+    pub namespace: Option<String>,
+    pub target: MetricsTarget,
+}
+
+#[derive(Clone, Copy, Debug, Encode, Decode, Serialize, Deserialize)]
+pub enum MetricsTarget {
+    Pods,
+    Nodes,
 }
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
@@ -90,7 +827,18 @@ pub struct PodKey {
     pub name: String,
 }
 
-#[derive(Clone, Debug, Encode, Decode)]
+/// Waiting reasons of containers that will not start without help, which
+/// make their pod count as failing.
+pub const FAILING_REASONS: &[&str] = &[
+    "CrashLoopBackOff",
+    "ImagePullBackOff",
+    "ErrImagePull",
+    "InvalidImageName",
+    "CreateContainerConfigError",
+    "CreateContainerError",
+];
+
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
 pub struct PodSummary {
     pub cluster: String,
     pub namespace: String,
@@ -100,6 +848,31 @@ pub struct PodSummary {
     pub message: Option<String>,
     pub ready: bool,
     pub restart_count: i32,
+
+    /// Status as kubectl shows it: `Running`, `Init:0/1`,
+    /// `CrashLoopBackOff`, `Terminating`, `Completed`...
+    pub status: String,
+
+    /// Containers that are ready and running, out of `total_containers`.
+    pub ready_containers: u32,
+    pub total_containers: u32,
+
+    pub created_at_epoch_ms: Option<i64>,
+
+    /// Node the pod is scheduled on, once it is.
+    pub node_name: Option<String>,
+    pub pod_ip: Option<String>,
+
+    /// When the kubelet started the pod, as Unix epoch milliseconds (UTC).
+    pub start_time_epoch_ms: Option<i64>,
+
+    /// Message of the latest Warning event of the pod, e.g. `Back-off
+    /// pulling image ...`; only looked up for `failed_only` listings.
+    pub last_warning: Option<String>,
+
+    /// Who to page about the pod; only looked up for `failed_only`
+    /// listings.
+    pub ownership: Option<Ownership>,
 }
 
 impl PodSummary {
@@ -117,6 +890,9 @@ impl PodSummary {
         let phase = status.as_ref().and_then(|s| s.phase.clone());
         let (reason, message, ready, restart_count) =
             extract_status_fields(status.as_ref());
+        let (status_text, ready_containers) = display_status(pod);
+        let total_containers =
+            pod.spec.as_ref().map_or(0, |s| s.containers.len() as u32);
 
         Some(PodSummary {
             cluster: cluster.to_string(),
@@ -127,8 +903,141 @@ impl PodSummary {
             message,
             ready,
             restart_count,
+            status: status_text,
+            ready_containers,
+            total_containers,
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .map(|t| t.0.timestamp_millis()),
+            node_name: pod.spec.as_ref().and_then(|s| s.node_name.clone()),
+            pod_ip: status.as_ref().and_then(|s| s.pod_ip.clone()),
+            start_time_epoch_ms: status
+                .as_ref()
+                .and_then(|s| s.start_time.as_ref())
+                .map(|t| t.0.timestamp_millis()),
+            last_warning: None,
+            ownership: None,
         })
     }
+
+    /// Why the pod is failing: the waiting reason of a container that
+    /// cannot start without help, one of `FAILING_REASONS`, or `Failed`
+    /// for its phase. `None` for a healthy or starting pod.
+    pub fn failure(&self) -> Option<&str> {
+        let status = self.status.strip_prefix("Init:").unwrap_or(&self.status);
+        [Some(status), self.reason.as_deref()]
+            .into_iter()
+            .flatten()
+            .find(|r| FAILING_REASONS.contains(r))
+            .or_else(|| {
+                (self.phase.as_deref() == Some("Failed")).then_some("Failed")
+            })
+    }
+
+    /// Whether the pod is failing, see `failure`.
+    pub fn is_failing(&self) -> bool {
+        self.failure().is_some()
+    }
+
+    /// Explicit ordering key: cluster, namespace and pod name.
+    ///
+    /// The daemon sorts responses by this key and renderers can rely on it
+    /// for deterministic output. Comparison is byte-wise, never locale aware.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// Change to the set of pods matching a `Request::WatchPods`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub enum PodEvent {
+    Added(PodSummary),
+    Modified(PodSummary),
+
+    /// The pod was deleted or no longer matches the request.
+    Deleted(PodSummary),
+}
+
+/// What a `Request::Subscribe` is about.
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, Encode, Decode, Serialize, Deserialize,
+)]
+pub enum Topic {
+    Pods,
+    Events,
+    Sessions,
+}
+
+/// Change pushed by the daemon to subscribers.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub enum Notification {
+    /// A pod was created or changed.
+    PodApplied(PodSummary),
+    PodDeleted(PodSummary),
+
+    /// A Kubernetes event was recorded, or seen again.
+    Event(EventSummary),
+
+    /// A login stored the session of an AWS profile.
+    SessionStored {
+        profile: String,
+
+        /// Expiry as Unix epoch milliseconds (UTC).
+        expires_at_epoch_ms: i64,
+    },
+
+    /// A logout removed the session of an AWS profile.
+    SessionRemoved {
+        profile: String,
+    },
+
+    /// The session of an AWS profile expires soon; `clusters` started with
+    /// it stop then unless the profile logs in again.
+    SessionExpiring {
+        profile: String,
+
+        /// Expiry as Unix epoch milliseconds (UTC).
+        expires_at_epoch_ms: i64,
+        clusters: Vec<String>,
+    },
+
+    /// The session of an AWS profile expired and was removed, stopping
+    /// the clusters started with it.
+    SessionExpired {
+        profile: String,
+        stopped_clusters: Vec<String>,
+    },
+
+    /// The subscriber fell behind and `skipped` notifications were lost;
+    /// a listing brings it back in sync.
+    Lagged {
+        skipped: u64,
+    },
+}
+
+impl Notification {
+    /// Topic of the notification; `None` for `Lagged`, which concerns
+    /// every subscription.
+    pub fn topic(&self) -> Option<Topic> {
+        match self {
+            Notification::PodApplied(_) | Notification::PodDeleted(_) => {
+                Some(Topic::Pods)
+            }
+            Notification::Event(_) => Some(Topic::Events),
+            Notification::SessionStored { .. }
+            | Notification::SessionRemoved { .. }
+            | Notification::SessionExpiring { .. }
+            | Notification::SessionExpired { .. } => Some(Topic::Sessions),
+            Notification::Lagged { .. } => None,
+        }
+    }
+}
+
+impl EnvEntry {
+    /// Explicit ordering key: container, variable name, then value.
+    pub fn sort_key(&self) -> (&str, &str, Option<&str>) {
+        (&self.container, &self.name, self.value.as_deref())
+    }
 }
 
 fn extract_status_fields(
@@ -148,7 +1057,7 @@ fn extract_status_fields(
 
         if let Some(cs) = &s.container_statuses {
             for c in cs {
-                restarts += c.restart_count as i32;
+                restarts += c.restart_count;
                 if let Some(state) = &c.state {
                     if let Some(w) = &state.waiting {
                         reason = w.reason.clone();
@@ -166,7 +1075,113 @@ fn extract_status_fields(
     (reason, message, ready, restarts)
 }
 
-#[derive(Debug, Encode, Decode)]
+/// Pod status the way `kubectl get pods` computes it, along with the
+/// number of ready and running containers.
+fn display_status(pod: &k8s_openapi::api::core::v1::Pod) -> (String, u32) {
+    let Some(status) = &pod.status else {
+        return ("Unknown".to_string(), 0);
+    };
+    let init_specs =
+        pod.spec.as_ref().and_then(|s| s.init_containers.as_ref());
+    let init_total = init_specs.map_or(0, |c| c.len());
+
+    let mut reason = status
+        .reason
+        .clone()
+        .or_else(|| status.phase.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let mut initializing = false;
+    for (i, c) in status.init_container_statuses.iter().flatten().enumerate() {
+        let state = c.state.as_ref();
+        let terminated = state.and_then(|s| s.terminated.as_ref());
+        let waiting = state.and_then(|s| s.waiting.as_ref());
+
+        if terminated.is_some_and(|t| t.exit_code == 0) {
+            continue;
+        }
+
+        // Sidecars (restartable init containers) keep running.
+        let sidecar = init_specs
+            .and_then(|specs| specs.iter().find(|s| s.name == c.name))
+            .is_some_and(|s| s.restart_policy.as_deref() == Some("Always"));
+        if sidecar && c.started == Some(true) {
+            continue;
+        }
+
+        reason = match (terminated, waiting) {
+            (Some(t), _) => match (&t.reason, t.signal) {
+                (Some(r), _) if !r.is_empty() => format!("Init:{r}"),
+                (_, Some(sig)) if sig != 0 => format!("Init:Signal:{sig}"),
+                _ => format!("Init:ExitCode:{}", t.exit_code),
+            },
+            (None, Some(w))
+                if w.reason.as_deref().is_some_and(|r| {
+                    !r.is_empty() && r != "PodInitializing"
+                }) =>
+            {
+                format!("Init:{}", w.reason.as_deref().unwrap_or_default())
+            }
+            _ => format!("Init:{i}/{init_total}"),
+        };
+        initializing = true;
+        break;
+    }
+
+    let mut ready_containers = 0;
+    if !initializing {
+        let mut has_running = false;
+
+        for c in status.container_statuses.iter().flatten().rev() {
+            let state = c.state.as_ref();
+            let terminated = state.and_then(|s| s.terminated.as_ref());
+            let waiting_reason = state
+                .and_then(|s| s.waiting.as_ref())
+                .and_then(|w| w.reason.as_deref())
+                .filter(|r| !r.is_empty());
+
+            if let Some(r) = waiting_reason {
+                reason = r.to_string();
+            } else if let Some(t) = terminated {
+                reason = match (&t.reason, t.signal) {
+                    (Some(r), _) if !r.is_empty() => r.clone(),
+                    (_, Some(sig)) if sig != 0 => format!("Signal:{sig}"),
+                    _ => format!("ExitCode:{}", t.exit_code),
+                };
+            } else if c.ready && state.is_some_and(|s| s.running.is_some()) {
+                has_running = true;
+                ready_containers += 1;
+            }
+        }
+
+        // Some containers finished, others are still up.
+        if reason == "Completed" && has_running {
+            let pod_ready = status
+                .conditions
+                .iter()
+                .flatten()
+                .any(|c| c.type_ == "Ready" && c.status == "True");
+            reason =
+                if pod_ready { "Running" } else { "NotReady" }.to_string();
+        }
+    }
+
+    if pod.metadata.deletion_timestamp.is_some() {
+        if status.reason.as_deref() == Some("NodeLost") {
+            reason = "Unknown".to_string();
+        } else {
+            reason = "Terminating".to_string();
+        }
+    }
+
+    (reason, ready_containers)
+}
+
+/// Written by the `Debug` impls in place of credentials, which must not
+/// end up in logs.
+pub(crate) const REDACTED: &str = "<redacted>";
+
+#[derive(Encode, Decode, Serialize, Deserialize)]
 pub struct LoginRequest {
     /// Logical profile name, e.g. "dev" or "prod".
     pub name: String,
@@ -191,4 +1206,154 @@ pub struct LoginRequest {
 
     /// Expiration of this session as Unix epoch milliseconds (UTC).
     pub expires_at_epoch_ms: i64,
+
+    /// Lets the daemon renew the session before it expires, when the SSO
+    /// login returned a refresh token.
+    pub refresh: Option<SsoRefresh>,
+}
+
+impl fmt::Debug for LoginRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoginRequest")
+            .field("name", &self.name)
+            .field("region", &self.region)
+            .field("account_id", &self.account_id)
+            .field("role_name", &self.role_name)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &REDACTED)
+            .field("session_token", &REDACTED)
+            .field("expires_at_epoch_ms", &self.expires_at_epoch_ms)
+            .field("refresh", &self.refresh)
+            .finish()
+    }
+}
+
+/// Refresh token of an SSO login, with the OIDC client it was issued to.
+#[derive(Clone, Encode, Decode, Serialize, Deserialize)]
+pub struct SsoRefresh {
+    pub region: String,
+    pub start_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub refresh_token: String,
+}
+
+impl fmt::Debug for SsoRefresh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SsoRefresh")
+            .field("region", &self.region)
+            .field("start_url", &self.start_url)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &REDACTED)
+            .field("refresh_token", &REDACTED)
+            .finish()
+    }
+}
+
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ClusterAddRequest {
+    /// EKS cluster name, also the name the cluster is known by in kops.
+    pub name: String,
+
+    /// Logged in profile whose session reaches the cluster.
+    pub profile: String,
+
+    /// Region of the cluster; defaults to the region of the session.
+    pub region: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_stay_out_of_debug_output() {
+        let req = Request::Login(LoginRequest {
+            name: "dev".to_string(),
+            region: Some("eu-west-1".to_string()),
+            account_id: "123456789012".to_string(),
+            role_name: "Admin".to_string(),
+            access_key_id: "ASIAEXAMPLE".to_string(),
+            secret_access_key: "secret-key".to_string(),
+            session_token: "session-token".to_string(),
+            expires_at_epoch_ms: 0,
+            refresh: Some(SsoRefresh {
+                region: "eu-west-1".to_string(),
+                start_url: "https://example.awsapps.com/start".to_string(),
+                client_id: "client".to_string(),
+                client_secret: "client-secret".to_string(),
+                refresh_token: "refresh-token".to_string(),
+            }),
+        });
+
+        let debug = format!("{req:?}");
+        assert!(debug.contains("ASIAEXAMPLE"));
+        for secret in
+            ["secret-key", "session-token", "client-secret", "refresh-token"]
+        {
+            assert!(!debug.contains(secret), "{secret} in {debug}");
+        }
+    }
+
+    #[test]
+    fn ownership_prefers_kops_annotations() {
+        let annotations: std::collections::BTreeMap<String, String> = [
+            ("kops.paastel.io/team", "payments"),
+            ("a8r.io/owner", "someone-else"),
+            ("a8r.io/chat", "#payments"),
+            ("kops.paastel.io/oncall", " "),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let ownership = Ownership::from_annotations(&annotations);
+        assert_eq!(ownership.team.as_deref(), Some("payments"));
+        assert_eq!(ownership.slack_channel.as_deref(), Some("#payments"));
+        assert_eq!(ownership.oncall, None);
+        assert_eq!(ownership.contacts(), "payments, #payments");
+
+        let namespace = Ownership {
+            oncall: Some("pay-primary".to_string()),
+            team: Some("platform".to_string()),
+            ..Ownership::default()
+        };
+        let ownership = ownership.or(namespace);
+        assert_eq!(ownership.team.as_deref(), Some("payments"));
+        assert_eq!(ownership.oncall.as_deref(), Some("pay-primary"));
+    }
+
+    #[test]
+    fn pods_fail_on_containers_that_cannot_start() {
+        let pod = |phase: &str, status: &str| PodSummary {
+            cluster: "dev".to_string(),
+            namespace: "default".to_string(),
+            name: "web".to_string(),
+            phase: Some(phase.to_string()),
+            reason: None,
+            message: None,
+            ready: false,
+            restart_count: 0,
+            status: status.to_string(),
+            ready_containers: 0,
+            total_containers: 1,
+            created_at_epoch_ms: None,
+            node_name: None,
+            pod_ip: None,
+            start_time_epoch_ms: None,
+            last_warning: None,
+            ownership: None,
+        };
+
+        for status in ["CrashLoopBackOff", "ErrImagePull", "ImagePullBackOff"]
+        {
+            assert_eq!(pod("Pending", status).failure(), Some(status));
+        }
+        let init = pod("Pending", "Init:CreateContainerConfigError");
+        assert_eq!(init.failure(), Some("CreateContainerConfigError"));
+        assert_eq!(pod("Failed", "Error").failure(), Some("Failed"));
+
+        assert!(!pod("Pending", "ContainerCreating").is_failing());
+        assert!(!pod("Running", "Running").is_failing());
+    }
 }
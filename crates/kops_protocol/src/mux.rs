@@ -0,0 +1,409 @@
+//
+// Copyright (c) 2025 murilo ijanc <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Client side of a multiplexed connection.
+//!
+//! Every request is sent as a `Request::Stream` with a fresh id, so any
+//! number of them can be in flight at once; a reader task routes the
+//! returned `Frame`s to the matching `Replies`. A request no longer
+//! wanted is stopped with `Mux::cancel`.
+//!
+//! Heartbeats flow both ways while the `Mux` lives; a daemon silent for
+//! `HEARTBEAT_TIMEOUT` is taken for dead and the connection closed.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncRead, AsyncWrite, WriteHalf};
+use tokio::sync::{Mutex as AsyncMutex, mpsc};
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, interval_at};
+
+use crate::wire::{
+    Frame, HEARTBEAT_INTERVAL, HEARTBEAT_TIMEOUT, WireError, read_message,
+    write_message,
+};
+use crate::{Request, Response};
+
+type Pending = Arc<Mutex<HashMap<u32, mpsc::UnboundedSender<Frame>>>>;
+
+/// A connection carrying concurrent requests.
+pub struct Mux<S> {
+    writer: Arc<AsyncMutex<WriteHalf<S>>>,
+    pending: Pending,
+    next_id: AtomicU32,
+    reader: JoinHandle<()>,
+    heartbeat: JoinHandle<()>,
+}
+
+impl<S> Mux<S>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    /// Take over `stream`, which must have completed the handshake.
+    pub fn new(stream: S) -> Self {
+        let (mut rd, writer) = tokio::io::split(stream);
+        let writer = Arc::new(AsyncMutex::new(writer));
+        let pending = Pending::default();
+
+        let routes = pending.clone();
+        let reader = tokio::spawn(async move {
+            loop {
+                let read = read_message::<_, Frame>(&mut rd);
+                let frame = match tokio::time::timeout(HEARTBEAT_TIMEOUT, read)
+                    .await
+                {
+                    Ok(Ok(Some(Frame::Heartbeat))) => continue,
+                    Ok(Ok(Some(frame))) => frame,
+                    // Closed, broken, or silent for too long.
+                    _ => break,
+                };
+
+                let id = frame.id();
+                let done =
+                    matches!(frame, Frame::End { .. } | Frame::Error { .. });
+
+                let mut routes = routes.lock().unwrap();
+                // Replies nobody waits for any more are dropped.
+                if let Some(tx) = routes.get(&id)
+                    && (tx.send(frame).is_err() || done)
+                {
+                    routes.remove(&id);
+                }
+            }
+
+            // Wake every waiter: the connection is gone.
+            routes.lock().unwrap().clear();
+        });
+
+        let heartbeat = {
+            let writer = writer.clone();
+            tokio::spawn(async move {
+                // not right away: the daemon tells a multiplexed
+                // connection by its first request being a `Stream`
+                let start = Instant::now() + HEARTBEAT_INTERVAL;
+                let mut ticks = interval_at(start, HEARTBEAT_INTERVAL);
+                loop {
+                    ticks.tick().await;
+                    let mut writer = writer.lock().await;
+                    let beat =
+                        write_message(&mut *writer, &Request::Heartbeat);
+                    if beat.await.is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+
+        Self { writer, pending, next_id: AtomicU32::new(1), reader, heartbeat }
+    }
+
+    /// Whether the daemon closed the connection.
+    pub fn is_closed(&self) -> bool {
+        self.reader.is_finished()
+    }
+
+    /// Send `req`; its responses are read from the returned `Replies`.
+    pub async fn open(&self, req: Request) -> Result<Replies, WireError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let req = Request::Stream { id, request: Box::new(req) };
+        let mut writer = self.writer.lock().await;
+        if let Err(e) = write_message(&mut *writer, &req).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        Ok(Replies { id, rx, done: false })
+    }
+
+    /// Ask the daemon to stop the request `replies` belongs to; whatever
+    /// it still sends for it is discarded.
+    pub async fn cancel(&self, replies: Replies) -> Result<(), WireError> {
+        if replies.done {
+            return Ok(());
+        }
+        self.pending.lock().unwrap().remove(&replies.id);
+
+        let req = Request::Cancel { request_id: replies.id };
+        let mut writer = self.writer.lock().await;
+        write_message(&mut *writer, &req).await
+    }
+
+    /// Send `req` and wait for its first response.
+    pub async fn call(&self, req: Request) -> Result<Response, WireError> {
+        let mut replies = self.open(req).await?;
+        replies.next().await?.ok_or_else(|| {
+            WireError::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "reply ended without a response",
+            ))
+        })
+    }
+}
+
+impl<S> Drop for Mux<S> {
+    fn drop(&mut self) {
+        self.reader.abort();
+        self.heartbeat.abort();
+    }
+}
+
+/// Responses to one request of a `Mux`.
+///
+/// Frames are queued without bound so that a slow reader never holds up
+/// the other requests of the connection.
+pub struct Replies {
+    id: u32,
+    rx: mpsc::UnboundedReceiver<Frame>,
+    done: bool,
+}
+
+impl Replies {
+    /// Next response, or `None` once the reply is complete. A failed
+    /// request yields a `Response::Error`.
+    pub async fn next(&mut self) -> Result<Option<Response>, WireError> {
+        while !self.done {
+            let Some(frame) = self.rx.recv().await else {
+                return Err(WireError::Io(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "daemon closed connection mid-stream",
+                )));
+            };
+
+            match frame {
+                Frame::Start { .. } | Frame::Heartbeat => {}
+                Frame::Chunk { response, .. } => return Ok(Some(*response)),
+                Frame::End { .. } => self.done = true,
+                Frame::Error { error, .. } => {
+                    self.done = true;
+                    return Ok(Some(Response::Error(error)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{DuplexStream, duplex};
+
+    use super::*;
+    use crate::{ErrorCode, KopsError};
+
+    /// The daemon end of a `Mux` connection.
+    struct Daemon(DuplexStream);
+
+    impl Daemon {
+        /// Next request of the client, heartbeats aside.
+        async fn request(&mut self) -> Request {
+            loop {
+                match read_message(&mut self.0).await.unwrap() {
+                    Some(Request::Heartbeat) => continue,
+                    Some(req) => return req,
+                    None => panic!("client closed the connection"),
+                }
+            }
+        }
+
+        /// Id of the next `Request::Stream` of the client.
+        async fn stream_id(&mut self) -> u32 {
+            match self.request().await {
+                Request::Stream { id, .. } => id,
+                req => panic!("not a stream request: {req:?}"),
+            }
+        }
+
+        async fn send(&mut self, frame: Frame) {
+            write_message(&mut self.0, &frame).await.unwrap();
+        }
+    }
+
+    fn connect() -> (Mux<DuplexStream>, Daemon) {
+        let (client, daemon) = duplex(64 * 1024);
+        (Mux::new(client), Daemon(daemon))
+    }
+
+    #[tokio::test]
+    async fn requests_are_wrapped_with_fresh_ids() {
+        let (mux, mut daemon) = connect();
+
+        let _first = mux.open(Request::Ping).await.unwrap();
+        let _second = mux.open(Request::Version).await.unwrap();
+
+        let Request::Stream { id, request } = daemon.request().await else {
+            panic!("first request not wrapped");
+        };
+        assert_eq!(id, 1);
+        assert!(matches!(*request, Request::Ping));
+
+        let Request::Stream { id, request } = daemon.request().await else {
+            panic!("second request not wrapped");
+        };
+        assert_eq!(id, 2);
+        assert!(matches!(*request, Request::Version));
+    }
+
+    #[tokio::test]
+    async fn interleaved_frames_reach_their_request() {
+        let (mux, mut daemon) = connect();
+
+        let mut first = mux.open(Request::Ping).await.unwrap();
+        let mut second = mux.open(Request::Ping).await.unwrap();
+        let (a, b) = (daemon.stream_id().await, daemon.stream_id().await);
+
+        daemon.send(Frame::Start { id: b }).await;
+        daemon.send(Frame::Start { id: a }).await;
+        daemon
+            .send(Frame::Chunk { id: b, response: Box::new(Response::Pong) })
+            .await;
+        daemon.send(Frame::End { id: b }).await;
+        daemon
+            .send(Frame::Chunk { id: a, response: Box::new(Response::Pong) })
+            .await;
+        daemon
+            .send(Frame::Chunk { id: a, response: Box::new(Response::Pong) })
+            .await;
+        daemon.send(Frame::End { id: a }).await;
+
+        assert!(matches!(second.next().await, Ok(Some(Response::Pong))));
+        assert!(matches!(second.next().await, Ok(None)));
+
+        assert!(matches!(first.next().await, Ok(Some(Response::Pong))));
+        assert!(matches!(first.next().await, Ok(Some(Response::Pong))));
+        assert!(matches!(first.next().await, Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn error_frame_ends_the_reply() {
+        let (mux, mut daemon) = connect();
+
+        let mut replies = mux.open(Request::Ping).await.unwrap();
+        let id = daemon.stream_id().await;
+        let error = KopsError::invalid("bad selector");
+        daemon.send(Frame::Error { id, error }).await;
+
+        match replies.next().await {
+            Ok(Some(Response::Error(error))) => {
+                assert_eq!(error.code, ErrorCode::InvalidRequest);
+                assert_eq!(error.message, "bad selector");
+            }
+            other => panic!("unexpected reply: {other:?}"),
+        }
+        assert!(matches!(replies.next().await, Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn call_returns_the_first_response() {
+        let (mux, mut daemon) = connect();
+
+        let daemon = async move {
+            let id = daemon.stream_id().await;
+            daemon.send(Frame::Start { id }).await;
+            daemon
+                .send(Frame::Chunk { id, response: Box::new(Response::Pong) })
+                .await;
+            daemon.send(Frame::End { id }).await;
+            daemon
+        };
+        let (resp, _daemon) = tokio::join!(mux.call(Request::Ping), daemon);
+
+        assert!(matches!(resp, Ok(Response::Pong)));
+    }
+
+    #[tokio::test]
+    async fn cancel_tells_the_daemon_and_drops_late_frames() {
+        let (mux, mut daemon) = connect();
+
+        let cancelled = mux.open(Request::Ping).await.unwrap();
+        let mut kept = mux.open(Request::Ping).await.unwrap();
+        let (a, b) = (daemon.stream_id().await, daemon.stream_id().await);
+
+        mux.cancel(cancelled).await.unwrap();
+        let req = daemon.request().await;
+        assert!(
+            matches!(req, Request::Cancel { request_id } if request_id == a)
+        );
+
+        // Sent before the daemon saw the cancel.
+        daemon
+            .send(Frame::Chunk { id: a, response: Box::new(Response::Pong) })
+            .await;
+        daemon
+            .send(Frame::Chunk { id: b, response: Box::new(Response::Pong) })
+            .await;
+        daemon.send(Frame::End { id: b }).await;
+
+        assert!(matches!(kept.next().await, Ok(Some(Response::Pong))));
+        assert!(matches!(kept.next().await, Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn closed_connection_fails_pending_replies() {
+        let (mux, mut daemon) = connect();
+
+        let mut replies = mux.open(Request::Ping).await.unwrap();
+        let id = daemon.stream_id().await;
+        daemon.send(Frame::Start { id }).await;
+        drop(daemon);
+
+        assert!(replies.next().await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn first_request_goes_before_any_heartbeat() {
+        let (mux, mut daemon) = connect();
+
+        // Let the heartbeat task start, short of its first beat.
+        tokio::task::yield_now().await;
+        tokio::time::advance(HEARTBEAT_INTERVAL / 2).await;
+        let _replies = mux.open(Request::Ping).await.unwrap();
+
+        let req: Option<Request> = read_message(&mut daemon.0).await.unwrap();
+        assert!(matches!(req, Some(Request::Stream { .. })));
+
+        // Then heartbeats, once an interval passed.
+        tokio::time::advance(HEARTBEAT_INTERVAL).await;
+        let req: Option<Request> = read_message(&mut daemon.0).await.unwrap();
+        assert!(matches!(req, Some(Request::Heartbeat)));
+    }
+
+    #[tokio::test]
+    async fn heartbeats_do_not_disturb_a_reply() {
+        let (mux, mut daemon) = connect();
+
+        let mut replies = mux.open(Request::Ping).await.unwrap();
+        let id = daemon.stream_id().await;
+        daemon.send(Frame::Heartbeat).await;
+        daemon.send(Frame::Start { id }).await;
+        daemon.send(Frame::Heartbeat).await;
+        daemon
+            .send(Frame::Chunk { id, response: Box::new(Response::Pong) })
+            .await;
+        daemon.send(Frame::Heartbeat).await;
+        daemon.send(Frame::End { id }).await;
+
+        assert!(matches!(replies.next().await, Ok(Some(Response::Pong))));
+        assert!(matches!(replies.next().await, Ok(None)));
+        assert!(!mux.is_closed());
+    }
+}
@@ -0,0 +1,48 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Location of the daemon socket, resolved the same way by `kopsd` and
+//! its clients.
+
+use std::env;
+use std::path::PathBuf;
+
+/// Environment variable naming the socket path.
+pub const SOCKET_ENV: &str = "KOPS_SOCKET";
+
+/// Socket of a system-wide daemon, used when no runtime directory is set.
+pub const SYSTEM_SOCKET_PATH: &str = "/var/run/kopsd/kopsd.sock";
+
+/// Resolve the socket path, from the first of:
+///
+/// - `flag`, given with `--socket`;
+/// - the `KOPS_SOCKET` environment variable;
+/// - `configured`, from the config file;
+/// - `kopsd/kopsd.sock` under `XDG_RUNTIME_DIR`;
+/// - [`SYSTEM_SOCKET_PATH`].
+pub fn resolve(flag: Option<PathBuf>, configured: Option<PathBuf>) -> PathBuf {
+    flag.or_else(|| non_empty_var(SOCKET_ENV).map(PathBuf::from))
+        .or(configured)
+        .or_else(|| {
+            non_empty_var("XDG_RUNTIME_DIR")
+                .map(|dir| PathBuf::from(dir).join("kopsd").join("kopsd.sock"))
+        })
+        .unwrap_or_else(|| PathBuf::from(SYSTEM_SOCKET_PATH))
+}
+
+fn non_empty_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|v| !v.is_empty())
+}
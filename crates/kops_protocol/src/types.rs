@@ -14,9 +14,16 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
+use std::collections::BTreeMap;
+use std::fmt;
+
 use bincode::{Decode, Encode};
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use serde::{Deserialize, Serialize};
+
+use crate::REDACTED;
 
-#[derive(Debug, Encode, Decode)]
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
 pub struct VersionInfo {
     /// Version
     pub daemon_version: String,
@@ -30,3 +37,1872 @@ pub struct VersionInfo {
     /// Date de build
     pub build_date: Option<String>,
 }
+
+/// One-shot health overview of the daemon, replied to `Request::Status`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// Process id of the daemon.
+    pub pid: u32,
+    pub uptime_secs: u64,
+
+    /// Client connections open, this one included.
+    pub connections: u32,
+
+    /// Running clusters, sorted by name.
+    pub clusters: Vec<ClusterStatus>,
+
+    /// Stored AWS sessions, sorted by profile.
+    pub sessions: Vec<SessionStatus>,
+
+    /// Open client connections, sorted by id.
+    pub clients: Vec<ConnectionStatus>,
+
+    /// Traffic of each user since the daemon started, sorted by uid.
+    pub users: Vec<UserTraffic>,
+}
+
+/// One open client connection of the daemon.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ConnectionStatus {
+    pub id: u64,
+    pub uid: u32,
+    pub pid: Option<i32>,
+    pub connected_secs: u64,
+    pub traffic: ClientTraffic,
+}
+
+/// Traffic of every connection of one user.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct UserTraffic {
+    pub uid: u32,
+
+    /// Connections of the user currently open.
+    pub connections: u32,
+    pub traffic: ClientTraffic,
+}
+
+/// Bytes exchanged with a client, as seen on the daemon socket.
+#[derive(Clone, Debug, Default, Encode, Decode, Serialize, Deserialize)]
+pub struct ClientTraffic {
+    /// Bytes of requests.
+    pub bytes_in: u64,
+
+    /// Bytes of responses.
+    pub bytes_out: u64,
+
+    /// Responses that waited for the client to read the previous ones: a
+    /// slow consumer.
+    pub stalled_writes: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ClusterStatus {
+    pub name: String,
+
+    /// A reflector keeps failing; the cluster data may be stale.
+    pub degraded: bool,
+
+    /// The AWS session the cluster was started with expires soon.
+    pub credentials_expiring: bool,
+
+    /// One entry per reflector, sorted by kind.
+    pub reflectors: Vec<ReflectorStatus>,
+}
+
+/// How kubectl reaches a cluster of the daemon, for `kopsctl kubeconfig`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ClusterAccess {
+    pub cluster: String,
+
+    /// AWS profile whose session signs the tokens.
+    pub profile: String,
+    pub server: String,
+
+    /// Base64 of the PEM of the cluster CA.
+    pub certificate_authority_data: String,
+}
+
+/// One cluster as listed by `kopsctl clusters`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ClusterSummary {
+    pub name: String,
+
+    /// AWS profile whose session the cluster was started with.
+    pub profile: Option<String>,
+
+    /// Cluster used when a request names none.
+    pub default: bool,
+
+    /// Configured clusters are listed even when not started.
+    pub started: bool,
+
+    /// Every reflector of the cluster is running.
+    pub running: bool,
+
+    /// Objects held by the stores of the cluster.
+    pub objects: u64,
+
+    /// When every store last held a complete listing, as Unix epoch
+    /// milliseconds (UTC).
+    pub last_sync_epoch_ms: Option<i64>,
+    pub started_at_epoch_ms: Option<i64>,
+}
+
+/// State of the reflector keeping the store of one kind up-to-date.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ReflectorStatus {
+    /// Lowercase kind, e.g. `pod` or `deployment`.
+    pub kind: String,
+    pub state: SyncState,
+
+    /// Objects held by the store.
+    pub objects: u64,
+
+    /// Watcher failures in a row; reset by the next successful event.
+    pub failures: u32,
+
+    /// Last watcher event, as Unix epoch milliseconds (UTC).
+    pub last_event_epoch_ms: Option<i64>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub enum SyncState {
+    /// The initial listing (or a relist) is in progress.
+    Pending,
+    Synced,
+
+    /// The watcher failed and is retrying; holds its last error.
+    Error(String),
+}
+
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct SessionStatus {
+    pub profile: String,
+
+    /// Expiry as Unix epoch milliseconds (UTC).
+    pub expires_at_epoch_ms: i64,
+}
+
+/// A stored AWS session close to its expiry, with the clusters that stop
+/// when it lapses.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ExpiringSession {
+    pub profile: String,
+
+    /// Expiry as Unix epoch milliseconds (UTC).
+    pub expires_at_epoch_ms: i64,
+
+    /// Clusters started with the session, sorted.
+    pub clusters: Vec<String>,
+}
+
+/// Temporary AWS credentials of a stored session, for the tools outside
+/// of kops (`kopsctl credentials export`).
+#[derive(Clone, Encode, Decode, Serialize, Deserialize)]
+pub struct SessionCredentials {
+    pub profile: String,
+    pub account_id: String,
+    pub role_name: String,
+    pub region: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: String,
+
+    /// Expiry as Unix epoch milliseconds (UTC).
+    pub expires_at_epoch_ms: i64,
+}
+
+impl fmt::Debug for SessionCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionCredentials")
+            .field("profile", &self.profile)
+            .field("account_id", &self.account_id)
+            .field("role_name", &self.role_name)
+            .field("region", &self.region)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &REDACTED)
+            .field("session_token", &REDACTED)
+            .field("expires_at_epoch_ms", &self.expires_at_epoch_ms)
+            .finish()
+    }
+}
+
+/// One Deployment as listed by `kopsctl deployments`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct DeploymentSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+
+    /// `spec.replicas`.
+    pub desired: i32,
+
+    /// `status.readyReplicas`.
+    pub ready: i32,
+
+    /// `status.updatedReplicas`.
+    pub updated: i32,
+
+    /// `status.availableReplicas`.
+    pub available: i32,
+
+    /// Images of the pod template containers, in spec order.
+    pub images: Vec<String>,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+impl DeploymentSummary {
+    pub fn from_deployment(
+        cluster: &str,
+        deploy: &k8s_openapi::api::apps::v1::Deployment,
+    ) -> Option<Self> {
+        let meta = &deploy.metadata;
+
+        let name = meta.name.clone()?;
+        let namespace =
+            meta.namespace.clone().unwrap_or_else(|| "default".to_string());
+
+        let spec = deploy.spec.as_ref();
+        let status = deploy.status.as_ref();
+
+        let images =
+            spec.map(|s| template_images(&s.template)).unwrap_or_default();
+
+        Some(DeploymentSummary {
+            cluster: cluster.to_string(),
+            namespace,
+            name,
+            desired: spec.and_then(|s| s.replicas).unwrap_or(1),
+            ready: status.and_then(|s| s.ready_replicas).unwrap_or(0),
+            updated: status.and_then(|s| s.updated_replicas).unwrap_or(0),
+            available: status.and_then(|s| s.available_replicas).unwrap_or(0),
+            images,
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and deployment name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// One StatefulSet as listed by `kopsctl statefulsets`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct StatefulSetSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+
+    /// `spec.replicas`.
+    pub desired: i32,
+    pub ready: i32,
+
+    /// Replicas at `current_revision` and at `update_revision`.
+    pub current: i32,
+    pub updated: i32,
+
+    /// `RollingUpdate` or `OnDelete`.
+    pub update_strategy: String,
+    pub current_revision: Option<String>,
+    pub update_revision: Option<String>,
+    pub images: Vec<String>,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+impl StatefulSetSummary {
+    pub fn from_stateful_set(
+        cluster: &str,
+        sts: &k8s_openapi::api::apps::v1::StatefulSet,
+    ) -> Option<Self> {
+        let meta = &sts.metadata;
+        let spec = sts.spec.as_ref();
+        let status = sts.status.as_ref();
+
+        Some(StatefulSetSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            desired: spec.and_then(|s| s.replicas).unwrap_or(1),
+            ready: status.and_then(|s| s.ready_replicas).unwrap_or(0),
+            current: status.and_then(|s| s.current_replicas).unwrap_or(0),
+            updated: status.and_then(|s| s.updated_replicas).unwrap_or(0),
+            update_strategy: spec
+                .and_then(|s| s.update_strategy.as_ref())
+                .and_then(|u| u.type_.clone())
+                .unwrap_or_else(|| "RollingUpdate".into()),
+            current_revision: status.and_then(|s| s.current_revision.clone()),
+            update_revision: status.and_then(|s| s.update_revision.clone()),
+            images: spec
+                .map(|s| template_images(&s.template))
+                .unwrap_or_default(),
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and statefulset name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// One DaemonSet as listed by `kopsctl daemonsets`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct DaemonSetSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+
+    /// Nodes that should run the daemon pod.
+    pub desired: i32,
+    pub current: i32,
+    pub ready: i32,
+    pub updated: i32,
+    pub available: i32,
+
+    /// `RollingUpdate` or `OnDelete`.
+    pub update_strategy: String,
+
+    /// Pod template generation, as tracked by the DaemonSet controller.
+    pub current_revision: Option<String>,
+    pub images: Vec<String>,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+impl DaemonSetSummary {
+    pub fn from_daemon_set(
+        cluster: &str,
+        ds: &k8s_openapi::api::apps::v1::DaemonSet,
+    ) -> Option<Self> {
+        let meta = &ds.metadata;
+        let spec = ds.spec.as_ref();
+        let status = ds.status.as_ref();
+
+        let current_revision = meta
+            .annotations
+            .as_ref()
+            .and_then(|a| a.get("deprecated.daemonset.template.generation"))
+            .cloned();
+
+        Some(DaemonSetSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            desired: status.map_or(0, |s| s.desired_number_scheduled),
+            current: status.map_or(0, |s| s.current_number_scheduled),
+            ready: status.map_or(0, |s| s.number_ready),
+            updated: status
+                .and_then(|s| s.updated_number_scheduled)
+                .unwrap_or(0),
+            available: status.and_then(|s| s.number_available).unwrap_or(0),
+            update_strategy: spec
+                .and_then(|s| s.update_strategy.as_ref())
+                .and_then(|u| u.type_.clone())
+                .unwrap_or_else(|| "RollingUpdate".into()),
+            current_revision,
+            images: spec
+                .map(|s| template_images(&s.template))
+                .unwrap_or_default(),
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and daemonset name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// One HorizontalPodAutoscaler as listed by `kopsctl hpa`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct HpaSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+
+    /// Scaled object as `<Kind>/<name>`.
+    pub target: String,
+
+    /// One entry per spec metric, as `<name>: <current>/<target>`.
+    pub metrics: Vec<String>,
+    pub min_replicas: i32,
+    pub max_replicas: i32,
+    pub current_replicas: i32,
+    pub desired_replicas: i32,
+
+    /// Last time the HPA changed the replica count, as Unix epoch
+    /// milliseconds (UTC).
+    pub last_scale_epoch_ms: Option<i64>,
+}
+
+impl HpaSummary {
+    pub fn from_hpa(
+        cluster: &str,
+        hpa: &k8s_openapi::api::autoscaling::v2::HorizontalPodAutoscaler,
+    ) -> Option<Self> {
+        let meta = &hpa.metadata;
+        let spec = hpa.spec.as_ref();
+        let status = hpa.status.as_ref();
+
+        let target = spec
+            .map(|s| {
+                format!(
+                    "{}/{}",
+                    s.scale_target_ref.kind, s.scale_target_ref.name
+                )
+            })
+            .unwrap_or_default();
+
+        // kubectl pairs spec and status metrics by position as well.
+        let current: &[_] = status
+            .and_then(|s| s.current_metrics.as_deref())
+            .unwrap_or_default();
+        let metrics = spec
+            .and_then(|s| s.metrics.as_ref())
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(i, m)| {
+                let (name, target) = metric_target(m);
+                let value = current
+                    .get(i)
+                    .and_then(metric_current)
+                    .unwrap_or_else(|| "<unknown>".to_string());
+                format!("{name}: {value}/{target}")
+            })
+            .collect();
+
+        Some(HpaSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            target,
+            metrics,
+            min_replicas: spec.and_then(|s| s.min_replicas).unwrap_or(1),
+            max_replicas: spec.map_or(0, |s| s.max_replicas),
+            current_replicas: status
+                .and_then(|s| s.current_replicas)
+                .unwrap_or(0),
+            desired_replicas: status.map_or(0, |s| s.desired_replicas),
+            last_scale_epoch_ms: status
+                .and_then(|s| s.last_scale_time.as_ref())
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and HPA name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// Metric name and formatted target of an HPA spec metric.
+fn metric_target(
+    m: &k8s_openapi::api::autoscaling::v2::MetricSpec,
+) -> (String, String) {
+    let (name, target) = if let Some(r) = &m.resource {
+        (r.name.clone(), &r.target)
+    } else if let Some(r) = &m.container_resource {
+        (format!("{}/{}", r.container, r.name), &r.target)
+    } else if let Some(p) = &m.pods {
+        (p.metric.name.clone(), &p.target)
+    } else if let Some(o) = &m.object {
+        (o.metric.name.clone(), &o.target)
+    } else if let Some(e) = &m.external {
+        (e.metric.name.clone(), &e.target)
+    } else {
+        return (m.type_.clone(), "<unknown>".to_string());
+    };
+
+    let target = if let Some(u) = target.average_utilization {
+        format!("{u}%")
+    } else if let Some(v) = &target.average_value {
+        v.0.clone()
+    } else if let Some(v) = &target.value {
+        v.0.clone()
+    } else {
+        "<unknown>".to_string()
+    };
+
+    (name, target)
+}
+
+/// Formatted current value of an HPA status metric.
+fn metric_current(
+    m: &k8s_openapi::api::autoscaling::v2::MetricStatus,
+) -> Option<String> {
+    let current = if let Some(r) = &m.resource {
+        &r.current
+    } else if let Some(r) = &m.container_resource {
+        &r.current
+    } else if let Some(p) = &m.pods {
+        &p.current
+    } else if let Some(o) = &m.object {
+        &o.current
+    } else if let Some(e) = &m.external {
+        &e.current
+    } else {
+        return None;
+    };
+
+    if let Some(u) = current.average_utilization {
+        Some(format!("{u}%"))
+    } else if let Some(v) = &current.average_value {
+        Some(v.0.clone())
+    } else {
+        current.value.as_ref().map(|v| v.0.clone())
+    }
+}
+
+/// Images of the containers of a pod template, in spec order.
+fn template_images(
+    template: &k8s_openapi::api::core::v1::PodTemplateSpec,
+) -> Vec<String> {
+    template
+        .spec
+        .as_ref()
+        .map(|s| s.containers.iter().filter_map(|c| c.image.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// Restarts of one container as listed by `kopsctl restarts`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct RestartSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub pod: String,
+    pub container: String,
+
+    /// Restarts observed within the requested window.
+    pub restarts: u32,
+    pub last_restart_epoch_ms: i64,
+
+    /// Termination reason and exit code of the most recent restart.
+    pub reason: Option<String>,
+    pub exit_code: Option<i32>,
+}
+
+impl RestartSummary {
+    /// Explicit ordering key: last restart, cluster, namespace, pod and
+    /// container.
+    pub fn sort_key(&self) -> (i64, &str, &str, &str, &str) {
+        (
+            self.last_restart_epoch_ms,
+            &self.cluster,
+            &self.namespace,
+            &self.pod,
+            &self.container,
+        )
+    }
+}
+
+/// One container restart as listed by `kopsctl history`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct RestartEntry {
+    pub cluster: String,
+    pub namespace: String,
+    pub pod: String,
+    pub container: String,
+
+    /// When the container terminated, as Unix epoch milliseconds (UTC).
+    pub at_epoch_ms: i64,
+
+    /// Restarts folded into this entry, when several happened between
+    /// two observations of the pod.
+    pub count: u32,
+    pub reason: Option<String>,
+    pub exit_code: Option<i32>,
+
+    /// Warning events about the pod or its node shortly before the
+    /// restart, oldest first.
+    pub causes: Vec<EventSummary>,
+}
+
+impl RestartEntry {
+    /// Explicit ordering key: restart time, cluster and container.
+    pub fn sort_key(&self) -> (i64, &str, &str) {
+        (self.at_epoch_ms, &self.cluster, &self.container)
+    }
+}
+
+/// One Event as listed by `kopsctl events`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct EventSummary {
+    pub cluster: String,
+    pub namespace: String,
+
+    /// Name of the Event object itself.
+    pub name: String,
+
+    /// `Normal` or `Warning`.
+    pub type_: String,
+    pub reason: String,
+
+    /// Involved object as `<Kind>/<name>`.
+    pub object: String,
+    pub message: String,
+
+    /// How many times the event was seen.
+    pub count: i32,
+
+    /// First time the event was seen, as Unix epoch milliseconds (UTC).
+    pub first_seen_epoch_ms: Option<i64>,
+
+    /// Last time the event was seen, as Unix epoch milliseconds (UTC).
+    pub last_seen_epoch_ms: Option<i64>,
+}
+
+impl EventSummary {
+    pub fn from_event(
+        cluster: &str,
+        event: &k8s_openapi::api::core::v1::Event,
+    ) -> Option<Self> {
+        let meta = &event.metadata;
+
+        let name = meta.name.clone()?;
+        let namespace =
+            meta.namespace.clone().unwrap_or_else(|| "default".to_string());
+
+        let involved = &event.involved_object;
+        let object = format!(
+            "{}/{}",
+            involved.kind.as_deref().unwrap_or_default(),
+            involved.name.as_deref().unwrap_or_default()
+        );
+
+        // Newer emitters only set `eventTime`, older ones only the
+        // `*Timestamp` fields.
+        let last_seen_epoch_ms = event
+            .last_timestamp
+            .as_ref()
+            .map(|t| t.0.timestamp_millis())
+            .or_else(|| {
+                event.event_time.as_ref().map(|t| t.0.timestamp_millis())
+            })
+            .or_else(|| {
+                event.first_timestamp.as_ref().map(|t| t.0.timestamp_millis())
+            })
+            .or_else(|| {
+                meta.creation_timestamp
+                    .as_ref()
+                    .map(|t| t.0.timestamp_millis())
+            });
+        let first_seen_epoch_ms = event
+            .first_timestamp
+            .as_ref()
+            .map(|t| t.0.timestamp_millis())
+            .or_else(|| {
+                event.event_time.as_ref().map(|t| t.0.timestamp_millis())
+            })
+            .or(last_seen_epoch_ms);
+
+        Some(EventSummary {
+            cluster: cluster.to_string(),
+            namespace,
+            name,
+            type_: event.type_.clone().unwrap_or_else(|| "Normal".into()),
+            reason: event.reason.clone().unwrap_or_default(),
+            object,
+            message: event.message.clone().unwrap_or_default(),
+            count: event.count.unwrap_or(1),
+            first_seen_epoch_ms,
+            last_seen_epoch_ms,
+        })
+    }
+
+    /// Identity of the occurrence this event reports, shared by the
+    /// separate Event objects emitted for each repetition: cluster,
+    /// namespace, object, type, reason and message.
+    ///
+    /// The event recorder throttles bursts of similar events into one
+    /// whose message is prefixed with "(combined from similar events): ";
+    /// the prefix is left out so it coalesces with the others.
+    pub fn occurrence_key(&self) -> (&str, &str, &str, &str, &str, &str) {
+        let message = self
+            .message
+            .strip_prefix("(combined from similar events): ")
+            .unwrap_or(&self.message);
+
+        (
+            &self.cluster,
+            &self.namespace,
+            &self.object,
+            &self.type_,
+            &self.reason,
+            message,
+        )
+    }
+
+    /// Fold `other`, a repetition of the same occurrence, into this
+    /// event: the counts add up, and the first and last seen times widen.
+    /// The newest of the two gives the name.
+    pub fn coalesce(&mut self, other: EventSummary) {
+        if other.last_seen_epoch_ms > self.last_seen_epoch_ms {
+            self.name = other.name;
+            self.message = other.message;
+            self.last_seen_epoch_ms = other.last_seen_epoch_ms;
+        }
+        self.first_seen_epoch_ms =
+            match (self.first_seen_epoch_ms, other.first_seen_epoch_ms) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        self.count = self.count.saturating_add(other.count);
+    }
+
+    /// Explicit ordering key: last seen time, then cluster, namespace and
+    /// event name to break ties.
+    pub fn sort_key(&self) -> (Option<i64>, &str, &str, &str) {
+        (self.last_seen_epoch_ms, &self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// One Namespace as listed by `kopsctl namespaces`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct NamespaceSummary {
+    pub cluster: String,
+    pub name: String,
+
+    /// `Active` or `Terminating`.
+    pub phase: Option<String>,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+impl NamespaceSummary {
+    pub fn from_namespace(
+        cluster: &str,
+        ns: &k8s_openapi::api::core::v1::Namespace,
+    ) -> Option<Self> {
+        Some(NamespaceSummary {
+            cluster: cluster.to_string(),
+            name: ns.metadata.name.clone()?,
+            phase: ns.status.as_ref().and_then(|s| s.phase.clone()),
+            created_at_epoch_ms: ns
+                .metadata
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster and namespace name.
+    pub fn sort_key(&self) -> (&str, &str) {
+        (&self.cluster, &self.name)
+    }
+}
+
+/// One ConfigMap as listed by `kopsctl configmap list`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ConfigMapSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+
+    /// Number of keys in `data` and `binaryData`.
+    pub keys: u32,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+impl ConfigMapSummary {
+    pub fn from_config_map(
+        cluster: &str,
+        cm: &k8s_openapi::api::core::v1::ConfigMap,
+    ) -> Option<Self> {
+        let meta = &cm.metadata;
+        let keys = cm.data.as_ref().map_or(0, |d| d.len())
+            + cm.binary_data.as_ref().map_or(0, |d| d.len());
+
+        Some(ConfigMapSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            keys: keys as u32,
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and configmap name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// Contents of a single ConfigMap.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ConfigMapData {
+    pub namespace: String,
+    pub name: String,
+
+    /// `data`, sorted by key.
+    pub data: Vec<(String, String)>,
+
+    /// `binaryData`, sorted by key.
+    pub binary_data: Vec<(String, Vec<u8>)>,
+}
+
+impl ConfigMapData {
+    pub fn from_config_map(
+        cm: &k8s_openapi::api::core::v1::ConfigMap,
+    ) -> Option<Self> {
+        let meta = &cm.metadata;
+
+        // Both maps are BTreeMaps, so keys come out sorted.
+        let data = cm
+            .data
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let binary_data = cm
+            .binary_data
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.clone(), v.0.clone()))
+            .collect();
+
+        Some(ConfigMapData {
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            data,
+            binary_data,
+        })
+    }
+}
+
+/// One Secret as listed by `kopsctl secrets`; never carries values.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct SecretSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+
+    /// e.g. `Opaque` or `kubernetes.io/tls`.
+    pub type_: String,
+
+    /// Key names in `data`, sorted.
+    pub keys: Vec<String>,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+impl SecretSummary {
+    pub fn from_secret(
+        cluster: &str,
+        secret: &k8s_openapi::api::core::v1::Secret,
+    ) -> Option<Self> {
+        let meta = &secret.metadata;
+
+        Some(SecretSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            type_: secret.type_.clone().unwrap_or_else(|| "Opaque".into()),
+            keys: secret
+                .data
+                .iter()
+                .flatten()
+                .map(|(k, _)| k.clone())
+                .collect(),
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and secret name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// Current CPU and memory usage of a pod or node, from metrics-server.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub cluster: String,
+
+    /// Unset for nodes.
+    pub namespace: Option<String>,
+    pub name: String,
+
+    /// CPU in millicores.
+    pub cpu_millis: u64,
+
+    /// Memory (working set) in bytes.
+    pub memory_bytes: u64,
+}
+
+impl ResourceUsage {
+    /// Explicit ordering key: cluster, namespace and name.
+    pub fn sort_key(&self) -> (&str, Option<&str>, &str) {
+        (&self.cluster, self.namespace.as_deref(), &self.name)
+    }
+}
+
+/// One Service as listed by `kopsctl services`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ServiceSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+
+    /// `ClusterIP`, `NodePort`, `LoadBalancer` or `ExternalName`.
+    pub type_: String,
+    pub cluster_ip: Option<String>,
+
+    /// Load balancer IPs/hostnames, `externalIPs` and `externalName`.
+    pub external: Vec<String>,
+
+    /// Ports as `<port>/<protocol>` or `<port>:<nodePort>/<protocol>`.
+    pub ports: Vec<String>,
+
+    /// Endpoints ready to serve, over all EndpointSlices of the service.
+    pub ready_endpoints: u32,
+    pub total_endpoints: u32,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+impl ServiceSummary {
+    /// `endpoints` is the (ready, total) endpoint count of the service.
+    pub fn from_service(
+        cluster: &str,
+        svc: &k8s_openapi::api::core::v1::Service,
+        endpoints: (u32, u32),
+    ) -> Option<Self> {
+        let meta = &svc.metadata;
+        let spec = svc.spec.as_ref();
+
+        let mut external: Vec<String> = svc
+            .status
+            .as_ref()
+            .and_then(|s| s.load_balancer.as_ref())
+            .and_then(|lb| lb.ingress.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|i| i.ip.clone().or_else(|| i.hostname.clone()))
+            .collect();
+        external.extend(
+            spec.and_then(|s| s.external_ips.clone()).into_iter().flatten(),
+        );
+        external.extend(spec.and_then(|s| s.external_name.clone()));
+
+        let ports = spec
+            .and_then(|s| s.ports.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|p| {
+                let protocol = p.protocol.as_deref().unwrap_or("TCP");
+                match p.node_port {
+                    Some(node_port) => {
+                        format!("{}:{node_port}/{protocol}", p.port)
+                    }
+                    None => format!("{}/{protocol}", p.port),
+                }
+            })
+            .collect();
+
+        Some(ServiceSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            type_: spec
+                .and_then(|s| s.type_.clone())
+                .unwrap_or_else(|| "ClusterIP".into()),
+            cluster_ip: spec.and_then(|s| s.cluster_ip.clone()),
+            external,
+            ports,
+            ready_endpoints: endpoints.0,
+            total_endpoints: endpoints.1,
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and service name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// One Ingress as listed by `kopsctl ingresses`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct IngressSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+    pub class: Option<String>,
+
+    /// Routes in spec order, the default backend (if any) first.
+    pub routes: Vec<IngressRoute>,
+
+    /// Secret names from `spec.tls`.
+    pub tls_secrets: Vec<String>,
+
+    /// Load balancer IPs/hostnames.
+    pub addresses: Vec<String>,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+/// A host/path pair and the backend it sends traffic to.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct IngressRoute {
+    /// Unset for rules matching any host.
+    pub host: Option<String>,
+
+    /// Unset for the default backend.
+    pub path: Option<String>,
+
+    /// `<service>:<port>` or `<Kind>/<name>` for resource backends.
+    pub backend: String,
+}
+
+impl IngressSummary {
+    pub fn from_ingress(
+        cluster: &str,
+        ing: &k8s_openapi::api::networking::v1::Ingress,
+    ) -> Option<Self> {
+        let meta = &ing.metadata;
+        let spec = ing.spec.as_ref();
+
+        let mut routes = Vec::new();
+        if let Some(backend) = spec.and_then(|s| s.default_backend.as_ref()) {
+            routes.push(IngressRoute {
+                host: None,
+                path: None,
+                backend: format_backend(backend),
+            });
+        }
+        for rule in spec.and_then(|s| s.rules.as_ref()).into_iter().flatten() {
+            let paths = rule.http.as_ref().map(|h| h.paths.as_slice());
+            for p in paths.unwrap_or_default() {
+                routes.push(IngressRoute {
+                    host: rule.host.clone(),
+                    path: Some(p.path.clone().unwrap_or_else(|| "/".into())),
+                    backend: format_backend(&p.backend),
+                });
+            }
+        }
+
+        let tls_secrets = spec
+            .and_then(|s| s.tls.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|t| t.secret_name.clone())
+            .collect();
+
+        let addresses = ing
+            .status
+            .as_ref()
+            .and_then(|s| s.load_balancer.as_ref())
+            .and_then(|lb| lb.ingress.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|i| i.ip.clone().or_else(|| i.hostname.clone()))
+            .collect();
+
+        Some(IngressSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            class: spec.and_then(|s| s.ingress_class_name.clone()),
+            routes,
+            tls_secrets,
+            addresses,
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and ingress name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+fn format_backend(
+    b: &k8s_openapi::api::networking::v1::IngressBackend,
+) -> String {
+    if let Some(svc) = &b.service {
+        let port = svc.port.as_ref().and_then(|p| {
+            p.name.clone().or_else(|| p.number.map(|n| n.to_string()))
+        });
+        return match port {
+            Some(port) => format!("{}:{port}", svc.name),
+            None => svc.name.clone(),
+        };
+    }
+
+    match &b.resource {
+        Some(r) => format!("{}/{}", r.kind, r.name),
+        None => "<none>".to_string(),
+    }
+}
+
+/// One Job as listed by `kopsctl jobs`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct JobSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+
+    /// CronJob that created this job, if any.
+    pub cron_job: Option<String>,
+
+    /// `Complete`, `Failed`, `Suspended` or `Running`.
+    pub status: String,
+
+    /// `spec.completions` (1 when unset).
+    pub completions: i32,
+    pub succeeded: i32,
+    pub failed: i32,
+    pub active: i32,
+
+    /// Reason and message of the `Failed` condition.
+    pub failure_reason: Option<String>,
+
+    /// Start and completion times as Unix epoch milliseconds (UTC).
+    pub started_at_epoch_ms: Option<i64>,
+    pub completed_at_epoch_ms: Option<i64>,
+}
+
+impl JobSummary {
+    pub fn from_job(
+        cluster: &str,
+        job: &k8s_openapi::api::batch::v1::Job,
+    ) -> Option<Self> {
+        let meta = &job.metadata;
+        let spec = job.spec.as_ref();
+        let status = job.status.as_ref();
+
+        let cron_job = meta
+            .owner_references
+            .iter()
+            .flatten()
+            .find(|o| o.kind == "CronJob")
+            .map(|o| o.name.clone());
+
+        let condition = |type_: &str| {
+            status
+                .and_then(|s| s.conditions.as_ref())
+                .into_iter()
+                .flatten()
+                .find(|c| c.type_ == type_ && c.status == "True")
+        };
+
+        let failed_condition = condition("Failed");
+        let failure_reason = failed_condition.map(|c| {
+            match (c.reason.as_deref(), c.message.as_deref()) {
+                (Some(r), Some(m)) => format!("{r}: {m}"),
+                (Some(r), None) => r.to_string(),
+                (None, Some(m)) => m.to_string(),
+                (None, None) => "Failed".to_string(),
+            }
+        });
+
+        let suspended = spec.and_then(|s| s.suspend).unwrap_or(false);
+        let job_status = if condition("Complete").is_some() {
+            "Complete"
+        } else if failed_condition.is_some() {
+            "Failed"
+        } else if suspended {
+            "Suspended"
+        } else {
+            "Running"
+        };
+
+        Some(JobSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            cron_job,
+            status: job_status.to_string(),
+            completions: spec.and_then(|s| s.completions).unwrap_or(1),
+            succeeded: status.and_then(|s| s.succeeded).unwrap_or(0),
+            failed: status.and_then(|s| s.failed).unwrap_or(0),
+            active: status.and_then(|s| s.active).unwrap_or(0),
+            failure_reason,
+            started_at_epoch_ms: status
+                .and_then(|s| s.start_time.as_ref())
+                .map(|t| t.0.timestamp_millis()),
+            completed_at_epoch_ms: status
+                .and_then(|s| s.completion_time.as_ref())
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and job name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// One CronJob as listed by `kopsctl cronjobs`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct CronJobSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+    pub schedule: String,
+    pub suspend: bool,
+
+    /// Number of jobs currently running.
+    pub active: u32,
+
+    /// Last schedule and last success as Unix epoch milliseconds (UTC).
+    pub last_schedule_epoch_ms: Option<i64>,
+    pub last_successful_epoch_ms: Option<i64>,
+}
+
+impl CronJobSummary {
+    pub fn from_cron_job(
+        cluster: &str,
+        cj: &k8s_openapi::api::batch::v1::CronJob,
+    ) -> Option<Self> {
+        let meta = &cj.metadata;
+        let spec = cj.spec.as_ref();
+        let status = cj.status.as_ref();
+
+        Some(CronJobSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            schedule: spec.map(|s| s.schedule.clone()).unwrap_or_default(),
+            suspend: spec.and_then(|s| s.suspend).unwrap_or(false),
+            active: status
+                .and_then(|s| s.active.as_ref())
+                .map_or(0, |a| a.len() as u32),
+            last_schedule_epoch_ms: status
+                .and_then(|s| s.last_schedule_time.as_ref())
+                .map(|t| t.0.timestamp_millis()),
+            last_successful_epoch_ms: status
+                .and_then(|s| s.last_successful_time.as_ref())
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and cronjob name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// One PersistentVolumeClaim as listed by `kopsctl pvc`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct PvcSummary {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+
+    /// `Pending`, `Bound` or `Lost`.
+    pub status: String,
+
+    /// Bound PersistentVolume.
+    pub volume: Option<String>,
+
+    /// Actual capacity once bound, requested size otherwise.
+    pub capacity: Option<String>,
+    pub access_modes: Vec<String>,
+    pub storage_class: Option<String>,
+
+    /// Pods mounting the claim, sorted.
+    pub consumers: Vec<String>,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+impl PvcSummary {
+    pub fn from_pvc(
+        cluster: &str,
+        pvc: &k8s_openapi::api::core::v1::PersistentVolumeClaim,
+        consumers: Vec<String>,
+    ) -> Option<Self> {
+        let meta = &pvc.metadata;
+        let spec = pvc.spec.as_ref();
+        let status = pvc.status.as_ref();
+
+        let storage = |m: Option<&BTreeMap<String, Quantity>>| {
+            m.and_then(|m| m.get("storage")).map(|q| q.0.clone())
+        };
+        let capacity = storage(status.and_then(|s| s.capacity.as_ref()))
+            .or_else(|| {
+                storage(
+                    spec.and_then(|s| s.resources.as_ref())
+                        .and_then(|r| r.requests.as_ref()),
+                )
+            });
+
+        Some(PvcSummary {
+            cluster: cluster.to_string(),
+            namespace: meta
+                .namespace
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            name: meta.name.clone()?,
+            status: status
+                .and_then(|s| s.phase.clone())
+                .unwrap_or_else(|| "Pending".into()),
+            volume: spec.and_then(|s| s.volume_name.clone()),
+            capacity,
+            access_modes: status
+                .and_then(|s| s.access_modes.clone())
+                .or_else(|| spec.and_then(|s| s.access_modes.clone()))
+                .unwrap_or_default(),
+            storage_class: spec.and_then(|s| s.storage_class_name.clone()),
+            consumers,
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster, namespace and claim name.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.namespace, &self.name)
+    }
+}
+
+/// A container image and the pods running it, for `kopsctl images`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ImageUsage {
+    pub cluster: String,
+
+    /// Image without tag or digest, e.g. `ghcr.io/org/app`.
+    pub repository: String,
+
+    /// Tag or `@<digest>`; `latest` when the reference has neither.
+    pub tag: String,
+
+    /// Number of running pods with a container using the image.
+    pub pods: u32,
+
+    /// Namespaces of those pods, sorted.
+    pub namespaces: Vec<String>,
+}
+
+impl ImageUsage {
+    /// Split an image reference into repository and tag (or digest).
+    pub fn split_image(image: &str) -> (&str, &str) {
+        if let Some(at) = image.find('@') {
+            return (&image[..at], &image[at..]);
+        }
+
+        // A ':' before the last '/' belongs to a registry port.
+        let name_start = image.rfind('/').map_or(0, |i| i + 1);
+        match image[name_start..].rfind(':') {
+            Some(i) => {
+                let at = name_start + i;
+                (&image[..at], &image[at + 1..])
+            }
+            None => (image, "latest"),
+        }
+    }
+
+    /// Explicit ordering key: cluster, repository and tag.
+    pub fn sort_key(&self) -> (&str, &str, &str) {
+        (&self.cluster, &self.repository, &self.tag)
+    }
+}
+
+/// One Node as listed by `kopsctl nodes`.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct NodeSummary {
+    pub cluster: String,
+    pub name: String,
+
+    /// `Ready`, `NotReady` or `Unknown`, with `,SchedulingDisabled` when
+    /// the node is cordoned.
+    pub status: String,
+
+    /// Roles from the `node-role.kubernetes.io/<role>` labels.
+    pub roles: Vec<String>,
+    pub kubelet_version: Option<String>,
+    pub instance_type: Option<String>,
+
+    /// Allocatable CPU and memory as reported by the kubelet.
+    pub allocatable_cpu: Option<String>,
+    pub allocatable_memory: Option<String>,
+
+    /// Creation time as Unix epoch milliseconds (UTC).
+    pub created_at_epoch_ms: Option<i64>,
+}
+
+impl NodeSummary {
+    pub fn from_node(
+        cluster: &str,
+        node: &k8s_openapi::api::core::v1::Node,
+    ) -> Option<Self> {
+        let meta = &node.metadata;
+        let status = node.status.as_ref();
+
+        let name = meta.name.clone()?;
+        let labels = meta.labels.clone().unwrap_or_default();
+
+        let roles = labels
+            .keys()
+            .filter_map(|k| k.strip_prefix("node-role.kubernetes.io/"))
+            .filter(|r| !r.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        let instance_type = labels
+            .get("node.kubernetes.io/instance-type")
+            .or_else(|| labels.get("beta.kubernetes.io/instance-type"))
+            .cloned();
+
+        let ready = status
+            .and_then(|s| s.conditions.as_ref())
+            .and_then(|cs| cs.iter().find(|c| c.type_ == "Ready"))
+            .map(|c| match c.status.as_str() {
+                "True" => "Ready",
+                "False" => "NotReady",
+                _ => "Unknown",
+            })
+            .unwrap_or("Unknown");
+
+        let unschedulable =
+            node.spec.as_ref().and_then(|s| s.unschedulable).unwrap_or(false);
+        let status_text = if unschedulable {
+            format!("{ready},SchedulingDisabled")
+        } else {
+            ready.to_string()
+        };
+
+        let allocatable = status.and_then(|s| s.allocatable.as_ref());
+
+        Some(NodeSummary {
+            cluster: cluster.to_string(),
+            name,
+            status: status_text,
+            roles,
+            kubelet_version: status
+                .and_then(|s| s.node_info.as_ref())
+                .map(|i| i.kubelet_version.clone()),
+            instance_type,
+            allocatable_cpu: allocatable
+                .and_then(|a| a.get("cpu"))
+                .map(|q| q.0.clone()),
+            allocatable_memory: allocatable
+                .and_then(|a| a.get("memory"))
+                .map(|q| q.0.clone()),
+            created_at_epoch_ms: meta
+                .creation_timestamp
+                .as_ref()
+                .map(|t| t.0.timestamp_millis()),
+        })
+    }
+
+    /// Explicit ordering key: cluster and node name.
+    pub fn sort_key(&self) -> (&str, &str) {
+        (&self.cluster, &self.name)
+    }
+}
+
+/// Who to page about a workload and which runbook to open, from the
+/// annotations of its pods, of the workload itself or of its namespace.
+///
+/// Each field is read from the first of its annotations that is set:
+/// `kops.paastel.io/<field>`, then the matching `a8r.io` one, if any.
+#[derive(
+    Clone, Debug, Default, PartialEq, Encode, Decode, Serialize, Deserialize,
+)]
+pub struct Ownership {
+    /// `kops.paastel.io/team`, or `a8r.io/owner`.
+    pub team: Option<String>,
+
+    /// `kops.paastel.io/oncall`, e.g. a pager rotation.
+    pub oncall: Option<String>,
+
+    /// `kops.paastel.io/slack-channel`, or `a8r.io/chat`.
+    pub slack_channel: Option<String>,
+
+    /// `kops.paastel.io/runbook-url`, or `a8r.io/runbook`.
+    pub runbook_url: Option<String>,
+}
+
+impl Ownership {
+    const TEAM: &[&str] = &["kops.paastel.io/team", "a8r.io/owner"];
+    const ONCALL: &[&str] = &["kops.paastel.io/oncall"];
+    const SLACK_CHANNEL: &[&str] =
+        &["kops.paastel.io/slack-channel", "a8r.io/chat"];
+    const RUNBOOK_URL: &[&str] =
+        &["kops.paastel.io/runbook-url", "a8r.io/runbook"];
+
+    pub fn from_annotations(annotations: &BTreeMap<String, String>) -> Self {
+        let read = |keys: &[&str]| {
+            keys.iter()
+                .filter_map(|k| annotations.get(*k))
+                .map(|v| v.trim())
+                .find(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        Ownership {
+            team: read(Self::TEAM),
+            oncall: read(Self::ONCALL),
+            slack_channel: read(Self::SLACK_CHANNEL),
+            runbook_url: read(Self::RUNBOOK_URL),
+        }
+    }
+
+    /// Whether `from_annotations` reads annotation `key`.
+    pub fn reads(key: &str) -> bool {
+        [Self::TEAM, Self::ONCALL, Self::SLACK_CHANNEL, Self::RUNBOOK_URL]
+            .iter()
+            .any(|keys| keys.contains(&key))
+    }
+
+    /// This ownership, with the fields it lacks taken from `other`.
+    pub fn or(self, other: Ownership) -> Ownership {
+        Ownership {
+            team: self.team.or(other.team),
+            oncall: self.oncall.or(other.oncall),
+            slack_channel: self.slack_channel.or(other.slack_channel),
+            runbook_url: self.runbook_url.or(other.runbook_url),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Ownership::default()
+    }
+
+    /// Team, on-call and channel, e.g. `payments, pay-primary, #payments`.
+    pub fn contacts(&self) -> String {
+        [&self.team, &self.oncall, &self.slack_channel]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Everything `kopsctl describe pod` shows about a pod.
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct PodDetail {
+    pub cluster: String,
+    pub namespace: String,
+    pub name: String,
+    pub node_name: Option<String>,
+    pub phase: Option<String>,
+    pub pod_ip: Option<String>,
+    pub start_time_epoch_ms: Option<i64>,
+    pub labels: Vec<(String, String)>,
+
+    /// Owner references as `<Kind>/<name>`.
+    pub owners: Vec<String>,
+
+    /// Init containers first, then regular containers, in spec order.
+    pub containers: Vec<ContainerDetail>,
+    pub conditions: Vec<PodConditionDetail>,
+    pub volumes: Vec<VolumeDetail>,
+
+    /// Tolerations rendered as `key=value:Effect` (or `key:Effect` for
+    /// `Exists`), with ` for Ns` when `tolerationSeconds` is set.
+    pub tolerations: Vec<String>,
+
+    /// Events involving the pod, oldest first.
+    pub events: Vec<EventSummary>,
+
+    /// Who to page about the pod; filled in by the daemon, which knows
+    /// its workload and namespace.
+    pub ownership: Option<Ownership>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct ContainerDetail {
+    pub name: String,
+    pub image: Option<String>,
+    pub init: bool,
+    pub ready: bool,
+    pub restart_count: i32,
+
+    /// Current state, e.g. `Running`, `Waiting (CrashLoopBackOff)` or
+    /// `Terminated (Error, exit code 1)`.
+    pub state: String,
+
+    /// Declared ports as `<port>/<protocol>`.
+    pub ports: Vec<String>,
+    pub requests: Vec<(String, String)>,
+    pub limits: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct PodConditionDetail {
+    pub type_: String,
+    pub status: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct VolumeDetail {
+    pub name: String,
+
+    /// Volume source kind, e.g. `ConfigMap`, `Secret` or `EmptyDir`.
+    pub kind: String,
+
+    /// Name of the referenced object or path, when the source has one.
+    pub source: Option<String>,
+}
+
+impl PodDetail {
+    pub fn from_pod(
+        cluster: &str,
+        pod: &k8s_openapi::api::core::v1::Pod,
+        events: Vec<EventSummary>,
+    ) -> Option<Self> {
+        let meta = &pod.metadata;
+        let spec = pod.spec.as_ref();
+        let status = pod.status.as_ref();
+
+        let name = meta.name.clone()?;
+        let namespace =
+            meta.namespace.clone().unwrap_or_else(|| "default".to_string());
+
+        let labels = meta
+            .labels
+            .as_ref()
+            .map(|l| l.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default();
+
+        let owners = meta
+            .owner_references
+            .as_ref()
+            .map(|o| {
+                o.iter().map(|r| format!("{}/{}", r.kind, r.name)).collect()
+            })
+            .unwrap_or_default();
+
+        let mut containers = Vec::new();
+        if let Some(spec) = spec {
+            let init_statuses =
+                status.and_then(|s| s.init_container_statuses.as_deref());
+            for c in spec.init_containers.iter().flatten() {
+                containers.push(ContainerDetail::new(c, true, init_statuses));
+            }
+
+            let statuses =
+                status.and_then(|s| s.container_statuses.as_deref());
+            for c in &spec.containers {
+                containers.push(ContainerDetail::new(c, false, statuses));
+            }
+        }
+
+        let conditions = status
+            .and_then(|s| s.conditions.as_ref())
+            .map(|cs| {
+                cs.iter()
+                    .map(|c| PodConditionDetail {
+                        type_: c.type_.clone(),
+                        status: c.status.clone(),
+                        reason: c.reason.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let volumes = spec
+            .and_then(|s| s.volumes.as_ref())
+            .map(|vs| vs.iter().map(VolumeDetail::from_volume).collect())
+            .unwrap_or_default();
+
+        let tolerations = spec
+            .and_then(|s| s.tolerations.as_ref())
+            .map(|ts| ts.iter().map(format_toleration).collect())
+            .unwrap_or_default();
+
+        Some(PodDetail {
+            cluster: cluster.to_string(),
+            namespace,
+            name,
+            node_name: spec.and_then(|s| s.node_name.clone()),
+            phase: status.and_then(|s| s.phase.clone()),
+            pod_ip: status.and_then(|s| s.pod_ip.clone()),
+            start_time_epoch_ms: status
+                .and_then(|s| s.start_time.as_ref())
+                .map(|t| t.0.timestamp_millis()),
+            labels,
+            owners,
+            containers,
+            conditions,
+            volumes,
+            tolerations,
+            events,
+            ownership: None,
+        })
+    }
+}
+
+impl ContainerDetail {
+    fn new(
+        container: &k8s_openapi::api::core::v1::Container,
+        init: bool,
+        statuses: Option<&[k8s_openapi::api::core::v1::ContainerStatus]>,
+    ) -> Self {
+        let status = statuses
+            .and_then(|ss| ss.iter().find(|s| s.name == container.name));
+
+        let state = status
+            .and_then(|s| s.state.as_ref())
+            .map(|st| {
+                if let Some(w) = &st.waiting {
+                    match &w.reason {
+                        Some(r) => format!("Waiting ({r})"),
+                        None => "Waiting".to_string(),
+                    }
+                } else if let Some(t) = &st.terminated {
+                    match &t.reason {
+                        Some(r) => format!(
+                            "Terminated ({r}, exit code {})",
+                            t.exit_code
+                        ),
+                        None => {
+                            format!("Terminated (exit code {})", t.exit_code)
+                        }
+                    }
+                } else if st.running.is_some() {
+                    "Running".to_string()
+                } else {
+                    "Unknown".to_string()
+                }
+            })
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let ports = container
+            .ports
+            .iter()
+            .flatten()
+            .map(|p| {
+                format!(
+                    "{}/{}",
+                    p.container_port,
+                    p.protocol.as_deref().unwrap_or("TCP")
+                )
+            })
+            .collect();
+
+        let resources = container.resources.as_ref();
+        ContainerDetail {
+            name: container.name.clone(),
+            image: container.image.clone(),
+            init,
+            ready: status.is_some_and(|s| s.ready),
+            restart_count: status.map(|s| s.restart_count).unwrap_or(0),
+            state,
+            ports,
+            requests: quantities(resources.and_then(|r| r.requests.as_ref())),
+            limits: quantities(resources.and_then(|r| r.limits.as_ref())),
+        }
+    }
+}
+
+impl VolumeDetail {
+    fn from_volume(v: &k8s_openapi::api::core::v1::Volume) -> Self {
+        let (kind, source) = if let Some(s) = &v.config_map {
+            ("ConfigMap", Some(s.name.clone()))
+        } else if let Some(s) = &v.secret {
+            ("Secret", s.secret_name.clone())
+        } else if let Some(s) = &v.persistent_volume_claim {
+            ("PersistentVolumeClaim", Some(s.claim_name.clone()))
+        } else if let Some(s) = &v.host_path {
+            ("HostPath", Some(s.path.clone()))
+        } else if v.empty_dir.is_some() {
+            ("EmptyDir", None)
+        } else if v.projected.is_some() {
+            ("Projected", None)
+        } else if v.downward_api.is_some() {
+            ("DownwardAPI", None)
+        } else {
+            ("Other", None)
+        };
+
+        VolumeDetail { name: v.name.clone(), kind: kind.to_string(), source }
+    }
+}
+
+fn quantities(
+    map: Option<&BTreeMap<String, Quantity>>,
+) -> Vec<(String, String)> {
+    map.map(|m| m.iter().map(|(k, v)| (k.clone(), v.0.clone())).collect())
+        .unwrap_or_default()
+}
+
+fn format_toleration(t: &k8s_openapi::api::core::v1::Toleration) -> String {
+    let key = t.key.as_deref().unwrap_or("");
+    let mut out = match (t.operator.as_deref(), t.value.as_deref()) {
+        (Some("Exists"), _) | (_, None) => key.to_string(),
+        (_, Some(value)) => format!("{key}={value}"),
+    };
+
+    if let Some(effect) = &t.effect {
+        out.push(':');
+        out.push_str(effect);
+    }
+    if let Some(secs) = t.toleration_seconds {
+        out.push_str(&format!(" for {secs}s"));
+    }
+
+    out
+}
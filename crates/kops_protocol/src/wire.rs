@@ -14,17 +14,143 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
-use std::{fmt, io};
+use std::{fmt, io, time::Duration};
 
 use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
-/// Error type for framed bincode I/O on the wire.
+use crate::{KopsError, Response};
+
+/// Protocol version of this build, the only one clients offer.
+///
+/// Bump when `Request` or `Response` change in a way an older peer would
+/// fail to decode:
+///
+/// - 1: plain frames.
+/// - 2: frames may be zstd-compressed, see `Codec`.
+/// - 3: paginated pod listings.
+/// - 4: heartbeats on multiplexed and exec connections.
+/// - 5: sessions, cluster management, subscriptions and the wider pod
+///   summaries; `Request::Heartbeat` declared last.
+pub const PROTOCOL_VERSION: u32 = 5;
+
+/// Versions the daemon accepts, newest first.
+///
+/// A previous version belongs here only while `Request` and `Response`
+/// still decode its messages; none does, the variants of 3 and 4 were
+/// renumbered since.
+pub const PROTOCOL_VERSIONS: &[u32] = &[PROTOCOL_VERSION];
+
+/// Payloads smaller than this are never compressed.
+pub const COMPRESS_THRESHOLD: usize = 16 * 1024;
+
+/// Length prefix bit flagging a zstd-compressed payload.
+const COMPRESSED: u32 = 1 << 31;
+
+/// Largest payload accepted from a peer, before and after decompression,
+/// so that a length prefix cannot make the reader allocate at will.
+pub const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// How often each side of a long-lived connection shows it is alive:
+/// `Request::Heartbeat` and `Frame::Heartbeat` on multiplexed ones,
+/// `ExecFrame::Heartbeat` both ways on exec sessions.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Silence after which a peer is considered dead, unless configured
+/// otherwise. Any message counts, not only heartbeats.
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Encoding of the messages on a connection.
+///
+/// Both are framed the same way, a big-endian `u32` length followed by
+/// the payload. The client picks one by the way it encodes its `Hello`;
+/// the daemon answers in kind for the rest of the connection.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Encoding {
+    /// bincode with the standard configuration, used by kopsctl.
+    #[default]
+    Bincode,
+
+    /// JSON with serde's externally tagged enums, for tooling that cannot
+    /// link bincode (shell scripts, editor plugins).
+    Json,
+}
+
+impl Encoding {
+    /// Tell the encoding of a `Hello` payload: a JSON one is an object,
+    /// a bincode one starts with the length of `protocol_versions`.
+    fn detect(buf: &[u8]) -> Self {
+        let first = buf.iter().find(|b| !b.is_ascii_whitespace());
+        if first == Some(&b'{') { Encoding::Json } else { Encoding::Bincode }
+    }
+
+    fn encode<T>(self, msg: &T) -> Result<Vec<u8>, WireError>
+    where
+        T: Encode + Serialize,
+    {
+        match self {
+            Encoding::Bincode => {
+                let config = bincode::config::standard();
+                Ok(bincode::encode_to_vec(msg, config)?)
+            }
+            Encoding::Json => Ok(serde_json::to_vec(msg)?),
+        }
+    }
+
+    fn decode<T>(self, buf: &[u8]) -> Result<T, WireError>
+    where
+        T: Decode<()> + DeserializeOwned,
+    {
+        match self {
+            Encoding::Bincode => {
+                let config = bincode::config::standard();
+                let (msg, _len) = bincode::decode_from_slice(buf, config)?;
+                Ok(msg)
+            }
+            Encoding::Json => Ok(serde_json::from_slice(buf)?),
+        }
+    }
+}
+
+/// How messages are written on a connection, as settled by the handshake.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Codec {
+    pub encoding: Encoding,
+
+    /// Whether payloads of at least `COMPRESS_THRESHOLD` bytes are sent
+    /// zstd-compressed, with the top bit of their length prefix set.
+    /// Compressed payloads are always accepted when reading.
+    pub compress: bool,
+}
+
+impl From<Encoding> for Codec {
+    fn from(encoding: Encoding) -> Self {
+        Codec { encoding, compress: false }
+    }
+}
+
+/// Error type for framed I/O on the wire.
 #[derive(Debug)]
 pub enum WireError {
     Io(io::Error),
     BinDecode(bincode::error::DecodeError),
     BinEncode(bincode::error::EncodeError),
+    Json(serde_json::Error),
+
+    /// The peers share no protocol version.
+    Incompatible {
+        ours: Vec<u32>,
+        theirs: Vec<u32>,
+    },
+
+    /// The peer closed the connection or sent garbage during the
+    /// handshake, which is what a release without one does.
+    NoHandshake,
+
+    /// A frame announced, or would carry, a payload over `MAX_FRAME_LEN`
+    /// bytes.
+    TooLarge(usize),
 }
 
 impl fmt::Display for WireError {
@@ -33,6 +159,21 @@ impl fmt::Display for WireError {
             WireError::Io(e) => write!(f, "I/O error: {e}"),
             WireError::BinDecode(e) => write!(f, "bincode decode error: {e}"),
             WireError::BinEncode(e) => write!(f, "bincode encode error: {e}"),
+            WireError::Json(e) => write!(f, "JSON error: {e}"),
+            WireError::Incompatible { ours, theirs } => write!(
+                f,
+                "protocol mismatch: versions {ours:?} here, {theirs:?} on \
+                 the other side; please upgrade the older of kopsctl and kopsd"
+            ),
+            WireError::NoHandshake => write!(
+                f,
+                "kopsd did not answer the protocol handshake; it is probably \
+                 older than kopsctl, please upgrade and restart it"
+            ),
+            WireError::TooLarge(len) => write!(
+                f,
+                "frame of {len} bytes is over the limit of {MAX_FRAME_LEN}"
+            ),
         }
     }
 }
@@ -57,6 +198,123 @@ impl From<bincode::error::EncodeError> for WireError {
     }
 }
 
+impl From<serde_json::Error> for WireError {
+    fn from(e: serde_json::Error) -> Self {
+        WireError::Json(e)
+    }
+}
+
+/// First message on every connection, sent by the client.
+///
+/// `Hello` and `HelloAck` must never change shape: they are what lets
+/// mismatched releases tell each other apart.
+///
+/// A JSON client sends `{"protocol_versions":[5]}`, with
+/// `PROTOCOL_VERSION`, and gets `{"Accepted":{"protocol_version":5}}`
+/// back.
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub struct Hello {
+    /// Versions the client speaks, newest first.
+    pub protocol_versions: Vec<u32>,
+}
+
+/// Daemon reply to `Hello`.
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub enum HelloAck {
+    /// Version used for the rest of the connection.
+    Accepted { protocol_version: u32 },
+
+    /// None of the offered versions is supported; the daemon closes the
+    /// connection.
+    Rejected { protocol_versions: Vec<u32> },
+}
+
+/// Frame of a reply to `Request::Stream`, tagged with the request id.
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub enum Frame {
+    /// The request was accepted; chunks follow.
+    Start { id: u32 },
+
+    /// One response of the reply.
+    Chunk { id: u32, response: Box<Response> },
+
+    /// The reply is complete.
+    End { id: u32 },
+
+    /// The request failed; nothing else follows for it.
+    Error { id: u32, error: KopsError },
+
+    /// Keepalive sent every `HEARTBEAT_INTERVAL`, tied to no request.
+    Heartbeat,
+}
+
+impl Frame {
+    /// Id of the request the frame belongs to, 0 (never a request id)
+    /// for heartbeats.
+    pub fn id(&self) -> u32 {
+        match self {
+            Frame::Start { id }
+            | Frame::Chunk { id, .. }
+            | Frame::End { id }
+            | Frame::Error { id, .. } => *id,
+            Frame::Heartbeat => 0,
+        }
+    }
+}
+
+/// Frame exchanged on a connection after it switched to an exec session.
+///
+/// Once `Request::Exec` is accepted both sides stop exchanging
+/// `Request`/`Response` and send `ExecFrame`s in both directions until the
+/// daemon sends `Exit` or `Error`, or the client disconnects.
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub enum ExecFrame {
+    /// Client to daemon: bytes for the process stdin.
+    Stdin(Vec<u8>),
+
+    /// Client to daemon: local stdin reached end of file.
+    StdinEof,
+
+    /// Client to daemon: local terminal was resized.
+    Resize { width: u16, height: u16 },
+
+    /// Daemon to client: bytes written by the process to stdout.
+    Stdout(Vec<u8>),
+
+    /// Daemon to client: bytes written by the process to stderr.
+    Stderr(Vec<u8>),
+
+    /// Daemon to client: the process exited with this code.
+    Exit { code: i32 },
+
+    /// Daemon to client: the session could not be started or broke.
+    Error { message: String },
+
+    /// Both ways: keepalive sent every `HEARTBEAT_INTERVAL`.
+    Heartbeat,
+}
+
+/// Frame exchanged on a connection after it switched to a port-forward.
+///
+/// A session multiplexes any number of TCP connections accepted by the
+/// client; each one is identified by the `stream` id the client picked when
+/// sending `Open`. Either side may close a stream; the session ends when the
+/// client disconnects.
+#[derive(Debug, Encode, Decode, Serialize, Deserialize)]
+pub enum ForwardFrame {
+    /// Client to daemon: a new local connection was accepted.
+    Open { stream: u32 },
+
+    /// Both ways: bytes for a stream.
+    Data { stream: u32, data: Vec<u8> },
+
+    /// Both ways: this side closed the stream.
+    Close { stream: u32 },
+
+    /// Daemon to client: a stream (or the whole session if `None`) failed.
+    Error { stream: Option<u32>, message: String },
+}
+
 /// Read a lenght-prefixed bincode message from the stream.
 ///
 /// Returns Ok(None) if the client closed the connection cleanly.
@@ -64,6 +322,77 @@ pub async fn read_message<R, T>(reader: &mut R) -> Result<Option<T>, WireError>
 where
     R: AsyncRead + Unpin,
     T: Decode<()>,
+{
+    let Some(buf) = read_payload(reader).await? else {
+        return Ok(None);
+    };
+
+    let config = bincode::config::standard();
+    let (msg, _len): (T, usize) = bincode::decode_from_slice(&buf, config)?;
+
+    Ok(Some(msg))
+}
+
+/// Write a length-prefixed bincode message to an async writer.
+pub async fn write_message<W, T>(
+    writer: &mut W,
+    msg: &T,
+) -> Result<(), WireError>
+where
+    W: AsyncWrite + Unpin,
+    T: Encode,
+{
+    let config = bincode::config::standard();
+    let encoded = bincode::encode_to_vec(msg, config)?;
+
+    write_payload(writer, &encoded, false).await
+}
+
+/// Like `read_message`, for a connection using `codec`.
+pub async fn read_message_as<R, T>(
+    reader: &mut R,
+    codec: Codec,
+) -> Result<Option<T>, WireError>
+where
+    R: AsyncRead + Unpin,
+    T: Decode<()> + DeserializeOwned,
+{
+    match read_payload(reader).await? {
+        Some(buf) => Ok(Some(codec.encoding.decode(&buf)?)),
+        None => Ok(None),
+    }
+}
+
+/// Like `write_message`, for a connection using `codec`.
+pub async fn write_message_as<W, T>(
+    writer: &mut W,
+    msg: &T,
+    codec: Codec,
+) -> Result<(), WireError>
+where
+    W: AsyncWrite + Unpin,
+    T: Encode + Serialize,
+{
+    let encoded = codec.encoding.encode(msg)?;
+    // The peer would fail to inflate it.
+    if encoded.len() > MAX_FRAME_LEN {
+        return Err(WireError::TooLarge(encoded.len()));
+    }
+    if codec.compress && encoded.len() >= COMPRESS_THRESHOLD {
+        let compressed = zstd::bulk::compress(&encoded, 0)?;
+        // Already compressed data may not shrink.
+        if compressed.len() < encoded.len() {
+            return write_payload(writer, &compressed, true).await;
+        }
+    }
+
+    write_payload(writer, &encoded, false).await
+}
+
+/// Read the payload of one frame, or None on a clean end of stream.
+async fn read_payload<R>(reader: &mut R) -> Result<Option<Vec<u8>>, WireError>
+where
+    R: AsyncRead + Unpin,
 {
     let mut buf_sz = [0u8; 4];
 
@@ -76,32 +405,482 @@ where
         Err(e) => return Err(WireError::Io(e)),
     }
 
-    let len = u32::from_be_bytes(buf_sz) as usize;
+    let prefix = u32::from_be_bytes(buf_sz);
+    let len = (prefix & !COMPRESSED) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(WireError::TooLarge(len));
+    }
     let mut buf = vec![0u8; len];
     reader.read_exact(&mut buf).await?;
 
-    let config = bincode::config::standard();
-    let (msg, _len): (T, usize) = bincode::decode_from_slice(&buf, config)?;
+    if prefix & COMPRESSED != 0 {
+        // Fails rather than inflate past the limit.
+        buf = zstd::bulk::decompress(&buf, MAX_FRAME_LEN)?;
+    }
 
-    Ok(Some(msg))
+    Ok(Some(buf))
 }
 
-/// Write a length-prefixed bincode message to an async writer.
-pub async fn write_message<W, T>(
+async fn write_payload<W>(
     writer: &mut W,
-    msg: &T,
+    payload: &[u8],
+    compressed: bool,
 ) -> Result<(), WireError>
 where
     W: AsyncWrite + Unpin,
-    T: Encode,
 {
-    let config = bincode::config::standard();
-    let encoded = bincode::encode_to_vec(msg, config)?;
-
-    let len = encoded.len() as u32;
+    if payload.len() > MAX_FRAME_LEN {
+        return Err(WireError::TooLarge(payload.len()));
+    }
+    let mut len = payload.len() as u32;
+    if compressed {
+        len |= COMPRESSED;
+    }
     writer.write_all(&len.to_be_bytes()).await?;
-    writer.write_all(&encoded).await?;
+    writer.write_all(payload).await?;
     writer.flush().await?;
 
     Ok(())
 }
+
+/// Client side of the handshake; returns the negotiated version.
+pub async fn client_handshake<S>(stream: &mut S) -> Result<u32, WireError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let hello = Hello { protocol_versions: vec![PROTOCOL_VERSION] };
+    write_message(stream, &hello).await?;
+
+    match read_message(stream).await {
+        Ok(Some(HelloAck::Accepted { protocol_version })) => {
+            Ok(protocol_version)
+        }
+        Ok(Some(HelloAck::Rejected { protocol_versions })) => {
+            Err(WireError::Incompatible {
+                ours: vec![PROTOCOL_VERSION],
+                theirs: protocol_versions,
+            })
+        }
+        Ok(None) | Err(WireError::BinDecode(_)) => Err(WireError::NoHandshake),
+        Err(e) => Err(e),
+    }
+}
+
+/// Daemon side of the handshake: picks the newest version both sides
+/// speak, or rejects the client.
+///
+/// Also returns how to write to the client: in the encoding of its
+/// `Hello`, compressed if that is bincode. JSON clients are scripts and
+/// editor plugins that cannot be expected to inflate zstd frames.
+pub async fn accept_handshake<S>(
+    stream: &mut S,
+) -> Result<(u32, Codec), WireError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let Some(buf) = read_payload(stream).await? else {
+        return Err(WireError::NoHandshake);
+    };
+    let encoding = Encoding::detect(&buf);
+    let hello: Hello = match encoding.decode(&buf) {
+        Ok(hello) => hello,
+        Err(WireError::BinDecode(_) | WireError::Json(_)) => {
+            return Err(WireError::NoHandshake);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let common = PROTOCOL_VERSIONS
+        .iter()
+        .find(|v| hello.protocol_versions.contains(v))
+        .copied();
+
+    match common {
+        Some(protocol_version) => {
+            let ack = HelloAck::Accepted { protocol_version };
+            write_message_as(stream, &ack, Codec::from(encoding)).await?;
+
+            let compress = encoding == Encoding::Bincode;
+            Ok((protocol_version, Codec { encoding, compress }))
+        }
+        None => {
+            let protocol_versions = PROTOCOL_VERSIONS.to_vec();
+            let ack = HelloAck::Rejected { protocol_versions };
+            write_message_as(stream, &ack, Codec::from(encoding)).await?;
+            Err(WireError::Incompatible {
+                ours: PROTOCOL_VERSIONS.to_vec(),
+                theirs: hello.protocol_versions,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorCode;
+
+    /// `msg` written with `codec` and read back.
+    async fn round_trip<T>(msg: &T, codec: Codec) -> T
+    where
+        T: Encode + Decode<()> + Serialize + DeserializeOwned,
+    {
+        let mut buf = Vec::new();
+        write_message_as(&mut buf, msg, codec).await.unwrap();
+
+        let mut rd = buf.as_slice();
+        let back = read_message_as(&mut rd, codec).await.unwrap().unwrap();
+        assert!(rd.is_empty(), "bytes left after the frame");
+        back
+    }
+
+    #[tokio::test]
+    async fn frames_keep_their_request_id() {
+        let frames = [
+            Frame::Start { id: 1 },
+            Frame::Chunk { id: 2, response: Box::new(Response::Pong) },
+            Frame::End { id: 3 },
+            Frame::Error { id: 4, error: KopsError::not_found("no pod") },
+        ];
+
+        for frame in frames {
+            let id = frame.id();
+            assert_eq!(round_trip(&frame, Codec::default()).await.id(), id);
+        }
+    }
+
+    #[tokio::test]
+    async fn chunk_carries_its_response() {
+        let frame = Frame::Chunk { id: 7, response: Box::new(Response::Pong) };
+
+        let back = round_trip(&frame, Codec::default()).await;
+        let Frame::Chunk { id, response } = back else {
+            panic!("not a chunk");
+        };
+        assert_eq!(id, 7);
+        assert!(matches!(*response, Response::Pong));
+    }
+
+    #[tokio::test]
+    async fn error_frame_carries_its_error() {
+        let error = KopsError::not_found("pod not found: default/web")
+            .with_cluster("dev")
+            .with_detail("status", 404);
+        let frame = Frame::Error { id: 3, error };
+
+        let Frame::Error { id, error } =
+            round_trip(&frame, Codec::default()).await
+        else {
+            panic!("not an error frame");
+        };
+        assert_eq!(id, 3);
+        assert_eq!(error.code, ErrorCode::NotFound);
+        assert_eq!(error.message, "pod not found: default/web");
+        assert_eq!(error.cluster.as_deref(), Some("dev"));
+        let status = ("status".to_string(), "404".to_string());
+        assert_eq!(error.details, [status]);
+    }
+
+    #[tokio::test]
+    async fn frames_are_read_in_order_until_the_end_of_stream() {
+        let mut buf = Vec::new();
+        for id in 1..=3 {
+            write_message(&mut buf, &Frame::End { id }).await.unwrap();
+        }
+
+        let mut rd = buf.as_slice();
+        for id in 1..=3 {
+            let frame: Frame = read_message(&mut rd).await.unwrap().unwrap();
+            assert_eq!(frame.id(), id);
+        }
+        let end: Option<Frame> = read_message(&mut rd).await.unwrap();
+        assert!(end.is_none());
+    }
+
+    #[tokio::test]
+    async fn handshake_settles_on_the_shared_version() {
+        let (mut client, mut daemon) = tokio::io::duplex(1024);
+
+        let (version, accepted) = tokio::join!(
+            client_handshake(&mut client),
+            accept_handshake(&mut daemon),
+        );
+
+        assert_eq!(version.unwrap(), PROTOCOL_VERSIONS[0]);
+        let (version, codec) = accepted.unwrap();
+        assert_eq!(version, PROTOCOL_VERSIONS[0]);
+        assert_eq!(codec.encoding, Encoding::Bincode);
+        assert!(codec.compress);
+    }
+
+    #[tokio::test]
+    async fn handshake_picks_the_newest_common_version() {
+        let (mut client, mut daemon) = tokio::io::duplex(1024);
+
+        let ours = PROTOCOL_VERSIONS[0];
+        let hello = Hello { protocol_versions: vec![ours + 1, ours, 1] };
+        write_message(&mut client, &hello).await.unwrap();
+
+        let (version, _) = accept_handshake(&mut daemon).await.unwrap();
+        assert_eq!(version, ours);
+
+        let ack: Option<HelloAck> = read_message(&mut client).await.unwrap();
+        let Some(HelloAck::Accepted { protocol_version }) = ack else {
+            panic!("unexpected ack: {ack:?}");
+        };
+        assert_eq!(protocol_version, ours);
+    }
+
+    #[tokio::test]
+    async fn daemon_rejects_a_client_without_a_common_version() {
+        let (mut client, mut daemon) = tokio::io::duplex(1024);
+
+        let hello = Hello { protocol_versions: vec![0] };
+        write_message(&mut client, &hello).await.unwrap();
+
+        match accept_handshake(&mut daemon).await {
+            Err(WireError::Incompatible { ours, theirs }) => {
+                assert_eq!(ours, PROTOCOL_VERSIONS);
+                assert_eq!(theirs, [0]);
+            }
+            other => panic!("unexpected handshake result: {other:?}"),
+        }
+
+        let ack: Option<HelloAck> = read_message(&mut client).await.unwrap();
+        let Some(HelloAck::Rejected { protocol_versions }) = ack else {
+            panic!("unexpected ack: {ack:?}");
+        };
+        assert_eq!(protocol_versions, PROTOCOL_VERSIONS);
+    }
+
+    #[tokio::test]
+    async fn client_reports_a_version_mismatch() {
+        let (mut client, mut daemon) = tokio::io::duplex(1024);
+
+        let daemon = async move {
+            let _: Option<Hello> = read_message(&mut daemon).await.unwrap();
+            let ack = HelloAck::Rejected { protocol_versions: vec![99] };
+            write_message(&mut daemon, &ack).await.unwrap();
+        };
+        let (res, ()) = tokio::join!(client_handshake(&mut client), daemon);
+
+        match res {
+            Err(WireError::Incompatible { ours, theirs }) => {
+                assert_eq!(ours, [PROTOCOL_VERSION]);
+                assert_eq!(theirs, [99]);
+            }
+            other => panic!("unexpected handshake result: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn client_detects_a_daemon_without_handshake() {
+        let (mut client, mut daemon) = tokio::io::duplex(1024);
+
+        // What a release before the handshake does with a `Hello` it
+        // cannot decode: drop the connection.
+        let daemon = async move {
+            let _ = read_payload(&mut daemon).await;
+        };
+        let (res, ()) = tokio::join!(client_handshake(&mut client), daemon);
+
+        assert!(matches!(res, Err(WireError::NoHandshake)));
+    }
+
+    #[tokio::test]
+    async fn daemon_detects_a_client_without_handshake() {
+        let (client, mut daemon) = tokio::io::duplex(1024);
+        drop(client);
+
+        let res = accept_handshake(&mut daemon).await;
+        assert!(matches!(res, Err(WireError::NoHandshake)));
+
+        let (mut client, mut daemon) = tokio::io::duplex(1024);
+        write_payload(&mut client, b"{not a hello", false).await.unwrap();
+
+        let res = accept_handshake(&mut daemon).await;
+        assert!(matches!(res, Err(WireError::NoHandshake)));
+    }
+
+    #[test]
+    fn encoding_is_told_by_the_first_byte() {
+        assert_eq!(
+            Encoding::detect(br#"{"protocol_versions":[4]}"#),
+            Encoding::Json
+        );
+        assert_eq!(Encoding::detect(b"  \n{}"), Encoding::Json);
+
+        let config = bincode::config::standard();
+        let hello = Hello { protocol_versions: vec![4, 3] };
+        let buf = bincode::encode_to_vec(&hello, config).unwrap();
+        assert_eq!(Encoding::detect(&buf), Encoding::Bincode);
+        assert_eq!(Encoding::detect(b""), Encoding::Bincode);
+    }
+
+    #[tokio::test]
+    async fn json_frames_round_trip() {
+        let codec = Codec::from(Encoding::Json);
+
+        let frame = Frame::Chunk { id: 5, response: Box::new(Response::Pong) };
+        let back = round_trip(&frame, codec).await;
+        let Frame::Chunk { id, response } = back else {
+            panic!("not a chunk");
+        };
+        assert_eq!(id, 5);
+        assert!(matches!(*response, Response::Pong));
+
+        let frame = Frame::Error { id: 6, error: KopsError::invalid("bad") };
+        let Frame::Error { id, error } = round_trip(&frame, codec).await
+        else {
+            panic!("not an error frame");
+        };
+        assert_eq!(id, 6);
+        assert_eq!(error.code, ErrorCode::InvalidRequest);
+    }
+
+    #[tokio::test]
+    async fn json_payload_is_plain_json() {
+        let mut buf = Vec::new();
+        let codec = Codec::from(Encoding::Json);
+        write_message_as(&mut buf, &Frame::End { id: 2 }, codec)
+            .await
+            .unwrap();
+
+        let (prefix, payload) = buf.split_at(4);
+        let len = u32::from_be_bytes(prefix.try_into().unwrap());
+        assert_eq!(len as usize, payload.len());
+        assert_eq!(payload, br#"{"End":{"id":2}}"#);
+    }
+
+    #[tokio::test]
+    async fn daemon_answers_a_json_client_in_json() {
+        let (mut client, mut daemon) = tokio::io::duplex(1024);
+
+        let ours = PROTOCOL_VERSIONS[0];
+        let hello = format!(r#"{{"protocol_versions":[{ours}]}}"#);
+        write_payload(&mut client, hello.as_bytes(), false).await.unwrap();
+
+        let (version, codec) = accept_handshake(&mut daemon).await.unwrap();
+        assert_eq!(version, ours);
+        assert_eq!(codec.encoding, Encoding::Json);
+        assert!(!codec.compress);
+
+        let ack = read_payload(&mut client).await.unwrap().unwrap();
+        let expected =
+            format!(r#"{{"Accepted":{{"protocol_version":{ours}}}}}"#);
+        assert_eq!(String::from_utf8(ack).unwrap(), expected);
+    }
+
+    /// Length prefix of the frame at the start of `buf`.
+    fn prefix(buf: &[u8]) -> u32 {
+        u32::from_be_bytes(buf[..4].try_into().unwrap())
+    }
+
+    #[tokio::test]
+    async fn large_payloads_are_compressed() {
+        let message = "x".repeat(COMPRESS_THRESHOLD * 2);
+        let frame =
+            Frame::Error { id: 1, error: KopsError::invalid(&message) };
+        let codec = Codec { encoding: Encoding::Bincode, compress: true };
+
+        let mut buf = Vec::new();
+        write_message_as(&mut buf, &frame, codec).await.unwrap();
+        assert_ne!(prefix(&buf) & COMPRESSED, 0);
+        assert!(buf.len() < COMPRESS_THRESHOLD);
+
+        let Frame::Error { error, .. } = round_trip(&frame, codec).await
+        else {
+            panic!("not an error frame");
+        };
+        assert_eq!(error.message, message);
+    }
+
+    #[tokio::test]
+    async fn small_or_uncompressed_payloads_are_sent_as_is() {
+        let compress = Codec { encoding: Encoding::Bincode, compress: true };
+        let mut buf = Vec::new();
+        write_message_as(&mut buf, &Frame::End { id: 1 }, compress)
+            .await
+            .unwrap();
+        assert_eq!(prefix(&buf) & COMPRESSED, 0);
+
+        let message = "x".repeat(COMPRESS_THRESHOLD * 2);
+        let frame =
+            Frame::Error { id: 1, error: KopsError::invalid(&message) };
+        let mut buf = Vec::new();
+        write_message_as(&mut buf, &frame, Codec::default()).await.unwrap();
+        assert_eq!(prefix(&buf) & COMPRESSED, 0);
+        assert_eq!(prefix(&buf) as usize, buf.len() - 4);
+    }
+
+    #[tokio::test]
+    async fn oversized_prefix_is_refused() {
+        let len = (MAX_FRAME_LEN + 1) as u32;
+        let buf = len.to_be_bytes();
+
+        let res = read_payload(&mut buf.as_slice()).await;
+        assert!(
+            matches!(res, Err(WireError::TooLarge(n)) if n == len as usize)
+        );
+
+        let buf = (len | COMPRESSED).to_be_bytes();
+        let res = read_payload(&mut buf.as_slice()).await;
+        assert!(matches!(res, Err(WireError::TooLarge(_))));
+    }
+
+    #[tokio::test]
+    async fn compressed_payload_cannot_inflate_past_the_limit() {
+        let bomb = vec![0u8; MAX_FRAME_LEN + 1];
+        let compressed = zstd::bulk::compress(&bomb, 0).unwrap();
+        assert!(compressed.len() < MAX_FRAME_LEN);
+
+        let mut buf = Vec::new();
+        write_payload(&mut buf, &compressed, true).await.unwrap();
+
+        let res = read_payload(&mut buf.as_slice()).await;
+        assert!(matches!(res, Err(WireError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn truncated_frame_is_an_error() {
+        let mut buf = Vec::new();
+        write_message(&mut buf, &Frame::End { id: 1 }).await.unwrap();
+        buf.pop();
+
+        let res: Result<Option<Frame>, _> =
+            read_message(&mut buf.as_slice()).await;
+        assert!(matches!(res, Err(WireError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn heartbeat_belongs_to_no_request() {
+        assert_eq!(Frame::Heartbeat.id(), 0);
+
+        let back = round_trip(&Frame::Heartbeat, Codec::default()).await;
+        assert!(matches!(back, Frame::Heartbeat));
+    }
+
+    #[tokio::test]
+    async fn daemon_rejects_a_version_it_cannot_decode() {
+        let (mut client, mut daemon) = tokio::io::duplex(1024);
+
+        let previous = PROTOCOL_VERSION - 1;
+        let hello = Hello { protocol_versions: vec![previous] };
+        write_message(&mut client, &hello).await.unwrap();
+
+        let res = accept_handshake(&mut daemon).await;
+        assert!(matches!(res, Err(WireError::Incompatible { .. })));
+    }
+
+    #[tokio::test]
+    async fn oversized_payload_is_not_written() {
+        let payload = vec![0u8; MAX_FRAME_LEN + 1];
+
+        let mut buf = Vec::new();
+        let res = write_payload(&mut buf, &payload, false).await;
+        assert!(
+            matches!(res, Err(WireError::TooLarge(n)) if n == payload.len())
+        );
+        assert!(buf.is_empty());
+    }
+}
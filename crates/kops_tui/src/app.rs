@@ -0,0 +1,357 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use ratatui::crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+};
+use ratatui::widgets::TableState;
+
+use kops_protocol::{EnvEntry, PodSummary};
+
+use crate::client::{PodTarget, Snapshot};
+
+/// Table shown in the main screen.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum View {
+    Pods,
+    Failures,
+    Restarts,
+}
+
+impl View {
+    pub(crate) const ALL: [View; 3] =
+        [View::Pods, View::Failures, View::Restarts];
+
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            View::Pods => "Pods",
+            View::Failures => "Failures",
+            View::Restarts => "Restarts",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            View::Pods => View::Failures,
+            View::Failures => View::Restarts,
+            View::Restarts => View::Pods,
+        }
+    }
+}
+
+/// What the keyboard is currently driving.
+pub(crate) enum Mode {
+    Browse,
+
+    /// Typing a namespace filter.
+    Filter,
+
+    /// Scrollable text about one pod (env or logs).
+    Detail {
+        title: String,
+        body: String,
+        scroll: u16,
+    },
+}
+
+/// Work the event loop must do after a key press.
+pub(crate) enum Action {
+    None,
+    Quit,
+    Env(PodTarget),
+    Logs(PodTarget),
+}
+
+/// One row of the main table, with the pod it drills into.
+pub(crate) struct Entry {
+    pub target: PodTarget,
+    pub cells: Vec<String>,
+}
+
+pub(crate) struct App {
+    pub view: View,
+    pub mode: Mode,
+    pub snapshot: Snapshot,
+
+    /// Only this cluster; every cluster when None.
+    pub cluster: Option<String>,
+
+    /// Substring a namespace must contain; empty shows all.
+    pub namespace: String,
+    pub filter_input: String,
+
+    pub table: TableState,
+
+    /// Last refresh or error, shown in the footer.
+    pub status: String,
+}
+
+impl App {
+    pub(crate) fn new() -> Self {
+        Self {
+            view: View::Pods,
+            mode: Mode::Browse,
+            snapshot: Snapshot::default(),
+            cluster: None,
+            namespace: String::new(),
+            filter_input: String::new(),
+            table: TableState::default().with_selected(0),
+            status: "loading...".to_string(),
+        }
+    }
+
+    pub(crate) fn update(&mut self, snapshot: Snapshot) {
+        self.snapshot = snapshot;
+        // The selected cluster may be gone after a refresh.
+        if let Some(c) = &self.cluster
+            && !self.snapshot.clusters.contains(c)
+        {
+            self.cluster = None;
+        }
+        self.clamp_selection();
+    }
+
+    pub(crate) fn show(&mut self, title: String, body: String) {
+        self.mode = Mode::Detail { title, body, scroll: 0 };
+    }
+
+    /// Column headers of the current view.
+    pub(crate) fn headers(&self) -> &'static [&'static str] {
+        match self.view {
+            View::Pods | View::Failures => &[
+                "CLUSTER",
+                "NAMESPACE",
+                "NAME",
+                "READY",
+                "STATUS",
+                "RESTARTS",
+            ],
+            View::Restarts => &[
+                "CLUSTER",
+                "NAMESPACE",
+                "POD",
+                "CONTAINER",
+                "RESTARTS",
+                "REASON",
+                "EXIT CODE",
+            ],
+        }
+    }
+
+    /// Rows of the current view after the cluster and namespace filters.
+    pub(crate) fn entries(&self) -> Vec<Entry> {
+        let visible = |cluster: &str, namespace: &str| {
+            self.cluster.as_deref().is_none_or(|c| c == cluster)
+                && namespace.contains(&self.namespace)
+        };
+
+        match self.view {
+            View::Pods | View::Failures => self
+                .snapshot
+                .pods
+                .iter()
+                .filter(|p| visible(&p.cluster, &p.namespace))
+                .filter(|p| self.view == View::Pods || is_failing(p))
+                .map(|p| Entry {
+                    target: PodTarget {
+                        cluster: p.cluster.clone(),
+                        namespace: p.namespace.clone(),
+                        pod: p.name.clone(),
+                    },
+                    cells: vec![
+                        p.cluster.clone(),
+                        p.namespace.clone(),
+                        p.name.clone(),
+                        format!(
+                            "{}/{}",
+                            p.ready_containers, p.total_containers
+                        ),
+                        p.status.clone(),
+                        p.restart_count.to_string(),
+                    ],
+                })
+                .collect(),
+            View::Restarts => self
+                .snapshot
+                .restarts
+                .iter()
+                .filter(|r| visible(&r.cluster, &r.namespace))
+                .map(|r| Entry {
+                    target: PodTarget {
+                        cluster: r.cluster.clone(),
+                        namespace: r.namespace.clone(),
+                        pod: r.pod.clone(),
+                    },
+                    cells: vec![
+                        r.cluster.clone(),
+                        r.namespace.clone(),
+                        r.pod.clone(),
+                        r.container.clone(),
+                        r.restarts.to_string(),
+                        r.reason.clone().unwrap_or_default(),
+                        r.exit_code.map(|c| c.to_string()).unwrap_or_default(),
+                    ],
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn on_event(&mut self, event: Event) -> Action {
+        let Event::Key(key) = event else {
+            return Action::None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return Action::None;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL)
+            && key.code == KeyCode::Char('c')
+        {
+            return Action::Quit;
+        }
+
+        match self.mode {
+            Mode::Browse => self.on_browse_key(key),
+            Mode::Filter => {
+                self.on_filter_key(key);
+                Action::None
+            }
+            Mode::Detail { .. } => {
+                self.on_detail_key(key);
+                Action::None
+            }
+        }
+    }
+
+    fn on_browse_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('q') => return Action::Quit,
+            KeyCode::Tab => {
+                self.view = self.view.next();
+                self.table.select(Some(0));
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.table.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.table.select_previous(),
+            KeyCode::Char('c') => self.next_cluster(),
+            KeyCode::Char('/') => {
+                self.filter_input = self.namespace.clone();
+                self.mode = Mode::Filter;
+            }
+            KeyCode::Enter | KeyCode::Char('e') => {
+                if let Some(target) = self.selected() {
+                    return Action::Env(target);
+                }
+            }
+            KeyCode::Char('l') => {
+                if let Some(target) = self.selected() {
+                    return Action::Logs(target);
+                }
+            }
+            _ => {}
+        }
+
+        self.clamp_selection();
+        Action::None
+    }
+
+    fn on_filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                self.namespace = self.filter_input.trim().to_string();
+                self.mode = Mode::Browse;
+                self.table.select(Some(0));
+            }
+            KeyCode::Esc => self.mode = Mode::Browse,
+            KeyCode::Backspace => {
+                self.filter_input.pop();
+            }
+            KeyCode::Char(c) => self.filter_input.push(c),
+            _ => {}
+        }
+    }
+
+    fn on_detail_key(&mut self, key: KeyEvent) {
+        let Mode::Detail { scroll, .. } = &mut self.mode else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Browse,
+            KeyCode::Down | KeyCode::Char('j') => {
+                *scroll = scroll.saturating_add(1)
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                *scroll = scroll.saturating_sub(1)
+            }
+            KeyCode::PageDown => *scroll = scroll.saturating_add(20),
+            KeyCode::PageUp => *scroll = scroll.saturating_sub(20),
+            _ => {}
+        }
+    }
+
+    /// Cycle the cluster filter: all clusters, then each one in turn.
+    fn next_cluster(&mut self) {
+        let clusters = &self.snapshot.clusters;
+        self.cluster = match &self.cluster {
+            None => clusters.first().cloned(),
+            Some(c) => clusters
+                .iter()
+                .position(|x| x == c)
+                .and_then(|i| clusters.get(i + 1))
+                .cloned(),
+        };
+        self.table.select(Some(0));
+    }
+
+    fn selected(&self) -> Option<PodTarget> {
+        let i = self.table.selected()?;
+        self.entries().into_iter().nth(i).map(|e| e.target)
+    }
+
+    fn clamp_selection(&mut self) {
+        let len = self.entries().len();
+        match self.table.selected() {
+            _ if len == 0 => self.table.select(None),
+            Some(i) if i >= len => self.table.select(Some(len - 1)),
+            None => self.table.select(Some(0)),
+            Some(_) => {}
+        }
+    }
+}
+
+/// Pods shown in the failures view: failed, crash looping, or running
+/// without being ready.
+fn is_failing(p: &PodSummary) -> bool {
+    match p.phase.as_deref() {
+        Some("Succeeded") => false,
+        Some("Failed") => true,
+        _ => !p.ready || p.reason.is_some(),
+    }
+}
+
+/// Render env vars as `NAME=value` lines.
+pub(crate) fn format_env(vars: &[EnvEntry]) -> String {
+    vars.iter()
+        .map(|v| {
+            let value = match &v.value {
+                Some(value) => value,
+                None if v.masked => "<hidden>",
+                None => "<none>",
+            };
+            format!("{}/{}={value}", v.container, v.name)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,202 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Result, bail};
+use tokio::{net::UnixStream, sync::Mutex};
+
+use kops_protocol::{
+    EnvEntry, EnvRequest, LogsRequest, NodesRequest, PodSummary, PodsRequest,
+    Request, Response, RestartSummary, RestartsRequest, mux::Mux,
+    wire::client_handshake,
+};
+
+/// Window of the restarts view.
+const RESTARTS_SINCE_SECONDS: i64 = 60 * 60;
+
+/// Lines of log shown when drilling into a pod.
+const LOG_TAIL_LINES: i64 = 500;
+
+/// Everything the dashboard shows, fetched in one refresh.
+#[derive(Default)]
+pub(crate) struct Snapshot {
+    pub clusters: Vec<String>,
+    pub pods: Vec<PodSummary>,
+    pub restarts: Vec<RestartSummary>,
+}
+
+/// Pod selected for a drill-down.
+#[derive(Clone, Debug)]
+pub(crate) struct PodTarget {
+    pub cluster: String,
+    pub namespace: String,
+    pub pod: String,
+}
+
+/// Requests to the daemon, multiplexed over one connection that is
+/// reopened when the daemon went away.
+pub(crate) struct Client {
+    socket: PathBuf,
+    mux: Mutex<Option<Arc<Mux<UnixStream>>>>,
+}
+
+impl Client {
+    pub(crate) fn new(socket: &Path) -> Self {
+        Self { socket: socket.to_path_buf(), mux: Mutex::new(None) }
+    }
+
+    async fn mux(&self) -> Result<Arc<Mux<UnixStream>>> {
+        let mut mux = self.mux.lock().await;
+        if let Some(m) = mux.as_ref()
+            && !m.is_closed()
+        {
+            return Ok(m.clone());
+        }
+
+        let mut stream = UnixStream::connect(&self.socket).await?;
+        client_handshake(&mut stream).await?;
+
+        let m = Arc::new(Mux::new(stream));
+        *mux = Some(m.clone());
+        Ok(m)
+    }
+
+    async fn send(&self, req: Request) -> Result<Response> {
+        match self.mux().await?.call(req).await? {
+            Response::Error(err) => bail!("{err}"),
+            resp => Ok(resp),
+        }
+    }
+
+    /// Pods listing, which may arrive in several chunks.
+    async fn pods(&self, req: PodsRequest) -> Result<Vec<PodSummary>> {
+        let mut replies = self.mux().await?.open(Request::Pods(req)).await?;
+
+        let mut pods = Vec::new();
+        while let Some(resp) = replies.next().await? {
+            match resp {
+                Response::Pods { pods: mut chunk, .. } => {
+                    pods.append(&mut chunk)
+                }
+                Response::Error(err) => bail!("{err}"),
+                _ => bail!("unexpected response to pods"),
+            }
+        }
+
+        Ok(pods)
+    }
+
+    /// Fetch pods of every cluster and the restarts of the last hour.
+    ///
+    /// The cluster list comes from the node listing, so clusters without
+    /// pods can still be selected.
+    pub(crate) async fn snapshot(&self) -> Result<Snapshot> {
+        let pods = PodsRequest {
+            cluster: None,
+            namespace: None,
+            failed_only: false,
+            all_clusters: true,
+            label_selector: None,
+            field_selector: None,
+            limit: None,
+            continue_token: None,
+        };
+        let restarts = RestartsRequest {
+            cluster: None,
+            namespace: None,
+            since_seconds: RESTARTS_SINCE_SECONDS,
+        };
+
+        // All three in flight at once.
+        let (nodes, pods, restarts) = tokio::try_join!(
+            self.send(Request::Nodes(NodesRequest { cluster: None })),
+            self.pods(pods),
+            self.send(Request::Restarts(restarts)),
+        )?;
+
+        let mut clusters: Vec<String> = match nodes {
+            Response::Nodes { nodes } => {
+                nodes.into_iter().map(|n| n.cluster).collect()
+            }
+            _ => bail!("unexpected response to nodes"),
+        };
+        clusters.sort();
+        clusters.dedup();
+
+        let mut restarts = match restarts {
+            Response::Restarts { restarts } => restarts,
+            _ => bail!("unexpected response to restarts"),
+        };
+        // Most recent first.
+        restarts.reverse();
+
+        Ok(Snapshot { clusters, pods, restarts })
+    }
+
+    /// Environment variables of every container of a pod.
+    pub(crate) async fn env(
+        &self,
+        target: &PodTarget,
+    ) -> Result<Vec<EnvEntry>> {
+        let req = EnvRequest {
+            cluster: Some(target.cluster.clone()),
+            namespace: target.namespace.clone(),
+            pod: target.pod.clone(),
+            container: None,
+            all_containers: true,
+            filter_regex: None,
+            resolve: true,
+            reveal_secrets: false,
+        };
+
+        match self.send(Request::Env(req)).await? {
+            Response::EnvVars { mut vars } => {
+                vars.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+                Ok(vars)
+            }
+            _ => bail!("unexpected response to env"),
+        }
+    }
+
+    /// Tail of the log of the default container of a pod.
+    pub(crate) async fn logs(&self, target: &PodTarget) -> Result<String> {
+        let req = LogsRequest {
+            cluster: Some(target.cluster.clone()),
+            namespace: target.namespace.clone(),
+            pod: target.pod.clone(),
+            container: None,
+            follow: false,
+            tail_lines: Some(LOG_TAIL_LINES),
+            since_seconds: None,
+        };
+
+        let mut replies = self.mux().await?.open(Request::Logs(req)).await?;
+
+        let mut data = Vec::new();
+        while let Some(resp) = replies.next().await? {
+            match resp {
+                Response::LogChunk { data: chunk } => data.extend(chunk),
+                Response::LogEnd => break,
+                Response::Error(err) => bail!("{err}"),
+                _ => bail!("unexpected response to logs"),
+            }
+        }
+
+        Ok(String::from_utf8_lossy(&data).into_owned())
+    }
+}
@@ -0,0 +1,135 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Live terminal dashboard over the kopsd daemon state.
+//!
+//! The dashboard polls the daemon every few seconds and shows pods,
+//! failing pods and recent restarts across every running cluster, with
+//! drill-down into a pod's env vars and logs.
+
+mod app;
+mod client;
+mod ui;
+
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Local;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event};
+use tokio::sync::mpsc;
+
+use crate::app::{Action, App, format_env};
+use crate::client::Client;
+
+/// How often the daemon state is fetched again.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Run the dashboard against the daemon listening on `socket` until the
+/// user quits.
+pub async fn run(socket: &Path) -> Result<()> {
+    let client = Client::new(socket);
+
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, &client).await;
+    ratatui::restore();
+
+    result
+}
+
+async fn run_app(
+    terminal: &mut DefaultTerminal,
+    client: &Client,
+) -> Result<()> {
+    let mut app = App::new();
+    let mut events = spawn_input();
+    let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+
+    loop {
+        terminal.draw(|frame| ui::draw(frame, &mut app))?;
+
+        tokio::select! {
+            _ = refresh.tick() => match client.snapshot().await {
+                Ok(snapshot) => {
+                    app.update(snapshot);
+                    app.status = format!(
+                        "updated {}",
+                        Local::now().format("%H:%M:%S")
+                    );
+                }
+                Err(err) => app.status = format!("refresh failed: {err}"),
+            },
+            event = events.recv() => {
+                let Some(event) = event else {
+                    return Ok(());
+                };
+
+                match app.on_event(event) {
+                    Action::None => {}
+                    Action::Quit => return Ok(()),
+                    Action::Env(target) => {
+                        let title = format!(
+                            "env {}/{}",
+                            target.namespace, target.pod
+                        );
+                        match client.env(&target).await {
+                            Ok(vars) => app.show(title, format_env(&vars)),
+                            Err(err) => app.status = format!("env: {err}"),
+                        }
+                    }
+                    Action::Logs(target) => {
+                        let title = format!(
+                            "logs {}/{}",
+                            target.namespace, target.pod
+                        );
+                        match client.logs(&target).await {
+                            Ok(logs) => app.show(title, logs),
+                            Err(err) => app.status = format!("logs: {err}"),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Read terminal events on a dedicated thread.
+///
+/// A plain thread rather than `spawn_blocking`, so a pending read never
+/// holds up runtime shutdown; it exits once the receiver is dropped.
+fn spawn_input() -> mpsc::UnboundedReceiver<Event> {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    thread::spawn(move || {
+        while !tx.is_closed() {
+            match event::poll(Duration::from_millis(200)) {
+                Ok(false) => continue,
+                Ok(true) => {}
+                Err(_) => break,
+            }
+            let Ok(ev) = event::read() else {
+                break;
+            };
+            if tx.send(ev).is_err() {
+                break;
+            }
+        }
+    });
+
+    rx
+}
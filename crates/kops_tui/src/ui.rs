@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, Tabs};
+
+use crate::app::{App, Mode, View};
+
+pub(crate) fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_header(frame, app, header);
+
+    if let Mode::Detail { title, body: text, scroll } = &app.mode {
+        let detail = Paragraph::new(text.as_str())
+            .block(Block::bordered().title(title.as_str()))
+            .scroll((*scroll, 0));
+        frame.render_widget(detail, body);
+    } else {
+        draw_table(frame, app, body);
+    }
+
+    draw_footer(frame, app, footer);
+}
+
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let selected =
+        View::ALL.iter().position(|v| *v == app.view).unwrap_or_default();
+    let tabs = Tabs::new(View::ALL.iter().map(|v| v.title()))
+        .select(selected)
+        .highlight_style(Style::new().bold().reversed());
+
+    let namespace =
+        if app.namespace.is_empty() { "all" } else { app.namespace.as_str() };
+    let scope = format!(
+        "cluster: {}  namespace: {namespace}",
+        app.cluster.as_deref().unwrap_or("all"),
+    );
+
+    let [left, right] =
+        Layout::horizontal([Constraint::Min(0), Constraint::Length(60)])
+            .areas(area);
+    frame.render_widget(tabs, left);
+    frame.render_widget(Line::from(scope).right_aligned(), right);
+}
+
+fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
+    let headers = app.headers();
+    let entries = app.entries();
+
+    let mut widths = vec![0u16; headers.len()];
+    for (w, h) in widths.iter_mut().zip(headers) {
+        *w = h.len() as u16;
+    }
+    for e in &entries {
+        for (w, c) in widths.iter_mut().zip(&e.cells) {
+            *w = (*w).max(c.len() as u16);
+        }
+    }
+
+    let rows = entries.into_iter().map(|e| Row::new(e.cells));
+    let table = Table::new(rows, widths.into_iter().map(Constraint::Length))
+        .header(Row::new(headers.iter().copied()).style(Style::new().bold()))
+        .block(Block::bordered().title(app.view.title()))
+        .row_highlight_style(Style::new().reversed());
+
+    frame.render_stateful_widget(table, area, &mut app.table);
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let line = match &app.mode {
+        Mode::Filter => {
+            Line::from(format!("namespace: {}_", app.filter_input))
+        }
+        Mode::Detail { .. } => {
+            Line::from("j/k scroll  PgUp/PgDn page  esc back").dim()
+        }
+        Mode::Browse => Line::from(format!(
+            "tab view  j/k move  c cluster  / namespace  enter env  l logs  \
+             q quit  | {}",
+            app.status
+        ))
+        .dim(),
+    };
+
+    frame.render_widget(line, area);
+}
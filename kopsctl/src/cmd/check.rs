@@ -0,0 +1,65 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::Result;
+
+use kops_protocol::PodsRequest;
+
+use crate::cmd::pods::{issues_exit_code, list, print_pods};
+use crate::helper::resolve_namespace;
+use crate::output::{OutputOptions, TableView};
+
+/// Print the failing and crashlooping pods of the scope, with who to page
+/// and their runbooks, returning the exit code
+/// [`ISSUES_EXIT_CODE`](crate::cmd::pods::ISSUES_EXIT_CODE) when there
+/// are any, e.g. for scripts and shift handovers.
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    all_clusters: bool,
+    output: OutputOptions,
+) -> Result<Option<i32>> {
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+    let req = PodsRequest {
+        cluster,
+        namespace,
+        failed_only: true,
+        all_clusters,
+        label_selector: None,
+        field_selector: None,
+        limit: None,
+        continue_token: None,
+    };
+    let (pods, _) = list(req).await?;
+
+    if pods.is_empty() && !output.structured() && !output.quiet {
+        println!("no failing pods");
+    } else {
+        print_pods(&pods, true, &TableView::default(), output)?;
+    }
+
+    // The OWNER column says who to page; the runbooks are too long for it.
+    if !output.structured() && !output.quiet {
+        for p in &pods {
+            if let Some(url) =
+                p.ownership.as_ref().and_then(|o| o.runbook_url.as_ref())
+            {
+                println!("runbook of {}/{}: {url}", p.namespace, p.name);
+            }
+        }
+    }
+    Ok(issues_exit_code(&pods))
+}
@@ -0,0 +1,71 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{ClusterAddRequest, Request, Response};
+use serde::Serialize;
+
+use crate::helper::send_request;
+use crate::output::{OutputOptions, print_structured};
+
+/// Outcome of `cluster add` and `cluster remove`, in JSON or YAML output.
+#[derive(Serialize)]
+struct ClusterChange<'a> {
+    cluster: &'a str,
+
+    /// Whether the cluster is now running.
+    running: bool,
+}
+
+pub async fn add(
+    name: String,
+    profile: String,
+    region: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let req = ClusterAddRequest { name: name.clone(), profile, region };
+    let resp = send_request(Request::ClusterAdd(req)).await?;
+
+    match resp {
+        Response::ClusterAdded if output.structured() => {
+            let change = ClusterChange { cluster: &name, running: true };
+            print_structured(&change, output)?
+        }
+        Response::ClusterAdded => println!("cluster {name} started"),
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to cluster add"),
+    }
+
+    Ok(())
+}
+
+pub async fn remove(name: String, output: OutputOptions) -> Result<()> {
+    let resp =
+        send_request(Request::ClusterRemove { name: name.clone() }).await?;
+
+    match resp {
+        Response::ClusterRemoved if output.structured() => {
+            let change = ClusterChange { cluster: &name, running: false };
+            print_structured(&change, output)?
+        }
+        Response::ClusterRemoved => println!("cluster {name} stopped"),
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to cluster remove"),
+    }
+
+    Ok(())
+}
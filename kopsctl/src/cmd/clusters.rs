@@ -0,0 +1,96 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{ClusterSummary, Request, Response};
+
+use crate::helper::send_request;
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(output: OutputOptions) -> Result<()> {
+    let resp = send_request(Request::Clusters).await?;
+
+    match resp {
+        Response::Clusters { clusters } => print_clusters(&clusters, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to clusters"),
+    }
+
+    Ok(())
+}
+
+fn print_clusters(
+    clusters: &[ClusterSummary],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(clusters, output);
+    }
+
+    if output.quiet {
+        print_names(clusters.iter().map(|c| c.name.clone()));
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("DEFAULT", 7),
+        Column::new("PROFILE", 12),
+        Column::new("STATE", 8),
+        Column::unstable("OBJECTS", 8),
+        Column::unstable("LAST SYNC", 10),
+        Column::unstable("AGE", 6),
+    ]);
+
+    for c in clusters {
+        let state = match (c.started, c.running) {
+            (false, _) => "idle",
+            (true, true) => "running",
+            (true, false) => "down",
+        };
+
+        let last_sync = match c.last_sync_epoch_ms {
+            Some(_) => format!("{} ago", format_age(c.last_sync_epoch_ms)),
+            None if c.started => "<pending>".to_string(),
+            None => "<never>".to_string(),
+        };
+
+        let age = match c.started_at_epoch_ms {
+            Some(_) => format_age(c.started_at_epoch_ms),
+            None => "-".to_string(),
+        };
+
+        table.push(
+            vec![c.name.clone()],
+            vec![
+                c.name.clone(),
+                if c.default { "*" } else { "" }.to_string(),
+                c.profile.clone().unwrap_or_else(|| "-".to_string()),
+                state.to_string(),
+                c.objects.to_string(),
+                last_sync,
+                age,
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::io::Write;
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{
+    ConfigMapData, ConfigMapDataRequest, ConfigMapsRequest, Request, Response,
+};
+
+use crate::helper::{send_request, split_ref};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn list(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let req = ConfigMapsRequest { cluster, namespace };
+    let resp = send_request(Request::ConfigMaps(req)).await?;
+
+    let config_maps = match resp {
+        Response::ConfigMaps { config_maps } => config_maps,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to configmaps"),
+    };
+
+    if output.structured() {
+        return print_structured(&config_maps, output);
+    }
+
+    if output.quiet {
+        print_names(
+            config_maps
+                .iter()
+                .map(|cm| format!("{}/{}", cm.namespace, cm.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 40),
+        Column::new("KEYS", 6),
+        Column::unstable("AGE", 6),
+    ]);
+
+    for cm in &config_maps {
+        let (cluster, namespace, name) = cm.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        table.push(
+            key,
+            vec![
+                cm.cluster.clone(),
+                cm.namespace.clone(),
+                cm.name.clone(),
+                cm.keys.to_string(),
+                format_age(cm.created_at_epoch_ms),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
+
+pub async fn get(
+    config_map: String,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    key: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let (namespace, name) = split_ref(&config_map, namespace);
+
+    let req = ConfigMapDataRequest { cluster, namespace, name };
+    let resp = send_request(Request::ConfigMapData(req)).await?;
+
+    let data = match resp {
+        Response::ConfigMapData(data) => data,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to configmap data"),
+    };
+
+    match key {
+        Some(key) => print_raw(&data, &key),
+        None if output.structured() => print_structured(&data, output),
+        None => {
+            print_all(&data);
+            Ok(())
+        }
+    }
+}
+
+/// Write a single value exactly as stored, for piping into files.
+fn print_raw(data: &ConfigMapData, key: &str) -> Result<()> {
+    let value = data
+        .data
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_bytes())
+        .or_else(|| {
+            data.binary_data
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_slice())
+        });
+
+    let Some(value) = value else {
+        bail!(
+            "key {key} not found in configmap {}/{}",
+            data.namespace,
+            data.name
+        );
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(value)?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+fn print_all(data: &ConfigMapData) {
+    println!("# {}/{}", data.namespace, data.name);
+
+    for (key, value) in &data.data {
+        if value.contains('\n') {
+            println!("{key}: |");
+            for line in value.lines() {
+                println!("  {line}");
+            }
+        } else {
+            println!("{key}: {value}");
+        }
+    }
+
+    for (key, value) in &data.binary_data {
+        println!("{key}: <binary, {} bytes>", value.len());
+    }
+}
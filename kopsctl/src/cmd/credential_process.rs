@@ -0,0 +1,99 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! `kopsctl credential-process`: kops as the `credential_process` of an
+//! AWS config profile, e.g.
+//!
+//! ```ini
+//! [profile dev]
+//! credential_process = kopsctl credential-process --profile dev
+//! ```
+//!
+//! Only the credentials go to stdout; when kopsd has no valid session for
+//! the profile, the SSO login runs first with its messages on stderr.
+
+use anyhow::{Result, bail};
+use kops_protocol::{ErrorCode, Request, Response, SessionCredentials};
+
+use crate::cmd::credentials::{fetch, print_credential_process};
+use crate::cmd::login::{self, LoginOptions, LoginOutput};
+use crate::helper::send_request_unchecked;
+
+pub async fn execute(profile: String) -> Result<()> {
+    let req = Request::SessionCredentials { profile: profile.clone() };
+
+    // Expired credentials are what this command fixes, so the reply is
+    // not checked for them.
+    let resp = send_request_unchecked(req).await?;
+    if let Some(creds) = stored_credentials(resp)? {
+        return print_credential_process(&creds);
+    }
+
+    let opts =
+        LoginOptions { output: LoginOutput::Stderr, ..Default::default() };
+    login::execute(profile.clone(), opts).await?;
+
+    let creds = fetch(&profile).await?;
+    print_credential_process(&creds)
+}
+
+/// Credentials of the daemon reply, or None when it has no valid session
+/// for the profile and a login must run first.
+fn stored_credentials(resp: Response) -> Result<Option<SessionCredentials>> {
+    match resp {
+        Response::SessionCredentials(creds) => Ok(Some(creds)),
+        Response::Error(err)
+            if matches!(
+                err.code,
+                ErrorCode::NotFound | ErrorCode::CredentialsExpired
+            ) =>
+        {
+            eprintln!("{err}, logging in");
+            Ok(None)
+        }
+        Response::Error(err) => bail!("response error {err}"),
+        _ => bail!("unexpected response to session credentials"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kops_protocol::KopsError;
+
+    use super::*;
+
+    fn error(code: ErrorCode) -> Response {
+        Response::Error(KopsError::new(code, "no session for dev"))
+    }
+
+    #[test]
+    fn expired_session_logs_in_again() {
+        let resp = error(ErrorCode::CredentialsExpired);
+        assert!(stored_credentials(resp).unwrap().is_none());
+    }
+
+    #[test]
+    fn missing_session_logs_in() {
+        let resp = error(ErrorCode::NotFound);
+        assert!(stored_credentials(resp).unwrap().is_none());
+    }
+
+    #[test]
+    fn other_errors_fail() {
+        let resp = error(ErrorCode::PermissionDenied);
+        assert!(stored_credentials(resp).is_err());
+    }
+}
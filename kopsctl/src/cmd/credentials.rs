@@ -0,0 +1,200 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! `kopsctl credentials`: the AWS session kopsd holds, for terraform, the
+//! AWS CLI and the other tools reading standard AWS credentials.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, SecondsFormat, Utc};
+use kops_protocol::{Request, Response, SessionCredentials};
+use serde_json::json;
+
+use crate::helper::send_request;
+
+/// How `kopsctl credentials export` prints the credentials.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Format {
+    /// `export AWS_ACCESS_KEY_ID=...` lines, for `eval`.
+    Env,
+
+    /// The session with its account, role and expiry.
+    Json,
+
+    /// The output expected from a `credential_process` of the AWS config.
+    AwsCredentialProcess,
+}
+
+/// Credentials of the session of `profile`.
+pub(crate) async fn fetch(profile: &str) -> Result<SessionCredentials> {
+    let req = Request::SessionCredentials { profile: profile.to_string() };
+
+    match send_request(req).await? {
+        Response::SessionCredentials(creds) => Ok(creds),
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to session credentials"),
+    }
+}
+
+pub async fn export(
+    profile: String,
+    format: Format,
+    aws_profile: Option<String>,
+) -> Result<()> {
+    let creds = fetch(&profile).await?;
+
+    if let Some(aws_profile) = aws_profile {
+        let path = write_shared_credentials(&aws_profile, &creds)?;
+        eprintln!(
+            "wrote profile {aws_profile} to {}, valid until {}",
+            path.display(),
+            expiration(&creds)
+        );
+        return Ok(());
+    }
+
+    match format {
+        Format::Env => {
+            println!("export AWS_ACCESS_KEY_ID={}", creds.access_key_id);
+            println!(
+                "export AWS_SECRET_ACCESS_KEY={}",
+                creds.secret_access_key
+            );
+            println!("export AWS_SESSION_TOKEN={}", creds.session_token);
+            if let Some(region) = &creds.region {
+                println!("export AWS_REGION={region}");
+            }
+        }
+        Format::Json => {
+            let value = json!({
+                "profile": creds.profile,
+                "account_id": creds.account_id,
+                "role_name": creds.role_name,
+                "region": creds.region,
+                "access_key_id": creds.access_key_id,
+                "secret_access_key": creds.secret_access_key,
+                "session_token": creds.session_token,
+                "expires_at": expiration(&creds),
+            });
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
+        Format::AwsCredentialProcess => print_credential_process(&creds)?,
+    }
+
+    Ok(())
+}
+
+/// Credentials in the format of a `credential_process`, version 1.
+pub(crate) fn print_credential_process(
+    creds: &SessionCredentials,
+) -> Result<()> {
+    let value = json!({
+        "Version": 1,
+        "AccessKeyId": creds.access_key_id,
+        "SecretAccessKey": creds.secret_access_key,
+        "SessionToken": creds.session_token,
+        "Expiration": expiration(creds),
+    });
+    println!("{}", serde_json::to_string(&value)?);
+
+    Ok(())
+}
+
+/// Expiry of `creds` in RFC 3339, as the AWS SDKs expect it.
+fn expiration(creds: &SessionCredentials) -> String {
+    DateTime::<Utc>::from_timestamp_millis(creds.expires_at_epoch_ms)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Replace the `[aws_profile]` section of the AWS shared credentials file,
+/// `AWS_SHARED_CREDENTIALS_FILE` or `~/.aws/credentials`, keeping the
+/// others as they are.
+fn write_shared_credentials(
+    aws_profile: &str,
+    creds: &SessionCredentials,
+) -> Result<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+    let path = var("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .or_else(|| {
+            var("HOME").map(|h| PathBuf::from(h).join(".aws/credentials"))
+        })
+        .context("cannot locate the AWS credentials file, HOME is not set")?;
+
+    let current = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            String::new()
+        }
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!("failed to read {}", path.display())
+            });
+        }
+    };
+
+    let mut content = String::new();
+    let mut skipping = false;
+    for line in current.lines() {
+        if let Some(header) =
+            line.trim().strip_prefix('[').and_then(|l| l.strip_suffix(']'))
+        {
+            skipping = header.trim() == aws_profile;
+        }
+        if !skipping {
+            content.push_str(line);
+            content.push('\n');
+        }
+    }
+    if !content.is_empty() && !content.ends_with("\n\n") {
+        content.push('\n');
+    }
+    content.push_str(&format!(
+        "[{aws_profile}]\n\
+         # written by kopsctl for profile {}, expires {}\n\
+         aws_access_key_id = {}\n\
+         aws_secret_access_key = {}\n\
+         aws_session_token = {}\n",
+        creds.profile,
+        expiration(creds),
+        creds.access_key_id,
+        creds.secret_access_key,
+        creds.session_token,
+    ));
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("failed to write {}", path.display()))?;
+
+    Ok(path)
+}
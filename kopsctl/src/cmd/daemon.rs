@@ -0,0 +1,152 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use tokio::time::{sleep, timeout};
+
+use kops_protocol::{DaemonStatus, Request, Response};
+
+use crate::helper::{
+    POLL_INTERVAL, START_TIMEOUT, launch_daemon, send_request, socket_path,
+};
+use crate::output::format_duration;
+
+/// How long the daemon may take to stop.
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a running daemon may take to answer.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub async fn reload() -> Result<()> {
+    let resp = send_request(Request::Reload).await?;
+
+    match resp {
+        Response::Reloaded { started, stopped, failed } => {
+            for cluster in &started {
+                println!("started cluster {cluster}");
+            }
+            for cluster in &stopped {
+                println!("stopped cluster {cluster}");
+            }
+            if started.is_empty() && stopped.is_empty() && failed.is_empty() {
+                println!("config reloaded, no cluster changed");
+            }
+            if !failed.is_empty() {
+                bail!("failed to start clusters: {}", failed.join(", "));
+            }
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to reload"),
+    }
+
+    Ok(())
+}
+
+/// Launch kopsd, which daemonizes with its own config, and wait until it
+/// answers.
+pub async fn start() -> Result<()> {
+    if let Some(status) = query_status().await {
+        println!("kopsd is already running (pid {})", status.pid);
+        return Ok(());
+    }
+    if socket_path().exists() {
+        bail!(
+            "kopsd does not answer on {}, stop it or remove the stale socket \
+             first",
+            socket_path().display()
+        );
+    }
+
+    launch_daemon().await?;
+
+    let deadline = Instant::now() + START_TIMEOUT;
+    loop {
+        if let Some(status) = query_status().await {
+            println!("kopsd started (pid {})", status.pid);
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "kopsd did not answer within {START_TIMEOUT:?}, check its logs"
+            );
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+pub async fn stop() -> Result<()> {
+    if !shutdown().await? {
+        println!("kopsd is not running");
+    }
+
+    Ok(())
+}
+
+pub async fn restart() -> Result<()> {
+    shutdown().await?;
+    start().await
+}
+
+pub async fn status() -> Result<()> {
+    if let Some(status) = query_status().await {
+        println!(
+            "kopsd is running (pid {}, up {})",
+            status.pid,
+            format_duration(status.uptime_secs as i64)
+        );
+        return Ok(());
+    }
+
+    if socket_path().exists() {
+        bail!("kopsd does not answer on {}", socket_path().display());
+    }
+    bail!("kopsd is not running");
+}
+
+/// Ask the daemon to stop and wait until it removed its socket; returns
+/// false if it was not running.
+async fn shutdown() -> Result<bool> {
+    let Ok(resp) = send_request(Request::Shutdown).await else {
+        return Ok(false);
+    };
+
+    let pid = match resp {
+        Response::ShuttingDown { pid } => pid,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to shutdown"),
+    };
+
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while socket_path().exists() {
+        if Instant::now() >= deadline {
+            bail!("kopsd (pid {pid}) is still running after {STOP_TIMEOUT:?}");
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+    println!("kopsd stopped (pid {pid})");
+
+    Ok(true)
+}
+
+/// Status of the daemon, or `None` if it does not answer in time.
+async fn query_status() -> Option<DaemonStatus> {
+    match timeout(ANSWER_TIMEOUT, send_request(Request::Status)).await {
+        Ok(Ok(Response::Status(status))) => Some(status),
+        _ => None,
+    }
+}
@@ -0,0 +1,104 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{DaemonSetSummary, Request, Response, WorkloadsRequest};
+
+use crate::helper::{resolve_namespace, send_request};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+
+    let req = WorkloadsRequest { cluster, namespace };
+    let resp = send_request(Request::DaemonSets(req)).await?;
+
+    match resp {
+        Response::DaemonSets { daemon_sets } => {
+            print_daemon_sets(&daemon_sets, output)?
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to daemonsets"),
+    }
+
+    Ok(())
+}
+
+fn print_daemon_sets(
+    sets: &[DaemonSetSummary],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(sets, output);
+    }
+
+    if output.quiet {
+        print_names(
+            sets.iter().map(|d| format!("{}/{}", d.namespace, d.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 30),
+        Column::new("DESIRED", 8),
+        Column::new("CURRENT", 8),
+        Column::new("READY", 6),
+        Column::new("UP-TO-DATE", 10),
+        Column::new("AVAILABLE", 10),
+        Column::new("STRATEGY", 13),
+        Column::new("REVISION", 8),
+        Column::new("IMAGES", 40),
+        Column::unstable("AGE", 6),
+    ]);
+
+    for d in sets {
+        let (cluster, namespace, name) = d.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        table.push(
+            key,
+            vec![
+                d.cluster.clone(),
+                d.namespace.clone(),
+                d.name.clone(),
+                d.desired.to_string(),
+                d.current.to_string(),
+                d.ready.to_string(),
+                d.updated.to_string(),
+                d.available.to_string(),
+                d.update_strategy.clone(),
+                d.current_revision.clone().unwrap_or_else(|| "<none>".into()),
+                d.images.join(","),
+                format_age(d.created_at_epoch_ms),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use dialoguer::Confirm;
+use kops_protocol::{DeletePodRequest, Request, Response};
+use serde::Serialize;
+
+use crate::helper::send_request;
+use crate::output::{OutputOptions, print_structured};
+use crate::picker;
+
+/// Outcome of `delete pod`, in JSON or YAML output.
+#[derive(Serialize)]
+struct Deleted<'a> {
+    namespace: &'a str,
+    pod: &'a str,
+
+    /// Gone already, rather than terminating.
+    deleted: bool,
+}
+
+pub async fn pod(
+    pod: Option<String>,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    grace_period_seconds: Option<u32>,
+    force: bool,
+    yes: bool,
+    output: OutputOptions,
+) -> Result<()> {
+    let (namespace, pod) =
+        picker::pod(pod, cluster.clone(), namespace).await?;
+
+    if !yes {
+        let prompt = if force {
+            format!("Force delete pod {namespace}/{pod}?")
+        } else {
+            format!("Delete pod {namespace}/{pod}?")
+        };
+
+        if !Confirm::new().with_prompt(prompt).default(false).interact()? {
+            println!("aborted");
+            return Ok(());
+        }
+    }
+
+    let req = DeletePodRequest {
+        cluster,
+        namespace: namespace.clone(),
+        pod: pod.clone(),
+        grace_period_seconds,
+        force,
+    };
+    let resp = send_request(Request::DeletePod(req)).await?;
+
+    match resp {
+        Response::PodDeleted { deleted } if output.structured() => {
+            let pod = Deleted { namespace: &namespace, pod: &pod, deleted };
+            print_structured(&pod, output)?
+        }
+        Response::PodDeleted { deleted: true } => {
+            println!("pod {namespace}/{pod} deleted")
+        }
+        Response::PodDeleted { deleted: false } => {
+            println!("pod {namespace}/{pod} terminating")
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to delete pod"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{
+    DeploymentSummary, DeploymentsRequest, Request, Response,
+};
+
+use crate::helper::send_request;
+use crate::output::{
+    Column, OutputOptions, Table, TableView, format_age, print_names,
+    print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    all_clusters: bool,
+    view: TableView,
+    output: OutputOptions,
+) -> Result<()> {
+    let req = DeploymentsRequest { cluster, namespace, all_clusters };
+    let resp = send_request(Request::Deployments(req)).await?;
+
+    match resp {
+        Response::Deployments { deployments } => {
+            print_deployments(&deployments, &view, output)?
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to deployments"),
+    }
+
+    Ok(())
+}
+
+fn print_deployments(
+    deployments: &[DeploymentSummary],
+    view: &TableView,
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(deployments, output);
+    }
+
+    if output.quiet {
+        print_names(
+            deployments.iter().map(|d| format!("{}/{}", d.namespace, d.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 30),
+        Column::new("READY", 8),
+        Column::new("UP-TO-DATE", 10),
+        Column::new("AVAILABLE", 10),
+        Column::new("IMAGES", 40),
+        Column::unstable("AGE", 6),
+    ]);
+
+    for d in deployments {
+        let (cluster, namespace, name) = d.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        table.push(
+            key,
+            vec![
+                d.cluster.clone(),
+                d.namespace.clone(),
+                d.name.clone(),
+                format!("{}/{}", d.ready, d.desired),
+                d.updated.to_string(),
+                d.available.to_string(),
+                d.images.join(","),
+                format_age(d.created_at_epoch_ms),
+            ],
+        );
+    }
+
+    table.apply_view(view)?;
+    table.print(output);
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{DescribePodRequest, PodDetail, Request, Response};
+
+use crate::helper::send_request;
+use crate::output::{OutputOptions, format_age, print_structured};
+use crate::picker;
+
+/// Width of the label column in `key: value` lines.
+const LABEL_WIDTH: usize = 16;
+
+pub async fn pod(
+    pod: Option<String>,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let (namespace, pod) =
+        picker::pod(pod, cluster.clone(), namespace).await?;
+
+    let req = DescribePodRequest { cluster, namespace, pod };
+    let resp = send_request(Request::DescribePod(req)).await?;
+
+    match resp {
+        Response::PodDetail(detail) if output.structured() => {
+            print_structured(&detail, output)?
+        }
+        Response::PodDetail(detail) => print_pod(&detail),
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to describe pod"),
+    }
+
+    Ok(())
+}
+
+fn print_pod(p: &PodDetail) {
+    field(0, "Name", &p.name);
+    field(0, "Namespace", &p.namespace);
+    field(0, "Cluster", &p.cluster);
+    field(0, "Node", p.node_name.as_deref().unwrap_or("<none>"));
+    field(0, "Age", &format_age(p.start_time_epoch_ms));
+    field(0, "Status", p.phase.as_deref().unwrap_or("Unknown"));
+    field(0, "IP", p.pod_ip.as_deref().unwrap_or("<none>"));
+    list(0, "Labels", p.labels.iter().map(|(k, v)| format!("{k}={v}")));
+    list(0, "Controlled By", p.owners.iter().cloned());
+
+    // Who to page and what to read first, when the pod is failing.
+    if let Some(o) = &p.ownership {
+        println!("Ownership:");
+        let fields = [
+            ("Team", &o.team),
+            ("On-call", &o.oncall),
+            ("Slack", &o.slack_channel),
+            ("Runbook", &o.runbook_url),
+        ];
+        for (label, value) in fields {
+            if let Some(value) = value {
+                field(2, label, value);
+            }
+        }
+    }
+
+    for (init, title) in [(true, "Init Containers"), (false, "Containers")] {
+        let containers: Vec<_> =
+            p.containers.iter().filter(|c| c.init == init).collect();
+        if containers.is_empty() {
+            continue;
+        }
+
+        println!("{title}:");
+        for c in containers {
+            println!("  {}:", c.name);
+            field(4, "Image", c.image.as_deref().unwrap_or("<none>"));
+            list(4, "Ports", c.ports.iter().cloned());
+            field(4, "State", &c.state);
+            field(4, "Ready", &c.ready.to_string());
+            field(4, "Restart Count", &c.restart_count.to_string());
+            list(
+                4,
+                "Requests",
+                c.requests.iter().map(|(k, v)| format!("{k}: {v}")),
+            );
+            list(
+                4,
+                "Limits",
+                c.limits.iter().map(|(k, v)| format!("{k}: {v}")),
+            );
+        }
+    }
+
+    println!("Conditions:");
+    for c in &p.conditions {
+        match &c.reason {
+            Some(reason) => {
+                println!("  {:<20} {} ({reason})", c.type_, c.status)
+            }
+            None => println!("  {:<20} {}", c.type_, c.status),
+        }
+    }
+
+    println!("Volumes:");
+    for v in &p.volumes {
+        match &v.source {
+            Some(source) => println!("  {}: {} ({source})", v.name, v.kind),
+            None => println!("  {}: {}", v.name, v.kind),
+        }
+    }
+
+    list(0, "Tolerations", p.tolerations.iter().cloned());
+
+    if p.events.is_empty() {
+        field(0, "Events", "<none>");
+        return;
+    }
+
+    println!("Events:");
+    println!("  {:<8} {:<20} {:<10} MESSAGE", "TYPE", "REASON", "AGE");
+    for e in &p.events {
+        println!(
+            "  {:<8} {:<20} {:<10} {}",
+            e.type_,
+            e.reason,
+            format_age(e.last_seen_epoch_ms),
+            e.message
+        );
+    }
+}
+
+/// Print a `label: value` line indented by `indent` spaces.
+fn field(indent: usize, label: &str, value: &str) {
+    let label = format!("{label}:");
+    println!(
+        "{:indent$}{:<width$} {value}",
+        "",
+        label,
+        width = LABEL_WIDTH.saturating_sub(indent)
+    );
+}
+
+/// Print a multi-value field, one value per line aligned on the first.
+fn list(indent: usize, label: &str, mut values: impl Iterator<Item = String>) {
+    let Some(first) = values.next() else {
+        field(indent, label, "<none>");
+        return;
+    };
+
+    field(indent, label, &first);
+    for value in values {
+        println!("{:pad$} {value}", "", pad = LABEL_WIDTH);
+    }
+}
@@ -14,78 +14,296 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
+use std::collections::{BTreeMap, BTreeSet};
+
 use anyhow::{Result, bail};
 
-use dialoguer::FuzzySelect;
-use kops_protocol::{EnvEntry, EnvRequest, PodsRequest, Request, Response};
+use kops_protocol::{
+    EnvEntry, EnvGrepRequest, EnvRequest, EnvSource, Request, Response,
+};
+
+use crate::helper::{connect_mux, resolve_namespace, send_request, split_ref};
+use crate::output::{
+    OutputOptions, print_names, print_structured, stable_sort,
+};
+use crate::picker;
+
+/// Which containers of the selected pod to read, and how to filter them.
+pub(crate) struct EnvQuery {
+    pub(crate) cluster: Option<String>,
+    pub(crate) namespace: Option<String>,
+    pub(crate) pod: Option<String>,
+    pub(crate) container: Option<String>,
+    pub(crate) all_containers: bool,
+    pub(crate) filter: Option<String>,
+    pub(crate) resolve: bool,
+    pub(crate) reveal_secrets: bool,
+}
+
+impl EnvQuery {
+    fn request(
+        &self,
+        cluster: Option<String>,
+        namespace: String,
+        pod: String,
+    ) -> EnvRequest {
+        EnvRequest {
+            cluster,
+            namespace,
+            pod,
+            container: self.container.clone(),
+            all_containers: self.all_containers,
+            filter_regex: self.filter.clone(),
+            resolve: self.resolve || self.reveal_secrets,
+            reveal_secrets: self.reveal_secrets,
+        }
+    }
+}
 
-use crate::helper::send_request;
+/// The two pods compared by `env diff`, each as `<name>` or
+/// `<namespace>/<name>`.
+pub(crate) struct DiffTargets {
+    pub(crate) left: String,
+    pub(crate) right: String,
+    pub(crate) left_cluster: Option<String>,
+    pub(crate) right_cluster: Option<String>,
+}
 
 pub async fn execute(
-    cluster: Option<String>,
-    namespace: Option<String>,
-    _pod: Option<String>,
-    container: Option<String>,
-    filter: Option<String>,
+    query: EnvQuery,
+    tree: bool,
+    output: OutputOptions,
 ) -> Result<()> {
-    let req = PodsRequest {
-        cluster: cluster.clone(),
-        namespace,
-        failed_only: false,
+    let (namespace, pod) = picker::pod(
+        query.pod.clone(),
+        query.cluster.clone(),
+        query.namespace.clone(),
+    )
+    .await?;
+
+    let req = query.request(query.cluster.clone(), namespace, pod);
+    show(fetch_vars(req).await?, &query, tree, output)
+}
+
+pub async fn diff(query: EnvQuery, targets: DiffTargets) -> Result<()> {
+    let side = |reference: &str, cluster: Option<String>| {
+        let cluster = cluster.or_else(|| query.cluster.clone());
+        let (namespace, pod) = split_ref(reference, query.namespace.clone());
+        let label = match &cluster {
+            Some(c) => format!("{c}/{namespace}/{pod}"),
+            None => format!("{namespace}/{pod}"),
+        };
+        (label, query.request(cluster, namespace, pod))
     };
-    let resp = send_request(Request::Pods(req)).await?;
 
-    match resp {
-        Response::Pods { pods } => {
-            let mut items: Vec<String> = pods
-                .iter()
-                .map(|p| format!("{} / {}", p.namespace, p.name))
-                .collect();
-
-            items.sort();
-
-            let selection = FuzzySelect::new()
-                .with_prompt("Select pod")
-                .items(&items)
-                .interact()
-                .unwrap();
-
-            let item = &items[selection];
-            let item: Vec<&str> = item.split('/').collect();
-
-            if item.len() != 2 {
-                bail!("unexpected response to version");
-            };
-
-            let resp = send_request(Request::Env(EnvRequest {
-                cluster,
-                namespace: item[0].trim().to_string(),
-                pod: item[1].trim().to_string(),
-                container,
-                filter_regex: filter,
-            }))
-            .await?;
-
-            match resp {
-                Response::EnvVars { vars } => print_vars(&vars),
-                Response::Error { message } => {
-                    bail!("reponse error {message}")
-                }
-                _ => bail!("unexpected response to version"),
+    let (left_label, left) = side(&targets.left, targets.left_cluster);
+    let (right_label, right) = side(&targets.right, targets.right_cluster);
+
+    let by_name = |vars: Vec<EnvEntry>| -> BTreeMap<String, EnvEntry> {
+        vars.into_iter()
+            .map(|v| (display_name(&v, query.all_containers), v))
+            .collect()
+    };
+    // Both pods are read at once, over one connection.
+    let mux = connect_mux().await?;
+    let (left, right) = tokio::try_join!(
+        mux.call(Request::Env(left)),
+        mux.call(Request::Env(right)),
+    )?;
+    let left = by_name(env_vars(left)?);
+    let right = by_name(env_vars(right)?);
+
+    println!("--- {left_label}");
+    println!("+++ {right_label}");
+
+    let names: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    for name in names {
+        match (left.get(name), right.get(name)) {
+            (Some(l), None) => {
+                removed += 1;
+                println!("- {name} = {}", describe_value(l));
+            }
+            (None, Some(r)) => {
+                added += 1;
+                println!("+ {name} = {}", describe_value(r));
             }
+            (Some(l), Some(r)) if differs(l, r) => {
+                changed += 1;
+                let (l, r) = (describe_value(l), describe_value(r));
+                println!("~ {name} = {l} -> {r}");
+            }
+            _ => {}
         }
-        _ => bail!("unexpected response to version"),
+    }
+
+    println!("{added} added, {removed} removed, {changed} changed");
+
+    Ok(())
+}
+
+/// Print the variables of every pod of the cluster whose name, literal
+/// value or referenced ConfigMap or Secret matches `pattern`, as
+/// `<namespace>/<pod>/<container>/<name> = <value>`.
+pub async fn grep(
+    query: EnvQuery,
+    pattern: String,
+    output: OutputOptions,
+) -> Result<()> {
+    let namespace =
+        resolve_namespace(query.cluster.clone(), query.namespace).await?;
+    let req = EnvGrepRequest { cluster: query.cluster, namespace, pattern };
+
+    let matches = match send_request(Request::EnvGrep(req)).await? {
+        Response::EnvMatches { matches } => matches,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to env grep"),
     };
 
+    if output.structured() {
+        return print_structured(&matches, output);
+    }
+
+    if output.quiet {
+        let pods: BTreeSet<String> = matches
+            .iter()
+            .map(|m| format!("{}/{}", m.namespace, m.pod))
+            .collect();
+        print_names(pods);
+        return Ok(());
+    }
+
+    for m in &matches {
+        println!(
+            "{}/{}/{} = {}",
+            m.namespace,
+            m.pod,
+            display_name(&m.var, true),
+            describe_reference(&m.var)
+        );
+    }
+
+    Ok(())
+}
+
+async fn fetch_vars(req: EnvRequest) -> Result<Vec<EnvEntry>> {
+    env_vars(send_request(Request::Env(req)).await?)
+}
+
+fn env_vars(resp: Response) -> Result<Vec<EnvEntry>> {
+    match resp {
+        Response::EnvVars { vars } => Ok(vars),
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to env"),
+    }
+}
+
+/// Values are compared when known; unresolved references by their source.
+fn differs(left: &EnvEntry, right: &EnvEntry) -> bool {
+    match (&left.value, &right.value) {
+        (None, None) => left.source != right.source,
+        (l, r) => l != r,
+    }
+}
+
+fn show(
+    mut vars: Vec<EnvEntry>,
+    query: &EnvQuery,
+    tree: bool,
+    output: OutputOptions,
+) -> Result<()> {
+    stable_sort(&mut vars, output, |a, b| a.sort_key().cmp(&b.sort_key()));
+    if output.structured() {
+        return print_structured(&vars, output);
+    }
+
+    if tree {
+        print_tree(&vars, query.all_containers)
+    } else {
+        print_vars(&vars, query.all_containers)
+    }
+
     Ok(())
 }
 
-fn print_vars(vars: &Vec<EnvEntry>) {
+fn print_vars(vars: &Vec<EnvEntry>, with_container: bool) {
     for v in vars {
         println!(
             "{} = {}",
-            v.name,
-            v.value.clone().unwrap_or("<none>".to_string())
+            display_name(v, with_container),
+            describe_value(v)
         );
     }
 }
+
+/// Print variables grouped by source, literals first.
+fn print_tree(vars: &[EnvEntry], with_container: bool) {
+    let mut groups: BTreeMap<(u8, String), Vec<&EnvEntry>> = BTreeMap::new();
+    for v in vars {
+        groups.entry(source_group(&v.source)).or_default().push(v);
+    }
+
+    for ((_, label), entries) in groups {
+        println!("{label}");
+
+        for (i, v) in entries.iter().enumerate() {
+            let branch =
+                if i + 1 == entries.len() { "└──" } else { "├──" };
+            println!(
+                "{branch} {} = {}",
+                display_name(v, with_container),
+                describe_value(v)
+            );
+        }
+    }
+}
+
+/// Variable name, prefixed with its container when several are shown.
+fn display_name(v: &EnvEntry, with_container: bool) -> String {
+    if with_container {
+        format!("{}/{}", v.container, v.name)
+    } else {
+        v.name.clone()
+    }
+}
+
+/// Group ordering rank and label for a source.
+fn source_group(source: &EnvSource) -> (u8, String) {
+    match source {
+        EnvSource::Literal => (0, "literal".to_string()),
+        EnvSource::ConfigMap { name, .. } => (1, format!("ConfigMap {name}")),
+        EnvSource::Secret { name, .. } => (2, format!("Secret {name}")),
+        EnvSource::DownwardApi { .. } => (3, "Downward API".to_string()),
+    }
+}
+
+/// Like `describe_value`, naming the ConfigMap or Secret a reference
+/// reads from.
+fn describe_reference(v: &EnvEntry) -> String {
+    match &v.source {
+        EnvSource::ConfigMap { name, key } => {
+            format!("<ConfigMap {name} key {key}>")
+        }
+        EnvSource::Secret { name, key } => {
+            format!("<Secret {name} key {key}>")
+        }
+        _ => describe_value(v),
+    }
+}
+
+fn describe_value(v: &EnvEntry) -> String {
+    if let Some(value) = &v.value {
+        return value.clone();
+    }
+    if v.masked {
+        return "<hidden>".to_string();
+    }
+
+    match &v.source {
+        EnvSource::Literal => "<none>".to_string(),
+        EnvSource::ConfigMap { key, .. } | EnvSource::Secret { key, .. } => {
+            format!("<key {key}>")
+        }
+        EnvSource::DownwardApi { path } => format!("<{path}>"),
+    }
+}
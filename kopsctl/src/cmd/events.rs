@@ -0,0 +1,111 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{EventSummary, EventsRequest, Request, Response};
+
+use crate::helper::{parse_duration, send_request};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    involved_object: Option<String>,
+    since: Option<String>,
+    all_clusters: bool,
+    output: OutputOptions,
+) -> Result<()> {
+    let since_seconds = match since {
+        Some(s) => Some(parse_duration(&s)?.as_secs() as i64),
+        None => None,
+    };
+
+    let req = EventsRequest {
+        cluster,
+        namespace,
+        involved_object,
+        since_seconds,
+        all_clusters,
+    };
+    let resp = send_request(Request::Events(req)).await?;
+
+    match resp {
+        Response::Events { events } => print_events(&events, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to events"),
+    }
+
+    Ok(())
+}
+
+fn print_events(events: &[EventSummary], output: OutputOptions) -> Result<()> {
+    if output.structured() {
+        return print_structured(events, output);
+    }
+
+    if output.quiet {
+        print_names(
+            events.iter().map(|e| format!("{}/{}", e.namespace, e.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::unstable("LAST SEEN", 10),
+        Column::unstable("FIRST SEEN", 10),
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("TYPE", 8),
+        Column::new("REASON", 20),
+        Column::new("OBJECT", 40),
+        Column::new("COUNT", 6),
+        Column::new("MESSAGE", 0),
+    ]);
+
+    for e in events {
+        let (seen, cluster, namespace, name) = e.sort_key();
+        // Zero-padded so the byte-wise ordering of stable mode stays
+        // chronological.
+        let key = vec![
+            format!("{:020}", seen.unwrap_or(0)),
+            cluster.to_string(),
+            namespace.to_string(),
+            name.to_string(),
+        ];
+
+        table.push(
+            key,
+            vec![
+                format_age(e.last_seen_epoch_ms),
+                format_age(e.first_seen_epoch_ms),
+                e.cluster.clone(),
+                e.namespace.clone(),
+                e.type_.clone(),
+                e.reason.clone(),
+                e.object.clone(),
+                e.count.to_string(),
+                e.message.clone(),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
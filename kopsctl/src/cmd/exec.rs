@@ -0,0 +1,188 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::unix::{OwnedReadHalf, OwnedWriteHalf},
+    signal::unix::{SignalKind, signal},
+    sync::mpsc,
+    time::{interval, timeout},
+};
+
+use kops_protocol::{
+    ExecRequest, Request,
+    wire::{
+        ExecFrame, HEARTBEAT_INTERVAL, HEARTBEAT_TIMEOUT, read_message,
+        write_message,
+    },
+};
+
+use crate::helper::connect;
+use crate::picker;
+
+pub async fn execute(
+    pod: Option<String>,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    container: Option<String>,
+    stdin: bool,
+    tty: bool,
+    command: Vec<String>,
+) -> Result<()> {
+    let (namespace, pod) =
+        picker::pod(pod, cluster.clone(), namespace).await?;
+
+    let req = Request::Exec(ExecRequest {
+        cluster,
+        namespace,
+        pod,
+        container,
+        command,
+        stdin,
+        tty,
+    });
+
+    let mut stream = connect().await?;
+    write_message(&mut stream, &req).await?;
+    let (mut rd, wr) = stream.into_split();
+
+    let raw_mode = if tty { Some(RawMode::enable()?) } else { None };
+
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(write_frames(wr, rx));
+    tokio::spawn(send_heartbeats(tx.clone()));
+    if tty {
+        tokio::spawn(forward_resizes(tx.clone()));
+    }
+    if stdin {
+        tokio::spawn(forward_stdin(tx.clone()));
+    }
+    drop(tx);
+
+    let code = read_frames(&mut rd).await;
+    drop(raw_mode);
+
+    // Exit right away: the blocking stdin reader would otherwise keep the
+    // runtime alive until the user presses enter.
+    std::process::exit(code?);
+}
+
+/// Print output frames until the daemon reports the exit code.
+async fn read_frames(rd: &mut OwnedReadHalf) -> Result<i32> {
+    let mut stdout = tokio::io::stdout();
+    let mut stderr = tokio::io::stderr();
+
+    loop {
+        let Ok(read) = timeout(HEARTBEAT_TIMEOUT, read_message(rd)).await
+        else {
+            bail!("daemon stopped responding mid-session");
+        };
+        let frame: ExecFrame = match read? {
+            Some(f) => f,
+            None => bail!("daemon closed connection mid-session"),
+        };
+
+        match frame {
+            ExecFrame::Stdout(data) => {
+                stdout.write_all(&data).await?;
+                stdout.flush().await?;
+            }
+            ExecFrame::Stderr(data) => {
+                stderr.write_all(&data).await?;
+                stderr.flush().await?;
+            }
+            ExecFrame::Heartbeat => {}
+            ExecFrame::Exit { code } => return Ok(code),
+            ExecFrame::Error { message } => bail!("exec failed: {message}"),
+            _ => bail!("unexpected frame from daemon"),
+        }
+    }
+}
+
+async fn write_frames(
+    mut wr: OwnedWriteHalf,
+    mut rx: mpsc::Receiver<ExecFrame>,
+) {
+    while let Some(frame) = rx.recv().await {
+        if write_message(&mut wr, &frame).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Show the daemon the session is alive, even without input.
+async fn send_heartbeats(tx: mpsc::Sender<ExecFrame>) {
+    let mut ticks = interval(HEARTBEAT_INTERVAL);
+    loop {
+        ticks.tick().await;
+        if tx.send(ExecFrame::Heartbeat).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn forward_stdin(tx: mpsc::Sender<ExecFrame>) {
+    let mut stdin = tokio::io::stdin();
+    let mut buf = vec![0u8; 4096];
+
+    loop {
+        let frame = match stdin.read(&mut buf).await {
+            Ok(0) | Err(_) => ExecFrame::StdinEof,
+            Ok(n) => ExecFrame::Stdin(buf[..n].to_vec()),
+        };
+
+        let eof = matches!(frame, ExecFrame::StdinEof);
+        if tx.send(frame).await.is_err() || eof {
+            break;
+        }
+    }
+}
+
+/// Send the current terminal size, then again on every SIGWINCH.
+async fn forward_resizes(tx: mpsc::Sender<ExecFrame>) -> Result<()> {
+    let mut winch = signal(SignalKind::window_change())?;
+
+    loop {
+        if let Ok((width, height)) = crossterm::terminal::size()
+            && tx.send(ExecFrame::Resize { width, height }).await.is_err()
+        {
+            break;
+        }
+
+        if winch.recv().await.is_none() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Keeps the local terminal in raw mode while alive.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        crossterm::terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
@@ -0,0 +1,94 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{GetRequest, Request, ResourceKind, Response};
+
+use crate::helper::{send_request, split_ref};
+use crate::output::{OutputFormat, OutputOptions, print_structured};
+
+/// Kinds the daemon keeps in its stores.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Kind {
+    Pod,
+    Deployment,
+    #[value(name = "statefulset")]
+    StatefulSet,
+    #[value(name = "daemonset")]
+    DaemonSet,
+    Service,
+    Ingress,
+    Job,
+    #[value(name = "cronjob")]
+    CronJob,
+    Hpa,
+    Pvc,
+    Node,
+    Namespace,
+}
+
+impl From<Kind> for ResourceKind {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Pod => Self::Pod,
+            Kind::Deployment => Self::Deployment,
+            Kind::StatefulSet => Self::StatefulSet,
+            Kind::DaemonSet => Self::DaemonSet,
+            Kind::Service => Self::Service,
+            Kind::Ingress => Self::Ingress,
+            Kind::Job => Self::Job,
+            Kind::CronJob => Self::CronJob,
+            Kind::Hpa => Self::Hpa,
+            Kind::Pvc => Self::Pvc,
+            Kind::Node => Self::Node,
+            Kind::Namespace => Self::Namespace,
+        }
+    }
+}
+
+/// Print the full object as the daemon caches it, as YAML unless JSON
+/// is asked for.
+pub async fn execute(
+    kind: Kind,
+    name: String,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let kind = ResourceKind::from(kind);
+    let (namespace, name) = if kind.namespaced() {
+        let (namespace, name) = split_ref(&name, namespace);
+        (Some(namespace), name)
+    } else {
+        (None, name)
+    };
+
+    let req = GetRequest { cluster, kind, namespace, name };
+    let object = match send_request(Request::Get(req)).await? {
+        Response::Manifest { object } => object,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to get"),
+    };
+
+    let object: serde_json::Value = serde_json::from_str(&object)?;
+    let format = match output.format {
+        OutputFormat::Json => OutputFormat::Json,
+        _ => OutputFormat::Yaml,
+    };
+
+    print_structured(&object, OutputOptions { format, ..output })
+}
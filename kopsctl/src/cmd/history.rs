@@ -0,0 +1,115 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{Request, Response, RestartEntry, RestartHistoryRequest};
+
+use crate::helper::send_request;
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+use crate::picker;
+
+pub async fn execute(
+    pod: Option<String>,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let (namespace, pod) =
+        picker::pod(pod, cluster.clone(), namespace).await?;
+
+    let req = RestartHistoryRequest { cluster, namespace, pod };
+    let resp = send_request(Request::RestartHistory(req)).await?;
+
+    match resp {
+        Response::RestartHistory { restarts } => {
+            print_history(&restarts, output)?
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to history"),
+    }
+
+    Ok(())
+}
+
+fn print_history(
+    restarts: &[RestartEntry],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(restarts, output);
+    }
+
+    // The containers, once each.
+    if output.quiet {
+        let mut seen = HashSet::new();
+        print_names(
+            restarts
+                .iter()
+                .map(|r| r.container.clone())
+                .filter(|container| seen.insert(container.clone())),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::unstable("RESTARTED", 10),
+        Column::new("CLUSTER", 20),
+        Column::new("CONTAINER", 20),
+        Column::new("RESTARTS", 8),
+        Column::new("REASON", 20),
+        Column::new("EXIT CODE", 9),
+        Column::new("CAUSE", 0),
+    ]);
+
+    let none = || "<none>".to_string();
+
+    for r in restarts {
+        let (at, cluster, container) = r.sort_key();
+        // Zero-padded so the byte-wise ordering of stable mode stays
+        // chronological.
+        let key =
+            vec![format!("{at:020}"), cluster.to_string(), container.into()];
+
+        // The closest event before the restart.
+        let cause = r
+            .causes
+            .last()
+            .map(|e| format!("{}: {}", e.reason, e.message))
+            .unwrap_or_else(none);
+
+        table.push(
+            key,
+            vec![
+                format_age(Some(r.at_epoch_ms)),
+                r.cluster.clone(),
+                r.container.clone(),
+                r.count.to_string(),
+                r.reason.clone().unwrap_or_else(none),
+                r.exit_code.map(|c| c.to_string()).unwrap_or_else(none),
+                cause,
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
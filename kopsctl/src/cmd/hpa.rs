@@ -0,0 +1,100 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{HpaSummary, Request, Response, WorkloadsRequest};
+
+use crate::helper::{resolve_namespace, send_request};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+
+    let req = WorkloadsRequest { cluster, namespace };
+    let resp = send_request(Request::Hpas(req)).await?;
+
+    match resp {
+        Response::Hpas { hpas } => print_hpas(&hpas, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to hpa"),
+    }
+
+    Ok(())
+}
+
+fn print_hpas(hpas: &[HpaSummary], output: OutputOptions) -> Result<()> {
+    if output.structured() {
+        return print_structured(hpas, output);
+    }
+
+    if output.quiet {
+        print_names(
+            hpas.iter().map(|h| format!("{}/{}", h.namespace, h.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 30),
+        Column::new("REFERENCE", 30),
+        Column::unstable("METRICS", 30),
+        Column::new("MIN", 4),
+        Column::new("MAX", 4),
+        Column::new("REPLICAS", 8),
+        Column::new("DESIRED", 7),
+        Column::unstable("LAST SCALE", 10),
+    ]);
+
+    for h in hpas {
+        let (cluster, namespace, name) = h.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        let last_scale = match h.last_scale_epoch_ms {
+            Some(_) => format!("{} ago", format_age(h.last_scale_epoch_ms)),
+            None => "<never>".to_string(),
+        };
+
+        table.push(
+            key,
+            vec![
+                h.cluster.clone(),
+                h.namespace.clone(),
+                h.name.clone(),
+                h.target.clone(),
+                h.metrics.join(", "),
+                h.min_replicas.to_string(),
+                h.max_replicas.to_string(),
+                h.current_replicas.to_string(),
+                h.desired_replicas.to_string(),
+                last_scale,
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
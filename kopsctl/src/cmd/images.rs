@@ -0,0 +1,88 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{ImageUsage, Request, Response, WorkloadsRequest};
+
+use crate::helper::send_request;
+use crate::output::{
+    Column, OutputOptions, Table, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let req = WorkloadsRequest { cluster, namespace };
+    let resp = send_request(Request::Images(req)).await?;
+
+    match resp {
+        Response::Images { images } => print_images(&images, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to images"),
+    }
+
+    Ok(())
+}
+
+fn print_images(images: &[ImageUsage], output: OutputOptions) -> Result<()> {
+    if output.structured() {
+        return print_structured(images, output);
+    }
+
+    // Full references, as a pod spec would name them.
+    if output.quiet {
+        print_names(images.iter().map(|i| {
+            if i.tag.starts_with('@') {
+                format!("{}{}", i.repository, i.tag)
+            } else {
+                format!("{}:{}", i.repository, i.tag)
+            }
+        }));
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("IMAGE", 50),
+        Column::new("TAG", 20),
+        Column::unstable("PODS", 5),
+        Column::new("NAMESPACES", 40),
+    ]);
+
+    for i in images {
+        let (cluster, repository, tag) = i.sort_key();
+        let key =
+            vec![cluster.to_string(), repository.to_string(), tag.to_string()];
+
+        table.push(
+            key,
+            vec![
+                i.cluster.clone(),
+                i.repository.clone(),
+                i.tag.clone(),
+                i.pods.to_string(),
+                i.namespaces.join(","),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{
+    IngressRoute, IngressSummary, IngressesRequest, Request, Response,
+};
+
+use crate::helper::{resolve_namespace, send_request};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+
+    let req = IngressesRequest { cluster, namespace };
+    let resp = send_request(Request::Ingresses(req)).await?;
+
+    match resp {
+        Response::Ingresses { ingresses } => {
+            print_ingresses(&ingresses, output)?
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to ingresses"),
+    }
+
+    Ok(())
+}
+
+/// One row per route, so every host/path shows its backend.
+fn print_ingresses(
+    ingresses: &[IngressSummary],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(ingresses, output);
+    }
+
+    if output.quiet {
+        print_names(
+            ingresses.iter().map(|i| format!("{}/{}", i.namespace, i.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 30),
+        Column::new("CLASS", 10),
+        Column::new("HOST", 30),
+        Column::new("PATH", 15),
+        Column::new("BACKEND", 30),
+        Column::new("TLS", 20),
+        Column::new("ADDRESS", 20),
+        Column::unstable("AGE", 6),
+    ]);
+
+    let none = || "<none>".to_string();
+    let join = |v: &[String]| if v.is_empty() { none() } else { v.join(",") };
+
+    // An ingress without rules still gets a row.
+    let empty = [IngressRoute { host: None, path: None, backend: none() }];
+
+    for i in ingresses {
+        let routes =
+            if i.routes.is_empty() { &empty[..] } else { &i.routes[..] };
+
+        for r in routes {
+            let host = r.host.clone().unwrap_or_else(|| "*".to_string());
+            let path = r.path.clone().unwrap_or_else(|| "<default>".into());
+
+            let (cluster, namespace, name) = i.sort_key();
+            let key = vec![
+                cluster.to_string(),
+                namespace.to_string(),
+                name.to_string(),
+                host.clone(),
+                path.clone(),
+            ];
+
+            table.push(
+                key,
+                vec![
+                    i.cluster.clone(),
+                    i.namespace.clone(),
+                    i.name.clone(),
+                    i.class.clone().unwrap_or_else(none),
+                    host,
+                    path,
+                    r.backend.clone(),
+                    join(&i.tls_secrets),
+                    join(&i.addresses),
+                    format_age(i.created_at_epoch_ms),
+                ],
+            );
+        }
+    }
+
+    table.print(output);
+
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{
+    CronJobSummary, CronJobsRequest, JobSummary, JobsRequest, Request,
+    Response,
+};
+
+use crate::helper::{resolve_namespace, send_request};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn jobs(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    failed_only: bool,
+    output: OutputOptions,
+) -> Result<()> {
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+
+    let req = JobsRequest { cluster, namespace, failed_only };
+    let resp = send_request(Request::Jobs(req)).await?;
+
+    match resp {
+        Response::Jobs { jobs } => print_jobs(&jobs, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to jobs"),
+    }
+
+    Ok(())
+}
+
+pub async fn cron_jobs(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+
+    let req = CronJobsRequest { cluster, namespace };
+    let resp = send_request(Request::CronJobs(req)).await?;
+
+    match resp {
+        Response::CronJobs { cron_jobs } => {
+            print_cron_jobs(&cron_jobs, output)?
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to cronjobs"),
+    }
+
+    Ok(())
+}
+
+fn print_jobs(jobs: &[JobSummary], output: OutputOptions) -> Result<()> {
+    if output.structured() {
+        return print_structured(jobs, output);
+    }
+
+    if output.quiet {
+        print_names(
+            jobs.iter().map(|j| format!("{}/{}", j.namespace, j.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 40),
+        Column::new("STATUS", 10),
+        Column::new("COMPLETIONS", 11),
+        Column::new("ACTIVE", 6),
+        Column::new("FAILED", 6),
+        Column::new("CRONJOB", 30),
+        Column::unstable("AGE", 6),
+        Column::unstable("REASON", 0),
+    ]);
+
+    for j in jobs {
+        let (cluster, namespace, name) = j.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        table.push(
+            key,
+            vec![
+                j.cluster.clone(),
+                j.namespace.clone(),
+                j.name.clone(),
+                j.status.clone(),
+                format!("{}/{}", j.succeeded, j.completions),
+                j.active.to_string(),
+                j.failed.to_string(),
+                j.cron_job.clone().unwrap_or_else(|| "<none>".into()),
+                format_age(j.started_at_epoch_ms),
+                j.failure_reason.clone().unwrap_or_default(),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
+
+fn print_cron_jobs(
+    cron_jobs: &[CronJobSummary],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(cron_jobs, output);
+    }
+
+    if output.quiet {
+        print_names(
+            cron_jobs.iter().map(|c| format!("{}/{}", c.namespace, c.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 40),
+        Column::new("SCHEDULE", 15),
+        Column::new("SUSPEND", 7),
+        Column::new("ACTIVE", 6),
+        Column::unstable("LAST SCHEDULE", 13),
+        Column::unstable("LAST SUCCESS", 12),
+    ]);
+
+    let ago = |t: Option<i64>| match t {
+        Some(_) => format!("{} ago", format_age(t)),
+        None => "<never>".to_string(),
+    };
+
+    for c in cron_jobs {
+        let (cluster, namespace, name) = c.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        table.push(
+            key,
+            vec![
+                c.cluster.clone(),
+                c.namespace.clone(),
+                c.name.clone(),
+                c.schedule.clone(),
+                c.suspend.to_string(),
+                c.active.to_string(),
+                ago(c.last_schedule_epoch_ms),
+                ago(c.last_successful_epoch_ms),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! `kopsctl kubeconfig`: kubeconfig entries for the EKS clusters of the
+//! daemon, so that kubectl reaches them with the tokens of `kopsctl token`.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result, bail};
+use kops_protocol::{ClusterAccess, Request, Response};
+use serde_yaml::{Mapping, Value};
+
+use crate::helper::send_request;
+
+/// Add or update the cluster, user and context of `cluster` (the default
+/// cluster when `None`) in the kubeconfig at `path`.
+pub async fn write(
+    cluster: Option<String>,
+    path: Option<PathBuf>,
+    context: Option<String>,
+    use_context: bool,
+) -> Result<()> {
+    let access = match send_request(Request::ClusterAccess { cluster }).await?
+    {
+        Response::ClusterAccess(access) => access,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to cluster access"),
+    };
+
+    let path = match path {
+        Some(path) => path,
+        None => default_path()?,
+    };
+    let mut config = read(&path)?;
+
+    let context = context.unwrap_or_else(|| access.cluster.clone());
+    let user = format!("kops-{}", access.cluster);
+    upsert(
+        &mut config,
+        "clusters",
+        &access.cluster,
+        "cluster",
+        cluster_entry(&access),
+    );
+    upsert(&mut config, "users", &user, "user", user_entry(&access));
+    upsert(
+        &mut config,
+        "contexts",
+        &context,
+        "context",
+        mapping([
+            ("cluster", Value::from(access.cluster.as_str())),
+            ("user", Value::from(user.as_str())),
+        ]),
+    );
+
+    let current = config.get("current-context").and_then(Value::as_str);
+    if use_context || current.is_none_or(str::is_empty) {
+        config.insert("current-context".into(), context.as_str().into());
+    }
+
+    save(&path, &config)?;
+    println!(
+        "context {context} of cluster {} written to {}",
+        access.cluster,
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// The first file of KUBECONFIG, like kubectl writes to, else
+/// `~/.kube/config`.
+fn default_path() -> Result<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+
+    if let Some(paths) = var("KUBECONFIG")
+        && let Some(first) = std::env::split_paths(&paths).next()
+    {
+        return Ok(first);
+    }
+    var("HOME")
+        .map(|home| PathBuf::from(home).join(".kube/config"))
+        .context("cannot locate the kubeconfig, HOME is not set")
+}
+
+fn read(path: &PathBuf) -> Result<Mapping> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            String::new()
+        }
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!("failed to read {}", path.display())
+            });
+        }
+    };
+
+    let config = match serde_yaml::from_str(&content)
+        .with_context(|| format!("invalid kubeconfig {}", path.display()))?
+    {
+        Value::Null => mapping([
+            ("apiVersion", "v1".into()),
+            ("kind", "Config".into()),
+            ("preferences", Value::Mapping(Mapping::new())),
+        ]),
+        Value::Mapping(config) => config,
+        _ => bail!("invalid kubeconfig {}: not a mapping", path.display()),
+    };
+
+    Ok(config)
+}
+
+fn save(path: &PathBuf, config: &Mapping) -> Result<()> {
+    let content = serde_yaml::to_string(config)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("failed to write {}", path.display()))?;
+
+    Ok(())
+}
+
+fn cluster_entry(access: &ClusterAccess) -> Mapping {
+    mapping([
+        ("server", access.server.as_str().into()),
+        (
+            "certificate-authority-data",
+            access.certificate_authority_data.as_str().into(),
+        ),
+    ])
+}
+
+/// User running `kopsctl token` as a client-go exec plugin.
+fn user_entry(access: &ClusterAccess) -> Mapping {
+    let args = ["token", "--cluster", access.cluster.as_str()];
+    let exec = mapping([
+        ("apiVersion", "client.authentication.k8s.io/v1".into()),
+        ("command", "kopsctl".into()),
+        ("args", args.into_iter().map(Value::from).collect()),
+        ("interactiveMode", "Never".into()),
+        ("provideClusterInfo", false.into()),
+    ]);
+
+    mapping([("exec", Value::Mapping(exec))])
+}
+
+/// Replace the entry `name` of the named list `list` (e.g. `clusters`),
+/// or append it, with `{name, <kind>: value}`.
+fn upsert(
+    config: &mut Mapping,
+    list: &str,
+    name: &str,
+    kind: &str,
+    value: Mapping,
+) {
+    let entry = Value::Mapping(mapping([
+        ("name", name.into()),
+        (kind, Value::Mapping(value)),
+    ]));
+
+    let entries = config
+        .entry(list.into())
+        .or_insert_with(|| Value::Sequence(Vec::new()));
+    if !entries.is_sequence() {
+        *entries = Value::Sequence(Vec::new());
+    }
+    let Some(entries) = entries.as_sequence_mut() else {
+        return;
+    };
+
+    let existing = entries
+        .iter_mut()
+        .find(|e| e.get("name").and_then(Value::as_str) == Some(name));
+    match existing {
+        Some(existing) => *existing = entry,
+        None => entries.push(entry),
+    }
+}
+
+fn mapping<const N: usize>(entries: [(&str, Value); N]) -> Mapping {
+    entries.into_iter().map(|(k, v)| (k.into(), v)).collect()
+}
@@ -14,33 +14,146 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
+use std::{
+    fmt::Display,
+    io::{self, BufRead, IsTerminal},
+};
+
 use anyhow::{Result, anyhow, bail};
+use aws_config::SdkConfig;
 use aws_types::region::Region;
-use kops_aws_sso::{SsoLoginConfig, login_device_flow};
-use kops_protocol::{LoginRequest, Request, Response};
+use dialoguer::FuzzySelect;
+use kops_aws_sso::{
+    AwsProfile, SsoLoginConfig, SsoToken, authenticate, list_account_roles,
+    list_accounts, role_session,
+};
+use kops_protocol::{
+    ClusterAddRequest, LoginRequest, Request, Response, SsoRefresh,
+};
+use serde_json::json;
+
+use crate::output::{OutputFormat, OutputOptions, print_structured_item};
+use crate::{config, helper::send_request};
+
+/// Output of `kopsctl login`.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum LoginOutput {
+    #[default]
+    Text,
+
+    /// One JSON object per line on stdout: the device code to approve,
+    /// then the outcome; the messages go to stderr.
+    Json,
+
+    /// The same events as YAML documents.
+    Yaml,
+
+    /// Messages only, on stderr, leaving stdout to the caller.
+    Stderr,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LoginOptions {
+    /// AWS region for SSO.
+    pub region: Option<String>,
+
+    /// SSO profile of ~/.aws/config.
+    pub aws_profile: Option<String>,
+
+    /// Print the verification URL instead of opening a browser.
+    pub no_browser: bool,
 
-use crate::helper::send_request;
+    /// Wait for a line on stdin once the code is shown, before polling.
+    pub confirm: bool,
+
+    pub output: LoginOutput,
+}
+
+/// Human messages, on stderr when stdout is machine-readable.
+#[derive(Clone, Copy)]
+struct Console {
+    output: LoginOutput,
+}
+
+impl Console {
+    fn say(self, msg: impl Display) {
+        match self.output {
+            LoginOutput::Text => println!("{msg}"),
+            LoginOutput::Json | LoginOutput::Yaml | LoginOutput::Stderr => {
+                eprintln!("{msg}")
+            }
+        }
+    }
+
+    fn emit(self, value: serde_json::Value) {
+        let format = match self.output {
+            LoginOutput::Json => OutputFormat::Json,
+            LoginOutput::Yaml => OutputFormat::Yaml,
+            LoginOutput::Text | LoginOutput::Stderr => return,
+        };
+        let output = OutputOptions { format, ..OutputOptions::default() };
+        let _ = print_structured_item(&value, output);
+    }
+}
+
+pub async fn execute(name: String, opts: LoginOptions) -> Result<()> {
+    let LoginOptions { region, aws_profile, no_browser, confirm, output } =
+        opts;
+    let console = Console { output };
+    let profile = config::load()?.profile.remove(&name).unwrap_or_default();
+    let env = |var: &str| std::env::var(var).ok().filter(|v| !v.is_empty());
+
+    // ~/.aws/config, overriding the kops profile when given on the CLI
+    let aws = match aws_profile.as_ref().or(profile.aws_profile.as_ref()) {
+        Some(aws_profile) => AwsProfile::load(aws_profile)?,
+        None => AwsProfile::default(),
+    };
+    let (start_url, account_id, role_name, sso_session) =
+        if aws_profile.is_some() {
+            (
+                aws.start_url.or(profile.start_url),
+                aws.account_id.or(profile.account_id),
+                aws.role_name.or(profile.role_name),
+                aws.session_name.or(profile.sso_session),
+            )
+        } else {
+            (
+                profile.start_url.or(aws.start_url),
+                profile.account_id.or(aws.account_id),
+                profile.role_name.or(aws.role_name),
+                profile.sso_session.or(aws.session_name),
+            )
+        };
 
-pub async fn execute(name: String, region: Option<String>) -> Result<()> {
     let region = region
-        .or_else(|| std::env::var("AWS_REGION").ok())
+        .or(profile.region)
+        .or(aws.sso_region)
+        .or_else(|| env("AWS_REGION"))
         .unwrap_or_else(|| "us-east-1".to_string());
 
-    let start_url = std::env::var("KOPS_SSO_START_URL")
-        .map_err(|_| anyhow!("KOPS_SSO_START_URL not set"))?;
-    let account_id = std::env::var("KOPS_SSO_ACCOUNT_ID")
-        .map_err(|_| anyhow!("KOPS_SSO_ACCOUNT_ID not set"))?;
-    let role_name = std::env::var("KOPS_SSO_ROLE_NAME")
-        .map_err(|_| anyhow!("KOPS_SSO_ROLE_NAME not set"))?;
+    let start_url =
+        env("KOPS_SSO_START_URL").or(start_url).ok_or_else(|| {
+            let path = config::path()
+                .map_or("the kops config".to_string(), |p| {
+                    p.display().to_string()
+                });
+            anyhow!(
+                "no SSO start URL for profile {name}: set start_url in \
+                 [profile.{name}] of {path}, or KOPS_SSO_START_URL"
+            )
+        })?;
+    // picked from the SSO portal when unset
+    let account_id = env("KOPS_SSO_ACCOUNT_ID").or(account_id);
+    let role_name = env("KOPS_SSO_ROLE_NAME").or(role_name);
 
-    let client_name = format!("kops");
+    let client_name = "kops".to_string();
 
     let sso_cfg = SsoLoginConfig {
         region: region.clone(),
         start_url,
-        account_id: account_id.clone(),
-        role_name: role_name.clone(),
         client_name,
+        // shares the token of `aws sso login` for this sso-session
+        session_name: env("KOPS_SSO_SESSION").or(sso_session),
     };
 
     let sdk_config = aws_config::from_env()
@@ -48,47 +161,79 @@ pub async fn execute(name: String, region: Option<String>) -> Result<()> {
         .load()
         .await;
 
-    println!("Starting AWS SSO device flow for profile '{name}'...");
-    println!("Region     : {region}");
-    println!("Account ID : {account_id}");
-    println!("Role name  : {role_name}");
-    println!();
-
-    let session = login_device_flow(&sdk_config, &sso_cfg, |info| {
-        println!("SSO user code       : {}", info.user_code);
-        println!("Verification URL    : {}", info.verification_uri);
+    console.say(format!("Logging in with AWS SSO for profile '{name}'..."));
+    console.say(format!("Region     : {region}"));
+    console.say("");
 
+    let token = authenticate(&sdk_config, &sso_cfg, |info| {
+        console.emit(json!({
+            "event": "verification",
+            "user_code": info.user_code,
+            "verification_uri": info.verification_uri,
+            "verification_uri_complete": info.verification_uri_complete,
+            "expires_in": info.expires_in,
+        }));
+        console.say(format!("SSO user code       : {}", info.user_code));
+        console
+            .say(format!("Verification URL    : {}", info.verification_uri));
         if let Some(full) = &info.verification_uri_complete {
-            println!("Verification (full) : {full}");
-
-            if let Err(err) = webbrowser::open(full) {
-                eprintln!("Failed to open browser automatically: {err}");
-                eprintln!("Please open the URL manually.");
-            } else {
-                println!(
-                    "Browser opened automatically, please finish authentication."
-                );
-            }
-        } else if let Err(err) = webbrowser::open(&info.verification_uri) {
+            console.say(format!("Verification (full) : {full}"));
+        }
+
+        let url = info
+            .verification_uri_complete
+            .as_ref()
+            .unwrap_or(&info.verification_uri);
+        if no_browser {
+            console.say("Open the URL in a browser to finish authentication.");
+        } else if let Err(err) = webbrowser::open(url) {
             eprintln!("Failed to open browser automatically: {err}");
             eprintln!("Please open the URL manually.");
         } else {
-            println!(
-                "Browser opened automatically, please finish authentication."
+            console.say(
+                "Browser opened automatically, please finish authentication.",
             );
         }
 
-        println!();
-        println!("Waiting for AWS SSO authorization...");
+        if confirm {
+            eprintln!("Press Enter once approved...");
+            // EOF or a read error just stops waiting
+            let _ = io::stdin().lock().read_line(&mut String::new());
+        }
+
+        console.say("");
+        console.say("Waiting for AWS SSO authorization...");
     })
     .await?;
 
-    println!(
+    let account_id = match account_id {
+        Some(account_id) => account_id,
+        None => pick_account(&sdk_config, &token).await?,
+    };
+    let role_name = match role_name {
+        Some(role_name) => role_name,
+        None => pick_role(&sdk_config, &token, &account_id).await?,
+    };
+    console.say(format!("Account ID : {account_id}"));
+    console.say(format!("Role name  : {role_name}"));
+
+    let session =
+        role_session(&sdk_config, &token, &account_id, &role_name).await?;
+
+    console.say(format!(
         "Successfully obtained AWS credentials for account {} role {}",
         session.account_id, session.role_name
-    );
+    ));
+    let expires_at = session.expires_at.to_rfc3339();
 
     let expires_at_epoch_ms = session.expires_at.timestamp_millis();
+    let refresh = session.refresh.map(|grant| SsoRefresh {
+        region: grant.region,
+        start_url: grant.start_url,
+        client_id: grant.client_id,
+        client_secret: grant.client_secret,
+        refresh_token: grant.refresh_token,
+    });
 
     let creds = session.credentials;
     let access_key_id = creds.access_key_id().to_string();
@@ -101,27 +246,158 @@ pub async fn execute(name: String, region: Option<String>) -> Result<()> {
     let req = Request::Login(LoginRequest {
         name: name.clone(),
         region: Some(region),
-        account_id,
-        role_name,
+        account_id: account_id.clone(),
+        role_name: role_name.clone(),
         access_key_id,
         secret_access_key,
         session_token,
         expires_at_epoch_ms,
+        refresh,
     });
 
     let resp = send_request(req).await?;
 
     match resp {
-        Response::LoginOk => {
-            println!(
+        Response::LoginOk { mut started, running, mut failed } => {
+            console.say(format!(
                 "kopsd registered AWS session for profile '{name}' successfully."
-            );
+            ));
+            for cluster in &started {
+                console.say(format!("started cluster {cluster}"));
+            }
+            for cluster in &running {
+                console.say(format!(
+                    "cluster {cluster} switched to the new session"
+                ));
+            }
+
+            // clusters of the profile the daemon config does not know
+            let pending: Vec<String> = profile
+                .clusters
+                .into_iter()
+                .filter(|c| !started.contains(c) && !running.contains(c))
+                .collect();
+            for cluster in pending {
+                match add_cluster(&cluster, &name).await {
+                    Ok(()) => {
+                        console.say(format!("started cluster {cluster}"));
+                        started.push(cluster);
+                    }
+                    Err(err) => failed.push(format!("{cluster}: {err:#}")),
+                }
+            }
+
+            console.emit(json!({
+                "event": "login",
+                "profile": name,
+                "account_id": account_id,
+                "role_name": role_name,
+                "expires_at": expires_at,
+                "started": started,
+                "running": running,
+                "failed": failed,
+            }));
+
+            if !failed.is_empty() {
+                bail!("failed to start clusters: {}", failed.join(", "));
+            }
         }
-        Response::Error { message } => {
-            bail!("daemon returned error on login: {message}");
+        Response::Error(err) => {
+            bail!("daemon returned error on login: {err}");
         }
         _ => bail!("unexpected response to login"),
     }
 
     Ok(())
 }
+
+/// Start `cluster` with the session of `profile`.
+async fn add_cluster(cluster: &str, profile: &str) -> Result<()> {
+    let req = ClusterAddRequest {
+        name: cluster.to_string(),
+        profile: profile.to_string(),
+        region: None,
+    };
+
+    match send_request(Request::ClusterAdd(req)).await? {
+        Response::ClusterAdded => Ok(()),
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to cluster add"),
+    }
+}
+
+/// Log in every profile of the kops config in turn, e.g. at the start of
+/// an on-call shift; profiles sharing an SSO portal reuse its token.
+pub async fn execute_all(opts: LoginOptions) -> Result<()> {
+    let profiles = config::load()?.profile;
+    if profiles.is_empty() {
+        let path = config::path().map_or("the kops config".to_string(), |p| {
+            p.display().to_string()
+        });
+        bail!("no [profile.<name>] section in {path}");
+    }
+
+    let mut failed = Vec::new();
+    for name in profiles.into_keys() {
+        if let Err(err) = execute(name.clone(), opts.clone()).await {
+            eprintln!("login of profile {name} failed: {err:#}");
+            failed.push(name);
+        }
+        if let LoginOutput::Text = opts.output {
+            println!();
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!("failed to log in profiles: {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+/// Let the user pick one of the accounts of the SSO portal.
+async fn pick_account(
+    sdk_config: &SdkConfig,
+    token: &SsoToken,
+) -> Result<String> {
+    let accounts = list_accounts(sdk_config, token).await?;
+    let items: Vec<String> = accounts
+        .iter()
+        .map(|a| match &a.account_name {
+            Some(name) => format!("{name} ({})", a.account_id),
+            None => a.account_id.clone(),
+        })
+        .collect();
+
+    let index = pick("AWS account", &items, "KOPS_SSO_ACCOUNT_ID")?;
+    Ok(accounts[index].account_id.clone())
+}
+
+/// Let the user pick one of the roles of `account_id` they may assume.
+async fn pick_role(
+    sdk_config: &SdkConfig,
+    token: &SsoToken,
+    account_id: &str,
+) -> Result<String> {
+    let mut roles = list_account_roles(sdk_config, token, account_id).await?;
+    let index = pick("role", &roles, "KOPS_SSO_ROLE_NAME")?;
+    Ok(roles.swap_remove(index))
+}
+
+/// Index of the item picked among `items`, the only one without asking.
+fn pick(what: &str, items: &[String], env: &str) -> Result<usize> {
+    match items.len() {
+        0 => bail!("no {what} available through AWS SSO"),
+        1 => return Ok(0),
+        _ => {}
+    }
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        bail!("several {what}s available through AWS SSO, set {env}");
+    }
+
+    let index = FuzzySelect::new()
+        .with_prompt(format!("Pick the {what}"))
+        .items(items)
+        .default(0)
+        .interact()?;
+    Ok(index)
+}
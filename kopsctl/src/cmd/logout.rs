@@ -0,0 +1,55 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{Request, Response};
+use serde::Serialize;
+
+use crate::helper::send_request;
+use crate::output::{OutputOptions, print_structured};
+
+/// Outcome of `logout`, in JSON or YAML output.
+#[derive(Serialize)]
+struct LoggedOut<'a> {
+    profile: &'a str,
+    stopped_clusters: &'a [String],
+}
+
+pub async fn execute(profile: String, output: OutputOptions) -> Result<()> {
+    let resp =
+        send_request(Request::Logout { profile: profile.clone() }).await?;
+
+    match resp {
+        Response::LoggedOut { stopped_clusters } if output.structured() => {
+            let logged_out = LoggedOut {
+                profile: &profile,
+                stopped_clusters: &stopped_clusters,
+            };
+            print_structured(&logged_out, output)?
+        }
+        Response::LoggedOut { stopped_clusters } => {
+            println!("removed AWS session for profile '{profile}'");
+            for cluster in stopped_clusters {
+                println!("stopped cluster {cluster}");
+            }
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to logout"),
+    }
+
+    Ok(())
+}
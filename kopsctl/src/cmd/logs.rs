@@ -0,0 +1,66 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::io::Write;
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{LogsRequest, Request, Response};
+
+use crate::helper::{parse_duration, stream_request};
+use crate::picker;
+
+pub async fn execute(
+    pod: Option<String>,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    container: Option<String>,
+    follow: bool,
+    tail: Option<i64>,
+    since: Option<String>,
+) -> Result<()> {
+    let (namespace, pod) =
+        picker::pod(pod, cluster.clone(), namespace).await?;
+
+    let since_seconds = match since {
+        Some(s) => Some(parse_duration(&s)?.as_secs() as i64),
+        None => None,
+    };
+
+    let req = Request::Logs(LogsRequest {
+        cluster,
+        namespace,
+        pod,
+        container,
+        follow,
+        tail_lines: tail,
+        since_seconds,
+    });
+
+    let mut stdout = std::io::stdout().lock();
+
+    stream_request(req, |resp| match resp {
+        Response::LogChunk { data } => {
+            stdout.write_all(&data)?;
+            stdout.flush()?;
+            Ok(true)
+        }
+        Response::LogEnd => Ok(false),
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to logs"),
+    })
+    .await
+}
@@ -14,8 +14,46 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
+pub mod check;
+pub mod cluster;
+pub mod clusters;
+pub mod configmap;
+pub mod credential_process;
+pub mod credentials;
+pub mod daemon;
+pub mod daemonsets;
+pub mod delete;
+pub mod deployments;
+pub mod describe;
 pub mod env;
+pub mod events;
+pub mod exec;
+pub mod get;
+pub mod history;
+pub mod hpa;
+pub mod images;
+pub mod ingresses;
+pub mod jobs;
+pub mod kubeconfig;
 pub mod login;
+pub mod logout;
+pub mod logs;
+pub mod namespaces;
+pub mod nodes;
 pub mod ping;
 pub mod pods;
+pub mod port_forward;
+pub mod pvc;
+pub mod restarts;
+pub mod rollout;
+pub mod scale;
+pub mod secrets;
+pub mod services;
+pub mod sessions;
+pub mod statefulsets;
+pub mod status;
+pub mod token;
+pub mod top;
+pub mod ui;
+pub mod use_context;
 pub mod version;
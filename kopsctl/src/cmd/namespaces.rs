@@ -0,0 +1,82 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{NamespaceSummary, NamespacesRequest, Request, Response};
+
+use crate::helper::send_request;
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let req = NamespacesRequest { cluster };
+    let resp = send_request(Request::Namespaces(req)).await?;
+
+    match resp {
+        Response::Namespaces { namespaces } => {
+            print_namespaces(&namespaces, output)?
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to namespaces"),
+    }
+
+    Ok(())
+}
+
+fn print_namespaces(
+    namespaces: &[NamespaceSummary],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(namespaces, output);
+    }
+
+    if output.quiet {
+        print_names(namespaces.iter().map(|n| n.name.clone()));
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAME", 30),
+        Column::new("STATUS", 12),
+        Column::unstable("AGE", 6),
+    ]);
+
+    for n in namespaces {
+        let (cluster, name) = n.sort_key();
+        let key = vec![cluster.to_string(), name.to_string()];
+
+        table.push(
+            key,
+            vec![
+                n.cluster.clone(),
+                n.name.clone(),
+                n.phase.clone().unwrap_or_default(),
+                format_age(n.created_at_epoch_ms),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
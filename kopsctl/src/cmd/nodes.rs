@@ -0,0 +1,105 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{NodeSummary, NodesRequest, Request, Response};
+
+use crate::helper::send_request;
+use crate::output::{
+    Column, OutputOptions, Table, TableView, Tone, format_age, print_names,
+    print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    view: TableView,
+    output: OutputOptions,
+) -> Result<()> {
+    let resp = send_request(Request::Nodes(NodesRequest { cluster })).await?;
+
+    match resp {
+        Response::Nodes { nodes } => print_nodes(&nodes, &view, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to nodes"),
+    }
+
+    Ok(())
+}
+
+fn print_nodes(
+    nodes: &[NodeSummary],
+    view: &TableView,
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(nodes, output);
+    }
+
+    if output.quiet {
+        print_names(nodes.iter().map(|n| n.name.clone()));
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAME", 45),
+        Column::new("STATUS", 10).highlighted(),
+        Column::new("ROLES", 15),
+        Column::new("VERSION", 20),
+        Column::new("INSTANCE-TYPE", 14),
+        Column::new("CPU", 6),
+        Column::new("MEMORY", 12),
+        Column::unstable("AGE", 6),
+    ]);
+
+    let none = || "<none>".to_string();
+
+    for n in nodes {
+        let (cluster, name) = n.sort_key();
+        let key = vec![cluster.to_string(), name.to_string()];
+
+        let roles =
+            if n.roles.is_empty() { none() } else { n.roles.join(",") };
+
+        let tone = match n.status.as_str() {
+            "Ready" => Tone::Good,
+            s if s.starts_with("Ready") => Tone::Plain,
+            _ => Tone::Bad,
+        };
+
+        table.push_toned(
+            key,
+            vec![
+                n.cluster.clone(),
+                n.name.clone(),
+                n.status.clone(),
+                roles,
+                n.kubelet_version.clone().unwrap_or_else(none),
+                n.instance_type.clone().unwrap_or_else(none),
+                n.allocatable_cpu.clone().unwrap_or_else(none),
+                n.allocatable_memory.clone().unwrap_or_else(none),
+                format_age(n.created_at_epoch_ms),
+            ],
+            tone,
+        );
+    }
+
+    table.apply_view(view)?;
+    table.print(output);
+
+    Ok(())
+}
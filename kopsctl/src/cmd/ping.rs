@@ -24,8 +24,8 @@ use crate::helper::send_request;
 pub async fn execute() -> Result<()> {
     let resp = send_request(Request::Ping).await?;
 
-    if let Response::Error { message } = resp {
-        error!("error from daemon: {message}");
+    if let Response::Error(err) = resp {
+        error!("error from daemon: {err}");
     }
     debug!("received pong response");
     Ok(())
@@ -16,56 +16,234 @@
 
 use anyhow::{Result, bail};
 
-use kops_protocol::{PodSummary, PodsRequest, Request, Response};
+use kops_protocol::{PodEvent, PodSummary, PodsRequest, Request, Response};
+use serde::Serialize;
 
-use crate::helper::send_request;
+use crate::helper::{resolve_namespace, stream_request};
+use crate::output::{
+    Column, OutputOptions, Table, TableView, Tone, format_age, print_names,
+    print_structured, print_structured_item,
+};
 
+/// Exit code of `--fail-on-issues` and `kopsctl check` when pods are
+/// failing, apart from the 1 of any other error.
+pub(crate) const ISSUES_EXIT_CODE: i32 = 2;
+
+/// List or watch pods; returns the exit code of `--fail-on-issues` when
+/// pods are failing.
 pub async fn execute(
-    cluster: Option<String>,
-    namespace: Option<String>,
-    failed_only: bool,
-) -> Result<()> {
-    let req = PodsRequest { cluster, namespace, failed_only };
-    let resp = send_request(Request::Pods(req)).await?;
+    mut req: PodsRequest,
+    watch: bool,
+    view: TableView,
+    fail_on_issues: bool,
+    output: OutputOptions,
+) -> Result<Option<i32>> {
+    req.namespace =
+        resolve_namespace(req.cluster.clone(), req.namespace).await?;
 
-    match resp {
-        Response::Pods { pods } => print_pods(&pods, failed_only),
-        Response::Error { message } => bail!("reponse error {message}"),
-        _ => bail!("unexpected response to version"),
+    let failed_only = req.failed_only;
+    if watch {
+        watch_pods(req, output).await?;
+        return Ok(None);
     }
 
-    Ok(())
+    let (pods, next) = list(req).await?;
+    print_pods(&pods, failed_only, &view, output)?;
+
+    if let Some(token) = next {
+        eprintln!("more pods left, list them with --continue {token}");
+    }
+
+    Ok(if fail_on_issues { issues_exit_code(&pods) } else { None })
 }
 
-fn print_pods(pods: &Vec<PodSummary>, failed_only: bool) {
-    println!(
-        "{:<20} {:<20} {:<30} {:<10} {:<10}",
-        "CLUSTER", "NAMESPACE", "NAME", "READY", "RESTARTS"
-    );
+/// Pods of `req`, with the continue token of a limited listing.
+pub(crate) async fn list(
+    req: PodsRequest,
+) -> Result<(Vec<PodSummary>, Option<String>)> {
+    // Large listings arrive in several chunks.
+    let mut pods = Vec::new();
+    let mut next = None;
+    stream_request(Request::Pods(req), |resp| match resp {
+        Response::Pods { pods: mut chunk, continue_token } => {
+            pods.append(&mut chunk);
+            next = continue_token;
+            Ok(true)
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to pods"),
+    })
+    .await?;
 
-    for p in pods {
-        if failed_only {
-            if let Some(msg) = &p.message {
-                println!(
-                    "{:<20} {:<20} {:<30} {:<10} {:<10} {:<10}",
-                    p.cluster,
-                    p.namespace,
-                    p.name,
-                    p.ready,
-                    p.restart_count,
-                    msg
-                );
-            } else {
-                println!(
-                    "{:<20} {:<20} {:<30} {:<10} {:<10}",
-                    p.cluster, p.namespace, p.name, p.ready, p.restart_count,
-                );
+    Ok((pods, next))
+}
+
+/// [`ISSUES_EXIT_CODE`] when any of `pods` is failing, saying how many.
+pub(crate) fn issues_exit_code(pods: &[PodSummary]) -> Option<i32> {
+    let failing = pods.iter().filter(|p| p.is_failing()).count();
+    if failing == 0 {
+        return None;
+    }
+    eprintln!("{failing} pods failing or crashlooping");
+    Some(ISSUES_EXIT_CODE)
+}
+
+/// A change seen by `--watch`, in JSON or YAML output.
+#[derive(Serialize)]
+struct WatchEvent<'a> {
+    event: &'a str,
+    pod: &'a PodSummary,
+}
+
+/// Print pod changes as the daemon pushes them, until interrupted.
+///
+/// The first reply lists the current pods as ADDED; a later full listing
+/// (sent when the daemon fell behind) is printed as RESYNC.
+async fn watch_pods(req: PodsRequest, output: OutputOptions) -> Result<()> {
+    let failed_only = req.failed_only;
+    let wide = output.wide();
+
+    let mut columns = vec![Column::new("EVENT", 9)];
+    columns.extend(columns_for(failed_only, wide));
+    let table = Table::new(columns);
+    if !output.structured() {
+        table.print_header(output);
+    }
+
+    let print = |event: &str, pod: &PodSummary| -> Result<()> {
+        if output.structured() {
+            return print_structured_item(&WatchEvent { event, pod }, output);
+        }
+
+        let mut cells = vec![event.to_string()];
+        cells.extend(cells_for(pod, failed_only, wide));
+        table.print_row(&cells, tone(pod), output);
+
+        Ok(())
+    };
+
+    let mut synced = false;
+    stream_request(Request::WatchPods(req), |resp| {
+        match resp {
+            Response::Pods { pods, .. } => {
+                let event = if synced { "RESYNC" } else { "ADDED" };
+                synced = true;
+                pods.iter().try_for_each(|p| print(event, p))?;
             }
-        } else {
-            println!(
-                "{:<20} {:<20} {:<30} {:<10} {:<10}",
-                p.cluster, p.namespace, p.name, p.ready, p.restart_count
-            );
+            Response::PodEvent(PodEvent::Added(p)) => print("ADDED", &p)?,
+            Response::PodEvent(PodEvent::Modified(p)) => {
+                print("MODIFIED", &p)?
+            }
+            Response::PodEvent(PodEvent::Deleted(p)) => print("DELETED", &p)?,
+            Response::Error(err) => bail!("reponse error {err}"),
+            _ => bail!("unexpected response to pods watch"),
         }
+        Ok(true)
+    })
+    .await
+}
+
+/// Pod columns. STATUS comes after RESTARTS because stable columns may
+/// only be appended. `wide` adds where and since when the pods run, as
+/// `kubectl get pods -o wide` does.
+fn columns_for(failed_only: bool, wide: bool) -> Vec<Column> {
+    let mut columns = vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 30),
+        Column::new("READY", 6),
+        Column::new("RESTARTS", 8),
+        Column::new("STATUS", 20).highlighted(),
+        Column::unstable("AGE", 6),
+    ];
+    if wide {
+        columns.extend([
+            Column::unstable("IP", 15),
+            Column::unstable("NODE", 40),
+            Column::unstable("STARTED", 7),
+        ]);
     }
+    if failed_only {
+        columns.push(Column::unstable("OWNER", 12));
+        columns.push(Column::unstable("MESSAGE", 10));
+    }
+
+    columns
+}
+
+fn cells_for(p: &PodSummary, failed_only: bool, wide: bool) -> Vec<String> {
+    let mut cells = vec![
+        p.cluster.clone(),
+        p.namespace.clone(),
+        p.name.clone(),
+        format!("{}/{}", p.ready_containers, p.total_containers),
+        p.restart_count.to_string(),
+        p.status.clone(),
+        format_age(p.created_at_epoch_ms),
+    ];
+    if wide {
+        let none = || "<none>".to_string();
+        cells.extend([
+            p.pod_ip.clone().unwrap_or_else(none),
+            p.node_name.clone().unwrap_or_else(none),
+            format_age(p.start_time_epoch_ms),
+        ]);
+    }
+    if failed_only {
+        let owner = p.ownership.as_ref().map(|o| o.contacts());
+        cells.push(owner.unwrap_or_default());
+
+        // The latest warning event says more than the container state,
+        // like why an image cannot be pulled.
+        let message = p.last_warning.as_ref().or(p.message.as_ref());
+        cells.push(message.cloned().unwrap_or_default());
+    }
+
+    cells
+}
+
+/// Red for failing pods, green for ready ones.
+fn tone(p: &PodSummary) -> Tone {
+    if p.is_failing() {
+        Tone::Bad
+    } else if p.ready {
+        Tone::Good
+    } else {
+        Tone::Plain
+    }
+}
+
+pub(crate) fn print_pods(
+    pods: &[PodSummary],
+    failed_only: bool,
+    view: &TableView,
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(pods, output);
+    }
+
+    if output.quiet {
+        print_names(
+            pods.iter().map(|p| format!("{}/{}", p.namespace, p.name)),
+        );
+        return Ok(());
+    }
+
+    // Picked columns may be wide ones, like `--columns name,node`.
+    let wide = output.wide() || !view.columns.is_empty();
+    let mut table = Table::new(columns_for(failed_only, wide));
+
+    for p in pods {
+        let (cluster, namespace, name) = p.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        table.push_toned(key, cells_for(p, failed_only, wide), tone(p));
+    }
+
+    table.apply_view(view)?;
+    table.print(output);
+
+    Ok(())
 }
@@ -0,0 +1,188 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, anyhow, bail};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        TcpListener, TcpStream,
+        unix::{OwnedReadHalf, OwnedWriteHalf},
+    },
+    sync::mpsc,
+};
+use tracing::debug;
+
+use kops_protocol::{
+    PortForwardRequest, Request,
+    wire::{ForwardFrame, read_message, write_message},
+};
+
+use crate::helper::{connect, split_ref};
+
+pub async fn execute(
+    pod: String,
+    ports: String,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    address: String,
+) -> Result<()> {
+    let (namespace, pod) = split_ref(&pod, namespace);
+    let (local, remote) = parse_ports(&ports)?;
+
+    let listener = TcpListener::bind((address.as_str(), local))
+        .await
+        .with_context(|| format!("failed to listen on {address}:{local}"))?;
+
+    let req = Request::PortForward(PortForwardRequest {
+        cluster,
+        namespace,
+        pod,
+        port: remote,
+    });
+
+    let mut stream = connect().await?;
+    write_message(&mut stream, &req).await?;
+    let (rd, wr) = stream.into_split();
+
+    println!("Forwarding from {address}:{local} -> {remote}");
+
+    let (to_daemon, rx) = mpsc::channel(64);
+    tokio::spawn(write_frames(wr, rx));
+
+    let (incoming_tx, mut incoming) = mpsc::channel(64);
+    tokio::spawn(read_frames(rd, incoming_tx));
+
+    let mut streams: HashMap<u32, mpsc::Sender<Vec<u8>>> = HashMap::new();
+    let mut next_id: u32 = 0;
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (sock, peer) = accepted?;
+                next_id = next_id.wrapping_add(1);
+                debug!("stream {next_id}: connection from {peer}");
+
+                let (data_tx, data_rx) = mpsc::channel(16);
+                streams.insert(next_id, data_tx);
+
+                to_daemon.send(ForwardFrame::Open { stream: next_id }).await?;
+                tokio::spawn(pump_local(
+                    next_id,
+                    sock,
+                    data_rx,
+                    to_daemon.clone(),
+                ));
+            }
+
+            frame = incoming.recv() => match frame {
+                Some(ForwardFrame::Data { stream, data }) => {
+                    if let Some(s) = streams.get(&stream)
+                        && s.send(data).await.is_err()
+                    {
+                        streams.remove(&stream);
+                    }
+                }
+                Some(ForwardFrame::Close { stream }) => {
+                    streams.remove(&stream);
+                }
+                Some(ForwardFrame::Error { stream: Some(id), message }) => {
+                    eprintln!("stream {id}: {message}");
+                    streams.remove(&id);
+                }
+                Some(ForwardFrame::Error { stream: None, message }) => {
+                    bail!("port-forward failed: {message}");
+                }
+                Some(ForwardFrame::Open { .. }) => {
+                    bail!("unexpected frame from daemon");
+                }
+                None => bail!("daemon closed the port-forward session"),
+            },
+        }
+    }
+}
+
+/// Parse `<local>:<remote>` or a single port used on both sides.
+fn parse_ports(spec: &str) -> Result<(u16, u16)> {
+    let parse = |p: &str| {
+        p.parse::<u16>().map_err(|_| anyhow!("invalid port '{p}' in {spec}"))
+    };
+
+    match spec.split_once(':') {
+        Some((local, remote)) => Ok((parse(local)?, parse(remote)?)),
+        None => {
+            let port = parse(spec)?;
+            Ok((port, port))
+        }
+    }
+}
+
+/// Relay one local TCP connection to its daemon stream.
+async fn pump_local(
+    stream: u32,
+    sock: TcpStream,
+    mut from_daemon: mpsc::Receiver<Vec<u8>>,
+    to_daemon: mpsc::Sender<ForwardFrame>,
+) {
+    let (mut rd, mut wr) = sock.into_split();
+    let mut buf = vec![0u8; 16 * 1024];
+
+    loop {
+        tokio::select! {
+            read = rd.read(&mut buf) => match read {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let frame =
+                        ForwardFrame::Data { stream, data: buf[..n].to_vec() };
+                    if to_daemon.send(frame).await.is_err() {
+                        break;
+                    }
+                }
+            },
+            data = from_daemon.recv() => match data {
+                Some(bytes) => {
+                    if wr.write_all(&bytes).await.is_err() {
+                        break;
+                    }
+                }
+                None => break,
+            },
+        }
+    }
+
+    debug!("stream {stream}: closed");
+    let _ = to_daemon.send(ForwardFrame::Close { stream }).await;
+}
+
+async fn read_frames(mut rd: OwnedReadHalf, tx: mpsc::Sender<ForwardFrame>) {
+    while let Ok(Some(frame)) = read_message(&mut rd).await {
+        if tx.send(frame).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn write_frames(
+    mut wr: OwnedWriteHalf,
+    mut rx: mpsc::Receiver<ForwardFrame>,
+) {
+    while let Some(frame) = rx.recv().await {
+        if write_message(&mut wr, &frame).await.is_err() {
+            break;
+        }
+    }
+}
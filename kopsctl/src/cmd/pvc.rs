@@ -0,0 +1,98 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{PvcSummary, Request, Response, WorkloadsRequest};
+
+use crate::helper::{resolve_namespace, send_request};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+
+    let req = WorkloadsRequest { cluster, namespace };
+    let resp = send_request(Request::Pvcs(req)).await?;
+
+    match resp {
+        Response::Pvcs { pvcs } => print_pvcs(&pvcs, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to pvc"),
+    }
+
+    Ok(())
+}
+
+fn print_pvcs(pvcs: &[PvcSummary], output: OutputOptions) -> Result<()> {
+    if output.structured() {
+        return print_structured(pvcs, output);
+    }
+
+    if output.quiet {
+        print_names(
+            pvcs.iter().map(|p| format!("{}/{}", p.namespace, p.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 30),
+        Column::new("STATUS", 8),
+        Column::new("VOLUME", 42),
+        Column::new("CAPACITY", 8),
+        Column::new("ACCESS MODES", 14),
+        Column::new("STORAGECLASS", 12),
+        Column::new("USED BY", 30),
+        Column::unstable("AGE", 6),
+    ]);
+
+    let none = || "<none>".to_string();
+    let join = |v: &[String]| if v.is_empty() { none() } else { v.join(",") };
+
+    for p in pvcs {
+        let (cluster, namespace, name) = p.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        table.push(
+            key,
+            vec![
+                p.cluster.clone(),
+                p.namespace.clone(),
+                p.name.clone(),
+                p.status.clone(),
+                p.volume.clone().unwrap_or_else(none),
+                p.capacity.clone().unwrap_or_else(none),
+                join(&p.access_modes),
+                p.storage_class.clone().unwrap_or_else(none),
+                join(&p.consumers),
+                format_age(p.created_at_epoch_ms),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
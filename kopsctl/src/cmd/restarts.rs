@@ -0,0 +1,111 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{Request, Response, RestartSummary, RestartsRequest};
+
+use crate::helper::{parse_duration, send_request};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    since: String,
+    output: OutputOptions,
+) -> Result<()> {
+    let since_seconds = parse_duration(&since)?.as_secs() as i64;
+
+    let req = RestartsRequest { cluster, namespace, since_seconds };
+    let resp = send_request(Request::Restarts(req)).await?;
+
+    match resp {
+        Response::Restarts { restarts } => print_restarts(&restarts, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to restarts"),
+    }
+
+    Ok(())
+}
+
+fn print_restarts(
+    restarts: &[RestartSummary],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(restarts, output);
+    }
+
+    // The pods, once each even with several restarting containers.
+    if output.quiet {
+        let mut seen = HashSet::new();
+        print_names(
+            restarts
+                .iter()
+                .map(|r| format!("{}/{}", r.namespace, r.pod))
+                .filter(|pod| seen.insert(pod.clone())),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::unstable("LAST RESTART", 12),
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("POD", 40),
+        Column::new("CONTAINER", 20),
+        Column::new("RESTARTS", 8),
+        Column::new("REASON", 20),
+        Column::new("EXIT CODE", 9),
+    ]);
+
+    let none = || "<none>".to_string();
+
+    for r in restarts {
+        let (last, cluster, namespace, pod, container) = r.sort_key();
+        // Zero-padded so the byte-wise ordering of stable mode stays
+        // chronological.
+        let key = vec![
+            format!("{last:020}"),
+            cluster.to_string(),
+            namespace.to_string(),
+            pod.to_string(),
+            container.to_string(),
+        ];
+
+        table.push(
+            key,
+            vec![
+                format_age(Some(r.last_restart_epoch_ms)),
+                r.cluster.clone(),
+                r.namespace.clone(),
+                r.pod.clone(),
+                r.container.clone(),
+                r.restarts.to_string(),
+                r.reason.clone().unwrap_or_else(none),
+                r.exit_code.map(|c| c.to_string()).unwrap_or_else(none),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{Request, Response, RolloutRestartRequest};
+use serde::Serialize;
+
+use crate::helper::{parse_duration, send_request, split_ref};
+use crate::output::{OutputOptions, print_structured};
+
+/// Outcome of `rollout restart`, in JSON or YAML output.
+#[derive(Serialize)]
+struct Restarted<'a> {
+    namespace: &'a str,
+    deployment: &'a str,
+    restarted_at: &'a str,
+
+    /// The new pods were waited for and are ready.
+    rolled_out: bool,
+}
+
+pub async fn restart(
+    deployment: String,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    wait: bool,
+    timeout: String,
+    output: OutputOptions,
+) -> Result<()> {
+    let (namespace, deployment) = split_ref(&deployment, namespace);
+
+    let wait_timeout_seconds =
+        if wait { Some(parse_duration(&timeout)?.as_secs()) } else { None };
+
+    // Progress stays off stdout when it is machine-readable.
+    if wait && output.structured() {
+        eprintln!(
+            "restarting deployment {namespace}/{deployment}, waiting..."
+        );
+    } else if wait {
+        println!("restarting deployment {namespace}/{deployment}, waiting...");
+    }
+
+    let req = RolloutRestartRequest {
+        cluster,
+        namespace: namespace.clone(),
+        deployment: deployment.clone(),
+        wait_timeout_seconds,
+    };
+    let resp = send_request(Request::RolloutRestart(req)).await?;
+
+    match resp {
+        Response::RolloutRestarted { restarted_at } if output.structured() => {
+            let restarted = Restarted {
+                namespace: &namespace,
+                deployment: &deployment,
+                restarted_at: &restarted_at,
+                rolled_out: wait,
+            };
+            print_structured(&restarted, output)?
+        }
+        Response::RolloutRestarted { restarted_at } => {
+            let done =
+                if wait { "restarted and rolled out" } else { "restarted" };
+            println!(
+                "deployment {namespace}/{deployment} {done} ({restarted_at})"
+            );
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to rollout restart"),
+    }
+
+    Ok(())
+}
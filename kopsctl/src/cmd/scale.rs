@@ -0,0 +1,76 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{Request, Response, ScaleRequest};
+use serde::Serialize;
+
+use crate::helper::{send_request, split_ref};
+use crate::output::{OutputOptions, print_structured};
+
+/// Outcome of `scale deployment`, in JSON or YAML output.
+#[derive(Serialize)]
+struct Scaled<'a> {
+    namespace: &'a str,
+    deployment: &'a str,
+    old_replicas: i32,
+    new_replicas: i32,
+}
+
+pub async fn deployment(
+    deployment: String,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    replicas: i32,
+    output: OutputOptions,
+) -> Result<()> {
+    if replicas < 0 {
+        bail!("replicas must not be negative");
+    }
+
+    let (namespace, deployment) = split_ref(&deployment, namespace);
+
+    let req = ScaleRequest {
+        cluster,
+        namespace: namespace.clone(),
+        deployment: deployment.clone(),
+        replicas,
+    };
+    let resp = send_request(Request::Scale(req)).await?;
+
+    match resp {
+        Response::Scaled { old_replicas, new_replicas }
+            if output.structured() =>
+        {
+            let scaled = Scaled {
+                namespace: &namespace,
+                deployment: &deployment,
+                old_replicas,
+                new_replicas,
+            };
+            print_structured(&scaled, output)?
+        }
+        Response::Scaled { old_replicas, new_replicas } => println!(
+            "deployment {namespace}/{deployment} scaled: \
+             {old_replicas} -> {new_replicas}"
+        ),
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to scale"),
+    }
+
+    Ok(())
+}
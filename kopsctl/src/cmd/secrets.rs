@@ -0,0 +1,151 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::io::Write;
+
+use anyhow::{Result, bail};
+
+use dialoguer::Confirm;
+use kops_protocol::{
+    Request, Response, SecretSummary, SecretValueRequest, SecretsRequest,
+};
+
+use crate::helper::{send_request, split_ref};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    name: Option<String>,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    reveal: bool,
+    key: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    if reveal {
+        let (Some(name), Some(key)) = (name, key) else {
+            bail!("--reveal needs a secret name and --key");
+        };
+        return reveal_key(name, cluster, namespace, key).await;
+    }
+
+    // A name narrows the listing to a single secret, in its namespace.
+    let (namespace, name) = match name {
+        Some(reference) => {
+            let (ns, name) = split_ref(&reference, namespace);
+            (Some(ns), Some(name))
+        }
+        None => (namespace, None),
+    };
+
+    let req = SecretsRequest { cluster, namespace };
+    let resp = send_request(Request::Secrets(req)).await?;
+
+    let mut secrets = match resp {
+        Response::Secrets { secrets } => secrets,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to secrets"),
+    };
+
+    if let Some(name) = name {
+        secrets.retain(|s| s.name == name);
+    }
+
+    print_secrets(&secrets, output)?;
+
+    Ok(())
+}
+
+async fn reveal_key(
+    reference: String,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    key: String,
+) -> Result<()> {
+    let (namespace, name) = split_ref(&reference, namespace);
+
+    // The prompt goes to stderr, so the value can still be piped.
+    let confirmed = Confirm::new()
+        .with_prompt(format!("Reveal key {key} of secret {namespace}/{name}?"))
+        .default(false)
+        .interact()?;
+    if !confirmed {
+        bail!("aborted");
+    }
+
+    let req = SecretValueRequest { cluster, namespace, name, key };
+    let resp = send_request(Request::SecretValue(req)).await?;
+
+    match resp {
+        Response::SecretValue { value } => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(&value)?;
+            stdout.flush()?;
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to secret value"),
+    }
+
+    Ok(())
+}
+
+fn print_secrets(
+    secrets: &[SecretSummary],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(secrets, output);
+    }
+
+    if output.quiet {
+        print_names(
+            secrets.iter().map(|s| format!("{}/{}", s.namespace, s.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 40),
+        Column::new("TYPE", 36),
+        Column::new("KEYS", 0),
+        Column::unstable("AGE", 6),
+    ]);
+
+    for s in secrets {
+        let (cluster, namespace, name) = s.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        table.push(
+            key,
+            vec![
+                s.cluster.clone(),
+                s.namespace.clone(),
+                s.name.clone(),
+                s.type_.clone(),
+                s.keys.join(","),
+                format_age(s.created_at_epoch_ms),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{Request, Response, ServiceSummary, ServicesRequest};
+
+use crate::helper::{resolve_namespace, send_request};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+
+    let req = ServicesRequest { cluster, namespace };
+    let resp = send_request(Request::Services(req)).await?;
+
+    match resp {
+        Response::Services { services } => print_services(&services, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to services"),
+    }
+
+    Ok(())
+}
+
+fn print_services(
+    services: &[ServiceSummary],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(services, output);
+    }
+
+    if output.quiet {
+        print_names(
+            services.iter().map(|s| format!("{}/{}", s.namespace, s.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 30),
+        Column::new("TYPE", 12),
+        Column::new("CLUSTER-IP", 15),
+        Column::new("EXTERNAL", 20),
+        Column::new("PORTS", 20),
+        Column::new("ENDPOINTS", 9),
+        Column::unstable("AGE", 6),
+    ]);
+
+    let none = || "<none>".to_string();
+
+    for s in services {
+        let (cluster, namespace, name) = s.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        let external =
+            if s.external.is_empty() { none() } else { s.external.join(",") };
+        let ports =
+            if s.ports.is_empty() { none() } else { s.ports.join(",") };
+
+        table.push(
+            key,
+            vec![
+                s.cluster.clone(),
+                s.namespace.clone(),
+                s.name.clone(),
+                s.type_.clone(),
+                s.cluster_ip.clone().unwrap_or_else(none),
+                external,
+                ports,
+                format!("{}/{}", s.ready_endpoints, s.total_endpoints),
+                format_age(s.created_at_epoch_ms),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
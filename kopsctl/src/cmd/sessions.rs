@@ -0,0 +1,73 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{Request, Response, SessionStatus};
+
+use crate::helper::send_request;
+use crate::output::{
+    Column, OutputOptions, Table, format_expiry, print_names, print_structured,
+};
+
+pub async fn execute(output: OutputOptions) -> Result<()> {
+    let resp = send_request(Request::Sessions).await?;
+
+    match resp {
+        Response::Sessions { sessions } => {
+            if sessions.is_empty() && !output.structured() && !output.quiet {
+                println!("no AWS sessions stored, see kopsctl login");
+            } else {
+                print_sessions(&sessions, output)?;
+            }
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to sessions"),
+    }
+
+    Ok(())
+}
+
+/// Table of stored sessions with their expiry countdown.
+pub(crate) fn print_sessions(
+    sessions: &[SessionStatus],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(sessions, output);
+    }
+
+    if output.quiet {
+        print_names(sessions.iter().map(|s| s.profile.clone()));
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("PROFILE", 20),
+        Column::unstable("EXPIRES", 12),
+    ]);
+
+    for s in sessions {
+        table.push(
+            vec![s.profile.clone()],
+            vec![s.profile.clone(), format_expiry(s.expires_at_epoch_ms)],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{Request, Response, StatefulSetSummary, WorkloadsRequest};
+
+use crate::helper::{resolve_namespace, send_request};
+use crate::output::{
+    Column, OutputOptions, Table, format_age, print_names, print_structured,
+};
+
+pub async fn execute(
+    cluster: Option<String>,
+    namespace: Option<String>,
+    output: OutputOptions,
+) -> Result<()> {
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+
+    let req = WorkloadsRequest { cluster, namespace };
+    let resp = send_request(Request::StatefulSets(req)).await?;
+
+    match resp {
+        Response::StatefulSets { stateful_sets } => {
+            print_stateful_sets(&stateful_sets, output)?
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to statefulsets"),
+    }
+
+    Ok(())
+}
+
+fn print_stateful_sets(
+    sets: &[StatefulSetSummary],
+    output: OutputOptions,
+) -> Result<()> {
+    if output.structured() {
+        return print_structured(sets, output);
+    }
+
+    if output.quiet {
+        print_names(
+            sets.iter().map(|s| format!("{}/{}", s.namespace, s.name)),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("NAMESPACE", 20),
+        Column::new("NAME", 30),
+        Column::new("READY", 8),
+        Column::new("CURRENT", 8),
+        Column::new("UP-TO-DATE", 10),
+        Column::new("STRATEGY", 13),
+        Column::new("REVISION", 30),
+        Column::new("IMAGES", 40),
+        Column::unstable("AGE", 6),
+    ]);
+
+    for s in sets {
+        let (cluster, namespace, name) = s.sort_key();
+        let key =
+            vec![cluster.to_string(), namespace.to_string(), name.to_string()];
+
+        // Mid-rollout both revisions are relevant.
+        let revision = match (&s.current_revision, &s.update_revision) {
+            (Some(c), Some(u)) if c != u => format!("{c} -> {u}"),
+            (Some(c), _) => c.clone(),
+            (None, Some(u)) => u.clone(),
+            (None, None) => "<none>".to_string(),
+        };
+
+        table.push(
+            key,
+            vec![
+                s.cluster.clone(),
+                s.namespace.clone(),
+                s.name.clone(),
+                format!("{}/{}", s.ready, s.desired),
+                s.current.to_string(),
+                s.updated.to_string(),
+                s.update_strategy.clone(),
+                revision,
+                s.images.join(","),
+                format_age(s.created_at_epoch_ms),
+            ],
+        );
+    }
+
+    table.print(output);
+
+    Ok(())
+}
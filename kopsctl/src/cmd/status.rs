@@ -0,0 +1,175 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{DaemonStatus, Request, Response, SyncState};
+
+use crate::cmd::sessions::print_sessions;
+use crate::cmd::top::format_bytes;
+use crate::helper::send_request;
+use crate::output::{
+    Column, OutputOptions, Table, format_age, format_duration,
+    print_structured,
+};
+
+pub async fn execute(output: OutputOptions) -> Result<()> {
+    let resp = send_request(Request::Status).await?;
+
+    match resp {
+        Response::Status(status) => print_status(&status, output)?,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to status"),
+    }
+
+    Ok(())
+}
+
+fn print_status(status: &DaemonStatus, output: OutputOptions) -> Result<()> {
+    if output.structured() {
+        return print_structured(status, output);
+    }
+
+    println!("pid         : {}", status.pid);
+    println!("uptime      : {}", format_duration(status.uptime_secs as i64));
+    println!("connections : {}", status.connections);
+    println!();
+
+    let mut table = Table::new(vec![
+        Column::new("CLUSTER", 20),
+        Column::new("KIND", 12),
+        Column::new("STATE", 10),
+        Column::new("OBJECTS", 8),
+        Column::new("FAILURES", 8),
+        Column::unstable("LAST EVENT", 10),
+        Column::unstable("ERROR", 40),
+    ]);
+
+    for c in &status.clusters {
+        for r in &c.reflectors {
+            let (state, error) = match &r.state {
+                SyncState::Pending => ("pending", String::new()),
+                SyncState::Synced => ("synced", String::new()),
+                SyncState::Error(err) => ("error", err.clone()),
+            };
+
+            let last_event = match r.last_event_epoch_ms {
+                Some(_) => {
+                    format!("{} ago", format_age(r.last_event_epoch_ms))
+                }
+                None => "<never>".to_string(),
+            };
+
+            table.push(
+                vec![c.name.clone(), r.kind.clone()],
+                vec![
+                    c.name.clone(),
+                    r.kind.clone(),
+                    state.to_string(),
+                    r.objects.to_string(),
+                    r.failures.to_string(),
+                    last_event,
+                    error,
+                ],
+            );
+        }
+    }
+
+    table.print(output);
+
+    for c in status.clusters.iter().filter(|c| c.degraded) {
+        eprintln!(
+            "warning: cluster {} is degraded, data may be stale",
+            c.name
+        );
+    }
+
+    for c in status.clusters.iter().filter(|c| c.credentials_expiring) {
+        eprintln!(
+            "warning: credentials of cluster {} expire soon, log in again \
+             to keep it running",
+            c.name
+        );
+    }
+
+    if !status.sessions.is_empty() {
+        println!();
+        print_sessions(&status.sessions, output)?;
+    }
+
+    if !status.users.is_empty() {
+        println!();
+        print_traffic(status, output);
+    }
+
+    Ok(())
+}
+
+/// Traffic of each user, then of each open connection.
+fn print_traffic(status: &DaemonStatus, output: OutputOptions) {
+    let mut table = Table::new(vec![
+        Column::new("UID", 8),
+        Column::new("CONNECTIONS", 11),
+        Column::new("IN", 8),
+        Column::new("OUT", 8),
+        Column::new("STALLED WRITES", 14),
+    ]);
+
+    for u in &status.users {
+        table.push(
+            vec![format!("{:010}", u.uid)],
+            vec![
+                u.uid.to_string(),
+                u.connections.to_string(),
+                format_bytes(u.traffic.bytes_in),
+                format_bytes(u.traffic.bytes_out),
+                u.traffic.stalled_writes.to_string(),
+            ],
+        );
+    }
+
+    table.print(output);
+    println!();
+
+    let mut table = Table::new(vec![
+        Column::new("CONNECTION", 10),
+        Column::new("UID", 8),
+        Column::new("PID", 8),
+        Column::unstable("AGE", 8),
+        Column::new("IN", 8),
+        Column::new("OUT", 8),
+        Column::new("STALLED WRITES", 14),
+    ]);
+
+    for c in &status.clients {
+        table.push(
+            vec![format!("{:020}", c.id)],
+            vec![
+                c.id.to_string(),
+                c.uid.to_string(),
+                c.pid
+                    .map(|p| p.to_string())
+                    .unwrap_or_else(|| "<none>".into()),
+                format_duration(c.connected_secs as i64),
+                format_bytes(c.traffic.bytes_in),
+                format_bytes(c.traffic.bytes_out),
+                c.traffic.stalled_writes.to_string(),
+            ],
+        );
+    }
+
+    table.print(output);
+}
@@ -0,0 +1,56 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! `kopsctl token`: a client-go exec plugin handing kubectl, k9s or Lens
+//! the EKS tokens of the daemon's session, see `kopsctl kubeconfig write`.
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, SecondsFormat, Utc};
+use kops_protocol::{Request, Response};
+use serde_json::json;
+
+use crate::helper::send_request;
+
+/// Print the `ExecCredential` of `cluster`, the default one when `None`.
+pub async fn execute(cluster: Option<String>) -> Result<()> {
+    let (token, expires_at_epoch_ms) =
+        match send_request(Request::ClusterToken { cluster }).await? {
+            Response::ClusterToken { token, expires_at_epoch_ms } => {
+                (token, expires_at_epoch_ms)
+            }
+            Response::Error(err) => bail!("response error {err}"),
+            _ => bail!("unexpected response to cluster token"),
+        };
+
+    let Some(expiration) =
+        DateTime::<Utc>::from_timestamp_millis(expires_at_epoch_ms)
+    else {
+        bail!("invalid token expiry {expires_at_epoch_ms}");
+    };
+    let expiration = expiration.to_rfc3339_opts(SecondsFormat::Secs, true);
+    let credential = json!({
+        "kind": "ExecCredential",
+        "apiVersion": "client.authentication.k8s.io/v1",
+        "spec": {},
+        "status": {
+            "expirationTimestamp": expiration,
+            "token": token,
+        },
+    });
+    println!("{}", serde_json::to_string(&credential)?);
+
+    Ok(())
+}
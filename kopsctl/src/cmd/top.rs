@@ -0,0 +1,127 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::{Result, bail};
+
+use kops_protocol::{MetricsRequest, MetricsTarget, Request, Response};
+
+use crate::helper::send_request;
+use crate::output::{
+    Column, OutputOptions, Table, print_names, print_structured,
+};
+
+/// Order of the rows in the default output.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum SortBy {
+    Name,
+    Cpu,
+    Memory,
+}
+
+pub async fn execute(
+    target: MetricsTarget,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    sort_by: SortBy,
+    output: OutputOptions,
+) -> Result<()> {
+    let req = MetricsRequest { cluster, namespace, target };
+    let resp = send_request(Request::Metrics(req)).await?;
+
+    let mut usage = match resp {
+        Response::Metrics { usage } => usage,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to metrics"),
+    };
+
+    // Heaviest first; the daemon already sorted by name.
+    match sort_by {
+        SortBy::Name => {}
+        SortBy::Cpu => usage.sort_by(|a, b| b.cpu_millis.cmp(&a.cpu_millis)),
+        SortBy::Memory => {
+            usage.sort_by(|a, b| b.memory_bytes.cmp(&a.memory_bytes))
+        }
+    }
+
+    if output.structured() {
+        return print_structured(&usage, output);
+    }
+
+    if output.quiet {
+        print_names(usage.iter().map(|u| match &u.namespace {
+            Some(namespace) => format!("{namespace}/{}", u.name),
+            None => u.name.clone(),
+        }));
+        return Ok(());
+    }
+
+    let pods = matches!(target, MetricsTarget::Pods);
+
+    let mut columns = vec![Column::new("CLUSTER", 20)];
+    if pods {
+        columns.push(Column::new("NAMESPACE", 20));
+    }
+    columns.extend([
+        Column::new("NAME", 45),
+        Column::new("CPU", 8),
+        Column::new("MEMORY", 10),
+    ]);
+
+    let mut table = Table::new(columns);
+
+    for u in &usage {
+        let (cluster, namespace, name) = u.sort_key();
+        let key = vec![
+            cluster.to_string(),
+            namespace.unwrap_or_default().to_string(),
+            name.to_string(),
+        ];
+
+        let mut cells = vec![u.cluster.clone()];
+        if pods {
+            cells.push(u.namespace.clone().unwrap_or_default());
+        }
+        cells.extend([
+            u.name.clone(),
+            format!("{}m", u.cpu_millis),
+            format_bytes(u.memory_bytes),
+        ]);
+
+        table.push(key, cells);
+    }
+
+    table.print(output);
+
+    Ok(())
+}
+
+/// Memory in the largest binary unit that keeps a whole number, kubectl
+/// style (`512Mi`, `3Gi`).
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["Ki", "Mi", "Gi", "Ti"];
+
+    let mut value = bytes / 1024;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if value < 1024 * 10 {
+            break;
+        }
+        value /= 1024;
+        unit = next;
+    }
+
+    format!("{value}{unit}")
+}
@@ -0,0 +1,66 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use anyhow::Result;
+
+use crate::context::{self, Context};
+
+pub fn cluster(name: Option<String>, unset: bool) -> Result<()> {
+    update("cluster", name, unset, |c| &mut c.cluster)
+}
+
+pub fn namespace(name: Option<String>, unset: bool) -> Result<()> {
+    update("namespace", name, unset, |c| &mut c.namespace)
+}
+
+/// Set the default `kind` to `name`, forget it with `unset`, or else print
+/// it.
+fn update<F>(
+    kind: &str,
+    name: Option<String>,
+    unset: bool,
+    field: F,
+) -> Result<()>
+where
+    F: FnOnce(&mut Context) -> &mut Option<String>,
+{
+    // A broken context.toml, which main already warned about, is
+    // replaced rather than left to break every command.
+    let mut context = context::load().unwrap_or_default();
+    let value = field(&mut context);
+
+    match name {
+        Some(name) => {
+            println!("using {kind} {name}");
+            *value = Some(name);
+        }
+        None if unset => {
+            println!("no default {kind}");
+            *value = None;
+        }
+        None => {
+            match value {
+                Some(value) => println!("{value}"),
+                None => println!(
+                    "no default {kind}, set one with kopsctl use {kind} <name>"
+                ),
+            }
+            return Ok(());
+        }
+    }
+
+    context::save(&context)
+}
@@ -19,13 +19,17 @@ use anyhow::{Result, bail};
 use kops_protocol::{Request, Response, VersionInfo};
 
 use crate::helper::send_request;
+use crate::output::{OutputOptions, print_structured};
 
-pub async fn execute() -> Result<()> {
+pub async fn execute(output: OutputOptions) -> Result<()> {
     let resp = send_request(Request::Version).await?;
 
     match resp {
+        Response::Version(info) if output.structured() => {
+            print_structured(&info, output)?
+        }
         Response::Version(info) => print_version_info(&info),
-        Response::Error { message } => bail!("reponse error {message}"),
+        Response::Error(err) => bail!("reponse error {err}"),
         _ => bail!("unexpected response to version"),
     }
 
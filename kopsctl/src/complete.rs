@@ -0,0 +1,161 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Dynamic shell completion: cluster, namespace and pod names are asked
+//! to the running daemon as the user presses Tab.
+
+use std::ffi::OsStr;
+use std::future::Future;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use clap_complete::engine::CompletionCandidate;
+use kops_protocol::{NamespacesRequest, PodsRequest, Request, Response};
+
+use crate::helper::{self, AutoStart};
+
+/// Shells `kopsctl completions` writes a script for.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+}
+
+/// Print the completion script of `shell`.
+///
+/// The script calls kopsctl back with `COMPLETE` set on every Tab, which
+/// `clap_complete` answers from `main`, so it is generated by running this
+/// kopsctl that way.
+pub(crate) fn print_script(shell: Shell) -> Result<()> {
+    let exe = std::env::current_exe().context("failed to locate kopsctl")?;
+    let status = Command::new(&exe)
+        .env("COMPLETE", shell.name())
+        .status()
+        .with_context(|| format!("failed to run {}", exe.display()))?;
+    if !status.success() {
+        bail!("{} exited with {status}", exe.display());
+    }
+
+    Ok(())
+}
+
+/// Names of the running clusters.
+pub(crate) fn clusters(current: &OsStr) -> Vec<CompletionCandidate> {
+    candidates(current, cluster_names())
+}
+
+/// Namespaces of the default cluster.
+pub(crate) fn namespaces(current: &OsStr) -> Vec<CompletionCandidate> {
+    candidates(current, namespace_names())
+}
+
+/// Pods of the default cluster, as `<name>` or, once a `/` is typed, as
+/// `<namespace>/<name>`.
+pub(crate) fn pods(current: &OsStr) -> Vec<CompletionCandidate> {
+    let qualified = current.to_string_lossy().contains('/');
+    candidates(current, pod_names(qualified))
+}
+
+async fn cluster_names() -> Result<Vec<String>> {
+    match helper::send_request(Request::Clusters).await? {
+        Response::Clusters { clusters } => {
+            Ok(clusters.into_iter().map(|c| c.name).collect())
+        }
+        _ => bail!("unexpected response to clusters"),
+    }
+}
+
+async fn namespace_names() -> Result<Vec<String>> {
+    let req = NamespacesRequest { cluster: None };
+    match helper::send_request(Request::Namespaces(req)).await? {
+        Response::Namespaces { namespaces } => {
+            Ok(namespaces.into_iter().map(|n| n.name).collect())
+        }
+        _ => bail!("unexpected response to namespaces"),
+    }
+}
+
+async fn pod_names(qualified: bool) -> Result<Vec<String>> {
+    let req = PodsRequest {
+        cluster: None,
+        namespace: None,
+        failed_only: false,
+        all_clusters: false,
+        label_selector: None,
+        field_selector: None,
+        limit: None,
+        continue_token: None,
+    };
+    let pods = match helper::send_request(Request::Pods(req)).await? {
+        Response::Pods { pods, .. } => pods,
+        _ => bail!("unexpected response to pods"),
+    };
+
+    Ok(pods
+        .into_iter()
+        .map(|p| {
+            if qualified {
+                format!("{}/{}", p.namespace, p.name)
+            } else {
+                p.name
+            }
+        })
+        .collect())
+}
+
+/// Candidates starting with `current` among the names `fetch` returns.
+///
+/// Completers run synchronously inside the runtime of `main`, so the
+/// daemon is asked from a runtime of its own on another thread. It is
+/// never started for a completion and any failure completes nothing.
+fn candidates<F>(current: &OsStr, fetch: F) -> Vec<CompletionCandidate>
+where
+    F: Future<Output = Result<Vec<String>>> + Send + 'static,
+{
+    helper::set_auto_start(AutoStart::Never);
+
+    let names = std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .ok()?;
+        runtime.block_on(fetch).ok()
+    })
+    .join()
+    .ok()
+    .flatten()
+    .unwrap_or_default();
+
+    let current = current.to_string_lossy();
+    let mut names: Vec<String> =
+        names.into_iter().filter(|n| n.starts_with(&*current)).collect();
+    names.sort();
+    names.dedup();
+
+    names.into_iter().map(CompletionCandidate::new).collect()
+}
@@ -0,0 +1,103 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Config of the kops user, shared by the kopsctl commands: `KOPS_CONFIG`,
+//! else `kops/config.toml` under `XDG_CONFIG_HOME` or `~/.config`.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct KopsConfig {
+    /// `[profile.<name>]` sections, by name.
+    #[serde(default)]
+    pub profile: BTreeMap<String, LoginProfile>,
+}
+
+/// How `kopsctl login <name>` logs in; KOPS_SSO_* variables override it.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub(crate) struct LoginProfile {
+    /// AWS SSO portal, e.g. `https://my-org.awsapps.com/start`.
+    pub start_url: Option<String>,
+
+    /// Picked from the SSO portal at login when unset.
+    pub account_id: Option<String>,
+    pub role_name: Option<String>,
+
+    /// Region of the SSO portal.
+    pub region: Option<String>,
+
+    /// sso-session of the AWS config sharing its token with the AWS CLI.
+    pub sso_session: Option<String>,
+
+    /// SSO profile of the AWS config filling in the keys left unset.
+    pub aws_profile: Option<String>,
+
+    /// EKS clusters started with the session once logged in, besides
+    /// those the daemon config maps to the profile.
+    #[serde(default)]
+    pub clusters: Vec<String>,
+}
+
+pub(crate) fn path() -> Option<PathBuf> {
+    std::env::var_os("KOPS_CONFIG")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(dir()?.join("config.toml")))
+}
+
+/// Directory of the kops files of the user: `kops` under
+/// `XDG_CONFIG_HOME` or `~/.config`.
+pub(crate) fn dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+
+    let config_home = var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(config_home.join("kops"))
+}
+
+/// The config of the user, empty when there is none.
+pub(crate) fn load() -> Result<KopsConfig> {
+    let Some(path) = path() else {
+        return Ok(KopsConfig::default());
+    };
+
+    let settings = config::Config::builder()
+        .add_source(config::File::from(path.clone()).required(false))
+        .build()
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    settings
+        .try_deserialize()
+        .with_context(|| format!("invalid config {}", path.display()))
+}
+
+/// `socket` of `[kops]` in the daemon config, read from the same sources
+/// as kopsd (`config/kopsd.*`, then `KOPSD__KOPS__SOCKET`), so that
+/// kopsctl connects where the daemon listens. An unreadable daemon config
+/// is left to kopsd to report.
+pub(crate) fn daemon_socket() -> Option<PathBuf> {
+    let settings = config::Config::builder()
+        .add_source(config::File::with_name("config/kopsd").required(false))
+        .add_source(config::Environment::with_prefix("KOPSD").separator("__"))
+        .build()
+        .ok()?;
+
+    settings.get::<PathBuf>("kops.socket").ok()
+}
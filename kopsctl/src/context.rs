@@ -0,0 +1,97 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Client-side context set with `kopsctl use`: the cluster and namespace
+//! every command defaults to, kept in `context.toml` next to the kops
+//! config.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result, anyhow};
+use clap::Command;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Context {
+    /// Default of `--cluster`.
+    pub cluster: Option<String>,
+
+    /// Default of `--namespace`.
+    pub namespace: Option<String>,
+}
+
+fn path() -> Result<PathBuf> {
+    config::dir()
+        .map(|dir| dir.join("context.toml"))
+        .ok_or_else(|| anyhow!("neither XDG_CONFIG_HOME nor HOME is set"))
+}
+
+/// The context of the user, empty when none was set.
+pub(crate) fn load() -> Result<Context> {
+    let path = path()?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Context::default());
+        }
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!("failed to read {}", path.display())
+            });
+        }
+    };
+
+    toml::from_str(&text)
+        .with_context(|| format!("invalid context {}", path.display()))
+}
+
+/// Persist `context`, creating the kops config directory if needed.
+pub(crate) fn save(context: &Context) -> Result<()> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+
+    fs::write(&path, toml::to_string(context)?)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Make the `--cluster` and `--namespace` of every subcommand of `cmd`
+/// default to `context`, so explicit flags still win.
+pub(crate) fn apply(mut cmd: Command, context: &Context) -> Command {
+    for (id, value) in
+        [("cluster", &context.cluster), ("namespace", &context.namespace)]
+    {
+        let Some(value) = value else {
+            continue;
+        };
+        if cmd.get_arguments().any(|a| a.get_id() == id) {
+            cmd = cmd.mut_arg(id, |arg| arg.default_value(value.clone()));
+        }
+    }
+
+    let names: Vec<String> =
+        cmd.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    for name in names {
+        cmd = cmd.mut_subcommand(name, |sub| apply(sub, context));
+    }
+
+    cmd
+}
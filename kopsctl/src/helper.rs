@@ -14,26 +14,343 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
-use anyhow::{Result, bail};
-use tokio::net::UnixStream;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow, bail};
+use crossterm::style::Stylize;
+use dialoguer::{Confirm, FuzzySelect};
+use tokio::{net::UnixStream, process::Command, signal, time::sleep};
 use tracing::debug;
 
 use kops_protocol::{
-    Request, Response,
-    wire::{read_message, write_message},
+    ErrorCode, NamespacesRequest, Request, Response,
+    mux::Mux,
+    socket,
+    wire::{client_handshake, read_message, write_message},
 };
 
-const SOCKET_PATH: &str = "/var/run/kopsd/kopsd.sock";
+use crate::config;
+use crate::output::{color_enabled, format_expiry};
+
+static SOCKET_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// How long a daemon launched by kopsctl may take to answer.
+pub(crate) const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delay between two checks while waiting for the daemon.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Whether `connect` launches the daemon when it is not running.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum AutoStart {
+    /// Ask first, when run from a terminal.
+    Ask,
+    Always,
+    Never,
+}
+
+static AUTO_START: OnceLock<AutoStart> = OnceLock::new();
+
+/// Use the socket of `--socket`, or else the one `socket::resolve` finds
+/// with the socket of the daemon config.
+pub(crate) fn set_socket_path(flag: Option<PathBuf>) {
+    let _ = SOCKET_PATH.set(socket::resolve(flag, config::daemon_socket()));
+}
+
+/// Socket of the daemon.
+pub(crate) fn socket_path() -> &'static Path {
+    SOCKET_PATH.get_or_init(|| socket::resolve(None, config::daemon_socket()))
+}
+
+/// Set how `connect` handles a daemon that is not running; `Ask` until
+/// set.
+pub(crate) fn set_auto_start(auto_start: AutoStart) {
+    let _ = AUTO_START.set(auto_start);
+}
+
+/// Open a connection to the daemon socket and negotiate the protocol,
+/// launching the daemon first if it is not running and `set_auto_start`
+/// allows it.
+pub(crate) async fn connect() -> Result<UnixStream> {
+    let err = match connect_socket().await {
+        Ok(stream) => return Ok(stream),
+        Err(err) => err,
+    };
+
+    if !daemon_missing(&err) {
+        return Err(err);
+    }
+    if !start_wanted()? {
+        return Err(err.context(format!(
+            "kopsd is not running on {}; start it with `kopsctl daemon \
+             start` or pass --auto-start",
+            socket_path().display()
+        )));
+    }
+
+    eprintln!("kopsd is not running, starting it...");
+    launch_daemon().await?;
+
+    let deadline = Instant::now() + START_TIMEOUT;
+    loop {
+        sleep(POLL_INTERVAL).await;
+        match connect_socket().await {
+            Ok(stream) => return Ok(stream),
+            Err(err) if Instant::now() >= deadline => {
+                return Err(err.context(format!(
+                    "kopsd did not answer within {START_TIMEOUT:?}"
+                )));
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+async fn connect_socket() -> Result<UnixStream> {
+    debug!("connecting to kopsd at {}", socket_path().display());
+    let mut stream = UnixStream::connect(socket_path()).await?;
+
+    let version = client_handshake(&mut stream).await?;
+    debug!(version, "protocol negotiated");
+
+    Ok(stream)
+}
+
+/// Whether `err` means nothing listens on the socket, as opposed to a
+/// daemon that failed the handshake or denied access.
+fn daemon_missing(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<io::Error>().is_some_and(|e| {
+        matches!(
+            e.kind(),
+            io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+        )
+    })
+}
+
+fn start_wanted() -> Result<bool> {
+    match AUTO_START.get().copied().unwrap_or(AutoStart::Ask) {
+        AutoStart::Always => Ok(true),
+        AutoStart::Never => Ok(false),
+        AutoStart::Ask => {
+            if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+                return Ok(false);
+            }
+
+            let start = Confirm::new()
+                .with_prompt("kopsd is not running, start it?")
+                .default(true)
+                .interact()?;
+            Ok(start)
+        }
+    }
+}
+
+/// Launch kopsd, which daemonizes with its own config; it answers on
+/// `socket_path` a moment later.
+pub(crate) async fn launch_daemon() -> Result<()> {
+    let kopsd = kopsd_path();
+    debug!("launching {}", kopsd.display());
+
+    let exit = Command::new(&kopsd)
+        .arg("--socket")
+        .arg(socket_path())
+        .status()
+        .await
+        .with_context(|| format!("failed to launch {}", kopsd.display()))?;
+    if !exit.success() {
+        bail!("{} exited with {exit}", kopsd.display());
+    }
+
+    Ok(())
+}
+
+/// kopsd installed next to this kopsctl, or else the one in PATH.
+fn kopsd_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("kopsd")))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from("kopsd"))
+}
+
+/// Open a multiplexed connection, for commands sending several requests
+/// at once.
+pub(crate) async fn connect_mux() -> Result<Mux<UnixStream>> {
+    Ok(Mux::new(connect().await?))
+}
 
 pub(crate) async fn send_request(req: Request) -> Result<Response> {
-    debug!("connecting to kopsd at {}", SOCKET_PATH);
-    let mut stream = UnixStream::connect(SOCKET_PATH).await?;
+    check_credentials(send_request_unchecked(req).await?)
+}
+
+/// Like `send_request`, leaving expired credentials to the caller.
+pub(crate) async fn send_request_unchecked(req: Request) -> Result<Response> {
+    let mut stream = connect().await?;
 
     write_message(&mut stream, &req).await?;
-    let resp: Response = match read_message(&mut stream).await? {
-        Some(r) => r,
+    match read_message(&mut stream).await? {
+        Some(r) => Ok(r),
         None => bail!("daemon closed connection without reply"),
-    };
+    }
+}
+
+/// Fail with a hint when the daemon reports expired credentials, which
+/// only a new login fixes; other errors are left to the command.
+fn check_credentials(resp: Response) -> Result<Response> {
+    if let Response::Error(err) = &resp
+        && err.code == ErrorCode::CredentialsExpired
+    {
+        bail!("{err}; run `kopsctl login <profile>` to log in again");
+    }
 
     Ok(resp)
 }
+
+/// Warn on stderr about the sessions the daemon reports as about to
+/// expire, in yellow on a terminal unless `no_color`. Best effort: it
+/// never starts the daemon and stays silent on any failure.
+pub(crate) async fn warn_expiring_sessions(no_color: bool) {
+    let Ok(mut stream) = connect_socket().await else {
+        return;
+    };
+    if write_message(&mut stream, &Request::SessionHealth).await.is_err() {
+        return;
+    }
+    let Ok(Some(Response::SessionHealth { expiring })) =
+        read_message(&mut stream).await
+    else {
+        return;
+    };
+
+    let color = color_enabled(no_color, io::stderr().is_terminal());
+    for session in expiring {
+        let warning = format!(
+            "profile {profile} expires {} — run kopsctl login {profile}",
+            format_expiry(session.expires_at_epoch_ms),
+            profile = session.profile,
+        );
+        if color {
+            eprintln!("{}", warning.yellow());
+        } else {
+            eprintln!("{warning}");
+        }
+    }
+}
+
+/// Send a request whose reply is a sequence of responses.
+///
+/// `on_response` is called for every response received and returns
+/// whether more are wanted. Returning `false`, or Ctrl-C, cancels the
+/// request in the daemon.
+pub(crate) async fn stream_request<F>(
+    req: Request,
+    mut on_response: F,
+) -> Result<()>
+where
+    F: FnMut(Response) -> Result<bool>,
+{
+    let mux = connect_mux().await?;
+    let mut replies = mux.open(req).await?;
+
+    loop {
+        let next = tokio::select! {
+            resp = replies.next() => Some(resp?),
+            _ = signal::ctrl_c() => None,
+        };
+        let Some(next) = next else {
+            debug!("interrupted, cancelling request");
+            break;
+        };
+        let Some(resp) = next else {
+            return Ok(());
+        };
+
+        let resp = check_credentials(resp)?;
+        if let Response::Error(err) = resp {
+            bail!("response error {err}");
+        }
+        if !on_response(resp)? {
+            break;
+        }
+    }
+
+    mux.cancel(replies).await?;
+    Ok(())
+}
+
+/// Parse a relative duration such as `30s`, `5m`, `2h` or `1d`.
+pub(crate) fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+
+    let value: u64 =
+        value.parse().map_err(|_| anyhow!("invalid duration: {s}"))?;
+
+    let unit_secs: u64 = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => bail!("invalid duration unit in {s} (use s, m, h or d)"),
+    };
+    let secs = value
+        .checked_mul(unit_secs)
+        .ok_or_else(|| anyhow!("duration too large: {s}"))?;
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// Split a `<namespace>/<name>` reference, falling back to `namespace`
+/// (or "default") when only a name is given.
+pub(crate) fn split_ref(
+    reference: &str,
+    namespace: Option<String>,
+) -> (String, String) {
+    match reference.split_once('/') {
+        Some((ns, name)) => (ns.to_string(), name.to_string()),
+        None => (
+            namespace.unwrap_or_else(|| "default".to_string()),
+            reference.to_string(),
+        ),
+    }
+}
+
+/// Resolve a user supplied `--namespace` against the cluster namespaces.
+///
+/// An exact match is returned as is; anything else opens a fuzzy selector
+/// pre-filled with what was typed, so partial names are enough.
+pub(crate) async fn resolve_namespace(
+    cluster: Option<String>,
+    namespace: Option<String>,
+) -> Result<Option<String>> {
+    let Some(namespace) = namespace else {
+        return Ok(None);
+    };
+
+    let resp =
+        send_request(Request::Namespaces(NamespacesRequest { cluster }))
+            .await?;
+    let names: Vec<String> = match resp {
+        Response::Namespaces { namespaces } => {
+            namespaces.into_iter().map(|n| n.name).collect()
+        }
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to namespaces"),
+    };
+
+    if names.is_empty() || names.contains(&namespace) {
+        return Ok(Some(namespace));
+    }
+
+    let selection = FuzzySelect::new()
+        .with_prompt("Select namespace")
+        .with_initial_text(namespace)
+        .items(&names)
+        .interact()?;
+
+    Ok(Some(names[selection].clone()))
+}
@@ -14,11 +14,27 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
 use anyhow::Result;
-use clap::{ArgAction, Parser, Subcommand};
+use clap::{
+    ArgAction, Args as ClapArgs, CommandFactory, FromArgMatches, Parser,
+    Subcommand, ValueEnum,
+};
+use clap_complete::{CompleteEnv, engine::ArgValueCompleter};
+use kops_protocol::{MetricsTarget, PodsRequest};
+
+use crate::helper::AutoStart;
+use crate::output::{OutputFormat, SortSpec, TableView};
 
 mod cmd;
+mod complete;
+mod config;
+mod context;
 mod helper;
+mod output;
+mod picker;
 
 const VERSION: &str = concat!(
     env!("CARGO_PKG_VERSION"),
@@ -36,47 +52,857 @@ enum Command {
 
     /// Login via AWS SSO and register credentials in kopsd
     Login {
-        /// Logical name for this credential set (e.g. dev, prod)
-        #[arg(long)]
-        name: String,
+        /// Logical name for this credential set (e.g. dev, prod), looked
+        /// up in the [profile.<name>] sections of the kops config
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+
+        /// Log in every profile of the kops config, one after the other
+        #[arg(long, conflicts_with_all = ["name", "aws_profile"])]
+        all: bool,
 
         /// AWS region for SSO (optional, defaults to config or us-east-1)
         #[arg(long)]
         region: Option<String>,
+
+        /// SSO profile of ~/.aws/config to read the start URL, account,
+        /// role and sso-session from
+        #[arg(long)]
+        aws_profile: Option<String>,
+
+        /// Print the verification URL and code instead of opening a
+        /// browser, e.g. over SSH
+        #[arg(long)]
+        no_browser: bool,
+
+        /// Wait for Enter once the code is shown before polling for the
+        /// approval
+        #[arg(long)]
+        confirm: bool,
+    },
+
+    /// List the AWS sessions stored in kopsd and when they expire
+    Sessions,
+
+    /// Remove the AWS session of a profile and stop its clusters
+    Logout {
+        /// Logical name given at login (e.g. dev, prod)
+        profile: String,
+    },
+
+    /// List configured and running clusters with their worker state
+    Clusters,
+
+    /// AWS credentials of a stored session, for the tools outside of kops
+    Credentials {
+        #[command(subcommand)]
+        action: CredentialsAction,
+    },
+
+    /// Print the credentials of a profile for the credential_process of
+    /// an AWS config profile, logging in first when needed
+    CredentialProcess {
+        /// Profile given at login (e.g. dev, prod)
+        #[arg(long)]
+        profile: String,
+    },
+
+    /// Print an ExecCredential for an EKS cluster, as a client-go exec
+    /// plugin of kubectl
+    Token {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+    },
+
+    /// kubeconfig entries for the EKS clusters of the daemon
+    Kubeconfig {
+        #[command(subcommand)]
+        action: KubeconfigAction,
+    },
+
+    /// Start or stop clusters in the running daemon
+    Cluster {
+        #[command(subcommand)]
+        action: ClusterAction,
+    },
+
+    /// Start, stop or control the daemon
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonAction,
     },
 
     /// Show daemon and protocol version
     Version,
 
-    Pods {
+    /// Set the cluster or namespace every command defaults to
+    Use {
+        #[command(subcommand)]
+        target: UseTarget,
+    },
+
+    /// Print a completion script that also completes cluster, namespace
+    /// and pod names from the daemon, e.g. `source <(kopsctl completions
+    /// bash)`
+    Completions {
+        #[arg(value_enum)]
+        shell: complete::Shell,
+    },
+
+    /// Daemon uptime, connections, reflector health and AWS sessions
+    Status,
+
+    /// Live dashboard of pods, failures and restarts across clusters
+    Ui,
+
+    /// Print the logs of a pod container
+    Logs {
+        /// Pod as <name> or <namespace>/<name>; picked from a list when
+        /// omitted
+        #[arg(add = ArgValueCompleter::new(complete::pods))]
+        pod: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+        namespace: Option<String>,
+
+        /// Container name (defaults to the only container of the pod)
+        #[arg(short, long)]
+        container: Option<String>,
+
+        /// Keep streaming new log lines
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Lines from the end of the log to show
         #[arg(long)]
+        tail: Option<i64>,
+
+        /// Only show logs newer than a duration (e.g. 30s, 5m, 1h)
+        #[arg(long)]
+        since: Option<String>,
+    },
+
+    /// Run a command in a pod container
+    Exec {
+        /// Pod as <name> or <namespace>/<name>; picked from a list when
+        /// omitted
+        #[arg(add = ArgValueCompleter::new(complete::pods))]
+        pod: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+        namespace: Option<String>,
+
+        /// Container name (defaults to the only container of the pod)
+        #[arg(short, long)]
+        container: Option<String>,
+
+        /// Pass stdin to the container
+        #[arg(short = 'i', long)]
+        stdin: bool,
+
+        /// Allocate a TTY for the command
+        #[arg(short, long)]
+        tty: bool,
+
+        /// Command and arguments, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Forward a local port to a pod port through the daemon
+    PortForward {
+        /// Pod as <name> or <namespace>/<name>
+        #[arg(add = ArgValueCompleter::new(complete::pods))]
+        pod: String,
+
+        /// Ports as <local>:<remote>, or a single port used for both
+        ports: String,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+        namespace: Option<String>,
+
+        /// Local address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        address: String,
+    },
+
+    Pods {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
         cluster: Option<String>,
 
+        #[command(flatten)]
+        namespace: NamespaceScope,
+
+        #[arg(long)]
+        failed_only: bool,
+
+        /// List every running cluster
+        #[arg(long, conflicts_with_all = ["cluster", "watch"])]
+        all_clusters: bool,
+
+        /// Label selector, e.g. `app=payments` or `tier in (web,api)`
+        #[arg(short = 'l', long = "selector")]
+        label_selector: Option<String>,
+
+        /// Field selector, e.g. `status.phase=Running`
         #[arg(long)]
+        field_selector: Option<String>,
+
+        /// Keep running and print pod changes as they happen
+        #[arg(short, long)]
+        watch: bool,
+
+        /// List at most this many pods
+        #[arg(long, conflicts_with = "watch")]
+        limit: Option<u32>,
+
+        /// Continue a limited listing from the token it printed
+        #[arg(long = "continue", value_name = "TOKEN")]
+        continue_token: Option<String>,
+
+        /// Columns to print, by header (e.g. name,restarts,status)
+        #[arg(
+            long,
+            value_delimiter = ',',
+            value_name = "COLUMNS",
+            conflicts_with = "watch"
+        )]
+        columns: Vec<String>,
+
+        /// Sort rows by a column, as <column>[:asc|:desc]
+        #[arg(long, value_name = "COLUMN", conflicts_with = "watch")]
+        sort_by: Option<SortSpec>,
+
+        /// Exit with code 2 when any listed pod is failing or
+        /// crashlooping
+        #[arg(long, conflicts_with = "watch")]
+        fail_on_issues: bool,
+    },
+
+    /// List failing and crashlooping pods, exiting with code 2 when
+    /// there are any
+    Check {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+
+        /// Check every running cluster
+        #[arg(long, conflicts_with = "cluster")]
+        all_clusters: bool,
+    },
+
+    /// Show details of a resource
+    Describe {
+        #[command(subcommand)]
+        kind: DescribeKind,
+    },
+
+    /// Print a cached object as YAML, or JSON with `-o json`
+    Get {
+        #[arg(value_enum)]
+        kind: cmd::get::Kind,
+
+        /// Object as <name> or <namespace>/<name>
+        name: String,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
         namespace: Option<String>,
+    },
+
+    /// Inspect configmaps
+    #[command(alias = "cm")]
+    Configmap {
+        #[command(subcommand)]
+        action: ConfigMapAction,
+    },
+
+    /// Show CPU and memory usage (requires metrics-server)
+    Top {
+        #[command(subcommand)]
+        target: TopTarget,
+    },
+
+    /// List secrets, or reveal the value of one key
+    Secrets {
+        /// Secret as <name> or <namespace>/<name>
+        name: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+        namespace: Option<String>,
+
+        /// Print the decoded value of --key, after confirmation
+        #[arg(long, requires = "key")]
+        reveal: bool,
+
+        /// Key to reveal
+        #[arg(long)]
+        key: Option<String>,
+    },
+
+    /// Delete a resource
+    Delete {
+        #[command(subcommand)]
+        kind: DeleteKind,
+    },
+
+    /// Set the replica count of a resource
+    Scale {
+        #[command(subcommand)]
+        kind: ScaleKind,
+    },
+
+    /// Manage the rollout of a resource
+    Rollout {
+        #[command(subcommand)]
+        action: RolloutAction,
+    },
+
+    /// List deployments with their replica counts and images
+    Deployments {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+
+        /// List every running cluster
+        #[arg(long, conflicts_with = "cluster")]
+        all_clusters: bool,
 
+        /// Columns to print, by header (e.g. name,restarts,status)
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Vec<String>,
+
+        /// Sort rows by a column, as <column>[:asc|:desc]
+        #[arg(long, value_name = "COLUMN")]
+        sort_by: Option<SortSpec>,
+    },
+
+    /// List services with their ports and ready endpoints
+    #[command(alias = "svc")]
+    Services {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+    },
+
+    /// List ingress routes with their backends and addresses
+    #[command(alias = "ing")]
+    Ingresses {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+    },
+
+    /// List jobs with their completion status and failure reason
+    Jobs {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+
+        /// Only jobs that failed
         #[arg(long)]
         failed_only: bool,
     },
 
+    /// List cronjobs with their last schedule and success
+    #[command(alias = "cj")]
+    Cronjobs {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+    },
+
+    /// List namespaces
+    Namespaces {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+    },
+
+    /// List nodes of one or every cluster
+    Nodes {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        /// Columns to print, by header (e.g. name,restarts,status)
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Vec<String>,
+
+        /// Sort rows by a column, as <column>[:asc|:desc]
+        #[arg(long, value_name = "COLUMN")]
+        sort_by: Option<SortSpec>,
+    },
+
+    /// List statefulsets with their replica counts and revisions
+    #[command(alias = "sts")]
+    Statefulsets {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+    },
+
+    /// List daemonsets with their scheduling counts and revisions
+    #[command(alias = "ds")]
+    Daemonsets {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+    },
+
+    /// List horizontal pod autoscalers with their metrics
+    Hpa {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+    },
+
+    /// List persistent volume claims and the pods using them
+    Pvc {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+    },
+
+    /// List running container images with pod counts per tag
+    Images {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+    },
+
+    /// List cluster events, oldest first
+    Events {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+
+        /// Only events about an object, as <name> or <kind>/<name>
+        #[arg(long = "for")]
+        involved_object: Option<String>,
+
+        /// Only events seen within a duration (e.g. 30s, 5m, 1h)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// List every running cluster
+        #[arg(long, conflicts_with = "cluster")]
+        all_clusters: bool,
+    },
+
+    /// List containers that restarted recently, as seen by the daemon
+    Restarts {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+
+        /// Restarts within a duration (e.g. 30m, 1h); the daemon keeps 24h
+        #[arg(long, default_value = "1h")]
+        since: String,
+    },
+
+    /// List the restarts of a pod with the Warning events that preceded
+    /// them
+    History {
+        /// Pod as <name> or <namespace>/<name>; picked from a list when
+        /// omitted
+        #[arg(add = ArgValueCompleter::new(complete::pods))]
+        pod: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+        namespace: Option<String>,
+    },
+
     Env {
+        #[command(subcommand)]
+        view: Option<EnvView>,
+
+        #[arg(
+            long,
+            global = true,
+            add = ArgValueCompleter::new(complete::clusters)
+        )]
+        cluster: Option<String>,
+
+        #[arg(
+            long,
+            global = true,
+            add = ArgValueCompleter::new(complete::namespaces)
+        )]
+        namespace: Option<String>,
+
+        #[arg(
+            long,
+            global = true,
+            add = ArgValueCompleter::new(complete::pods)
+        )]
+        pod: Option<String>,
+
+        #[arg(long, global = true)]
+        container: Option<String>,
+
+        /// Show variables of every container, prefixed with its name
+        #[arg(long, global = true, conflicts_with = "container")]
+        all_containers: bool,
+
+        /// Only show variables whose name matches this regex
+        #[arg(long, global = true)]
+        filter: Option<String>,
+
+        /// Resolve valueFrom references and expand envFrom sources
+        #[arg(long, global = true)]
+        resolve: bool,
+
+        /// Show Secret values when resolving instead of masking them
+        #[arg(long, global = true)]
+        reveal_secrets: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum DescribeKind {
+    /// Containers, resources, volumes, conditions and events of a pod
+    Pod {
+        /// Pod as <name> or <namespace>/<name>; picked from a list when
+        /// omitted
+        #[arg(add = ArgValueCompleter::new(complete::pods))]
+        pod: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+        namespace: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum TopTarget {
+    Pods {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+
+        #[arg(long, value_enum, default_value = "name")]
+        sort_by: cmd::top::SortBy,
+    },
+
+    Nodes {
+        /// Only this cluster; every running cluster by default
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, value_enum, default_value = "name")]
+        sort_by: cmd::top::SortBy,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum UseTarget {
+    /// Default of --cluster; prints the current one without a name
+    Cluster {
+        #[arg(add = ArgValueCompleter::new(complete::clusters))]
+        name: Option<String>,
+
+        /// Forget the default cluster
+        #[arg(long, conflicts_with = "name")]
+        unset: bool,
+    },
+
+    /// Default of --namespace; prints the current one without a name
+    Namespace {
+        #[arg(add = ArgValueCompleter::new(complete::namespaces))]
+        name: Option<String>,
+
+        /// Forget the default namespace
+        #[arg(long, conflicts_with = "name")]
+        unset: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigMapAction {
+    /// List configmaps
+    List {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[command(flatten)]
+        namespace: NamespaceScope,
+    },
+
+    /// Print the keys and values of a configmap
+    Get {
+        /// ConfigMap as <name> or <namespace>/<name>
+        name: String,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+        namespace: Option<String>,
+
+        /// Print only this key, raw
         #[arg(long)]
+        key: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum DeleteKind {
+    Pod {
+        /// Pod as <name> or <namespace>/<name>; picked from a list when
+        /// omitted
+        #[arg(add = ArgValueCompleter::new(complete::pods))]
+        pod: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
         cluster: Option<String>,
 
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+        namespace: Option<String>,
+
+        /// Seconds the pod is given to terminate gracefully
+        #[arg(long)]
+        grace_period: Option<u32>,
+
+        /// Delete immediately, without waiting for termination
+        #[arg(long)]
+        force: bool,
+
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ScaleKind {
+    #[command(alias = "deploy")]
+    Deployment {
+        /// Deployment as <name> or <namespace>/<name>
+        name: String,
+
         #[arg(long)]
+        replicas: i32,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
         namespace: Option<String>,
+    },
+}
 
+#[derive(Debug, Subcommand)]
+enum ClusterAction {
+    /// Start an EKS cluster with the session of a logged in profile
+    Add {
+        /// EKS cluster name
         #[arg(long)]
-        pod: Option<String>,
+        name: String,
 
+        /// Profile given at login (e.g. dev, prod)
         #[arg(long)]
-        container: Option<String>,
+        profile: String,
 
+        /// Region of the cluster (defaults to the session's region)
         #[arg(long)]
-        filter: Option<String>,
+        region: Option<String>,
+    },
+
+    /// Stop a running cluster
+    Remove {
+        /// Cluster name, as listed by kopsctl status
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CredentialsAction {
+    /// Print the credentials of a profile, or write them to
+    /// ~/.aws/credentials
+    Export {
+        /// Profile given at login (e.g. dev, prod)
+        #[arg(long)]
+        profile: String,
+
+        #[arg(long, value_enum, default_value = "env")]
+        format: cmd::credentials::Format,
+
+        /// Write the credentials as this profile of ~/.aws/credentials
+        /// instead of printing them
+        #[arg(long)]
+        write_aws_profile: Option<String>,
     },
 }
 
+#[derive(Debug, Subcommand)]
+enum KubeconfigAction {
+    /// Add or update the cluster, user and context of a cluster, its
+    /// tokens coming from `kopsctl token`
+    Write {
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        /// File to update, instead of the first of KUBECONFIG or
+        /// ~/.kube/config
+        #[arg(long)]
+        kubeconfig: Option<PathBuf>,
+
+        /// Name of the context, the cluster name by default
+        #[arg(long)]
+        context: Option<String>,
+
+        /// Make it the current context
+        #[arg(long = "use")]
+        use_context: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum DaemonAction {
+    /// Launch the daemon and wait until it answers
+    Start,
+
+    /// Stop the daemon and wait until it exits
+    Stop,
+
+    /// Stop the daemon if it runs, then start it again
+    Restart,
+
+    /// Tell whether the daemon runs and answers
+    Status,
+
+    /// Re-read the daemon config and apply its cluster list
+    Reload,
+}
+
+#[derive(Debug, Subcommand)]
+enum RolloutAction {
+    /// Restart the pods of a resource
+    Restart {
+        kind: RolloutKind,
+
+        /// Name as <name> or <namespace>/<name>
+        name: String,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        cluster: Option<String>,
+
+        #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+        namespace: Option<String>,
+
+        /// Wait for the rollout to complete
+        #[arg(long)]
+        wait: bool,
+
+        /// How long to wait with --wait (e.g. 30s, 5m)
+        #[arg(long, default_value = "5m")]
+        timeout: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum RolloutKind {
+    #[value(alias = "deploy")]
+    Deployment,
+}
+
+#[derive(Debug, Subcommand)]
+enum EnvView {
+    /// Group variables by source (literal, ConfigMap, Secret, Downward API).
+    Tree,
+
+    /// Compare the variables of two pods, possibly in different clusters.
+    Diff {
+        /// First pod as <name> or <namespace>/<name>
+        #[arg(add = ArgValueCompleter::new(complete::pods))]
+        left: String,
+
+        /// Second pod as <name> or <namespace>/<name>
+        #[arg(add = ArgValueCompleter::new(complete::pods))]
+        right: String,
+
+        /// Cluster of the first pod, instead of --cluster
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        left_cluster: Option<String>,
+
+        /// Cluster of the second pod, instead of --cluster
+        #[arg(long, add = ArgValueCompleter::new(complete::clusters))]
+        right_cluster: Option<String>,
+    },
+
+    /// Find the variables of every pod whose name, value or referenced
+    /// ConfigMap or Secret matches a regex
+    Grep {
+        /// Regex, e.g. `old-db\.internal` or `legacy-api-key`
+        pattern: String,
+    },
+}
+
+/// `--namespace` of the listing commands, where no namespace means every
+/// namespace.
+#[derive(Debug, ClapArgs)]
+struct NamespaceScope {
+    #[arg(long, add = ArgValueCompleter::new(complete::namespaces))]
+    namespace: Option<String>,
+
+    /// List every namespace, despite the one set with `kopsctl use`
+    #[arg(short = 'A', long, conflicts_with = "namespace")]
+    all_namespaces: bool,
+}
+
+impl NamespaceScope {
+    /// The namespace to list, `None` for every one.
+    fn namespace(self) -> Option<String> {
+        if self.all_namespaces { None } else { self.namespace }
+    }
+}
+
 #[derive(Debug, Parser)]
 #[command(
     name = env!("CARGO_PKG_NAME"),
@@ -92,6 +918,37 @@ struct Args {
     #[arg(short, long, global = true, action = ArgAction::Count)]
     verbose: u8,
 
+    /// Deterministic ordering and a fixed column set.
+    ///
+    /// Rows are sorted byte-wise by the keys the daemon sorts by and only
+    /// columns guaranteed across versions are printed, tab-separated. Meant
+    /// for snapshot tests in CI.
+    #[arg(long, global = true)]
+    stable_output: bool,
+
+    /// Print results as a table, a wide table with extra columns, or as
+    /// JSON or YAML for jq and scripts.
+    #[arg(short, long, global = true, value_enum, default_value = "table")]
+    output: OutputFormat,
+
+    /// Print only the names of the listed resources, one per line, as
+    /// <namespace>/<name> for namespaced ones
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print tables and warnings without colors, as a non-empty NO_COLOR
+    /// does.
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Start the daemon without asking when it is not running.
+    #[arg(long, global = true)]
+    auto_start: bool,
+
+    /// Daemon socket, instead of the one of KOPS_SOCKET or the default.
+    #[arg(long, global = true)]
+    socket: Option<PathBuf>,
+
     /// Command to execute.
     #[command(subcommand)]
     command: Command,
@@ -99,23 +956,461 @@ struct Args {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
+    // Answers the completion scripts, which run kopsctl with COMPLETE set.
+    CompleteEnv::with_factory(Args::command).complete();
+
+    // `kopsctl use` defaults, which explicit flags override. A broken
+    // context.toml must not break every command, `kopsctl use` included.
+    let context = context::load().unwrap_or_else(|err| {
+        eprintln!("ignoring the defaults of kopsctl use: {err:#}");
+        context::Context::default()
+    });
+    let matches = context::apply(Args::command(), &context).get_matches();
+    let args =
+        Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
 
     kops_log::init(args.verbose);
 
+    let output = output::OutputOptions {
+        stable: args.stable_output,
+        format: args.output,
+        color: output::color_enabled(
+            args.no_color,
+            std::io::stdout().is_terminal(),
+        ),
+        quiet: args.quiet,
+    };
+
+    helper::set_socket_path(args.socket.clone());
+
+    // `daemon` manages the daemon itself.
+    helper::set_auto_start(match args.command {
+        Command::Daemon { .. } => AutoStart::Never,
+        _ if args.auto_start => AutoStart::Always,
+        _ => AutoStart::Ask,
+    });
+
+    // Commands about the sessions or the daemon themselves, and the ones
+    // run by other tools, go without the expiry warning.
+    let warn_expiring = !matches!(
+        args.command,
+        Command::Ping
+            | Command::Login { .. }
+            | Command::Sessions
+            | Command::Logout { .. }
+            | Command::CredentialProcess { .. }
+            | Command::Token { .. }
+            | Command::Daemon { .. }
+            | Command::Version
+            | Command::Use { .. }
+            | Command::Completions { .. }
+            | Command::Ui
+    );
+
+    // Set by the commands exiting with another code than 0 on success,
+    // once the expiry warning is out.
+    let mut exit_code = None;
+
     match args.command {
         Command::Ping => cmd::ping::execute().await?,
-        Command::Login { name, region } => {
-            cmd::login::execute(name, region).await?
+        Command::Login {
+            name,
+            all,
+            region,
+            aws_profile,
+            no_browser,
+            confirm,
+        } => {
+            // JSON prints the code to approve and the outcome as one
+            // object per line on stdout.
+            let output = match output.format {
+                OutputFormat::Table | OutputFormat::Wide => {
+                    cmd::login::LoginOutput::Text
+                }
+                OutputFormat::Json => cmd::login::LoginOutput::Json,
+                OutputFormat::Yaml => cmd::login::LoginOutput::Yaml,
+            };
+            let opts = cmd::login::LoginOptions {
+                region,
+                aws_profile,
+                no_browser,
+                confirm,
+                output,
+            };
+            match name {
+                Some(name) if !all => cmd::login::execute(name, opts).await?,
+                _ => cmd::login::execute_all(opts).await?,
+            }
+        }
+        Command::Sessions => cmd::sessions::execute(output).await?,
+        Command::Logout { profile } => {
+            cmd::logout::execute(profile, output).await?
+        }
+        Command::Clusters => cmd::clusters::execute(output).await?,
+        Command::Credentials { action } => match action {
+            CredentialsAction::Export {
+                profile,
+                format,
+                write_aws_profile,
+            } => {
+                cmd::credentials::export(profile, format, write_aws_profile)
+                    .await?
+            }
+        },
+        Command::CredentialProcess { profile } => {
+            cmd::credential_process::execute(profile).await?
+        }
+        Command::Token { cluster } => cmd::token::execute(cluster).await?,
+        Command::Kubeconfig { action } => match action {
+            KubeconfigAction::Write {
+                cluster,
+                kubeconfig,
+                context,
+                use_context,
+            } => {
+                cmd::kubeconfig::write(
+                    cluster,
+                    kubeconfig,
+                    context,
+                    use_context,
+                )
+                .await?
+            }
+        },
+        Command::Cluster { action } => match action {
+            ClusterAction::Add { name, profile, region } => {
+                cmd::cluster::add(name, profile, region, output).await?
+            }
+            ClusterAction::Remove { name } => {
+                cmd::cluster::remove(name, output).await?
+            }
+        },
+        Command::Daemon { action } => match action {
+            DaemonAction::Start => cmd::daemon::start().await?,
+            DaemonAction::Stop => cmd::daemon::stop().await?,
+            DaemonAction::Restart => cmd::daemon::restart().await?,
+            DaemonAction::Status => cmd::daemon::status().await?,
+            DaemonAction::Reload => cmd::daemon::reload().await?,
+        },
+        Command::Version => cmd::version::execute(output).await?,
+        Command::Use { target } => match target {
+            UseTarget::Cluster { name, unset } => {
+                cmd::use_context::cluster(name, unset)?
+            }
+            UseTarget::Namespace { name, unset } => {
+                cmd::use_context::namespace(name, unset)?
+            }
+        },
+        Command::Completions { shell } => complete::print_script(shell)?,
+        Command::Status => cmd::status::execute(output).await?,
+        Command::Ui => cmd::ui::execute().await?,
+        Command::Logs {
+            pod,
+            cluster,
+            namespace,
+            container,
+            follow,
+            tail,
+            since,
+        } => {
+            cmd::logs::execute(
+                pod, cluster, namespace, container, follow, tail, since,
+            )
+            .await?
         }
-        Command::Version => cmd::version::execute().await?,
-        Command::Pods { cluster, namespace, failed_only } => {
-            cmd::pods::execute(cluster, namespace, failed_only).await?
+        Command::Exec {
+            pod,
+            cluster,
+            namespace,
+            container,
+            stdin,
+            tty,
+            command,
+        } => {
+            cmd::exec::execute(
+                pod, cluster, namespace, container, stdin, tty, command,
+            )
+            .await?
+        }
+        Command::PortForward { pod, ports, cluster, namespace, address } => {
+            cmd::port_forward::execute(pod, ports, cluster, namespace, address)
+                .await?
+        }
+        Command::Pods {
+            cluster,
+            namespace,
+            failed_only,
+            all_clusters,
+            label_selector,
+            field_selector,
+            watch,
+            limit,
+            continue_token,
+            columns,
+            sort_by,
+            fail_on_issues,
+        } => {
+            let req = PodsRequest {
+                cluster,
+                namespace: namespace.namespace(),
+                failed_only,
+                all_clusters,
+                label_selector,
+                field_selector,
+                limit,
+                continue_token,
+            };
+            let view = TableView { columns, sort_by };
+            exit_code =
+                cmd::pods::execute(req, watch, view, fail_on_issues, output)
+                    .await?;
+        }
+        Command::Check { cluster, namespace, all_clusters } => {
+            exit_code = cmd::check::execute(
+                cluster,
+                namespace.namespace(),
+                all_clusters,
+                output,
+            )
+            .await?;
+        }
+        Command::Describe { kind } => match kind {
+            DescribeKind::Pod { pod, cluster, namespace } => {
+                cmd::describe::pod(pod, cluster, namespace, output).await?
+            }
+        },
+        Command::Get { kind, name, cluster, namespace } => {
+            cmd::get::execute(kind, name, cluster, namespace, output).await?
+        }
+        Command::Configmap { action } => match action {
+            ConfigMapAction::List { cluster, namespace } => {
+                cmd::configmap::list(cluster, namespace.namespace(), output)
+                    .await?
+            }
+            ConfigMapAction::Get { name, cluster, namespace, key } => {
+                cmd::configmap::get(name, cluster, namespace, key, output)
+                    .await?
+            }
+        },
+        Command::Top { target } => match target {
+            TopTarget::Pods { cluster, namespace, sort_by } => {
+                cmd::top::execute(
+                    MetricsTarget::Pods,
+                    cluster,
+                    namespace.namespace(),
+                    sort_by,
+                    output,
+                )
+                .await?
+            }
+            TopTarget::Nodes { cluster, sort_by } => {
+                cmd::top::execute(
+                    MetricsTarget::Nodes,
+                    cluster,
+                    None,
+                    sort_by,
+                    output,
+                )
+                .await?
+            }
+        },
+        Command::Secrets { name, cluster, namespace, reveal, key } => {
+            cmd::secrets::execute(
+                name, cluster, namespace, reveal, key, output,
+            )
+            .await?
+        }
+        Command::Delete { kind } => match kind {
+            DeleteKind::Pod {
+                pod,
+                cluster,
+                namespace,
+                grace_period,
+                force,
+                yes,
+            } => {
+                cmd::delete::pod(
+                    pod,
+                    cluster,
+                    namespace,
+                    grace_period,
+                    force,
+                    yes,
+                    output,
+                )
+                .await?
+            }
+        },
+        Command::Scale { kind } => match kind {
+            ScaleKind::Deployment { name, replicas, cluster, namespace } => {
+                cmd::scale::deployment(
+                    name, cluster, namespace, replicas, output,
+                )
+                .await?
+            }
+        },
+        Command::Rollout { action } => match action {
+            RolloutAction::Restart {
+                kind: RolloutKind::Deployment,
+                name,
+                cluster,
+                namespace,
+                wait,
+                timeout,
+            } => {
+                cmd::rollout::restart(
+                    name, cluster, namespace, wait, timeout, output,
+                )
+                .await?
+            }
+        },
+        Command::Deployments {
+            cluster,
+            namespace,
+            all_clusters,
+            columns,
+            sort_by,
+        } => {
+            let view = TableView { columns, sort_by };
+            cmd::deployments::execute(
+                cluster,
+                namespace.namespace(),
+                all_clusters,
+                view,
+                output,
+            )
+            .await?
         }
-        Command::Env { cluster, namespace, pod, container, filter } => {
-            cmd::env::execute(cluster, namespace, pod, container, filter)
+        Command::Services { cluster, namespace } => {
+            cmd::services::execute(cluster, namespace.namespace(), output)
                 .await?
         }
+        Command::Ingresses { cluster, namespace } => {
+            cmd::ingresses::execute(cluster, namespace.namespace(), output)
+                .await?
+        }
+        Command::Jobs { cluster, namespace, failed_only } => {
+            cmd::jobs::jobs(
+                cluster,
+                namespace.namespace(),
+                failed_only,
+                output,
+            )
+            .await?
+        }
+        Command::Cronjobs { cluster, namespace } => {
+            cmd::jobs::cron_jobs(cluster, namespace.namespace(), output)
+                .await?
+        }
+        Command::Namespaces { cluster } => {
+            cmd::namespaces::execute(cluster, output).await?
+        }
+        Command::Nodes { cluster, columns, sort_by } => {
+            let view = TableView { columns, sort_by };
+            cmd::nodes::execute(cluster, view, output).await?
+        }
+        Command::Statefulsets { cluster, namespace } => {
+            cmd::statefulsets::execute(cluster, namespace.namespace(), output)
+                .await?
+        }
+        Command::Daemonsets { cluster, namespace } => {
+            cmd::daemonsets::execute(cluster, namespace.namespace(), output)
+                .await?
+        }
+        Command::Hpa { cluster, namespace } => {
+            cmd::hpa::execute(cluster, namespace.namespace(), output).await?
+        }
+        Command::Pvc { cluster, namespace } => {
+            cmd::pvc::execute(cluster, namespace.namespace(), output).await?
+        }
+        Command::Restarts { cluster, namespace, since } => {
+            cmd::restarts::execute(
+                cluster,
+                namespace.namespace(),
+                since,
+                output,
+            )
+            .await?
+        }
+        Command::History { pod, cluster, namespace } => {
+            cmd::history::execute(pod, cluster, namespace, output).await?
+        }
+        Command::Images { cluster, namespace } => {
+            cmd::images::execute(cluster, namespace.namespace(), output)
+                .await?
+        }
+        Command::Events {
+            cluster,
+            namespace,
+            involved_object,
+            since,
+            all_clusters,
+        } => {
+            cmd::events::execute(
+                cluster,
+                namespace.namespace(),
+                involved_object,
+                since,
+                all_clusters,
+                output,
+            )
+            .await?
+        }
+        Command::Env {
+            view,
+            cluster,
+            namespace,
+            pod,
+            container,
+            all_containers,
+            filter,
+            resolve,
+            reveal_secrets,
+        } => {
+            let query = cmd::env::EnvQuery {
+                cluster,
+                namespace,
+                pod,
+                container,
+                all_containers,
+                filter,
+                resolve,
+                reveal_secrets,
+            };
+            match view {
+                Some(EnvView::Diff {
+                    left,
+                    right,
+                    left_cluster,
+                    right_cluster,
+                }) => {
+                    let targets = cmd::env::DiffTargets {
+                        left,
+                        right,
+                        left_cluster,
+                        right_cluster,
+                    };
+                    cmd::env::diff(query, targets).await?
+                }
+                Some(EnvView::Grep { pattern }) => {
+                    cmd::env::grep(query, pattern, output).await?
+                }
+                view => {
+                    let tree = matches!(view, Some(EnvView::Tree));
+                    cmd::env::execute(query, tree, output).await?
+                }
+            }
+        }
+    }
+
+    if warn_expiring {
+        helper::warn_expiring_sessions(args.no_color).await;
+    }
+
+    if let Some(code) = exit_code {
+        // exit skips the flush of a normal return
+        let _ = std::io::stdout().flush();
+        std::process::exit(code);
     }
 
     Ok(())
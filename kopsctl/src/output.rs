@@ -0,0 +1,584 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::cmp::Ordering;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, bail};
+use clap::ValueEnum;
+use crossterm::style::Stylize;
+use serde::Serialize;
+
+/// How results are printed (`--output`).
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// Human-readable tables.
+    #[default]
+    Table,
+
+    /// Tables with extra columns, like the node and IP of pods.
+    Wide,
+
+    /// The results as pretty-printed JSON, for jq and scripts.
+    Json,
+
+    /// The results as YAML, for reading nested fields.
+    Yaml,
+}
+
+/// Rendering options shared by every command.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct OutputOptions {
+    /// Deterministic ordering and fixed column set (`--stable-output`).
+    pub stable: bool,
+
+    pub format: OutputFormat,
+
+    /// Color table cells; off for `--no-color`, `NO_COLOR` or when stdout
+    /// is not a terminal.
+    pub color: bool,
+
+    /// Only the names of the listed resources (`--quiet`).
+    pub quiet: bool,
+}
+
+impl OutputOptions {
+    /// Whether results are printed as data rather than as tables.
+    pub fn structured(&self) -> bool {
+        matches!(self.format, OutputFormat::Json | OutputFormat::Yaml)
+    }
+
+    /// Whether tables include their extra columns (`-o wide`).
+    pub fn wide(&self) -> bool {
+        self.format == OutputFormat::Wide
+    }
+}
+
+/// Whether colors are wanted on a stream: not disabled by `--no-color`
+/// or a non-empty `NO_COLOR` (see no-color.org), and the stream is a
+/// `terminal`.
+pub(crate) fn color_enabled(no_color: bool, terminal: bool) -> bool {
+    !no_color
+        && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+        && terminal
+}
+
+/// Print `value` as JSON or YAML, as `--output` asks.
+///
+/// The values are the `kops_protocol` types the daemon replied with, so
+/// fields keep their protocol names.
+pub(crate) fn print_structured<T>(value: &T, opts: OutputOptions) -> Result<()>
+where
+    T: Serialize + ?Sized,
+{
+    match opts.format {
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+        OutputFormat::Json | OutputFormat::Table | OutputFormat::Wide => {
+            println!("{}", serde_json::to_string_pretty(value)?)
+        }
+    }
+
+    Ok(())
+}
+
+/// Print one name per line, for `--quiet`: `<namespace>/<name>` for
+/// namespaced resources, so they can be passed back to kopsctl.
+pub(crate) fn print_names<I>(names: I)
+where
+    I: IntoIterator<Item = String>,
+{
+    for name in names {
+        println!("{name}");
+    }
+}
+
+/// Print one item of a stream as JSON or YAML: a compact JSON object per
+/// line, or one YAML document each, so consumers can process them as they
+/// arrive.
+pub(crate) fn print_structured_item<T>(
+    value: &T,
+    opts: OutputOptions,
+) -> Result<()>
+where
+    T: Serialize + ?Sized,
+{
+    match opts.format {
+        OutputFormat::Yaml => {
+            print!("---\n{}", serde_yaml::to_string(value)?)
+        }
+        OutputFormat::Json | OutputFormat::Table | OutputFormat::Wide => {
+            println!("{}", serde_json::to_string(value)?)
+        }
+    }
+
+    Ok(())
+}
+
+/// Columns and row order of a listing picked on the command line
+/// (`--columns name,restarts --sort-by restarts:desc`).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TableView {
+    /// Headers of the columns to print, in order; every column when
+    /// empty.
+    pub columns: Vec<String>,
+
+    pub sort_by: Option<SortSpec>,
+}
+
+/// A column to sort rows by, as `<column>[:asc|:desc]`.
+#[derive(Debug, Clone)]
+pub(crate) struct SortSpec {
+    pub column: String,
+    pub descending: bool,
+}
+
+impl FromStr for SortSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (column, order) = match s.rsplit_once(':') {
+            Some((column, order)) => (column, Some(order)),
+            None => (s, None),
+        };
+        if column.is_empty() {
+            return Err("missing column name".to_string());
+        }
+
+        let descending = match order {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(other) => {
+                return Err(format!("unknown order {other}, use asc or desc"));
+            }
+        };
+
+        Ok(Self { column: column.to_string(), descending })
+    }
+}
+
+/// A table column.
+#[derive(Clone, Copy)]
+pub(crate) struct Column {
+    /// Header printed on the first line.
+    pub name: &'static str,
+
+    /// Minimum width used for padding in the default mode.
+    pub width: usize,
+
+    /// Part of the stable column set printed with `--stable-output`.
+    ///
+    /// Columns in this set must never be renamed, reordered or removed.
+    pub stable: bool,
+
+    /// Cells are colored with the tone of their row.
+    pub highlight: bool,
+}
+
+impl Column {
+    pub fn new(name: &'static str, width: usize) -> Self {
+        Self { name, width, stable: true, highlight: false }
+    }
+
+    /// Column only shown in the default (human) mode.
+    pub fn unstable(name: &'static str, width: usize) -> Self {
+        Self { name, width, stable: false, highlight: false }
+    }
+
+    /// Color the cells of this column, usually a status, with the tone
+    /// of their row.
+    pub fn highlighted(self) -> Self {
+        Self { highlight: true, ..self }
+    }
+}
+
+/// Health of a row, shown by the color of its highlighted cells.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum Tone {
+    #[default]
+    Plain,
+
+    /// Healthy, in green: a ready pod or node.
+    Good,
+
+    /// Failing, in red.
+    Bad,
+}
+
+struct Row {
+    key: Vec<String>,
+    cells: Vec<String>,
+    tone: Tone,
+}
+
+/// Table renderer used by the list commands.
+///
+/// Rows carry an explicit sort key (usually the one the daemon sorted by).
+/// In the default mode rows are printed in insertion order with padded
+/// columns, narrowed to fit the terminal, and highlighted cells colored.
+/// In stable mode rows are sorted byte-wise by their key, only
+/// stable columns are kept and cells are tab-separated, so the output does
+/// not depend on the locale, the data widths or the kopsctl version.
+pub(crate) struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Row>,
+
+    /// Rows were sorted by `apply_view`, which stable mode keeps.
+    sorted: bool,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self { columns, rows: Vec::new(), sorted: false }
+    }
+
+    /// Sort the rows and keep only the columns `view` asks for, once all
+    /// rows are pushed.
+    ///
+    /// Columns are named by their header, case-insensitively. Ties keep
+    /// the order of the sort keys, so the result stays deterministic for
+    /// `--stable-output`.
+    pub fn apply_view(&mut self, view: &TableView) -> Result<()> {
+        if let Some(sort) = &view.sort_by {
+            let i = self.column_index(&sort.column)?;
+
+            self.rows.sort_by(|a, b| a.key.cmp(&b.key));
+            self.rows.sort_by(|a, b| {
+                let ord = compare_cells(&a.cells[i], &b.cells[i]);
+                if sort.descending { ord.reverse() } else { ord }
+            });
+            self.sorted = true;
+        }
+
+        if view.columns.is_empty() {
+            return Ok(());
+        }
+
+        let keep = view
+            .columns
+            .iter()
+            .map(|name| self.column_index(name))
+            .collect::<Result<Vec<usize>>>()?;
+
+        self.columns = keep.iter().map(|&i| self.columns[i]).collect();
+
+        for row in &mut self.rows {
+            row.cells = keep.iter().map(|&i| row.cells[i].clone()).collect();
+        }
+
+        Ok(())
+    }
+
+    fn column_index(&self, name: &str) -> Result<usize> {
+        let wanted = column_name(name);
+        if let Some(i) =
+            self.columns.iter().position(|c| column_name(c.name) == wanted)
+        {
+            return Ok(i);
+        }
+
+        let known: Vec<String> =
+            self.columns.iter().map(|c| column_name(c.name)).collect();
+        bail!("unknown column {name}, expected one of {}", known.join(", "))
+    }
+
+    /// Add a row; `cells` must follow the column order.
+    pub fn push(&mut self, key: Vec<String>, cells: Vec<String>) {
+        self.push_toned(key, cells, Tone::Plain);
+    }
+
+    /// Add a row whose highlighted cells are colored with `tone`.
+    pub fn push_toned(
+        &mut self,
+        key: Vec<String>,
+        cells: Vec<String>,
+        tone: Tone,
+    ) {
+        debug_assert_eq!(cells.len(), self.columns.len());
+        self.rows.push(Row { key, cells, tone });
+    }
+
+    pub fn print(mut self, opts: OutputOptions) {
+        if opts.stable {
+            self.print_stable();
+        } else {
+            self.print_default(opts);
+        }
+    }
+
+    /// Print only the header, for tables whose rows are printed one at a
+    /// time with `print_row` as they arrive.
+    pub fn print_header(&self, opts: OutputOptions) {
+        let header: Vec<String> =
+            self.columns.iter().map(|c| c.name.to_string()).collect();
+        self.print_row(&header, Tone::Plain, opts);
+    }
+
+    /// Print a single row right away; no sorting is possible here, and
+    /// the columns keep their own width since later rows are unknown.
+    pub fn print_row(
+        &self,
+        cells: &[String],
+        tone: Tone,
+        opts: OutputOptions,
+    ) {
+        if opts.stable {
+            let cells: Vec<&str> = self
+                .columns
+                .iter()
+                .zip(cells)
+                .filter(|(c, _)| c.stable)
+                .map(|(_, cell)| cell.as_str())
+                .collect();
+            println!("{}", cells.join("\t"));
+        } else {
+            let widths: Vec<usize> = self
+                .columns
+                .iter()
+                .zip(cells)
+                .map(|(col, cell)| col.width.max(text_width(cell)))
+                .collect();
+            let line = self.format_line(cells, &widths, tone, opts.color);
+            println!("{line}");
+        }
+    }
+
+    fn print_default(&self, opts: OutputOptions) {
+        let widths = self.fit_widths();
+
+        let header: Vec<String> =
+            self.columns.iter().map(|c| c.name.to_string()).collect();
+        println!("{}", self.format_line(&header, &widths, Tone::Plain, false));
+
+        for row in &self.rows {
+            let line =
+                self.format_line(&row.cells, &widths, row.tone, opts.color);
+            println!("{line}");
+        }
+    }
+
+    /// Widths of the columns: wide enough for every cell, then narrowed,
+    /// widest first and down to their header, to fit the terminal.
+    fn fit_widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                self.rows
+                    .iter()
+                    .map(|row| text_width(&row.cells[i]))
+                    .fold(col.width.max(text_width(col.name)), usize::max)
+            })
+            .collect();
+
+        let Some(limit) = terminal_width() else {
+            return widths;
+        };
+        let gaps = widths.len().saturating_sub(1);
+
+        while widths.iter().sum::<usize>() + gaps > limit {
+            let widest = (0..widths.len())
+                .filter(|&i| widths[i] > text_width(self.columns[i].name))
+                .max_by_key(|&i| widths[i]);
+            let Some(i) = widest else {
+                break;
+            };
+            widths[i] -= 1;
+        }
+
+        widths
+    }
+
+    /// A line of padded cells, truncated to `widths`; the last one is not
+    /// padded.
+    fn format_line(
+        &self,
+        cells: &[String],
+        widths: &[usize],
+        tone: Tone,
+        color: bool,
+    ) -> String {
+        let last = cells.len().saturating_sub(1);
+        let line: Vec<String> = self
+            .columns
+            .iter()
+            .zip(cells)
+            .zip(widths)
+            .enumerate()
+            .map(|(i, ((col, cell), &width))| {
+                let mut text = truncate(cell, width);
+                if i < last {
+                    text = format!("{text:<width$}");
+                }
+
+                match tone {
+                    Tone::Good if color && col.highlight => {
+                        text.green().to_string()
+                    }
+                    Tone::Bad if color && col.highlight => {
+                        text.red().to_string()
+                    }
+                    _ => text,
+                }
+            })
+            .collect();
+
+        line.join(" ").trim_end().to_string()
+    }
+
+    fn print_stable(&mut self) {
+        // `String` ordering is byte-wise, which is what we want here.
+        if !self.sorted {
+            self.rows.sort_by(|a, b| a.key.cmp(&b.key));
+        }
+
+        let keep: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.stable)
+            .map(|(i, _)| i)
+            .collect();
+
+        let header: Vec<&str> =
+            keep.iter().map(|&i| self.columns[i].name).collect();
+        println!("{}", header.join("\t"));
+
+        for row in &self.rows {
+            let cells: Vec<&str> =
+                keep.iter().map(|&i| row.cells[i].as_str()).collect();
+            println!("{}", cells.join("\t"));
+        }
+    }
+}
+
+/// Sort `items` with `cmp` when stable output is requested.
+///
+/// Used by commands that do not render a table; `cmp` should compare the
+/// explicit sort keys exposed by `kops_protocol` types.
+pub(crate) fn stable_sort<T, F>(items: &mut [T], opts: OutputOptions, cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if opts.stable {
+        items.sort_by(cmp);
+    }
+}
+
+/// Age of an object created at `created_at_epoch_ms`, kubectl style
+/// (`45s`, `12m`, `5h`, `3d`).
+pub(crate) fn format_age(created_at_epoch_ms: Option<i64>) -> String {
+    let Some(created) = created_at_epoch_ms else {
+        return "<unknown>".to_string();
+    };
+
+    format_duration((now_epoch_ms() - created) / 1000)
+}
+
+/// Time left until `expires_at_epoch_ms` (`in 45m`), or `expired`.
+pub(crate) fn format_expiry(expires_at_epoch_ms: i64) -> String {
+    let secs = (expires_at_epoch_ms - now_epoch_ms()) / 1000;
+    if secs > 0 {
+        format!("in {}", format_duration(secs))
+    } else {
+        "expired".to_string()
+    }
+}
+
+/// A number of seconds in its largest unit, like `format_age`.
+pub(crate) fn format_duration(secs: i64) -> String {
+    match secs.max(0) {
+        s if s < 60 => format!("{s}s"),
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s if s < 24 * 60 * 60 => format!("{}h", s / (60 * 60)),
+        s => format!("{}d", s / (24 * 60 * 60)),
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Width of `text` in columns, one per character.
+fn text_width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// `text` cut to `width` columns, ending with `…` when cut.
+fn truncate(text: &str, width: usize) -> String {
+    if text_width(text) <= width {
+        return text.to_string();
+    }
+
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Columns of the terminal stdout is, if it is one.
+fn terminal_width() -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+
+    crossterm::terminal::size().ok().map(|(columns, _)| columns as usize)
+}
+
+/// Compare two cells by their value, so `10` sorts after `9` and `2h`
+/// after `45m`; cells without one (`<none>`) come last, byte-wise.
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (cell_value(a), cell_value(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Header as typed on the command line: `last-event` for `LAST EVENT`.
+fn column_name(header: &str) -> String {
+    header.trim().to_ascii_lowercase().replace([' ', '_'], "-")
+}
+
+/// Value of a number cell: a plain number, the first part of a ready
+/// count (`3/4`), an age (`45s`, `12m`, `5h`, `3d`) in seconds or a
+/// quantity in binary units (`512Mi`).
+fn cell_value(cell: &str) -> Option<f64> {
+    let end = cell
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(cell.len());
+    let value: f64 = cell[..end].parse().ok()?;
+
+    let unit = match &cell[end..] {
+        "" | "s" => 1.0,
+        rest if rest.starts_with('/') => 1.0,
+        "m" => 60.0,
+        "h" => 60.0 * 60.0,
+        "d" => 24.0 * 60.0 * 60.0,
+        "Ki" => 1024.0,
+        "Mi" => 1024.0 * 1024.0,
+        "Gi" => 1024.0 * 1024.0 * 1024.0,
+        "Ti" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+
+    Some(value * unit)
+}
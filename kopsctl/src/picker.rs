@@ -0,0 +1,77 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::io::{self, IsTerminal};
+
+use anyhow::{Result, bail};
+use dialoguer::FuzzySelect;
+use kops_protocol::{PodsRequest, Request, Response};
+
+use crate::helper::{resolve_namespace, send_request, split_ref};
+
+/// Namespace and name of the pod `reference` names, as `<name>` or
+/// `<namespace>/<name>`, or else of the pod picked from a fuzzy list.
+///
+/// The list holds the pods of `cluster`, narrowed to `namespace` when one
+/// is given, and is filtered as the user types. Without a terminal to ask
+/// on, a missing reference is an error.
+pub(crate) async fn pod(
+    reference: Option<String>,
+    cluster: Option<String>,
+    namespace: Option<String>,
+) -> Result<(String, String)> {
+    if let Some(reference) = reference {
+        return Ok(split_ref(&reference, namespace));
+    }
+
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        bail!("no pod given and no terminal to pick one");
+    }
+
+    let namespace = resolve_namespace(cluster.clone(), namespace).await?;
+    let req = PodsRequest {
+        cluster,
+        namespace,
+        failed_only: false,
+        all_clusters: false,
+        label_selector: None,
+        field_selector: None,
+        limit: None,
+        continue_token: None,
+    };
+
+    let pods = match send_request(Request::Pods(req)).await? {
+        Response::Pods { pods, .. } => pods,
+        Response::Error(err) => bail!("reponse error {err}"),
+        _ => bail!("unexpected response to pods"),
+    };
+    if pods.is_empty() {
+        bail!("no pods to pick from");
+    }
+
+    let items: Vec<String> = pods
+        .iter()
+        .map(|p| format!("{} / {}  {}", p.namespace, p.name, p.status))
+        .collect();
+
+    let selection = FuzzySelect::new()
+        .with_prompt("Select pod")
+        .items(&items)
+        .interact()?;
+
+    let picked = &pods[selection];
+    Ok((picked.namespace.clone(), picked.name.clone()))
+}
@@ -0,0 +1,94 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Who may talk to the daemon, decided from the credentials of the process
+//! at the other end of the socket (`SO_PEERCRED`).
+//!
+//! The daemon holds AWS credentials, so the socket permissions alone are
+//! not trusted: the daemon's own user, the users listed in
+//! `allowed_users` and the members of `allowed_group` (the daemon group by
+//! default) are let in, everybody else is refused.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result, bail};
+use nix::unistd::{Group, User, geteuid};
+use tokio::net::unix::UCred;
+use tracing::warn;
+
+use crate::config::DaemonConfig;
+
+/// Users and group allowed to use the daemon, resolved at startup.
+#[derive(Debug)]
+pub(crate) struct AccessPolicy {
+    uids: HashSet<u32>,
+    gid: Option<u32>,
+}
+
+impl AccessPolicy {
+    /// Resolve the allowlist of `config`, failing on unknown names so that
+    /// a typo does not lock everybody out silently.
+    pub(crate) fn from_config(config: Option<&DaemonConfig>) -> Result<Self> {
+        // the user the daemon runs as, once privileges are dropped
+        let mut uids = HashSet::from([geteuid().as_raw()]);
+        let mut gid = None;
+
+        let Some(config) = config else {
+            return Ok(Self { uids, gid });
+        };
+
+        for name in &config.allowed_users {
+            let user = User::from_name(name)
+                .with_context(|| format!("failed to look up user {name}"))?;
+            let Some(user) = user else {
+                bail!("unknown user {name} in allowed_users");
+            };
+            uids.insert(user.uid.as_raw());
+        }
+
+        if let Some(name) =
+            config.allowed_group.as_ref().or(config.group.as_ref())
+        {
+            let group = Group::from_name(name)
+                .with_context(|| format!("failed to look up group {name}"))?;
+            let Some(group) = group else {
+                bail!("unknown group {name} in the daemon config");
+            };
+            gid = Some(group.gid.as_raw());
+
+            // members having the group as a supplementary one only
+            for member in &group.mem {
+                match User::from_name(member) {
+                    Ok(Some(user)) => {
+                        uids.insert(user.uid.as_raw());
+                    }
+                    _ => warn!(
+                        member,
+                        group = name,
+                        "unknown member of the allowed group"
+                    ),
+                }
+            }
+        }
+
+        Ok(Self { uids, gid })
+    }
+
+    /// Whether the peer with credentials `cred` may use the daemon.
+    pub(crate) fn allows(&self, cred: &UCred) -> bool {
+        self.uids.contains(&cred.uid()) || self.gid == Some(cred.gid())
+    }
+}
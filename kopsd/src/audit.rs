@@ -0,0 +1,439 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Audit trail of the requests served by the daemon.
+//!
+//! Every request is appended as one JSON object per line to the file set
+//! by `audit_log` in the `[daemon]` section: its type, the uid of the
+//! client, the cluster and namespace it targets, the AWS profile (and the
+//! account and role, when known) of session requests, how it ended and how
+//! long it took. The file is rotated to `<file>.1`, `<file>.2`... once it
+//! grows past `audit_max_size_mb`, keeping `audit_keep` of them.
+
+use std::{
+    ffi::OsString,
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::error;
+
+use kops_protocol::{ErrorCode, KopsError, Request, Response};
+
+use crate::config::DaemonConfig;
+
+/// Size of the audit log rotated when `audit_max_size_mb` is unset.
+const DEFAULT_MAX_SIZE_MB: u64 = 64;
+
+/// Rotated audit logs kept when `audit_keep` is unset.
+const DEFAULT_KEEP: usize = 5;
+
+/// How a request ended.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Ok,
+    Error,
+
+    /// The client went away or cancelled the request before its end.
+    Cancelled,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Serialize)]
+struct Record {
+    time: DateTime<Utc>,
+    request: &'static str,
+    uid: u32,
+
+    /// As sent by the client, `None` standing for the default cluster.
+    cluster: Option<String>,
+    namespace: Option<String>,
+
+    /// Profile of logins, logouts and credential exports.
+    profile: Option<String>,
+    account_id: Option<String>,
+    role_name: Option<String>,
+    result: Outcome,
+    code: Option<ErrorCode>,
+    error: Option<String>,
+    duration_ms: u64,
+}
+
+/// The audit log of the daemon, written by a thread of its own so that
+/// requests never wait on the disk.
+pub(crate) struct AuditLog {
+    tx: Option<mpsc::UnboundedSender<Record>>,
+}
+
+impl AuditLog {
+    /// Open the audit log set in `config`; nothing is recorded when there
+    /// is none.
+    pub(crate) fn open(config: Option<&DaemonConfig>) -> Result<Self> {
+        let enabled = config.and_then(|c| Some((c, c.audit_log.as_ref()?)));
+        let Some((config, path)) = enabled else {
+            return Ok(Self { tx: None });
+        };
+
+        let max_size = config.audit_max_size_mb.unwrap_or(DEFAULT_MAX_SIZE_MB)
+            * 1024
+            * 1024;
+        let keep = config.audit_keep.unwrap_or(DEFAULT_KEEP);
+        let mut file = RotatingFile::open(path.into(), max_size, keep)
+            .with_context(|| format!("failed to open audit log {path}"))?;
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<Record>();
+        std::thread::spawn(move || {
+            while let Some(record) = rx.blocking_recv() {
+                if let Err(e) = file.append(&record) {
+                    error!("failed to write audit log: {e}");
+                }
+            }
+        });
+
+        Ok(Self { tx: Some(tx) })
+    }
+
+    /// Audit of the requests of the client with `uid`.
+    pub(crate) fn client(&self, uid: u32) -> Audit {
+        Audit { tx: self.tx.clone(), uid }
+    }
+}
+
+/// Audit of the requests of one client connection.
+#[derive(Clone)]
+pub(crate) struct Audit {
+    tx: Option<mpsc::UnboundedSender<Record>>,
+    uid: u32,
+}
+
+impl Audit {
+    /// Start auditing `req`, recorded once the returned entry is finished.
+    pub(crate) fn begin(&self, req: &Request) -> Entry {
+        let (request, cluster, namespace) = describe(req);
+        let account = match req {
+            Request::Login(r) => Some(Account {
+                profile: r.name.clone(),
+                account_id: Some(r.account_id.clone()),
+                role_name: Some(r.role_name.clone()),
+            }),
+            Request::Logout { profile }
+            | Request::SessionCredentials { profile } => {
+                Some(Account::of_profile(profile))
+            }
+            _ => None,
+        };
+
+        Entry {
+            tx: self.tx.clone(),
+            time: Utc::now(),
+            started: Instant::now(),
+            request,
+            uid: self.uid,
+            cluster: cluster.map(str::to_string),
+            namespace: namespace.map(str::to_string),
+            account,
+        }
+    }
+}
+
+/// AWS profile a session request acts on.
+struct Account {
+    profile: String,
+    account_id: Option<String>,
+    role_name: Option<String>,
+}
+
+impl Account {
+    fn of_profile(profile: &str) -> Self {
+        Self {
+            profile: profile.to_string(),
+            account_id: None,
+            role_name: None,
+        }
+    }
+}
+
+/// A request being served, recorded by one of its finishing methods, or as
+/// cancelled when dropped before.
+pub(crate) struct Entry {
+    tx: Option<mpsc::UnboundedSender<Record>>,
+    time: DateTime<Utc>,
+    started: Instant,
+    request: &'static str,
+    uid: u32,
+    cluster: Option<String>,
+    namespace: Option<String>,
+    account: Option<Account>,
+}
+
+impl Entry {
+    pub(crate) fn ok(mut self) {
+        self.finish(Outcome::Ok, None, None);
+    }
+
+    /// The request failed inside the daemon, without a `KopsError`.
+    pub(crate) fn failed(mut self, error: impl fmt::Display) {
+        self.finish(Outcome::Error, None, Some(error.to_string()));
+    }
+
+    pub(crate) fn error(mut self, error: &KopsError) {
+        let message = Some(error.message.clone());
+        self.finish(Outcome::Error, Some(error.code), message);
+    }
+
+    /// Record the end of a request replied with `resp`.
+    pub(crate) fn response(mut self, resp: &Response) {
+        match resp {
+            Response::Error(error) => self.error(error),
+            Response::SessionCredentials(creds) => {
+                // which account the exported credentials belong to
+                if let Some(account) = self.account.as_mut() {
+                    account.account_id = Some(creds.account_id.clone());
+                    account.role_name = Some(creds.role_name.clone());
+                }
+                self.ok()
+            }
+            _ => self.ok(),
+        }
+    }
+
+    /// Record nothing, for requests audited elsewhere.
+    pub(crate) fn discard(mut self) {
+        self.tx = None;
+    }
+
+    fn finish(
+        &mut self,
+        result: Outcome,
+        code: Option<ErrorCode>,
+        error: Option<String>,
+    ) {
+        let Some(tx) = self.tx.take() else {
+            return;
+        };
+
+        let account = self.account.take();
+        let (profile, account_id, role_name) = match account {
+            Some(a) => (Some(a.profile), a.account_id, a.role_name),
+            None => (None, None, None),
+        };
+        let record = Record {
+            time: self.time,
+            request: self.request,
+            uid: self.uid,
+            cluster: self.cluster.take(),
+            namespace: self.namespace.take(),
+            profile,
+            account_id,
+            role_name,
+            result,
+            code,
+            error,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+        };
+        // only fails once the writer thread is gone
+        let _ = tx.send(record);
+    }
+}
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        self.finish(Outcome::Cancelled, None, None);
+    }
+}
+
+/// Append-only file rotated past `max_size` bytes.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_size: u64, keep: usize) -> io::Result<Self> {
+        let file = open_append(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, file, size, max_size, keep })
+    }
+
+    fn append(&mut self, record: &Record) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        let len = line.len() as u64;
+        if self.size > 0 && self.size + len > self.max_size {
+            self.rotate()?;
+        }
+
+        self.file.write_all(&line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    /// Shift `<file>.N` to `<file>.N+1`, dropping the oldest, and start a
+    /// new file.
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            for n in (1..self.keep).rev() {
+                let from = rotated(&self.path, n);
+                if from.exists() {
+                    std::fs::rename(from, rotated(&self.path, n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, rotated(&self.path, 1))?;
+        }
+
+        self.file = open_append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Requests carry credentials-backed activity, keep the log private.
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).mode(0o600).open(path)
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = OsString::from(path);
+    name.push(format!(".{n}"));
+    name.into()
+}
+
+/// Type of `req` as audited, safe to log.
+pub(crate) fn kind(req: &Request) -> &'static str {
+    describe(req).0
+}
+
+/// Type, cluster and namespace of `req`.
+fn describe(req: &Request) -> (&'static str, Option<&str>, Option<&str>) {
+    let (request, (cluster, namespace)) = match req {
+        Request::Ping => ("ping", (None, None)),
+        Request::Login(_) => ("login", (None, None)),
+        Request::Sessions => ("sessions", (None, None)),
+        Request::Logout { .. } => ("logout", (None, None)),
+        Request::SessionCredentials { .. } => {
+            ("session_credentials", (None, None))
+        }
+        Request::SessionHealth => ("session_health", (None, None)),
+        Request::ClusterAdd(r) => ("cluster_add", (Some(&*r.name), None)),
+        Request::ClusterRemove { name } => {
+            ("cluster_remove", (Some(&**name), None))
+        }
+        Request::ClusterAccess { cluster } => {
+            ("cluster_access", (cluster.as_deref(), None))
+        }
+        Request::ClusterToken { cluster } => {
+            ("cluster_token", (cluster.as_deref(), None))
+        }
+        Request::Clusters => ("clusters", (None, None)),
+        Request::Reload => ("reload", (None, None)),
+        Request::Shutdown => ("shutdown", (None, None)),
+        Request::Pods(r) => ("pods", scoped(&r.cluster, &r.namespace)),
+        Request::WatchPods(r) => {
+            ("watch_pods", scoped(&r.cluster, &r.namespace))
+        }
+        Request::Subscribe { .. } => ("subscribe", (None, None)),
+        Request::DescribePod(r) => {
+            ("describe_pod", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::Get(r) => ("get", scoped(&r.cluster, &r.namespace)),
+        Request::Deployments(r) => {
+            ("deployments", scoped(&r.cluster, &r.namespace))
+        }
+        Request::StatefulSets(r) => {
+            ("statefulsets", scoped(&r.cluster, &r.namespace))
+        }
+        Request::DaemonSets(r) => {
+            ("daemonsets", scoped(&r.cluster, &r.namespace))
+        }
+        Request::Events(r) => ("events", scoped(&r.cluster, &r.namespace)),
+        Request::Restarts(r) => ("restarts", scoped(&r.cluster, &r.namespace)),
+        Request::RestartHistory(r) => {
+            ("restart_history", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::Nodes(r) => ("nodes", (r.cluster.as_deref(), None)),
+        Request::Services(r) => ("services", scoped(&r.cluster, &r.namespace)),
+        Request::Ingresses(r) => {
+            ("ingresses", scoped(&r.cluster, &r.namespace))
+        }
+        Request::Jobs(r) => ("jobs", scoped(&r.cluster, &r.namespace)),
+        Request::Hpas(r) => ("hpas", scoped(&r.cluster, &r.namespace)),
+        Request::Pvcs(r) => ("pvcs", scoped(&r.cluster, &r.namespace)),
+        Request::Images(r) => ("images", scoped(&r.cluster, &r.namespace)),
+        Request::CronJobs(r) => ("cronjobs", scoped(&r.cluster, &r.namespace)),
+        Request::Namespaces(r) => ("namespaces", (r.cluster.as_deref(), None)),
+        Request::RolloutRestart(r) => {
+            ("rollout_restart", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::DeletePod(r) => {
+            ("delete_pod", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::Scale(r) => {
+            ("scale", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::ConfigMaps(r) => {
+            ("configmaps", scoped(&r.cluster, &r.namespace))
+        }
+        Request::ConfigMapData(r) => {
+            ("configmap_data", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::Secrets(r) => ("secrets", scoped(&r.cluster, &r.namespace)),
+        Request::SecretValue(r) => {
+            ("secret_value", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::Metrics(r) => ("metrics", scoped(&r.cluster, &r.namespace)),
+        Request::Env(r) => {
+            ("env", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::EnvGrep(r) => ("env_grep", scoped(&r.cluster, &r.namespace)),
+        Request::Logs(r) => {
+            ("logs", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::Exec(r) => {
+            ("exec", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::PortForward(r) => {
+            ("port_forward", (r.cluster.as_deref(), Some(&*r.namespace)))
+        }
+        Request::Stream { request, .. } => return describe(request),
+        Request::Cancel { .. } => ("cancel", (None, None)),
+        Request::Heartbeat => ("heartbeat", (None, None)),
+        Request::Version => ("version", (None, None)),
+        Request::Status => ("status", (None, None)),
+    };
+    (request, cluster, namespace)
+}
+
+fn scoped<'a>(
+    cluster: &'a Option<String>,
+    namespace: &'a Option<String>,
+) -> (Option<&'a str>, Option<&'a str>) {
+    (cluster.as_deref(), namespace.as_deref())
+}
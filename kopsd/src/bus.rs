@@ -0,0 +1,54 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Daemon-wide notifications, pushed to `Request::Subscribe` clients.
+
+use kops_protocol::Notification;
+use tokio::sync::broadcast;
+
+/// Notifications buffered per subscriber before it is considered lagging.
+const CAPACITY: usize = 1024;
+
+/// Fan-out of notifications from reflectors and handlers to subscribers.
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<Notification>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(CAPACITY);
+        Self { tx }
+    }
+}
+
+impl EventBus {
+    /// Whether anyone listens, so publishers can skip building
+    /// notifications nobody reads.
+    pub fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    pub fn publish(&self, notification: Notification) {
+        // No subscriber is not an error.
+        let _ = self.tx.send(notification);
+    }
+
+    /// Receive notifications from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Notification> {
+        self.tx.subscribe()
+    }
+}
@@ -0,0 +1,265 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Pods decoded with only what listings, selectors and the pod watchers
+//! read, for clusters with `compact_pods`.
+//!
+//! A watch can only leave out the whole spec and status of the objects
+//! (`metadata_watcher`), and listings need both. The pods are decoded
+//! through a narrower shape instead: the other fields are skipped by the
+//! parser and never allocated. Requests needing them use
+//! `ClusterState::full_pod`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use k8s_openapi::api::core::v1::{
+    Container, ContainerPort, PersistentVolumeClaimVolumeSource, Pod, PodSpec,
+    PodStatus, Volume,
+};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{
+    ObjectMeta, OwnerReference, Time,
+};
+use k8s_openapi::{Metadata, Resource};
+use kops_protocol::Ownership;
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::handler::DEFAULT_CONTAINER_ANNOTATION;
+
+/// A Pod decoded as `PodShape`, watched in place of `Pod`.
+#[derive(Clone, Debug)]
+pub struct CompactPod(Pod);
+
+impl CompactPod {
+    pub fn into_pod(self) -> Pod {
+        self.0
+    }
+}
+
+impl Resource for CompactPod {
+    const API_VERSION: &'static str = Pod::API_VERSION;
+    const GROUP: &'static str = Pod::GROUP;
+    const KIND: &'static str = Pod::KIND;
+    const VERSION: &'static str = Pod::VERSION;
+    const URL_PATH_SEGMENT: &'static str = Pod::URL_PATH_SEGMENT;
+    type Scope = <Pod as Resource>::Scope;
+}
+
+impl Metadata for CompactPod {
+    type Ty = ObjectMeta;
+
+    fn metadata(&self) -> &ObjectMeta {
+        &self.0.metadata
+    }
+
+    fn metadata_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.0.metadata
+    }
+}
+
+impl<'de> Deserialize<'de> for CompactPod {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        PodShape::deserialize(deserializer).map(|shape| Self(shape.into()))
+    }
+}
+
+/// What is kept of a pod: of the annotations only those the daemon reads,
+/// no managed fields, and of the spec only the scheduling fields, the
+/// names, images and ports of the containers and the claimed volumes.
+#[derive(Deserialize)]
+struct PodShape {
+    metadata: MetaShape,
+    spec: Option<SpecShape>,
+    status: Option<PodStatus>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MetaShape {
+    name: Option<String>,
+    namespace: Option<String>,
+    uid: Option<String>,
+    resource_version: Option<String>,
+    generate_name: Option<String>,
+    labels: Option<BTreeMap<String, String>>,
+    annotations: Option<KeptAnnotations>,
+    owner_references: Option<Vec<OwnerReference>>,
+    creation_timestamp: Option<Time>,
+    deletion_timestamp: Option<Time>,
+}
+
+/// The default container and ownership annotations; the others, e.g. a
+/// `last-applied-configuration`, are skipped.
+struct KeptAnnotations(BTreeMap<String, String>);
+
+impl<'de> Deserialize<'de> for KeptAnnotations {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Kept;
+
+        impl<'de> Visitor<'de> for Kept {
+            type Value = KeptAnnotations;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of annotations")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut kept = BTreeMap::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == DEFAULT_CONTAINER_ANNOTATION
+                        || Ownership::reads(&key)
+                    {
+                        kept.insert(key, map.next_value()?);
+                    } else {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+                Ok(KeptAnnotations(kept))
+            }
+        }
+
+        deserializer.deserialize_map(Kept)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpecShape {
+    node_name: Option<String>,
+    restart_policy: Option<String>,
+    scheduler_name: Option<String>,
+    service_account_name: Option<String>,
+    #[serde(default)]
+    containers: Vec<ContainerShape>,
+    init_containers: Option<Vec<ContainerShape>>,
+    volumes: Option<Vec<VolumeShape>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContainerShape {
+    name: String,
+    image: Option<String>,
+    ports: Option<Vec<ContainerPort>>,
+    restart_policy: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VolumeShape {
+    name: String,
+    persistent_volume_claim: Option<PersistentVolumeClaimVolumeSource>,
+}
+
+impl From<PodShape> for Pod {
+    fn from(shape: PodShape) -> Self {
+        let meta = shape.metadata;
+        let metadata = ObjectMeta {
+            name: meta.name,
+            namespace: meta.namespace,
+            uid: meta.uid,
+            resource_version: meta.resource_version,
+            generate_name: meta.generate_name,
+            labels: meta.labels,
+            annotations: meta
+                .annotations
+                .map(|kept| kept.0)
+                .filter(|kept| !kept.is_empty()),
+            owner_references: meta.owner_references,
+            creation_timestamp: meta.creation_timestamp,
+            deletion_timestamp: meta.deletion_timestamp,
+            ..ObjectMeta::default()
+        };
+
+        let container = |c: ContainerShape| Container {
+            name: c.name,
+            image: c.image,
+            ports: c.ports,
+            restart_policy: c.restart_policy,
+            ..Container::default()
+        };
+
+        let spec = shape.spec.map(|spec| PodSpec {
+            node_name: spec.node_name,
+            restart_policy: spec.restart_policy,
+            scheduler_name: spec.scheduler_name,
+            service_account_name: spec.service_account_name,
+            containers: spec.containers.into_iter().map(container).collect(),
+            init_containers: spec
+                .init_containers
+                .map(|cs| cs.into_iter().map(container).collect()),
+            volumes: spec.volumes.map(|vs| {
+                vs.into_iter()
+                    .filter_map(|v| {
+                        Some(Volume {
+                            name: v.name,
+                            persistent_volume_claim: Some(
+                                v.persistent_volume_claim?,
+                            ),
+                            ..Volume::default()
+                        })
+                    })
+                    .collect()
+            }),
+            ..PodSpec::default()
+        });
+
+        Pod { metadata, spec, status: shape.status }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_pod_keeps_the_annotations_read() {
+        let pod = serde_json::json!({
+            "metadata": {
+                "name": "api-0",
+                "annotations": {
+                    "kops.paastel.io/team": "payments",
+                    "a8r.io/runbook": "https://runbooks/api",
+                    "kubectl.kubernetes.io/default-container": "api",
+                    "kubectl.kubernetes.io/last-applied-configuration": "{}",
+                },
+            },
+        });
+
+        let pod =
+            serde_json::from_value::<CompactPod>(pod).unwrap().into_pod();
+        let annotations = pod.metadata.annotations.unwrap();
+        assert_eq!(annotations.len(), 3);
+        assert_eq!(annotations[DEFAULT_CONTAINER_ANNOTATION], "api");
+
+        let ownership = Ownership::from_annotations(&annotations);
+        assert_eq!(ownership.team.as_deref(), Some("payments"));
+        assert_eq!(
+            ownership.runbook_url.as_deref(),
+            Some("https://runbooks/api")
+        );
+    }
+}
@@ -15,23 +15,155 @@
 //
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::Result;
+use kops_notify::NotifyConfig;
+use kops_protocol::wire::HEARTBEAT_TIMEOUT;
 use serde::Deserialize;
 use tracing::debug;
 
 #[derive(Debug, Deserialize, Default, Clone)]
 pub struct KopsSection {
     pub default_cluster: Option<String>,
+
+    /// Seconds without a message after which a client of a multiplexed
+    /// connection or an exec session is dropped. Clients send a heartbeat
+    /// every 10 seconds, so keep it well above that.
+    pub heartbeat_timeout_secs: Option<u64>,
+
+    /// Socket to listen on, unless KOPS_SOCKET or `--socket` says
+    /// otherwise.
+    pub socket: Option<PathBuf>,
+
+    /// Keep AWS sessions on disk across restarts (default true); when
+    /// false, those kept by an earlier run are removed.
+    pub persist_sessions: Option<bool>,
+
+    /// Directory of the persisted sessions (default:
+    /// `$XDG_STATE_HOME/kopsd`).
+    pub state_dir: Option<PathBuf>,
+
+    /// Persist sessions in the OS keyring when one is reachable, before
+    /// the encrypted file in `state_dir` (default true).
+    pub keyring: Option<bool>,
+
+    /// HTTP proxy of the AWS API calls and of the clusters without one of
+    /// their own; HTTPS_PROXY and friends apply to AWS calls otherwise.
+    pub proxy_url: Option<String>,
+}
+
+impl KopsSection {
+    pub(crate) fn heartbeat_timeout(&self) -> Duration {
+        self.heartbeat_timeout_secs
+            .map_or(HEARTBEAT_TIMEOUT, Duration::from_secs)
+    }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
 pub struct ClusterConfig {
     pub name: String,
     pub kubeconfig: Option<PathBuf>,
     pub context: Option<String>,
     pub namespaces: Option<Vec<String>>,
+
+    /// Keep only what listings need of each pod in memory; describe and
+    /// env fetch the whole pod from the API server instead.
+    pub compact_pods: Option<bool>,
+
+    /// AWS profile whose session reaches the cluster, as an EKS cluster;
+    /// it starts once the profile logs in.
+    pub profile: Option<String>,
+
+    /// How the cluster is reached (default: `eks` with a `profile`,
+    /// `kubeconfig` otherwise).
+    pub auth: Option<ClusterAuth>,
+
+    /// Name of the EKS cluster, when it differs from `name`.
+    pub eks_name: Option<String>,
+
+    /// Region of the EKS cluster; the region of the session by default.
+    pub region: Option<String>,
+
+    /// Namespace of the requests not naming one (default: `default`, or
+    /// the one of the kubeconfig context).
+    pub default_namespace: Option<String>,
+
+    /// Timeouts of the API server connections (default: 30s to connect,
+    /// 295s to read, or those of the kubeconfig).
+    pub connect_timeout_secs: Option<u64>,
+    pub read_timeout_secs: Option<u64>,
+
+    /// HTTP proxy the API server is reached through.
+    pub proxy_url: Option<String>,
+
+    /// Name the certificate of the API server is checked against, instead
+    /// of the host of its URL, e.g. behind a re-encrypting proxy.
+    pub tls_server_name: Option<String>,
+
+    /// API server URL, instead of the one of EKS or of the kubeconfig,
+    /// e.g. a private endpoint reached over a VPN.
+    pub endpoint: Option<String>,
+
+    /// PEM bundles of CAs trusted besides the one of the cluster.
+    pub ca_bundles: Option<Vec<PathBuf>>,
+
+    /// FIPS and dual-stack endpoints for the AWS calls of an EKS cluster,
+    /// including the STS endpoint its tokens are signed for (default
+    /// false, or AWS_USE_FIPS_ENDPOINT and AWS_USE_DUALSTACK_ENDPOINT).
+    pub use_fips: Option<bool>,
+    pub use_dual_stack: Option<bool>,
+}
+
+/// AWS profile whose EKS clusters are discovered in several regions, and
+/// started like configured clusters once it logs in.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ProfileConfig {
+    pub name: String,
+
+    /// Regions listed concurrently for EKS clusters.
+    pub regions: Vec<String>,
+
+    /// Watched namespaces and pod compaction of the discovered clusters,
+    /// as for a configured cluster.
+    pub namespaces: Option<Vec<String>>,
+    pub compact_pods: Option<bool>,
 }
+
+/// How kopsd authenticates to a cluster.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClusterAuth {
+    /// EKS tokens signed with the session of `profile`.
+    Eks,
+
+    /// Credentials of `kubeconfig` and `context`, or of the default
+    /// discovery (KUBECONFIG, in-cluster) without a `kubeconfig`.
+    Kubeconfig,
+}
+
+impl ClusterConfig {
+    /// Name of the EKS cluster behind this cluster.
+    pub fn eks_name(&self) -> &str {
+        self.eks_name.as_deref().unwrap_or(&self.name)
+    }
+
+    pub fn auth(&self) -> ClusterAuth {
+        self.auth.unwrap_or(match self.profile {
+            Some(_) => ClusterAuth::Eks,
+            None => ClusterAuth::Kubeconfig,
+        })
+    }
+
+    /// Profile whose session the cluster starts with, for EKS clusters.
+    pub fn eks_profile(&self) -> Option<&str> {
+        match self.auth() {
+            ClusterAuth::Eks => self.profile.as_deref(),
+            ClusterAuth::Kubeconfig => None,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Default, Clone)]
 pub struct DaemonConfig {
     pub pid_file: Option<String>,
@@ -39,13 +171,84 @@ pub struct DaemonConfig {
     pub stderr: Option<String>,
     pub user: Option<String>,
     pub group: Option<String>,
+
+    /// Users allowed to use the daemon besides the one it runs as.
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+
+    /// Group whose members may use the daemon; `group` by default.
+    pub allowed_group: Option<String>,
+
+    /// JSONL file every request is recorded to; no audit when unset.
+    pub audit_log: Option<String>,
+
+    /// Size in MiB past which the audit log is rotated (default 64).
+    pub audit_max_size_mb: Option<u64>,
+
+    /// Rotated audit logs kept (default 5).
+    pub audit_keep: Option<usize>,
+
+    /// Address Prometheus metrics are served on, e.g. `127.0.0.1:9464`;
+    /// none when unset.
+    pub metrics_listen: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default, Clone)]
 pub struct KopsdConfig {
     pub kops: KopsSection,
     pub daemon: Option<DaemonConfig>,
+
+    /// Clusters started at startup; none when they are all discovered or
+    /// added at runtime.
+    #[serde(default)]
     pub cluster: Vec<ClusterConfig>,
+
+    /// Profiles whose EKS clusters are discovered.
+    #[serde(default)]
+    pub profile: Vec<ProfileConfig>,
+
+    /// Alerts about failing pods; none are sent when unset.
+    pub notify: Option<NotifyConfig>,
+
+    /// Failover pair this daemon is part of; none when unset.
+    pub replication: Option<ReplicationConfig>,
+}
+
+/// Role of a daemon in a failover pair, see `replication`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplicationRole {
+    /// Serves clients and sends its state to the secondary.
+    Primary,
+
+    /// Follows the primary, and serves reads while it is down.
+    Secondary,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReplicationConfig {
+    pub role: ReplicationRole,
+
+    /// Address the primary accepts the secondary on, e.g. `0.0.0.0:7443`.
+    pub listen: Option<String>,
+
+    /// Address of the primary, for the secondary.
+    pub primary: Option<String>,
+
+    /// Name the certificate of the primary is checked against; the host
+    /// of `primary` by default.
+    pub server_name: Option<String>,
+
+    /// PEM certificate chain and key of this daemon, and the CA both
+    /// daemons of the pair are checked against (mutual TLS).
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    pub ca: PathBuf,
+
+    /// Also replicate the AWS sessions, without their SSO refresh
+    /// tokens (default false).
+    #[serde(default)]
+    pub sessions: bool,
 }
 
 pub(crate) fn load() -> Result<KopsdConfig> {
@@ -60,3 +263,29 @@ pub(crate) fn load() -> Result<KopsdConfig> {
 
     Ok(cfg.try_deserialize()?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_may_list_only_profiles() {
+        let toml = r#"
+            [kops]
+            keyring = false
+
+            [[profile]]
+            name = "dev"
+            regions = ["eu-west-1", "us-east-1"]
+        "#;
+
+        let cfg: KopsdConfig = config::Config::builder()
+            .add_source(config::File::from_str(toml, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert!(cfg.cluster.is_empty());
+        assert_eq!(cfg.profile[0].regions.len(), 2);
+    }
+}
@@ -0,0 +1,182 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Discovery of the EKS clusters of a profile across its regions.
+//!
+//! A `[[profile]]` of the config file lists regions; once the profile
+//! logs in, each of them is listed concurrently and the clusters found
+//! start like configured ones, tagged with their region. Clusters of the
+//! config file are not discovered twice.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::future::join_all;
+use kops_aws_eks::list_eks_clusters;
+use tracing::warn;
+
+use crate::config::{ClusterAuth, ClusterConfig, ProfileConfig};
+use crate::kube_worker::eks_sdk_config;
+use crate::state::{AwsSession, ClusterState, EksCluster};
+
+/// EKS clusters of `profile` in its regions, reached with `session`,
+/// leaving out those of `configured`.
+///
+/// A cluster is named after its EKS cluster unless a cluster of
+/// `configured` or `running`, or one of an earlier region, has that name
+/// already, see `cluster_name`. Running clusters of `profile` keep their
+/// name. Regions that cannot be listed are skipped.
+pub(crate) async fn discover(
+    profile: &ProfileConfig,
+    session: &AwsSession,
+    configured: &[ClusterConfig],
+    running: &[Arc<ClusterState>],
+) -> Vec<ClusterConfig> {
+    let lists = profile.regions.iter().map(|region| async move {
+        let eks = EksCluster {
+            name: String::new(),
+            region: Some(region.clone()),
+            use_fips: false,
+            use_dual_stack: false,
+        };
+        let res = match eks_sdk_config(&eks, session).await {
+            Ok(sdk_config) => list_eks_clusters(&sdk_config).await,
+            Err(err) => Err(err),
+        };
+        (region, res)
+    });
+
+    // name -> profile of the cluster holding it
+    let mut taken: HashMap<String, Option<String>> = configured
+        .iter()
+        .map(|c| (c.name.clone(), c.profile.clone()))
+        .collect();
+    for cluster in running {
+        if cluster.profile() != Some(profile.name.as_str()) {
+            let holder = cluster.profile().map(str::to_string);
+            taken.insert(cluster.name().to_string(), holder);
+        }
+    }
+    let mut found = Vec::new();
+
+    for (region, res) in join_all(lists).await {
+        let names = match res {
+            Ok(names) => names,
+            Err(err) => {
+                warn!(
+                    "failed to list EKS clusters of profile '{}' in {region}: \
+                     {err:#}",
+                    profile.name
+                );
+                continue;
+            }
+        };
+
+        for eks_name in names {
+            if is_configured(configured, profile, session, region, &eks_name) {
+                continue;
+            }
+
+            let name = cluster_name(&taken, &profile.name, region, &eks_name);
+            taken.insert(name.clone(), Some(profile.name.clone()));
+
+            found.push(ClusterConfig {
+                name,
+                namespaces: profile.namespaces.clone(),
+                compact_pods: profile.compact_pods,
+                profile: Some(profile.name.clone()),
+                auth: Some(ClusterAuth::Eks),
+                eks_name: Some(eks_name),
+                region: Some(region.clone()),
+                ..Default::default()
+            });
+        }
+    }
+
+    found
+}
+
+/// Name of EKS cluster `eks_name` of `region` found for `profile`, free
+/// in `taken`: `<name>`, else `<name>@<region>` when `profile` holds it,
+/// else `<name>@<profile>`, else `<name>@<profile>/<region>`.
+///
+/// A cluster of another profile is never replaced: clusters of the same
+/// name are common across accounts.
+fn cluster_name(
+    taken: &HashMap<String, Option<String>>,
+    profile: &str,
+    region: &str,
+    eks_name: &str,
+) -> String {
+    let Some(holder) = taken.get(eks_name) else {
+        return eks_name.to_string();
+    };
+
+    let name = if holder.as_deref() == Some(profile) {
+        format!("{eks_name}@{region}")
+    } else {
+        format!("{eks_name}@{profile}")
+    };
+    if taken.contains_key(&name) {
+        format!("{eks_name}@{profile}/{region}")
+    } else {
+        name
+    }
+}
+
+/// EKS cluster `eks_name` of `region` is a configured cluster of
+/// `profile`.
+fn is_configured(
+    configured: &[ClusterConfig],
+    profile: &ProfileConfig,
+    session: &AwsSession,
+    region: &str,
+    eks_name: &str,
+) -> bool {
+    configured.iter().any(|c| {
+        c.eks_profile() == Some(profile.name.as_str())
+            && c.eks_name() == eks_name
+            && c.region.as_deref().or(session.region.as_deref())
+                == Some(region)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_of_other_profiles_are_never_reused() {
+        let mut taken = HashMap::new();
+        taken.insert("web".to_string(), Some("prod".to_string()));
+        taken.insert("api".to_string(), Some("dev".to_string()));
+        taken.insert("db".to_string(), None);
+
+        assert_eq!(cluster_name(&taken, "dev", "us-east-1", "jobs"), "jobs");
+        assert_eq!(cluster_name(&taken, "dev", "us-east-1", "web"), "web@dev");
+        assert_eq!(
+            cluster_name(&taken, "dev", "us-east-1", "api"),
+            "api@us-east-1"
+        );
+        assert_eq!(cluster_name(&taken, "dev", "us-east-1", "db"), "db@dev");
+
+        taken.insert("web@dev".to_string(), Some("dev".to_string()));
+        assert_eq!(
+            cluster_name(&taken, "dev", "us-east-1", "web"),
+            "web@dev/us-east-1"
+        );
+    }
+}
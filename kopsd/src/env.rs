@@ -0,0 +1,220 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Resolution of `valueFrom` and `envFrom` references of a container.
+//!
+//! Referenced ConfigMaps and Secrets are read from the API server, once per
+//! request. Secret values are masked unless explicitly revealed.
+
+use std::collections::{BTreeMap, HashMap};
+
+use k8s_openapi::api::core::v1::{
+    ConfigMap, Container, EnvFromSource, EnvVar, Pod, ResourceFieldSelector,
+    Secret,
+};
+use kops_protocol::{EnvEntry, EnvSource};
+use kube::{Api, Client};
+
+use crate::selector::pod_field;
+
+type Data = BTreeMap<String, String>;
+
+/// Looks up the objects referenced by the environment of a pod.
+pub(crate) struct EnvResolver {
+    config_maps: Api<ConfigMap>,
+    secrets: Api<Secret>,
+    reveal_secrets: bool,
+
+    /// Data of the objects read so far; `None` when they do not exist.
+    config_map_data: HashMap<String, Option<Data>>,
+    secret_data: HashMap<String, Option<Data>>,
+}
+
+impl EnvResolver {
+    pub(crate) fn new(
+        client: Client,
+        namespace: &str,
+        reveal_secrets: bool,
+    ) -> Self {
+        Self {
+            config_maps: Api::namespaced(client.clone(), namespace),
+            secrets: Api::namespaced(client, namespace),
+            reveal_secrets,
+            config_map_data: HashMap::new(),
+            secret_data: HashMap::new(),
+        }
+    }
+
+    /// Fill in the value of a `valueFrom` variable. Missing objects and
+    /// keys leave the value unset.
+    pub(crate) async fn resolve(
+        &mut self,
+        pod: &Pod,
+        container: &Container,
+        var: &EnvVar,
+        entry: &mut EnvEntry,
+    ) -> Result<(), kube::Error> {
+        let Some(from) = &var.value_from else {
+            return Ok(());
+        };
+
+        if let Some(r) = &from.config_map_key_ref {
+            let data = self.config_map(&r.name).await?;
+            entry.value = data.and_then(|d| d.get(&r.key).cloned());
+        } else if let Some(r) = &from.secret_key_ref {
+            // Masked values are never read.
+            if !self.reveal_secrets {
+                entry.masked = true;
+                return Ok(());
+            }
+            let data = self.secret(&r.name).await?;
+            entry.value = data.and_then(|d| d.get(&r.key).cloned());
+        } else if let Some(r) = &from.field_ref {
+            entry.value = field_value(pod, &r.field_path);
+        } else if let Some(r) = &from.resource_field_ref {
+            entry.value = resource_value(pod, container, r);
+        }
+
+        Ok(())
+    }
+
+    /// Variables an `envFrom` source expands to. Secrets are read even
+    /// when masked, to learn their keys.
+    pub(crate) async fn expand(
+        &mut self,
+        container: &str,
+        from: &EnvFromSource,
+    ) -> Result<Vec<EnvEntry>, kube::Error> {
+        let prefix = from.prefix.as_deref().unwrap_or_default();
+
+        if let Some(r) = &from.config_map_ref {
+            let data = self.config_map(&r.name).await?.cloned();
+            let vars = data.unwrap_or_default().into_iter();
+            return Ok(vars
+                .map(|(key, value)| EnvEntry {
+                    container: container.to_string(),
+                    name: format!("{prefix}{key}"),
+                    value: Some(value),
+                    source: EnvSource::ConfigMap { name: r.name.clone(), key },
+                    masked: false,
+                })
+                .collect());
+        }
+
+        if let Some(r) = &from.secret_ref {
+            let reveal = self.reveal_secrets;
+            let data = self.secret(&r.name).await?.cloned();
+            let vars = data.unwrap_or_default().into_iter();
+            return Ok(vars
+                .map(|(key, value)| EnvEntry {
+                    container: container.to_string(),
+                    name: format!("{prefix}{key}"),
+                    value: reveal.then_some(value),
+                    source: EnvSource::Secret { name: r.name.clone(), key },
+                    masked: !reveal,
+                })
+                .collect());
+        }
+
+        Ok(Vec::new())
+    }
+
+    async fn config_map(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<&Data>, kube::Error> {
+        if !self.config_map_data.contains_key(name) {
+            let cm = self.config_maps.get_opt(name).await?;
+            let data = cm.map(|cm| cm.data.unwrap_or_default());
+            self.config_map_data.insert(name.to_string(), data);
+        }
+
+        Ok(self.config_map_data[name].as_ref())
+    }
+
+    async fn secret(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<&Data>, kube::Error> {
+        if !self.secret_data.contains_key(name) {
+            let secret = self.secrets.get_opt(name).await?;
+            // `ByteString` is already base64-decoded by k8s-openapi.
+            let data = secret.map(|s| {
+                s.data
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(k, v)| (k, String::from_utf8_lossy(&v.0).into()))
+                    .collect()
+            });
+            self.secret_data.insert(name.to_string(), data);
+        }
+
+        Ok(self.secret_data[name].as_ref())
+    }
+}
+
+/// Value of a downward API `fieldRef`.
+fn field_value(pod: &Pod, path: &str) -> Option<String> {
+    let meta = &pod.metadata;
+    if let Some(key) = subscript(path, "metadata.labels") {
+        return meta.labels.as_ref()?.get(key).cloned();
+    }
+    if let Some(key) = subscript(path, "metadata.annotations") {
+        return meta.annotations.as_ref()?.get(key).cloned();
+    }
+
+    let status = pod.status.as_ref();
+    match path {
+        "metadata.uid" => meta.uid.clone(),
+        "status.hostIP" => status?.host_ip.clone(),
+        "status.podIPs" => {
+            let ips = status?.pod_ips.as_ref()?;
+            let ips: Vec<&str> = ips.iter().map(|ip| ip.ip.as_str()).collect();
+            Some(ips.join(","))
+        }
+        _ => pod_field(pod, path).map(str::to_string),
+    }
+}
+
+/// The `key` of a `field['key']` path.
+fn subscript<'a>(path: &'a str, field: &str) -> Option<&'a str> {
+    path.strip_prefix(field)?.strip_prefix("['")?.strip_suffix("']")
+}
+
+/// Quantity a `resourceFieldRef` points at, as written in the container
+/// spec. The kubelet further scales it by `divisor`.
+fn resource_value(
+    pod: &Pod,
+    container: &Container,
+    r: &ResourceFieldSelector,
+) -> Option<String> {
+    let container = match &r.container_name {
+        Some(name) => {
+            pod.spec.as_ref()?.containers.iter().find(|c| &c.name == name)?
+        }
+        None => container,
+    };
+    let resources = container.resources.as_ref()?;
+
+    let (kind, resource) = r.resource.split_once('.')?;
+    let quantities = match kind {
+        "limits" => resources.limits.as_ref()?,
+        "requests" => resources.requests.as_ref()?,
+        _ => return None,
+    };
+
+    quantities.get(resource).map(|q| q.0.clone())
+}
@@ -0,0 +1,275 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use k8s_openapi::api::core::v1::Event;
+use kops_protocol::EventSummary;
+use kube::runtime::reflector::ObjectRef;
+
+/// Owned `EventSummary::occurrence_key`: cluster, namespace, object,
+/// type, reason and message.
+type OccurrenceKey = (String, String, String, String, String, String);
+
+/// One occurrence, coalesced from the Event objects reporting it.
+struct Occurrence {
+    summary: EventSummary,
+
+    /// Event objects folded into `summary`.
+    objects: usize,
+}
+
+/// What an Event object added to its occurrence.
+struct Reported {
+    key: Arc<OccurrenceKey>,
+    count: i32,
+}
+
+#[derive(Default)]
+struct Inner {
+    occurrences: HashMap<Arc<OccurrenceKey>, Occurrence>,
+    objects: HashMap<ObjectRef<Event>, Reported>,
+
+    /// Occurrences about each involved object, as `<Kind>/<name>`.
+    by_object: HashMap<String, HashSet<Arc<OccurrenceKey>>>,
+}
+
+impl Inner {
+    fn add(&mut self, obj: ObjectRef<Event>, summary: EventSummary) {
+        let (cluster, namespace, object, type_, reason, message) =
+            summary.occurrence_key();
+        let key = (
+            cluster.to_string(),
+            namespace.to_string(),
+            object.to_string(),
+            type_.to_string(),
+            reason.to_string(),
+            message.to_string(),
+        );
+        let count = summary.count;
+
+        let key = match self.occurrences.get_key_value(&key) {
+            Some((key, _)) => key.clone(),
+            None => Arc::new(key),
+        };
+        match self.occurrences.get_mut(&key) {
+            Some(occurrence) => {
+                occurrence.summary.coalesce(summary);
+                occurrence.objects += 1;
+            }
+            None => {
+                self.by_object
+                    .entry(key.2.clone())
+                    .or_default()
+                    .insert(key.clone());
+                self.occurrences
+                    .insert(key.clone(), Occurrence { summary, objects: 1 });
+            }
+        }
+
+        self.objects.insert(obj, Reported { key, count });
+    }
+
+    fn remove(&mut self, obj: &ObjectRef<Event>) {
+        let Some(Reported { key, count }) = self.objects.remove(obj) else {
+            return;
+        };
+        let Some(occurrence) = self.occurrences.get_mut(&key) else {
+            return;
+        };
+
+        occurrence.objects -= 1;
+        if occurrence.objects > 0 {
+            occurrence.summary.count =
+                occurrence.summary.count.saturating_sub(count);
+            return;
+        }
+
+        self.occurrences.remove(&key);
+        if let Some(keys) = self.by_object.get_mut(&key.2) {
+            keys.remove(&key);
+            if keys.is_empty() {
+                self.by_object.remove(&key.2);
+            }
+        }
+    }
+}
+
+/// Events of a cluster, fed by the event reflector.
+///
+/// Repetitions of an occurrence often come as separate Event objects, of
+/// which noisy clusters hold thousands. They are coalesced as they
+/// arrive: one summary per occurrence, whose count adds up theirs and
+/// whose first and last seen times span theirs. Of each Event object,
+/// only its occurrence and count are kept.
+///
+/// First and last seen times are not narrowed back when an Event object
+/// goes away.
+pub struct EventLog {
+    cluster: String,
+    inner: RwLock<Inner>,
+}
+
+impl EventLog {
+    pub fn new(cluster: impl Into<String>) -> Self {
+        Self { cluster: cluster.into(), inner: RwLock::default() }
+    }
+
+    /// Add `event`, or update it when it was added before.
+    pub fn apply(&self, event: &Event) {
+        let Some(summary) = EventSummary::from_event(&self.cluster, event)
+        else {
+            return;
+        };
+        let Ok(mut inner) = self.inner.write() else {
+            return;
+        };
+
+        let obj = ObjectRef::from_obj(event);
+        inner.remove(&obj);
+        inner.add(obj, summary);
+    }
+
+    /// Take the Event object `obj` out of its occurrence.
+    pub fn remove(&self, obj: &ObjectRef<Event>) {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.remove(obj);
+        }
+    }
+
+    /// Every occurrence, unordered.
+    pub fn all(&self) -> Vec<EventSummary> {
+        let Ok(inner) = self.inner.read() else {
+            return Vec::new();
+        };
+
+        inner.occurrences.values().map(|o| o.summary.clone()).collect()
+    }
+
+    /// Occurrences about `object`, as `<Kind>/<name>`, in any namespace;
+    /// unordered.
+    pub fn about(&self, object: &str) -> Vec<EventSummary> {
+        let Ok(inner) = self.inner.read() else {
+            return Vec::new();
+        };
+
+        inner
+            .by_object
+            .get(object)
+            .into_iter()
+            .flatten()
+            .filter_map(|key| inner.occurrences.get(key))
+            .map(|o| o.summary.clone())
+            .collect()
+    }
+
+    /// Number of occurrences.
+    pub fn len(&self) -> usize {
+        self.inner.read().map(|i| i.occurrences.len()).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use k8s_openapi::api::core::v1::ObjectReference;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, Time};
+
+    use super::*;
+
+    fn event(name: &str, pod: &str, count: i32, last_seen: i64) -> Event {
+        Event {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
+            },
+            involved_object: ObjectReference {
+                kind: Some("Pod".to_string()),
+                name: Some(pod.to_string()),
+                ..Default::default()
+            },
+            type_: Some("Warning".to_string()),
+            reason: Some("Unhealthy".to_string()),
+            message: Some("Readiness probe failed".to_string()),
+            count: Some(count),
+            first_timestamp: Some(Time(
+                Utc.timestamp_millis_opt(last_seen - 1000).unwrap(),
+            )),
+            last_timestamp: Some(Time(
+                Utc.timestamp_millis_opt(last_seen).unwrap(),
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn repetitions_coalesce_into_one_occurrence() {
+        let log = EventLog::new("dev");
+        log.apply(&event("api.1", "api", 2, 10_000));
+        log.apply(&event("api.2", "api", 3, 20_000));
+
+        let events = log.all();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].cluster, "dev");
+        assert_eq!(events[0].name, "api.2");
+        assert_eq!(events[0].count, 5);
+        assert_eq!(events[0].first_seen_epoch_ms, Some(9_000));
+        assert_eq!(events[0].last_seen_epoch_ms, Some(20_000));
+    }
+
+    #[test]
+    fn updates_replace_the_count_of_their_object() {
+        let log = EventLog::new("dev");
+        log.apply(&event("api.1", "api", 2, 10_000));
+        log.apply(&event("api.2", "api", 3, 20_000));
+        log.apply(&event("api.1", "api", 4, 30_000));
+
+        let events = log.all();
+        assert_eq!(events[0].count, 7);
+        assert_eq!(events[0].name, "api.1");
+        assert_eq!(events[0].last_seen_epoch_ms, Some(30_000));
+    }
+
+    #[test]
+    fn occurrences_go_with_their_last_object() {
+        let log = EventLog::new("dev");
+        let first = event("api.1", "api", 2, 10_000);
+        let second = event("api.2", "api", 3, 20_000);
+        log.apply(&first);
+        log.apply(&second);
+
+        log.remove(&ObjectRef::from_obj(&first));
+        assert_eq!(log.all()[0].count, 3);
+
+        log.remove(&ObjectRef::from_obj(&second));
+        assert_eq!(log.len(), 0);
+        assert!(log.about("Pod/api").is_empty());
+    }
+
+    #[test]
+    fn occurrences_are_indexed_by_object() {
+        let log = EventLog::new("dev");
+        log.apply(&event("api.1", "api", 1, 10_000));
+        log.apply(&event("web.1", "web", 1, 10_000));
+
+        let about = log.about("Pod/api");
+        assert_eq!(about.len(), 1);
+        assert_eq!(about[0].object, "Pod/api");
+        assert!(log.about("Pod/db").is_empty());
+    }
+}
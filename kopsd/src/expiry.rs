@@ -0,0 +1,239 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Background monitor of AWS session expirations.
+//!
+//! Sessions holding an SSO refresh token are renewed shortly before they
+//! expire, with no login needed. Clusters started with a session about to
+//! expire are marked as such and subscribers told to log in again. Once a
+//! session expires, it is removed and its clusters stopped, so requests to
+//! them fail with `ErrorCode::CredentialsExpired` instead of an opaque
+//! 401.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use aws_config::Region;
+use chrono::{DateTime, Utc};
+use kops_aws_sso::RefreshGrant;
+use kops_protocol::{Notification, SsoRefresh};
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::{info, warn};
+
+use crate::proxy;
+use crate::state::{AwsSession, DaemonState, ProfileName};
+
+/// How often sessions are checked.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long before its expiry a session counts as expiring.
+pub(crate) const EXPIRING_WITHIN: Duration = Duration::from_secs(10 * 60);
+
+/// How long before its expiry a session is renewed, ahead of the warning.
+const RENEW_WITHIN: Duration = Duration::from_secs(15 * 60);
+
+/// Watch the sessions of `state` until the daemon exits.
+pub(crate) async fn monitor(state: Arc<DaemonState>) {
+    let mut tick = interval(CHECK_INTERVAL);
+    tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    // Expiry each profile was last warned about, so a new login with a
+    // new expiry is warned about again.
+    let mut warned: HashMap<ProfileName, DateTime<Utc>> = HashMap::new();
+
+    // Expiry of the sessions that failed to renew, tried once each.
+    let mut unrenewed: HashMap<ProfileName, DateTime<Utc>> = HashMap::new();
+
+    loop {
+        tick.tick().await;
+        renew(&state, &mut unrenewed).await;
+        check(&state, &mut warned);
+    }
+}
+
+/// Renew the sessions about to expire that hold an SSO refresh token, and
+/// switch their clusters to the new credentials.
+async fn renew(
+    state: &DaemonState,
+    unrenewed: &mut HashMap<ProfileName, DateTime<Utc>>,
+) {
+    let now = Utc::now();
+    let sessions = state.sessions();
+    unrenewed.retain(|profile, _| sessions.iter().any(|(p, _)| p == profile));
+
+    for (profile, session) in sessions {
+        let Some(refresh) = &session.refresh else {
+            continue;
+        };
+        let left = (session.expires_at - now).num_milliseconds();
+        if left > RENEW_WITHIN.as_millis() as i64
+            || unrenewed.get(&profile) == Some(&session.expires_at)
+        {
+            continue;
+        }
+
+        let renewed = match refresh_session(&session, refresh).await {
+            Ok(renewed) => renewed,
+            Err(e) => {
+                warn!("failed to renew session of profile '{profile}': {e:#}");
+                unrenewed.insert(profile, session.expires_at);
+                continue;
+            }
+        };
+        info!(
+            "renewed AWS session for profile '{profile}' until {}",
+            renewed.expires_at
+        );
+
+        for cluster in state.clusters() {
+            if cluster.profile() == Some(profile.as_str()) {
+                cluster.update_credentials(&renewed);
+            }
+        }
+
+        // stored first, subscribers read the renewed session
+        let stored = Notification::SessionStored {
+            profile: profile.clone(),
+            expires_at_epoch_ms: renewed.expires_at.timestamp_millis(),
+        };
+        state.store_session(profile, renewed);
+        state.bus.publish(stored);
+    }
+}
+
+/// New credentials for the account and role of `session`, from the SSO
+/// refresh token of its login.
+async fn refresh_session(
+    session: &AwsSession,
+    refresh: &SsoRefresh,
+) -> Result<AwsSession> {
+    let mut loader = aws_config::from_env()
+        .region(Region::new(refresh.region.clone()))
+        .no_credentials();
+    if let Some(http_client) = proxy::aws_http_client() {
+        loader = loader.http_client(http_client);
+    }
+    let sdk_config = loader.load().await;
+
+    let grant = RefreshGrant {
+        region: refresh.region.clone(),
+        start_url: refresh.start_url.clone(),
+        client_id: refresh.client_id.clone(),
+        client_secret: refresh.client_secret.clone(),
+        refresh_token: refresh.refresh_token.clone(),
+    };
+    let renewed = kops_aws_sso::refresh_session(
+        &sdk_config,
+        &grant,
+        &session.account_id,
+        &session.role_name,
+    )
+    .await?;
+
+    let credentials = renewed.credentials;
+    let session_token = credentials
+        .session_token()
+        .context("missing session token in renewed credentials")?
+        .to_string();
+
+    Ok(AwsSession {
+        account_id: renewed.account_id,
+        role_name: renewed.role_name,
+        region: session.region.clone(),
+        access_key_id: credentials.access_key_id().to_string(),
+        secret_access_key: credentials.secret_access_key().to_string(),
+        session_token,
+        expires_at: renewed.expires_at,
+        refresh: renewed.refresh.map(|grant| SsoRefresh {
+            region: grant.region,
+            start_url: grant.start_url,
+            client_id: grant.client_id,
+            client_secret: grant.client_secret,
+            refresh_token: grant.refresh_token,
+        }),
+    })
+}
+
+fn check(
+    state: &DaemonState,
+    warned: &mut HashMap<ProfileName, DateTime<Utc>>,
+) {
+    let now = Utc::now();
+    let sessions = state.sessions();
+    warned.retain(|profile, _| sessions.iter().any(|(p, _)| p == profile));
+
+    for (profile, session) in sessions {
+        let left = (session.expires_at - now).num_milliseconds();
+        if left <= 0 {
+            expire(state, &profile, now);
+            warned.remove(&profile);
+            continue;
+        }
+
+        let expiring = left <= EXPIRING_WITHIN.as_millis() as i64;
+        let mut clusters: Vec<String> = state
+            .clusters()
+            .into_iter()
+            .filter(|c| c.profile() == Some(profile.as_str()))
+            .map(|c| {
+                c.set_credentials_expiring(expiring);
+                c.name().to_string()
+            })
+            .collect();
+
+        if !expiring || warned.get(&profile) == Some(&session.expires_at) {
+            continue;
+        }
+
+        warn!(
+            "AWS session for profile '{profile}' expires at {}",
+            session.expires_at
+        );
+        warned.insert(profile.clone(), session.expires_at);
+
+        clusters.sort();
+        state.bus.publish(Notification::SessionExpiring {
+            profile,
+            expires_at_epoch_ms: session.expires_at.timestamp_millis(),
+            clusters,
+        });
+    }
+}
+
+/// Remove the expired session of `profile` and stop its clusters.
+fn expire(state: &DaemonState, profile: &str, now: DateTime<Utc>) {
+    // A new login may have replaced it since it was read.
+    if state.remove_expired_session(profile, now).is_none() {
+        return;
+    }
+    info!("AWS session for profile '{profile}' expired, removed");
+
+    let mut stopped_clusters = Vec::new();
+    for cluster in state.remove_profile_clusters(profile) {
+        info!("stopping cluster worker for cluster '{}'", cluster.name());
+        state.mark_expired(cluster.name(), profile);
+        cluster.stop();
+        stopped_clusters.push(cluster.name().to_string());
+    }
+    stopped_clusters.sort();
+
+    state.bus.publish(Notification::SessionExpired {
+        profile: profile.to_string(),
+        stopped_clusters,
+    });
+}
@@ -14,19 +14,87 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
-use std::sync::Arc;
 use anyhow::Context;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use chrono::{TimeZone, Utc};
-use k8s_openapi::api::core::v1::Pod;
+use futures::future::join_all;
+use futures::{AsyncReadExt, SinkExt};
+use k8s_openapi::api::apps::v1::Deployment;
+use k8s_openapi::api::core::v1::{ConfigMap, Container, EnvVar, Pod, Secret};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Status;
 use kops_protocol::{
-    EnvEntry, EnvRequest, LoginRequest, PodSummary, PodsRequest, Request,
-    Response,
+    ClusterAccess, ClusterAddRequest, ClusterStatus, ClusterSummary,
+    ConfigMapData, ConfigMapDataRequest, ConfigMapSummary, ConfigMapsRequest,
+    ConnectionStatus, CronJobSummary, CronJobsRequest, DaemonSetSummary,
+    DaemonStatus, DeletePodRequest, DeploymentSummary, DeploymentsRequest,
+    DescribePodRequest, EnvEntry, EnvGrepRequest, EnvMatch, EnvRequest,
+    EnvSource, ErrorCode, EventSummary, EventsRequest, ExecRequest,
+    ExpiringSession, GetRequest, HpaSummary, ImageUsage, IngressSummary,
+    IngressesRequest, JobSummary, JobsRequest, KopsError, LoginRequest,
+    LogsRequest, MetricsRequest, MetricsTarget, NamespaceSummary,
+    NamespacesRequest, NodeSummary, NodesRequest, Notification, Ownership,
+    PodDetail, PodEvent, PodSummary, PodsRequest, PortForwardRequest,
+    PvcSummary, ReflectorStatus, Request, Response, RestartEntry,
+    RestartHistoryRequest, RestartSummary, RestartsRequest,
+    RolloutRestartRequest, ScaleRequest, SecretSummary, SecretValueRequest,
+    SecretsRequest, ServiceSummary, ServicesRequest, SessionCredentials,
+    SessionStatus, StatefulSetSummary, SyncState, Topic, UserTraffic,
+    WorkloadsRequest,
+    wire::{
+        ExecFrame, ForwardFrame, HEARTBEAT_INTERVAL, PROTOCOL_VERSION,
+        WireError, read_message, write_message,
+    },
+};
+use kube::{
+    Api, Resource, ResourceExt,
+    api::{
+        AttachParams, DeleteParams, ListParams, LogParams, Patch, PatchParams,
+        TerminalSize,
+    },
+    runtime::reflector::{ObjectRef, Store},
+};
+use regex::Regex;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt},
+    sync::{broadcast::error::RecvError, mpsc},
+    task::JoinSet,
+    time::{interval, timeout},
 };
-use kube::ResourceExt;
-use tracing::info;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::config::{ClusterAuth, ClusterConfig};
+use crate::discovery;
+use crate::env::EnvResolver;
+use crate::expiry::EXPIRING_WITHIN;
+use crate::kube_worker::{eks_sdk_config, start_configured_cluster};
+use crate::manifest;
+use crate::metrics;
+use crate::page;
+use crate::reload;
+use crate::restarts;
+use crate::selector::{FieldSelector, LabelSelector};
+use crate::state::{
+    AwsSession, ClusterState, DaemonState, EksCluster, PodChange,
+};
+
+/// Maximum payload of a single `Response::LogChunk`.
+const LOG_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Pods per `Response::Pods` of a streamed listing.
+const POD_CHUNK_SIZE: usize = 500;
 
-use crate::state::{AwsSession, DaemonState};
+/// Annotation naming the container kubectl picks when none is given.
+pub(crate) const DEFAULT_CONTAINER_ANNOTATION: &str =
+    "kubectl.kubernetes.io/default-container";
+
+/// How often a waited rollout is checked.
+const ROLLOUT_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 pub struct Handler {
     state: Arc<DaemonState>,
@@ -37,13 +105,583 @@ impl Handler {
         Self { state }
     }
 
+    pub fn state(&self) -> &Arc<DaemonState> {
+        &self.state
+    }
+
     pub async fn handle(&self, req: Request) -> Response {
         match req {
             Request::Ping => Response::Pong,
             Request::Login(login_req) => self.handle_login(login_req).await,
+            Request::Sessions => self.handle_sessions(),
+            Request::Logout { profile } => self.handle_logout(&profile),
+            Request::SessionCredentials { profile } => {
+                self.handle_session_credentials(&profile)
+            }
+            Request::SessionHealth => self.handle_session_health(),
+            Request::ClusterAdd(r) => self.handle_cluster_add(r).await,
+            Request::ClusterRemove { name } => {
+                self.handle_cluster_remove(&name)
+            }
+            Request::ClusterAccess { cluster } => {
+                self.handle_cluster_access(cluster.as_deref()).await
+            }
+            Request::ClusterToken { cluster } => {
+                self.handle_cluster_token(cluster.as_deref()).await
+            }
+            Request::Clusters => self.handle_clusters(),
+            Request::Reload => self.handle_reload().await,
+            Request::Shutdown => self.handle_shutdown(),
             Request::Version => self.handle_version().await,
+            Request::Status => self.handle_status(),
             Request::Pods(p) => self.handle_pods(p).await,
+            Request::DescribePod(r) => self.handle_describe_pod(r).await,
+            Request::Get(r) => self.handle_get(r).await,
+            Request::Deployments(r) => self.handle_deployments(r),
+            Request::StatefulSets(r) => self.handle_stateful_sets(r),
+            Request::DaemonSets(r) => self.handle_daemon_sets(r),
+            Request::Events(r) => self.handle_events(r),
+            Request::Restarts(r) => self.handle_restarts(r),
+            Request::RestartHistory(r) => self.handle_restart_history(r),
+            Request::Nodes(r) => self.handle_nodes(r),
+            Request::Services(r) => self.handle_services(r),
+            Request::Ingresses(r) => self.handle_ingresses(r),
+            Request::Jobs(r) => self.handle_jobs(r),
+            Request::Hpas(r) => self.handle_hpas(r),
+            Request::Pvcs(r) => self.handle_pvcs(r),
+            Request::Images(r) => self.handle_images(r),
+            Request::CronJobs(r) => self.handle_cron_jobs(r),
+            Request::Namespaces(r) => self.handle_namespaces(r),
+            Request::RolloutRestart(r) => self.handle_rollout_restart(r).await,
+            Request::DeletePod(r) => self.handle_delete_pod(r).await,
+            Request::Scale(r) => self.handle_scale(r).await,
+            Request::ConfigMaps(r) => self.handle_config_maps(r).await,
+            Request::ConfigMapData(r) => self.handle_config_map_data(r).await,
+            Request::Secrets(r) => self.handle_secrets(r).await,
+            Request::Metrics(r) => self.handle_metrics(r).await,
+            Request::SecretValue(r) => self.handle_secret_value(r).await,
             Request::Env(r) => self.handle_env(r).await,
+            Request::EnvGrep(r) => self.handle_env_grep(r).await,
+            Request::Logs(_)
+            | Request::WatchPods(_)
+            | Request::Subscribe { .. }
+            | Request::Exec(_)
+            | Request::PortForward(_)
+            | Request::Stream { .. } => Response::Error(KopsError::invalid(
+                "request is only served as a stream",
+            )),
+            Request::Cancel { .. } | Request::Heartbeat => {
+                Response::Error(KopsError::invalid(
+                    "request is only valid on a multiplexed connection",
+                ))
+            }
+        }
+    }
+
+    /// Look up a running cluster by name, or the default one.
+    fn cluster(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Arc<ClusterState>, KopsError> {
+        let Some(name) = name.or(self.state.default_cluster()) else {
+            return Err(KopsError::invalid(
+                "no cluster given and no default cluster, pass --cluster or \
+                 set kops.default_cluster",
+            ));
+        };
+
+        if let Some(cluster) = self.state.cluster(name) {
+            return Ok(cluster);
+        }
+
+        let error = match self.state.expired_profile(name) {
+            Some(profile) => KopsError::new(
+                ErrorCode::CredentialsExpired,
+                format!(
+                    "cluster {name} was stopped, the session of profile \
+                     {profile} expired"
+                ),
+            ),
+            None => KopsError::new(
+                ErrorCode::ClusterNotFound,
+                format!("cluster not found: {name}"),
+            ),
+        };
+
+        Err(error.with_cluster(name))
+    }
+
+    /// Look up one running cluster by name, or every running cluster.
+    fn clusters(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Vec<Arc<ClusterState>>, KopsError> {
+        if let Some(name) = name {
+            return self.cluster(Some(name)).map(|c| vec![c]);
+        }
+
+        Ok(self.state.clusters())
+    }
+
+    /// Clusters a listing covers: every running cluster with `all`,
+    /// otherwise the one named (or the default one).
+    fn scope(
+        &self,
+        name: Option<&str>,
+        all: bool,
+    ) -> Result<Vec<Arc<ClusterState>>, KopsError> {
+        if all {
+            return Ok(self.state.clusters());
+        }
+
+        self.cluster(name).map(|c| vec![c])
+    }
+
+    /// Produce the responses to a streamed request on `tx`.
+    ///
+    /// Returns once the reply is complete, after an error response, as
+    /// soon as `tx` has no receiver, or when `cancel` fires, in which case
+    /// whatever the request was waiting on is dropped.
+    pub async fn stream(
+        &self,
+        req: Request,
+        tx: mpsc::Sender<Response>,
+        cancel: CancellationToken,
+    ) {
+        tokio::select! {
+            () = self.reply(req, &tx) => {}
+            () = cancel.cancelled() => debug!("request cancelled"),
+        }
+    }
+
+    async fn reply(&self, req: Request, tx: &mpsc::Sender<Response>) {
+        match req {
+            Request::Logs(r) => self.stream_logs(r, tx).await,
+            Request::WatchPods(r) => self.watch_pods(r, tx).await,
+            Request::Subscribe { topic } => self.subscribe(topic, tx).await,
+            Request::Pods(r) => {
+                let (mut pods, mut token) = match self.handle_pods(r).await {
+                    Response::Pods { pods, continue_token } => {
+                        (pods, continue_token)
+                    }
+                    resp => {
+                        let _ = tx.send(resp).await;
+                        return;
+                    }
+                };
+
+                // At least one chunk, even for an empty listing; the token
+                // goes with the last one.
+                loop {
+                    let rest = pods.split_off(POD_CHUNK_SIZE.min(pods.len()));
+                    let last = rest.is_empty();
+                    let continue_token =
+                        if last { token.take() } else { None };
+                    let chunk = Response::Pods { pods, continue_token };
+                    if tx.send(chunk).await.is_err() || last {
+                        return;
+                    }
+                    pods = rest;
+                }
+            }
+            Request::Exec(_)
+            | Request::PortForward(_)
+            | Request::Stream { .. }
+            | Request::Cancel { .. }
+            | Request::Heartbeat => {
+                let err = KopsError::invalid("request cannot be streamed");
+                let _ = tx.send(Response::Error(err)).await;
+            }
+            req => {
+                let _ = tx.send(self.handle(req).await).await;
+            }
+        }
+    }
+
+    /// Stream container logs to `tx`.
+    ///
+    /// Sends `Response::LogChunk`s as data arrives from the API server and
+    /// terminates with `Response::LogEnd`, or with a single
+    /// `Response::Error` if the stream cannot be opened or breaks.
+    async fn stream_logs(
+        &self,
+        req: LogsRequest,
+        tx: &mpsc::Sender<Response>,
+    ) {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => {
+                let _ = tx.send(Response::Error(err)).await;
+                return;
+            }
+        };
+
+        let pods: Api<Pod> = Api::namespaced(cluster.client(), &req.namespace);
+        let params = LogParams {
+            container: req.container,
+            follow: req.follow,
+            tail_lines: req.tail_lines,
+            since_seconds: req.since_seconds,
+            ..LogParams::default()
+        };
+
+        let stream = match pods.log_stream(&req.pod, &params).await {
+            Ok(s) => s,
+            Err(err) => {
+                let action =
+                    format!("open logs of {}/{}", req.namespace, req.pod);
+                let _ =
+                    tx.send(Response::Error(api_error(err, &action))).await;
+                return;
+            }
+        };
+        futures::pin_mut!(stream);
+
+        let mut buf = vec![0u8; LOG_CHUNK_SIZE];
+        loop {
+            let n = match stream.read(&mut buf).await {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(err) => {
+                    let err = KopsError::new(
+                        ErrorCode::KubeApi,
+                        format!("log stream failed: {err}"),
+                    )
+                    .retryable();
+                    let _ = tx.send(Response::Error(err)).await;
+                    return;
+                }
+            };
+
+            let chunk = Response::LogChunk { data: buf[..n].to_vec() };
+            if tx.send(chunk).await.is_err() {
+                return;
+            }
+        }
+
+        let _ = tx.send(Response::LogEnd).await;
+    }
+
+    /// Push pod changes to `tx` until the client goes away.
+    ///
+    /// Sends the matching pods as `Response::Pods`, then a
+    /// `Response::PodEvent` for every change seen by the pod reflector.
+    async fn watch_pods(&self, req: PodsRequest, tx: &mpsc::Sender<Response>) {
+        let found = if req.all_clusters {
+            Err(KopsError::invalid(
+                "watching pods of every cluster is not supported",
+            ))
+        } else {
+            self.cluster(req.cluster.as_deref())
+                .and_then(|c| PodFilter::new(&req).map(|f| (c, f)))
+        };
+        let (cluster, filter) = match found {
+            Ok(found) => found,
+            Err(err) => {
+                let _ = tx.send(Response::Error(err)).await;
+                return;
+            }
+        };
+
+        // Subscribe before the snapshot so no change falls in between.
+        let mut changes = cluster.subscribe_pods();
+
+        let Some(mut known) = send_pod_snapshot(&cluster, &filter, tx).await
+        else {
+            return;
+        };
+
+        loop {
+            let change = tokio::select! {
+                // The receiver is dropped once the client went away.
+                _ = tx.closed() => return,
+                change = changes.recv() => change,
+            };
+
+            let (pod, deleted) = match change {
+                Ok(PodChange::Applied(pod)) => (pod, false),
+                Ok(PodChange::Deleted(pod)) => (pod, true),
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "pod watcher lagged, resending pods");
+                    match send_pod_snapshot(&cluster, &filter, tx).await {
+                        Some(k) => known = k,
+                        None => return,
+                    }
+                    continue;
+                }
+                // The pod reflector is gone along with its cluster.
+                Err(RecvError::Closed) => return,
+            };
+
+            let Some(p) = PodSummary::from_pod(cluster.name(), &pod) else {
+                continue;
+            };
+            let key = (p.namespace.clone(), p.name.clone());
+
+            let event = if !deleted && filter.matches(&pod, &p) {
+                if known.insert(key) {
+                    PodEvent::Added(p)
+                } else {
+                    PodEvent::Modified(p)
+                }
+            } else if known.remove(&key) {
+                // Deleted, or changed so that it no longer matches.
+                PodEvent::Deleted(p)
+            } else {
+                continue;
+            };
+
+            if tx.send(Response::PodEvent(event)).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Push the notifications about `topic` to `tx` until the client goes
+    /// away.
+    async fn subscribe(&self, topic: Topic, tx: &mpsc::Sender<Response>) {
+        let mut notifications = self.state.bus.subscribe();
+
+        loop {
+            let received = tokio::select! {
+                _ = tx.closed() => return,
+                received = notifications.recv() => received,
+            };
+
+            let notification = match received {
+                Ok(n) if n.topic().is_none_or(|t| t == topic) => n,
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "subscriber lagged");
+                    Notification::Lagged { skipped }
+                }
+                Err(RecvError::Closed) => return,
+            };
+
+            let resp = Response::Notification(notification);
+            if tx.send(resp).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Run an exec session, relaying `ExecFrame`s over `stream`.
+    ///
+    /// Returns once the process exits (after sending `ExecFrame::Exit`) or
+    /// the client goes away or stays silent for `heartbeat_timeout`, in
+    /// which case the session is dropped.
+    pub async fn exec<S>(
+        &self,
+        req: ExecRequest,
+        stream: &mut S,
+        heartbeat_timeout: Duration,
+    ) -> Result<(), WireError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => {
+                let message = err.message;
+                return write_message(stream, &ExecFrame::Error { message })
+                    .await;
+            }
+        };
+
+        let pods: Api<Pod> = Api::namespaced(cluster.client(), &req.namespace);
+        let mut params = AttachParams::default()
+            .stdin(req.stdin)
+            .stdout(true)
+            .stderr(!req.tty)
+            .tty(req.tty);
+        if let Some(container) = req.container {
+            params = params.container(container);
+        }
+
+        let mut process = match pods.exec(&req.pod, req.command, &params).await
+        {
+            Ok(p) => p,
+            Err(err) => {
+                let message = format!(
+                    "failed to exec in {}/{}: {err}",
+                    req.namespace, req.pod
+                );
+                return write_message(stream, &ExecFrame::Error { message })
+                    .await;
+            }
+        };
+
+        let mut stdin = process.stdin();
+        let stdout = process.stdout();
+        let stderr = process.stderr();
+        let mut resize = process.terminal_size();
+        let status = process.take_status();
+
+        let (mut rd, mut wr) = tokio::io::split(stream);
+
+        // Client frames: forward stdin and resizes until the client leaves.
+        let input = async {
+            loop {
+                let read = read_message(&mut rd);
+                let Some(frame) = timeout(heartbeat_timeout, read)
+                    .await
+                    .map_err(|_| {
+                        io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "exec client missed its heartbeats",
+                        )
+                    })??
+                else {
+                    break;
+                };
+
+                match frame {
+                    ExecFrame::Stdin(data) => {
+                        if let Some(w) = stdin.as_mut() {
+                            w.write_all(&data).await?;
+                        }
+                    }
+                    ExecFrame::StdinEof => {
+                        if let Some(mut w) = stdin.take() {
+                            w.shutdown().await?;
+                        }
+                    }
+                    ExecFrame::Resize { width, height } => {
+                        if let Some(tx) = resize.as_mut() {
+                            let size = TerminalSize { width, height };
+                            if tx.send(size).await.is_err() {
+                                debug!(
+                                    "exec session no longer accepts resizes"
+                                );
+                            }
+                        }
+                    }
+                    ExecFrame::Heartbeat => {}
+                    frame => {
+                        debug!("ignoring unexpected exec frame {frame:?}")
+                    }
+                }
+            }
+            Ok::<(), WireError>(())
+        };
+
+        // Process output: relay stdout/stderr, then report the exit code.
+        let output = async {
+            let (tx, mut rx) = mpsc::channel(16);
+
+            let out = pump_output(stdout, tx.clone(), ExecFrame::Stdout);
+            let err = pump_output(stderr, tx, ExecFrame::Stderr);
+            let write = async {
+                let mut heartbeat = interval(HEARTBEAT_INTERVAL);
+                loop {
+                    let frame = tokio::select! {
+                        frame = rx.recv() => match frame {
+                            Some(frame) => frame,
+                            None => break,
+                        },
+                        _ = heartbeat.tick() => ExecFrame::Heartbeat,
+                    };
+                    write_message(&mut wr, &frame).await?;
+                }
+                Ok::<(), WireError>(())
+            };
+
+            let ((), (), written) = tokio::join!(out, err, write);
+            written?;
+
+            let code = match status {
+                Some(status) => exit_code(status.await),
+                None => 0,
+            };
+            write_message(&mut wr, &ExecFrame::Exit { code }).await
+        };
+
+        tokio::select! {
+            res = output => res,
+            res = input => {
+                debug!("exec client went away, dropping session");
+                res
+            }
+        }
+    }
+
+    /// Run a port-forward session, relaying `ForwardFrame`s over `stream`.
+    ///
+    /// Every stream opened by the client gets its own forwarder task; all
+    /// of them are aborted when the client disconnects.
+    pub async fn port_forward<S>(
+        &self,
+        req: PortForwardRequest,
+        stream: &mut S,
+    ) -> Result<(), WireError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => {
+                let message = err.message;
+                let frame = ForwardFrame::Error { stream: None, message };
+                return write_message(stream, &frame).await;
+            }
+        };
+
+        let pods: Api<Pod> = Api::namespaced(cluster.client(), &req.namespace);
+        let (mut rd, mut wr) = tokio::io::split(stream);
+        let (tx, mut rx) = mpsc::channel(64);
+
+        let input = async {
+            let mut streams: HashMap<u32, mpsc::Sender<Vec<u8>>> =
+                HashMap::new();
+            let mut tasks = JoinSet::new();
+
+            while let Some(frame) = read_message(&mut rd).await? {
+                match frame {
+                    ForwardFrame::Open { stream } => {
+                        let (data_tx, data_rx) = mpsc::channel(16);
+                        streams.insert(stream, data_tx);
+                        tasks.spawn(forward_stream(
+                            pods.clone(),
+                            req.pod.clone(),
+                            req.port,
+                            stream,
+                            data_rx,
+                            tx.clone(),
+                        ));
+                    }
+                    ForwardFrame::Data { stream, data } => {
+                        if let Some(s) = streams.get(&stream)
+                            && s.send(data).await.is_err()
+                        {
+                            streams.remove(&stream);
+                        }
+                    }
+                    ForwardFrame::Close { stream } => {
+                        streams.remove(&stream);
+                    }
+                    frame => {
+                        debug!("ignoring unexpected forward frame {frame:?}")
+                    }
+                }
+            }
+
+            // dropping `tasks` aborts the remaining forwarders
+            Ok::<(), WireError>(())
+        };
+
+        let output = async {
+            while let Some(frame) = rx.recv().await {
+                write_message(&mut wr, &frame).await?;
+            }
+            Ok::<(), WireError>(())
+        };
+
+        tokio::select! {
+            res = input => {
+                debug!("port-forward client went away, closing session");
+                res
+            }
+            res = output => res,
         }
     }
 
@@ -56,7 +694,12 @@ impl Handler {
         let expires_at = Utc
             .timestamp_millis_opt(req.expires_at_epoch_ms)
             .single()
-            .unwrap_or_else(|| Utc::now());
+            .unwrap_or_else(Utc::now);
+
+        let stored = Notification::SessionStored {
+            profile: req.name.clone(),
+            expires_at_epoch_ms: expires_at.timestamp_millis(),
+        };
 
         let session = AwsSession {
             account_id: req.account_id,
@@ -66,263 +709,1586 @@ impl Handler {
             secret_access_key: req.secret_access_key,
             session_token: req.session_token,
             expires_at,
+            refresh: req.refresh,
         };
 
-        {
-            let mut map = match self.state.aws_sessions.lock() {
-                Ok(m) => m,
-                Err(_) => {
-                    return Response::Error {
-                        message: "failed to lock aws_sessions map".into(),
-                    };
-                }
-            };
-
-            map.insert(req.name.clone(), session);
-            info!("stored AWS session for profile '{}'", req.name);
-        }
+        self.state.store_session(req.name.clone(), session.clone());
+        info!("stored AWS session for profile '{}'", req.name);
+        self.state.bus.publish(stored);
 
-        if let Err(err) = self.start_clusters_for_profile(&req.name).await {
-            return Response::Error {
-                message: format!(
-                    "stored session but failed to start clusters for profile {}: {err}",
-                    req.name
-                ),
-            };
-        }
+        let (started, running, failed) =
+            self.start_clusters_for_profile(&req.name, &session).await;
 
-        Response::LoginOk
+        Response::LoginOk { started, running, failed }
     }
 
+    /// Switch the running clusters of `profile` to `session` and start
+    /// its configured and discovered clusters that are not running,
+    /// concurrently.
+    ///
+    /// Returns the clusters started, already running and failed to start
+    /// (as `name: error`), each sorted by name.
     async fn start_clusters_for_profile(
         &self,
         profile: &str,
-    ) -> anyhow::Result<()> {
-        let session = {
-            let map = self
-                .state
-                .aws_sessions
-                .lock()
-                .unwrap();
-                // .context("failed to lock aws_sessions map")?;
-
-            map.get(profile)
-                .cloned()
-                .context("no aws session stored for this profile")?
-        };
-
-        // for (name, cfg) in &self.state.clusters {
-            // if cfg.session_name != profile {
-            //     continue;
-            // }
-
-            // // Se cluster já está rodando, não faz nada
-            // if self.state.clusters.contains_key(name) {
-            //     continue;
-            // }
-
-        let name = String::from("eks-platform-dev");
-            tracing::info!(
-                "starting cluster worker for cluster '{}' (profile '{}')",
-                name,
-                profile
+        session: &AwsSession,
+    ) -> (Vec<String>, Vec<String>, Vec<String>) {
+        let mut running: Vec<String> = self
+            .state
+            .clusters()
+            .into_iter()
+            .filter(|c| c.profile() == Some(profile))
+            .filter(|c| c.update_credentials(session))
+            .map(|c| c.name().to_string())
+            .collect();
+
+        let configured = self.state.configured();
+        let mut clusters: Vec<ClusterConfig> = configured
+            .iter()
+            .filter(|c| c.eks_profile() == Some(profile))
+            .cloned()
+            .collect();
+        if let Some(discovery) = self.state.profile_config(profile) {
+            clusters.extend(
+                discovery::discover(
+                    &discovery,
+                    session,
+                    &configured,
+                    &self.state.clusters(),
+                )
+                .await,
             );
+        }
+
+        let starts = clusters
+            .iter()
+            .filter(|c| !running.contains(&c.name))
+            .map(|cfg| async move {
+                (&cfg.name, self.start_cluster(cfg, session).await)
+            });
+
+        let mut started = Vec::new();
+        let mut failed = Vec::new();
+        for (name, res) in join_all(starts).await {
+            match res {
+                Ok(()) => started.push(name.clone()),
+                Err(err) => {
+                    error!("failed to start cluster '{name}': {err:#}");
+                    failed.push(format!("{name}: {err:#}"));
+                }
+            }
+        }
 
-            let sdk_config = sdk_config_from_session(&session).await?;
+        started.sort();
+        running.sort();
+        failed.sort();
 
-            let client = kops_aws_eks::create_kube_client(&sdk_config, &name)
-                .await
-                .with_context(|| format!("failed to create kube client for cluster {}", name))?;
+        (started, running, failed)
+    }
 
-            let cluster_state = crate::kube_worker::init_cluster_state(name.clone(), client)
-                .await
-                .with_context(|| format!("failed to start worker for cluster {}", name))?;
+    /// Start the worker of EKS cluster `cfg` with `session`, stopping a
+    /// running cluster of the same name.
+    async fn start_cluster(
+        &self,
+        cfg: &ClusterConfig,
+        session: &AwsSession,
+    ) -> anyhow::Result<()> {
+        let name = &cfg.name;
+        info!(
+            "starting cluster worker for cluster '{name}' (profile '{}')",
+            cfg.profile.as_deref().unwrap_or_default()
+        );
 
-            self.state
-    .clusters
-    .lock()
-    .unwrap()
-    .insert(name.clone(), cluster_state);
+        let bus = self.state.bus.clone();
+        let cluster_state = start_configured_cluster(cfg, Some(session), bus)
+            .await
+            .with_context(|| {
+                format!("failed to start worker for cluster {name}")
+            })?;
 
-        // }
+        // E.g. a new login restarts the clusters of its profile.
+        if let Some(old) = self.state.insert_cluster(cluster_state) {
+            old.stop();
+        }
 
         Ok(())
     }
 
+    async fn handle_cluster_add(&self, req: ClusterAddRequest) -> Response {
+        if self.state.cluster(&req.name).is_some() {
+            return Response::Error(
+                KopsError::invalid(format!(
+                    "cluster {} is already running",
+                    req.name
+                ))
+                .with_cluster(&req.name),
+            );
+        }
 
+        let Some(session) = self.state.session(&req.profile) else {
+            return Response::Error(KopsError::not_found(format!(
+                "no AWS session stored for profile {}, log in first",
+                req.profile
+            )));
+        };
 
-    async fn handle_env(&self, req: EnvRequest) -> Response {
-        let cluster = req
-            .cluster
-            .as_deref()
-            .unwrap_or_else(|| self.state.default_cluster());
-
-        let clusters = self.state.clusters.lock().unwrap();
-        let Some(cs) = clusters.get(cluster) else {
-            return Response::Error {
-                message: format!("cluster not found: {cluster}"),
-            };
+        let cfg = ClusterConfig {
+            name: req.name.clone(),
+            profile: Some(req.profile),
+            auth: Some(ClusterAuth::Eks),
+            region: req.region,
+            ..Default::default()
         };
 
-        // snapshot atual do cluster
-        let pods = cs.store().state();
+        if let Err(err) = self.start_cluster(&cfg, &session).await {
+            return Response::Error(
+                KopsError::internal(format!("{err:#}"))
+                    .with_cluster(&req.name),
+            );
+        }
 
-        // encontrar o pod
-        let pod: Option<Arc<Pod>> = pods
-            .iter()
-            .find(|p| {
-                p.namespace().as_deref() == Some(&req.namespace)
-                    && p.name_any() == req.pod
-            })
-            .cloned();
+        Response::ClusterAdded
+    }
 
-        let Some(pod) = pod else {
-            return Response::Error {
-                message: format!(
-                    "pod {}/{} not found",
-                    req.namespace, req.pod
-                ),
-            };
+    /// EKS cluster `name`, the default one when `None`, with the session
+    /// of its profile.
+    fn eks_cluster(
+        &self,
+        name: Option<&str>,
+    ) -> Result<(Arc<ClusterState>, EksCluster, AwsSession), KopsError> {
+        let cluster = self.cluster(name)?;
+        let name = cluster.name();
+
+        let (Some(profile), Some(eks)) = (cluster.profile(), cluster.eks())
+        else {
+            return Err(KopsError::invalid(format!(
+                "cluster {name} is not an EKS cluster, use its kubeconfig"
+            ))
+            .with_cluster(name));
+        };
+        let Some(session) = self.state.session(profile) else {
+            return Err(KopsError::not_found(format!(
+                "no AWS session stored for profile {profile}, log in first"
+            ))
+            .with_cluster(name));
         };
 
-        // selecionar container
-        let spec = match &pod.spec {
-            Some(s) => s,
-            None => {
-                return Response::Error { message: "pod has no spec".into() };
+        let eks = eks.clone();
+        Ok((cluster, eks, session))
+    }
+
+    async fn handle_cluster_access(&self, name: Option<&str>) -> Response {
+        let (cluster, eks, session) = match self.eks_cluster(name) {
+            Ok(found) => found,
+            Err(err) => return Response::Error(err),
+        };
+        let name = cluster.name();
+
+        let endpoint = match eks_sdk_config(&eks, &session).await {
+            Ok(sdk_config) => {
+                kops_aws_eks::eks_cluster_endpoint(&sdk_config, &eks.name)
+                    .await
             }
+            Err(err) => Err(err),
         };
+        match endpoint {
+            Ok(endpoint) => Response::ClusterAccess(ClusterAccess {
+                cluster: name.to_string(),
+                profile: cluster.profile().unwrap_or_default().to_string(),
+                server: endpoint.server,
+                certificate_authority_data: endpoint
+                    .certificate_authority_data,
+            }),
+            Err(err) => Response::Error(
+                KopsError::internal(format!(
+                    "failed to describe EKS cluster {}: {err:#}",
+                    eks.name
+                ))
+                .with_cluster(name),
+            ),
+        }
+    }
 
-        let mut vars: Vec<EnvEntry> = Vec::new();
+    async fn handle_cluster_token(&self, name: Option<&str>) -> Response {
+        let (cluster, eks, session) = match self.eks_cluster(name) {
+            Ok(found) => found,
+            Err(err) => return Response::Error(err),
+        };
+
+        let token = match eks_sdk_config(&eks, &session).await {
+            Ok(sdk_config) => {
+                kops_aws_eks::create_cluster_token(&sdk_config, &eks.name)
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        // EKS accepts a token for 15 minutes, whatever it is signed for
+        let expires_at = (Utc::now() + chrono::Duration::minutes(14))
+            .min(session.expires_at);
+        match token {
+            Ok(token) => Response::ClusterToken {
+                token,
+                expires_at_epoch_ms: expires_at.timestamp_millis(),
+            },
+            Err(err) => Response::Error(
+                KopsError::internal(format!(
+                    "failed to create a token for EKS cluster {}: {err:#}",
+                    eks.name
+                ))
+                .with_cluster(cluster.name()),
+            ),
+        }
+    }
 
-        // let container_name = req.container.clone().unwrap_or_else(|| {
-        //     spec.containers[0].name.clone() // default: first container
-        // });
+    fn handle_cluster_remove(&self, name: &str) -> Response {
+        let Some(cluster) = self.state.remove_cluster(name) else {
+            return Response::Error(
+                KopsError::new(
+                    ErrorCode::ClusterNotFound,
+                    format!("cluster not found: {name}"),
+                )
+                .with_cluster(name),
+            );
+        };
 
-        for container in spec.containers.clone() {
-            let container_vars: Vec<EnvEntry> = container
-                .env
-                .clone()
-                .unwrap_or_default()
-                .into_iter()
-                .filter(|_e| {
-                    // if let Some(re) = &regex { re.is_match(&e.name) } else { true }
-                    true
-                })
-                .map(|e| EnvEntry { name: e.name, value: e.value })
-                .collect();
-            vars.extend(container_vars);
-        }
-
-        vars.sort();
-
-        // let container =
-        //     match spec.containers.iter().find(|c| c.name == container_name) {
-        //         Some(c) => c,
-        //         None => {
-        //             return Response::Error {
-        //                 message: format!(
-        //                     "container '{}' not found in pod {}",
-        //                     container_name, req.pod
-        //                 ),
-        //             };
-        //         }
-        //     };
-
-        // filtrar vars
-        // let regex = match req.filter_regex {
-        //     Some(r) => Some(match Regex::new(&r) {
-        //         Ok(re) => re,
-        //         Err(err) => {
-        //             return Response::Error {
-        //                 message: format!("invalid regex: {err}"),
-        //             };
-        //         }
-        //     }),
-        //     None => None,
-        // };
-
-        // let vars: Vec<EnvEntry> = container
-        //     .env
-        //     .clone()
-        //     .unwrap_or_default()
-        //     .into_iter()
-        //     .filter(|_e| {
-        //         // if let Some(re) = &regex { re.is_match(&e.name) } else { true }
-        //         true
-        //     })
-        //     .map(|e| EnvEntry { name: e.name, value: e.value })
-        //     .collect();
+        info!("stopping cluster worker for cluster '{name}'");
+        cluster.stop();
 
-        Response::EnvVars { vars }
+        Response::ClusterRemoved
     }
 
-    async fn handle_version(&self) -> Response {
-        let daemon_version = env!("CARGO_PKG_VERSION").to_string();
-        let protocol_version = "1".to_string();
+    fn handle_clusters(&self) -> Response {
+        let started = self.state.clusters();
 
-        let git_sha = option_env!("GIT_HASH").map(|s| s.to_string());
-        let build_date = option_env!("BUILD_DATE").map(|s| s.to_string());
+        let default = self.state.default_cluster();
+        let mut clusters: Vec<ClusterSummary> = started
+            .iter()
+            .map(|c| ClusterSummary {
+                name: c.name().to_string(),
+                profile: c.profile().map(str::to_string),
+                default: Some(c.name()) == default,
+                started: true,
+                running: c.running(),
+                objects: c.store_sizes().values().sum::<usize>() as u64,
+                last_sync_epoch_ms: c
+                    .last_sync()
+                    .map(|t| t.timestamp_millis()),
+                started_at_epoch_ms: Some(c.started_at().timestamp_millis()),
+            })
+            .collect();
 
-        let info = kops_protocol::VersionInfo {
-            daemon_version,
-            protocol_version,
-            git_sha,
-            build_date,
-        };
+        for cfg in self.state.configured() {
+            if started.iter().any(|c| c.name() == cfg.name) {
+                continue;
+            }
+            clusters.push(ClusterSummary {
+                name: cfg.name.clone(),
+                profile: cfg.profile,
+                default: Some(cfg.name.as_str()) == default,
+                started: false,
+                running: false,
+                objects: 0,
+                last_sync_epoch_ms: None,
+                started_at_epoch_ms: None,
+            });
+        }
 
-        Response::Version(info)
+        clusters.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Response::Clusters { clusters }
     }
 
-    async fn handle_pods(&self, req: PodsRequest) -> Response {
-        let cluster_name = req
-            .cluster
-            .as_deref()
-            .unwrap_or_else(|| self.state.default_cluster());
+    async fn handle_reload(&self) -> Response {
+        match reload::reload(&self.state).await {
+            Ok(r) => Response::Reloaded {
+                started: r.started,
+                stopped: r.stopped,
+                failed: r.failed,
+            },
+            Err(err) => Response::Error(KopsError::internal(format!(
+                "failed to reload config: {err:#}"
+            ))),
+        }
+    }
 
-        let clusters = self.state.clusters.lock().unwrap();
-        let Some(cluster_state) = clusters.get(cluster_name) else {
-            return Response::Error {
-                message: format!("cluster not found: {cluster_name}"),
-            };
+    async fn handle_env(&self, req: EnvRequest) -> Response {
+        let cs = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
         };
 
-        // let mut pods: Vec<PodSummary> = Vec::new();
-        let pods_snapshot = cluster_state.store().state();
-        // for pod in pods_snapshot {
-        //     if let Some(summary) = PodSummary::from_pod(cluster_name, &pod) {
-        //         pods.push(summary);
-        //     }
-        // }
+        let pod = match cs.full_pod(&req.namespace, &req.pod).await {
+            Ok(pod) => pod,
+            Err(err) => return Response::Error(api_error(err, "get pod")),
+        };
 
-        // // let map = cluster_state.pods.read().await;
-        // let map = cluster_state.store().state();
+        let Some(pod) = pod else {
+            return Response::Error(KopsError::not_found(format!(
+                "pod {}/{} not found",
+                req.namespace, req.pod
+            )));
+        };
+
+        let spec = match &pod.spec {
+            Some(s) => s,
+            None => {
+                return Response::Error(KopsError::internal(
+                    "pod has no spec",
+                ));
+            }
+        };
+
+        let containers: Vec<&Container> = if req.all_containers {
+            spec.containers.iter().collect()
+        } else {
+            // Like kubectl: the named container, else the one in the
+            // default-container annotation, else the first one.
+            let wanted = req.container.clone().or_else(|| {
+                pod.annotations().get(DEFAULT_CONTAINER_ANNOTATION).cloned()
+            });
+            let found = match &wanted {
+                Some(name) => spec.containers.iter().find(|c| &c.name == name),
+                None => spec.containers.first(),
+            };
+            let Some(container) = found else {
+                let names: Vec<&str> =
+                    spec.containers.iter().map(|c| c.name.as_str()).collect();
+                return Response::Error(KopsError::not_found(format!(
+                    "container {} not found in {}/{}, available: {}",
+                    wanted.unwrap_or_default(),
+                    req.namespace,
+                    req.pod,
+                    names.join(", ")
+                )));
+            };
+            vec![container]
+        };
+
+        let regex = match req.filter_regex.as_deref().map(Regex::new) {
+            Some(Ok(re)) => Some(re),
+            Some(Err(err)) => {
+                return Response::Error(KopsError::invalid(format!(
+                    "invalid filter regex: {err}"
+                )));
+            }
+            None => None,
+        };
+        let wanted =
+            |name: &str| regex.as_ref().is_none_or(|re| re.is_match(name));
+
+        if req.reveal_secrets {
+            info!(
+                cluster = cs.name(),
+                "revealing env secrets of {}/{}", req.namespace, req.pod
+            );
+        }
+        let mut resolver =
+            EnvResolver::new(cs.client(), &req.namespace, req.reveal_secrets);
+
+        // Filtered before resolving, so that only the references of the
+        // wanted variables are read; an `envFrom` source is read whole to
+        // learn its names.
+        let mut vars: Vec<EnvEntry> = Vec::new();
+        for c in containers {
+            // `envFrom` comes first; `env` entries override it.
+            let env_from = match &c.env_from {
+                Some(sources) if req.resolve => sources.as_slice(),
+                _ => &[],
+            };
+            for from in env_from {
+                match resolver.expand(&c.name, from).await {
+                    Ok(expanded) => vars.extend(
+                        expanded.into_iter().filter(|e| wanted(&e.name)),
+                    ),
+                    Err(err) => {
+                        return Response::Error(api_error(
+                            err,
+                            "read envFrom source",
+                        ));
+                    }
+                }
+            }
+
+            for e in c.env.iter().flatten().filter(|e| wanted(&e.name)) {
+                let mut entry = EnvEntry {
+                    container: c.name.clone(),
+                    name: e.name.clone(),
+                    value: e.value.clone(),
+                    source: env_source(e),
+                    masked: false,
+                };
+                if req.resolve
+                    && let Err(err) =
+                        resolver.resolve(&pod, c, e, &mut entry).await
+                {
+                    let action = format!("resolve {}", e.name);
+                    return Response::Error(api_error(err, &action));
+                }
+                vars.push(entry);
+            }
+        }
+
+        vars.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::EnvVars { vars }
+    }
+
+    /// Variables are matched as the pod specs define them, never resolved,
+    /// so Secret values stay out of the search.
+    async fn handle_env_grep(&self, req: EnvGrepRequest) -> Response {
+        let cs = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let regex = match Regex::new(&req.pattern) {
+            Ok(re) => re,
+            Err(err) => {
+                return Response::Error(KopsError::invalid(format!(
+                    "invalid regex: {err}"
+                )));
+            }
+        };
+
+        let pods = match cs.full_pods(req.namespace.as_deref()).await {
+            Ok(pods) => pods,
+            Err(err) => return Response::Error(api_error(err, "list pods")),
+        };
+
+        let mut matches: Vec<EnvMatch> = Vec::new();
+        for pod in &pods {
+            let (Some(namespace), Some(pod_name), Some(spec)) =
+                (&pod.metadata.namespace, &pod.metadata.name, &pod.spec)
+            else {
+                continue;
+            };
+
+            let containers =
+                spec.init_containers.iter().flatten().chain(&spec.containers);
+            for c in containers {
+                for e in c.env.iter().flatten() {
+                    let source = env_source(e);
+                    let referenced = match &source {
+                        EnvSource::ConfigMap { name, key }
+                        | EnvSource::Secret { name, key } => {
+                            regex.is_match(name) || regex.is_match(key)
+                        }
+                        _ => false,
+                    };
+                    let value = e.value.as_deref();
+                    if !referenced
+                        && !regex.is_match(&e.name)
+                        && !value.is_some_and(|v| regex.is_match(v))
+                    {
+                        continue;
+                    }
+
+                    matches.push(EnvMatch {
+                        namespace: namespace.clone(),
+                        pod: pod_name.clone(),
+                        var: EnvEntry {
+                            container: c.name.clone(),
+                            name: e.name.clone(),
+                            value: e.value.clone(),
+                            source,
+                            masked: false,
+                        },
+                    });
+                }
+            }
+        }
+        matches.sort();
+
+        Response::EnvMatches { matches }
+    }
+
+    async fn handle_version(&self) -> Response {
+        let daemon_version = env!("CARGO_PKG_VERSION").to_string();
+        let protocol_version = PROTOCOL_VERSION.to_string();
+
+        let git_sha = option_env!("GIT_HASH").map(|s| s.to_string());
+        let build_date = option_env!("BUILD_DATE").map(|s| s.to_string());
+
+        let info = kops_protocol::VersionInfo {
+            daemon_version,
+            protocol_version,
+            git_sha,
+            build_date,
+        };
+
+        Response::Version(info)
+    }
+
+    fn handle_status(&self) -> Response {
+        let mut clusters: Vec<ClusterStatus> =
+            self.state.clusters().iter().map(|c| cluster_status(c)).collect();
+        clusters.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let sessions = self.sessions();
+
+        let mut clients: Vec<ConnectionStatus> = self
+            .state
+            .clients
+            .iter()
+            .map(|c| ConnectionStatus {
+                id: *c.key(),
+                uid: c.uid,
+                pid: c.pid,
+                connected_secs: c.since.elapsed().as_secs(),
+                traffic: c.traffic.snapshot(),
+            })
+            .collect();
+        clients.sort_by_key(|c| c.id);
+
+        let mut users: Vec<UserTraffic> = self
+            .state
+            .user_traffic
+            .iter()
+            .map(|u| UserTraffic {
+                uid: *u.key(),
+                connections: clients
+                    .iter()
+                    .filter(|c| c.uid == *u.key())
+                    .count() as u32,
+                traffic: u.snapshot(),
+            })
+            .collect();
+        users.sort_by_key(|u| u.uid);
+
+        let connections = self.state.connections.load(Ordering::Relaxed);
+        Response::Status(DaemonStatus {
+            pid: std::process::id(),
+            uptime_secs: self.state.started_at.elapsed().as_secs(),
+            connections: connections as u32,
+            clusters,
+            sessions,
+            clients,
+            users,
+        })
+    }
+
+    fn handle_shutdown(&self) -> Response {
+        warn!("shutdown requested by a client, shutting down gracefully...");
+        self.state.shutdown.cancel();
+
+        Response::ShuttingDown { pid: std::process::id() }
+    }
+
+    fn handle_sessions(&self) -> Response {
+        Response::Sessions { sessions: self.sessions() }
+    }
+
+    /// Stored AWS sessions, sorted by profile.
+    fn sessions(&self) -> Vec<SessionStatus> {
+        let mut sessions: Vec<SessionStatus> = self
+            .state
+            .sessions()
+            .into_iter()
+            .map(|(profile, s)| SessionStatus {
+                profile,
+                expires_at_epoch_ms: s.expires_at.timestamp_millis(),
+            })
+            .collect();
+        sessions.sort_by(|a, b| a.profile.cmp(&b.profile));
+
+        sessions
+    }
+
+    fn handle_session_credentials(&self, profile: &str) -> Response {
+        let Some(session) = self.state.session(profile) else {
+            return Response::Error(KopsError::not_found(format!(
+                "no AWS session stored for profile {profile}"
+            )));
+        };
+        if session.expires_at <= Utc::now() {
+            return Response::Error(KopsError::new(
+                ErrorCode::CredentialsExpired,
+                format!("AWS session of profile {profile} expired"),
+            ));
+        }
+        info!("exported AWS credentials of profile '{profile}'");
+
+        Response::SessionCredentials(SessionCredentials {
+            profile: profile.to_string(),
+            account_id: session.account_id,
+            role_name: session.role_name,
+            region: session.region,
+            access_key_id: session.access_key_id,
+            secret_access_key: session.secret_access_key,
+            session_token: session.session_token,
+            expires_at_epoch_ms: session.expires_at.timestamp_millis(),
+        })
+    }
+
+    fn handle_session_health(&self) -> Response {
+        let now = Utc::now();
+        let clusters = self.state.clusters();
+
+        let mut expiring: Vec<ExpiringSession> = self
+            .state
+            .sessions()
+            .into_iter()
+            .filter(|(_, s)| {
+                let left = (s.expires_at - now).num_milliseconds();
+                left > 0 && left <= EXPIRING_WITHIN.as_millis() as i64
+            })
+            .map(|(profile, s)| {
+                let mut names: Vec<String> = clusters
+                    .iter()
+                    .filter(|c| c.profile() == Some(profile.as_str()))
+                    .map(|c| c.name().to_string())
+                    .collect();
+                names.sort();
+
+                ExpiringSession {
+                    profile,
+                    expires_at_epoch_ms: s.expires_at.timestamp_millis(),
+                    clusters: names,
+                }
+            })
+            .collect();
+        expiring.sort_by(|a, b| a.profile.cmp(&b.profile));
+
+        Response::SessionHealth { expiring }
+    }
+
+    fn handle_logout(&self, profile: &str) -> Response {
+        if self.state.remove_session(profile).is_none() {
+            return Response::Error(KopsError::not_found(format!(
+                "no AWS session stored for profile {profile}"
+            )));
+        }
+        info!("removed AWS session for profile '{profile}'");
+
+        let stopped = self.state.remove_profile_clusters(profile);
+
+        let mut stopped_clusters = Vec::with_capacity(stopped.len());
+        for cluster in stopped {
+            info!("stopping cluster worker for cluster '{}'", cluster.name());
+            cluster.stop();
+            stopped_clusters.push(cluster.name().to_string());
+        }
+        stopped_clusters.sort();
+
+        self.state.bus.publish(Notification::SessionRemoved {
+            profile: profile.to_string(),
+        });
+
+        Response::LoggedOut { stopped_clusters }
+    }
+
+    async fn handle_pods(&self, req: PodsRequest) -> Response {
+        let filter = match PodFilter::new(&req) {
+            Ok(f) => f,
+            Err(err) => return Response::Error(err),
+        };
+
+        let clusters =
+            match self.scope(req.cluster.as_deref(), req.all_clusters) {
+                Ok(c) => c,
+                Err(err) => return Response::Error(err),
+            };
+
+        let mut pods: Vec<PodSummary> =
+            clusters.iter().flat_map(|c| matching_pods(c, &filter)).collect();
+
+        pods.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        let continue_token = match page::paginate(
+            &mut pods,
+            PodSummary::sort_key,
+            req.limit,
+            req.continue_token.as_deref(),
+        ) {
+            Ok(token) => token,
+            Err(err) => return Response::Error(err),
+        };
+
+        Response::Pods { pods, continue_token }
+    }
+
+    fn handle_restarts(&self, req: RestartsRequest) -> Response {
+        let clusters = match self.clusters(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let since_ms = Utc::now()
+            .timestamp_millis()
+            .saturating_sub(req.since_seconds.saturating_mul(1000));
+
+        let mut restarts: Vec<RestartSummary> = Vec::new();
+        for cluster in &clusters {
+            // (namespace, pod, container) -> summary
+            let mut by_container: HashMap<
+                (String, String, String),
+                RestartSummary,
+            > = HashMap::new();
+
+            for r in cluster.restarts().since(since_ms) {
+                if req.namespace.as_ref().is_some_and(|ns| *ns != r.namespace)
+                {
+                    continue;
+                }
+
+                let key =
+                    (r.namespace.clone(), r.pod.clone(), r.container.clone());
+                let summary = by_container.entry(key).or_insert_with(|| {
+                    RestartSummary {
+                        cluster: cluster.name().to_string(),
+                        namespace: r.namespace.clone(),
+                        pod: r.pod.clone(),
+                        container: r.container.clone(),
+                        restarts: 0,
+                        last_restart_epoch_ms: r.at_epoch_ms,
+                        reason: None,
+                        exit_code: None,
+                    }
+                });
+
+                summary.restarts += r.count;
+                if r.at_epoch_ms >= summary.last_restart_epoch_ms {
+                    summary.last_restart_epoch_ms = r.at_epoch_ms;
+                    summary.reason = r.reason;
+                    summary.exit_code = r.exit_code;
+                }
+            }
+
+            restarts.extend(by_container.into_values());
+        }
+
+        restarts.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Restarts { restarts }
+    }
+
+    fn handle_restart_history(&self, req: RestartHistoryRequest) -> Response {
+        let clusters = match self.clusters(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut restarts = Vec::new();
+        for cluster in &clusters {
+            let records = cluster.restarts().of_pod(&req.namespace, &req.pod);
+            if records.is_empty() {
+                continue;
+            }
+
+            // Causes are looked up now rather than on every restart, which
+            // would hold up the pod watcher.
+            let events = cluster.events();
+            let mut warnings = events.about(&format!("Pod/{}", req.pod));
+            let mut nodes = HashSet::new();
+            for node in records.iter().filter_map(|r| r.node.as_ref()) {
+                if nodes.insert(node) {
+                    warnings.extend(events.about(&format!("Node/{node}")));
+                }
+            }
+
+            restarts.extend(records.into_iter().map(|r| RestartEntry {
+                cluster: cluster.name().to_string(),
+                causes: restarts::causes(&r, &warnings),
+                namespace: r.namespace,
+                pod: r.pod,
+                container: r.container,
+                at_epoch_ms: r.at_epoch_ms,
+                count: r.count,
+                reason: r.reason,
+                exit_code: r.exit_code,
+            }));
+        }
+
+        restarts.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::RestartHistory { restarts }
+    }
+
+    fn handle_nodes(&self, req: NodesRequest) -> Response {
+        let clusters = match self.clusters(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut nodes: Vec<NodeSummary> = clusters
+            .iter()
+            .flat_map(|c| {
+                c.nodes()
+                    .state()
+                    .into_iter()
+                    .filter_map(move |n| NodeSummary::from_node(c.name(), &n))
+            })
+            .collect();
+
+        nodes.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Nodes { nodes }
+    }
+
+    fn handle_services(&self, req: ServicesRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let in_namespace = |ns: Option<&String>| {
+            req.namespace.is_none() || ns == req.namespace.as_ref()
+        };
+
+        // (namespace, service) -> (ready, total)
+        let mut endpoints: HashMap<(String, String), (u32, u32)> =
+            HashMap::new();
+        for slice in cluster.endpoint_slices().state() {
+            if !in_namespace(slice.metadata.namespace.as_ref()) {
+                continue;
+            }
+            let Some(service) = slice
+                .metadata
+                .labels
+                .as_ref()
+                .and_then(|l| l.get("kubernetes.io/service-name"))
+            else {
+                continue;
+            };
+
+            let key = (
+                slice.metadata.namespace.clone().unwrap_or_default(),
+                service.clone(),
+            );
+            let count = endpoints.entry(key).or_default();
+            for ep in &slice.endpoints {
+                // A missing `ready` condition means ready.
+                let ready = ep
+                    .conditions
+                    .as_ref()
+                    .and_then(|c| c.ready)
+                    .unwrap_or(true);
+                count.0 += ready as u32;
+                count.1 += 1;
+            }
+        }
+
+        let mut services: Vec<ServiceSummary> = cluster
+            .services()
+            .state()
+            .into_iter()
+            .filter(|s| in_namespace(s.metadata.namespace.as_ref()))
+            .filter_map(|s| {
+                let key = (
+                    s.metadata.namespace.clone().unwrap_or_default(),
+                    s.metadata.name.clone().unwrap_or_default(),
+                );
+                let counts = endpoints.get(&key).copied().unwrap_or_default();
+                ServiceSummary::from_service(cluster.name(), &s, counts)
+            })
+            .collect();
+
+        services.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Services { services }
+    }
+
+    fn handle_ingresses(&self, req: IngressesRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut ingresses: Vec<IngressSummary> = cluster
+            .ingresses()
+            .state()
+            .into_iter()
+            .filter_map(|i| IngressSummary::from_ingress(cluster.name(), &i))
+            .filter(|i| {
+                req.namespace.as_ref().is_none_or(|ns| &i.namespace == ns)
+            })
+            .collect();
+
+        ingresses.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Ingresses { ingresses }
+    }
+
+    fn handle_stateful_sets(&self, req: WorkloadsRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut stateful_sets: Vec<StatefulSetSummary> = cluster
+            .stateful_sets()
+            .state()
+            .into_iter()
+            .filter_map(|s| {
+                StatefulSetSummary::from_stateful_set(cluster.name(), &s)
+            })
+            .filter(|s| {
+                req.namespace.as_ref().is_none_or(|ns| &s.namespace == ns)
+            })
+            .collect();
+
+        stateful_sets.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::StatefulSets { stateful_sets }
+    }
+
+    fn handle_daemon_sets(&self, req: WorkloadsRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut daemon_sets: Vec<DaemonSetSummary> = cluster
+            .daemon_sets()
+            .state()
+            .into_iter()
+            .filter_map(|d| {
+                DaemonSetSummary::from_daemon_set(cluster.name(), &d)
+            })
+            .filter(|d| {
+                req.namespace.as_ref().is_none_or(|ns| &d.namespace == ns)
+            })
+            .collect();
+
+        daemon_sets.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::DaemonSets { daemon_sets }
+    }
+
+    fn handle_hpas(&self, req: WorkloadsRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut hpas: Vec<HpaSummary> = cluster
+            .hpas()
+            .state()
+            .into_iter()
+            .filter_map(|h| HpaSummary::from_hpa(cluster.name(), &h))
+            .filter(|h| {
+                req.namespace.as_ref().is_none_or(|ns| &h.namespace == ns)
+            })
+            .collect();
+
+        hpas.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Hpas { hpas }
+    }
+
+    fn handle_pvcs(&self, req: WorkloadsRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let in_namespace = |ns: Option<&String>| {
+            req.namespace.is_none() || ns == req.namespace.as_ref()
+        };
+
+        // (namespace, claim) -> pods mounting it
+        let mut consumers: HashMap<(String, String), Vec<String>> =
+            HashMap::new();
+        for pod in cluster.store().state() {
+            if !in_namespace(pod.metadata.namespace.as_ref()) {
+                continue;
+            }
+            let volumes = pod.spec.as_ref().and_then(|s| s.volumes.as_ref());
+            for claim in volumes
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.persistent_volume_claim.as_ref())
+            {
+                let key = (
+                    pod.namespace().unwrap_or_default(),
+                    claim.claim_name.clone(),
+                );
+                consumers.entry(key).or_default().push(pod.name_any());
+            }
+        }
+
+        let mut pvcs: Vec<PvcSummary> = cluster
+            .pvcs()
+            .state()
+            .into_iter()
+            .filter(|p| in_namespace(p.metadata.namespace.as_ref()))
+            .filter_map(|p| {
+                let key = (p.namespace().unwrap_or_default(), p.name_any());
+                let mut pods = consumers.remove(&key).unwrap_or_default();
+                pods.sort();
+                PvcSummary::from_pvc(cluster.name(), &p, pods)
+            })
+            .collect();
+
+        pvcs.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Pvcs { pvcs }
+    }
+
+    fn handle_images(&self, req: WorkloadsRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let in_namespace = |ns: Option<&String>| {
+            req.namespace.is_none() || ns == req.namespace.as_ref()
+        };
+
+        // image -> (pods, namespaces)
+        let mut usage: HashMap<String, (u32, BTreeSet<String>)> =
+            HashMap::new();
+        for pod in cluster.store().state() {
+            if !in_namespace(pod.metadata.namespace.as_ref()) {
+                continue;
+            }
+
+            let phase = pod.status.as_ref().and_then(|s| s.phase.as_deref());
+            if matches!(phase, Some("Succeeded" | "Failed")) {
+                continue;
+            }
+
+            // Count a pod once per image, even with several containers.
+            let images: BTreeSet<&str> = pod
+                .spec
+                .iter()
+                .flat_map(|s| &s.containers)
+                .filter_map(|c| c.image.as_deref())
+                .collect();
+
+            for image in images {
+                let entry = usage.entry(image.to_string()).or_default();
+                entry.0 += 1;
+                entry.1.insert(pod.namespace().unwrap_or_default());
+            }
+        }
+
+        let mut images: Vec<ImageUsage> = usage
+            .into_iter()
+            .map(|(image, (pods, namespaces))| {
+                let (repository, tag) = ImageUsage::split_image(&image);
+                ImageUsage {
+                    cluster: cluster.name().to_string(),
+                    repository: repository.to_string(),
+                    tag: tag.to_string(),
+                    pods,
+                    namespaces: namespaces.into_iter().collect(),
+                }
+            })
+            .collect();
+
+        images.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Images { images }
+    }
+
+    fn handle_jobs(&self, req: JobsRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut jobs: Vec<JobSummary> = cluster
+            .jobs()
+            .state()
+            .into_iter()
+            .filter_map(|j| JobSummary::from_job(cluster.name(), &j))
+            .filter(|j| {
+                req.namespace.as_ref().is_none_or(|ns| &j.namespace == ns)
+            })
+            .filter(|j| !req.failed_only || j.status == "Failed")
+            .collect();
+
+        jobs.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Jobs { jobs }
+    }
+
+    fn handle_cron_jobs(&self, req: CronJobsRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut cron_jobs: Vec<CronJobSummary> = cluster
+            .cron_jobs()
+            .state()
+            .into_iter()
+            .filter_map(|c| CronJobSummary::from_cron_job(cluster.name(), &c))
+            .filter(|c| {
+                req.namespace.as_ref().is_none_or(|ns| &c.namespace == ns)
+            })
+            .collect();
+
+        cron_jobs.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::CronJobs { cron_jobs }
+    }
+
+    fn handle_namespaces(&self, req: NamespacesRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut namespaces: Vec<NamespaceSummary> = cluster
+            .namespaces()
+            .state()
+            .into_iter()
+            .filter_map(|n| {
+                NamespaceSummary::from_namespace(cluster.name(), &n)
+            })
+            .collect();
+
+        namespaces.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Namespaces { namespaces }
+    }
+
+    async fn handle_describe_pod(&self, req: DescribePodRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
 
-        let mut pods: Vec<PodSummary> = pods_snapshot
+        let pod = match cluster.full_pod(&req.namespace, &req.pod).await {
+            Ok(pod) => pod,
+            Err(err) => return Response::Error(api_error(err, "get pod")),
+        };
+
+        let Some(pod) = pod else {
+            return Response::Error(KopsError::not_found(format!(
+                "pod not found: {}/{}",
+                req.namespace, req.pod
+            )));
+        };
+
+        let mut events: Vec<EventSummary> = cluster
+            .events()
+            .about(&format!("Pod/{}", req.pod))
             .into_iter()
-            .filter_map(|p| PodSummary::from_pod(cluster_name, &p))
-            .filter(|p| {
-                if let Some(ns) = &req.namespace {
-                    if &p.namespace != ns {
-                        return false;
+            .filter(|e| e.namespace == req.namespace)
+            .collect();
+        events.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        match PodDetail::from_pod(cluster.name(), &pod, events) {
+            Some(mut detail) => {
+                detail.ownership = pod_ownership(&cluster, &pod);
+                Response::PodDetail(Box::new(detail))
+            }
+            None => Response::Error(KopsError::internal(format!(
+                "pod has no name: {}",
+                req.pod
+            ))),
+        }
+    }
+
+    async fn handle_get(&self, req: GetRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let kind = req.kind.name();
+        let namespace =
+            req.namespace.as_deref().filter(|_| req.kind.namespaced());
+        let target = match namespace {
+            Some(ns) => format!("{ns}/{}", req.name),
+            None if req.kind.namespaced() => {
+                return Response::Error(KopsError::invalid(format!(
+                    "a {kind} is looked up within a namespace"
+                )));
+            }
+            None => req.name.clone(),
+        };
+
+        match manifest::get(&cluster, req.kind, namespace, &req.name).await {
+            Ok(Some(object)) => Response::Manifest { object },
+            Ok(None) => Response::Error(KopsError::not_found(format!(
+                "{kind} not found: {target}"
+            ))),
+            Err(err) => {
+                Response::Error(api_error(err, &format!("get {kind}")))
+            }
+        }
+    }
+
+    async fn handle_rollout_restart(
+        &self,
+        req: RolloutRestartRequest,
+    ) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let api: Api<Deployment> =
+            Api::namespaced(cluster.client(), &req.namespace);
+
+        let restarted_at = Utc::now().to_rfc3339();
+        let patch = serde_json::json!({
+            "spec": {
+                "template": {
+                    "metadata": {
+                        "annotations": {
+                            "kubectl.kubernetes.io/restartedAt": restarted_at,
+                        }
                     }
                 }
-                if req.failed_only {
-                    if p.phase.as_deref() != Some("Failed")
-                        && p.reason.as_deref() != Some("CrashLoopBackOff")
-                    {
-                        return false;
-                    }
+            }
+        });
+
+        let target =
+            format!("deployment {}/{}", req.namespace, req.deployment);
+        info!(cluster = cluster.name(), "restarting {target}");
+
+        if let Err(err) = api
+            .patch(
+                &req.deployment,
+                &PatchParams::default(),
+                &Patch::Merge(&patch),
+            )
+            .await
+        {
+            return Response::Error(api_error(
+                err,
+                &format!("patch {target}"),
+            ));
+        }
+
+        if let Some(secs) = req.wait_timeout_seconds {
+            let wait = wait_rollout(&api, &req.deployment);
+            match tokio::time::timeout(Duration::from_secs(secs), wait).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => {
+                    return Response::Error(api_error(
+                        err,
+                        &format!("watch {target}"),
+                    ));
+                }
+                Err(_) => {
+                    let message = format!(
+                        "timed out after {secs}s waiting for {target} to \
+                         roll out"
+                    );
+                    return Response::Error(
+                        KopsError::new(ErrorCode::KubeApi, message)
+                            .retryable(),
+                    );
+                }
+            }
+        }
+
+        Response::RolloutRestarted { restarted_at }
+    }
+
+    async fn handle_delete_pod(&self, req: DeletePodRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let pods: Api<Pod> = Api::namespaced(cluster.client(), &req.namespace);
+
+        let params = DeleteParams {
+            grace_period_seconds: if req.force {
+                Some(0)
+            } else {
+                req.grace_period_seconds
+            },
+            ..DeleteParams::default()
+        };
+
+        let target = format!("pod {}/{}", req.namespace, req.pod);
+        info!(
+            cluster = cluster.name(),
+            force = req.force,
+            "deleting {target}"
+        );
+
+        match pods.delete(&req.pod, &params).await {
+            // The API returns the object while it is still terminating and
+            // a Status once it is gone.
+            Ok(either) => Response::PodDeleted { deleted: either.is_right() },
+            Err(err) => {
+                Response::Error(api_error(err, &format!("delete {target}")))
+            }
+        }
+    }
+
+    async fn handle_scale(&self, req: ScaleRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let api: Api<Deployment> =
+            Api::namespaced(cluster.client(), &req.namespace);
+
+        let target =
+            format!("deployment {}/{}", req.namespace, req.deployment);
+
+        let old_replicas = match api.get_scale(&req.deployment).await {
+            Ok(scale) => scale.spec.and_then(|s| s.replicas).unwrap_or(0),
+            Err(err) => {
+                return Response::Error(api_error(
+                    err,
+                    &format!("read scale of {target}"),
+                ));
+            }
+        };
+
+        info!(
+            cluster = cluster.name(),
+            "scaling {target} from {old_replicas} to {}", req.replicas
+        );
+
+        let patch =
+            serde_json::json!({ "spec": { "replicas": req.replicas } });
+        match api
+            .patch_scale(
+                &req.deployment,
+                &PatchParams::default(),
+                &Patch::Merge(&patch),
+            )
+            .await
+        {
+            Ok(scale) => Response::Scaled {
+                old_replicas,
+                new_replicas: scale
+                    .spec
+                    .and_then(|s| s.replicas)
+                    .unwrap_or(req.replicas),
+            },
+            Err(err) => {
+                Response::Error(api_error(err, &format!("scale {target}")))
+            }
+        }
+    }
+
+    /// ConfigMaps are read from the API server on demand rather than kept
+    /// in a reflector: they are numerous, can be large and are rarely
+    /// looked at.
+    async fn handle_config_maps(&self, req: ConfigMapsRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let api: Api<ConfigMap> = match &req.namespace {
+            Some(ns) => Api::namespaced(cluster.client(), ns),
+            None => Api::all(cluster.client()),
+        };
+
+        let list = match api.list(&ListParams::default()).await {
+            Ok(list) => list,
+            Err(err) => {
+                return Response::Error(api_error(err, "list configmaps"));
+            }
+        };
+
+        let mut config_maps: Vec<ConfigMapSummary> = list
+            .items
+            .iter()
+            .filter_map(|cm| {
+                ConfigMapSummary::from_config_map(cluster.name(), cm)
+            })
+            .collect();
+
+        config_maps.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::ConfigMaps { config_maps }
+    }
+
+    async fn handle_config_map_data(
+        &self,
+        req: ConfigMapDataRequest,
+    ) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let api: Api<ConfigMap> =
+            Api::namespaced(cluster.client(), &req.namespace);
+
+        let target = format!("configmap {}/{}", req.namespace, req.name);
+        match api.get(&req.name).await {
+            Ok(cm) => match ConfigMapData::from_config_map(&cm) {
+                Some(data) => Response::ConfigMapData(data),
+                None => Response::Error(KopsError::internal(format!(
+                    "{target} has no name"
+                ))),
+            },
+            Err(err) => {
+                Response::Error(api_error(err, &format!("read {target}")))
+            }
+        }
+    }
+
+    async fn handle_metrics(&self, req: MetricsRequest) -> Response {
+        let clusters = match self.clusters(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let mut usage = Vec::new();
+        for cluster in clusters {
+            let result = match req.target {
+                MetricsTarget::Pods => {
+                    metrics::pod_usage(
+                        cluster.client(),
+                        cluster.name(),
+                        req.namespace.as_deref(),
+                    )
+                    .await
+                }
+                MetricsTarget::Nodes => {
+                    metrics::node_usage(cluster.client(), cluster.name()).await
+                }
+            };
+
+            match result {
+                Ok(mut u) => usage.append(&mut u),
+                Err(err) => {
+                    let action = format!(
+                        "read metrics.k8s.io on cluster {} \
+                         (is metrics-server installed?)",
+                        cluster.name()
+                    );
+                    return Response::Error(api_error(err, &action));
                 }
-                true
+            }
+        }
+
+        usage.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Metrics { usage }
+    }
+
+    /// Secrets are always read from the API server so that their values
+    /// never sit in the daemon memory.
+    async fn handle_secrets(&self, req: SecretsRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let api: Api<Secret> = match &req.namespace {
+            Some(ns) => Api::namespaced(cluster.client(), ns),
+            None => Api::all(cluster.client()),
+        };
+
+        let list = match api.list(&ListParams::default()).await {
+            Ok(list) => list,
+            Err(err) => {
+                return Response::Error(api_error(err, "list secrets"));
+            }
+        };
+
+        let mut secrets: Vec<SecretSummary> = list
+            .items
+            .iter()
+            .filter_map(|s| SecretSummary::from_secret(cluster.name(), s))
+            .collect();
+
+        secrets.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Secrets { secrets }
+    }
+
+    async fn handle_secret_value(&self, req: SecretValueRequest) -> Response {
+        let cluster = match self.cluster(req.cluster.as_deref()) {
+            Ok(c) => c,
+            Err(err) => return Response::Error(err),
+        };
+
+        let api: Api<Secret> =
+            Api::namespaced(cluster.client(), &req.namespace);
+
+        let target = format!("secret {}/{}", req.namespace, req.name);
+        info!(cluster = cluster.name(), key = %req.key, "revealing {target}");
+
+        let secret = match api.get(&req.name).await {
+            Ok(s) => s,
+            Err(err) => {
+                return Response::Error(api_error(
+                    err,
+                    &format!("read {target}"),
+                ));
+            }
+        };
+
+        // `ByteString` is already base64-decoded by k8s-openapi.
+        match secret.data.and_then(|mut d| d.remove(&req.key)) {
+            Some(value) => Response::SecretValue { value: value.0 },
+            None => Response::Error(KopsError::not_found(format!(
+                "key {} not found in {target}",
+                req.key
+            ))),
+        }
+    }
+
+    fn handle_deployments(&self, req: DeploymentsRequest) -> Response {
+        let clusters =
+            match self.scope(req.cluster.as_deref(), req.all_clusters) {
+                Ok(c) => c,
+                Err(err) => return Response::Error(err),
+            };
+
+        let mut deployments: Vec<DeploymentSummary> = clusters
+            .iter()
+            .flat_map(|c| {
+                c.deployments().state().into_iter().filter_map(move |d| {
+                    DeploymentSummary::from_deployment(c.name(), &d)
+                })
+            })
+            .filter(|d| {
+                req.namespace.as_ref().is_none_or(|ns| &d.namespace == ns)
             })
             .collect();
 
-        pods.sort_by(|a, b| {
-            a.namespace.cmp(&b.namespace).then(a.name.cmp(&b.name))
+        deployments.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Deployments { deployments }
+    }
+
+    fn handle_events(&self, req: EventsRequest) -> Response {
+        let clusters =
+            match self.scope(req.cluster.as_deref(), req.all_clusters) {
+                Ok(c) => c,
+                Err(err) => return Response::Error(err),
+            };
+
+        let not_before = req.since_seconds.map(|s| {
+            Utc::now()
+                .timestamp_millis()
+                .saturating_sub(s.saturating_mul(1000))
         });
 
-        Response::Pods { pods }
+        // Repetitions seen before the window still count towards the
+        // occurrences seen within it.
+        let mut events: Vec<EventSummary> = clusters
+            .iter()
+            .flat_map(|c| c.events().all())
+            .filter(|e| {
+                req.namespace.as_ref().is_none_or(|ns| &e.namespace == ns)
+            })
+            .filter(|e| {
+                req.involved_object
+                    .as_deref()
+                    .is_none_or(|obj| involves(&e.object, obj))
+            })
+            .filter(|e| {
+                not_before.is_none_or(|t| {
+                    e.last_seen_epoch_ms.is_some_and(|seen| seen >= t)
+                })
+            })
+            .collect();
+
+        events.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        Response::Events { events }
     }
 
     // async fn handle_reset(&self, cluster: Option<String>) -> Response {
@@ -345,37 +2311,427 @@ impl Handler {
     // }
 }
 
-use aws_config::{Region, SdkConfig};
-use aws_credential_types::{provider::SharedCredentialsProvider, Credentials};
-
-pub async fn sdk_config_from_session(
-    session: &AwsSession,
-) -> anyhow::Result<SdkConfig> {
-    // 1. Cria objeto Credentials a partir da sessão
-    let creds = Credentials::new(
-        session.access_key_id.clone(),
-        session.secret_access_key.clone(),
-        Some(session.session_token.clone()),
-        Some(session.expires_at.into()),
-        "kops-sso-session-dev",
-    );
-
-    let creds_provider = SharedCredentialsProvider::new(creds);
-
-    // 2. Resolve região
-    let region = session
-        .region
-        .clone()
-        .unwrap_or_else(|| "us-east-1".to_string());
-
-    let region = Region::new(region);
-
-    // 3. Monta o SdkConfig manualmente
-    let sdk_config = aws_config::from_env()
-        .region(region)
-        .credentials_provider(creds_provider)
-        .load()
-        .await;
-
-    Ok(sdk_config)
+/// Forward one client stream to `port` on the pod.
+///
+/// Bytes from the client arrive on `data`; bytes from the pod are sent back
+/// as `ForwardFrame::Data`. Ends with `ForwardFrame::Close` once either side
+/// closes.
+async fn forward_stream(
+    pods: Api<Pod>,
+    pod: String,
+    port: u16,
+    stream: u32,
+    mut data: mpsc::Receiver<Vec<u8>>,
+    tx: mpsc::Sender<ForwardFrame>,
+) {
+    let upstream = match pods.portforward(&pod, &[port]).await {
+        Ok(mut forwarder) => {
+            forwarder.take_stream(port).map(|s| (forwarder, s))
+        }
+        Err(err) => {
+            let message = format!("failed to forward to {pod}:{port}: {err}");
+            let _ = tx
+                .send(ForwardFrame::Error { stream: Some(stream), message })
+                .await;
+            None
+        }
+    };
+
+    if let Some((_forwarder, mut upstream)) = upstream {
+        let mut buf = vec![0u8; LOG_CHUNK_SIZE];
+
+        loop {
+            tokio::select! {
+                incoming = data.recv() => match incoming {
+                    Some(bytes) => {
+                        if upstream.write_all(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                },
+                read = upstream.read(&mut buf) => match read {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        let frame = ForwardFrame::Data {
+                            stream,
+                            data: buf[..n].to_vec(),
+                        };
+                        if tx.send(frame).await.is_err() {
+                            break;
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    let _ = tx.send(ForwardFrame::Close { stream }).await;
+}
+
+/// Copy a process output stream into `ExecFrame`s built with `frame`.
+async fn pump_output<R>(
+    reader: Option<R>,
+    tx: mpsc::Sender<ExecFrame>,
+    frame: fn(Vec<u8>) -> ExecFrame,
+) where
+    R: AsyncRead + Unpin,
+{
+    let Some(mut reader) = reader else {
+        return;
+    };
+
+    let mut buf = vec![0u8; LOG_CHUNK_SIZE];
+    loop {
+        match reader.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => {
+                if tx.send(frame(buf[..n].to_vec())).await.is_err() {
+                    break;
+                }
+            }
+            Err(err) => {
+                debug!("exec output stream failed: {err}");
+                break;
+            }
+        }
+    }
+}
+
+/// Poll a deployment until its latest generation is fully rolled out.
+async fn wait_rollout(
+    api: &Api<Deployment>,
+    name: &str,
+) -> Result<(), kube::Error> {
+    loop {
+        let deploy = api.get(name).await?;
+        if rollout_complete(&deploy) {
+            return Ok(());
+        }
+
+        tokio::time::sleep(ROLLOUT_POLL_INTERVAL).await;
+    }
+}
+
+/// Same criteria as `kubectl rollout status`.
+fn rollout_complete(deploy: &Deployment) -> bool {
+    let Some(status) = &deploy.status else {
+        return false;
+    };
+
+    let generation = deploy.metadata.generation.unwrap_or(0);
+    let desired = deploy.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+    let updated = status.updated_replicas.unwrap_or(0);
+
+    status.observed_generation.unwrap_or(0) >= generation
+        && updated == desired
+        && status.replicas.unwrap_or(0) == updated
+        && status.available_replicas.unwrap_or(0) == updated
+}
+
+/// Turn an API error into a message that tells permission problems apart.
+fn api_error(err: kube::Error, action: &str) -> KopsError {
+    let e = match err {
+        kube::Error::Api(e) => e,
+        kube::Error::Auth(err) => {
+            let message = format!("cannot authenticate to {action}: {err}");
+            return KopsError::new(ErrorCode::CredentialsExpired, message);
+        }
+        err => {
+            let message = format!("failed to {action}: {err}");
+            return KopsError::new(ErrorCode::KubeApi, message).retryable();
+        }
+    };
+
+    let (code, message) = match e.code {
+        401 => (
+            ErrorCode::CredentialsExpired,
+            format!("credentials rejected to {action}: {}", e.message),
+        ),
+        403 => (
+            ErrorCode::PermissionDenied,
+            format!("permission denied to {action}: {}", e.message),
+        ),
+        404 => {
+            (ErrorCode::NotFound, format!("cannot {action}: {}", e.message))
+        }
+        _ => {
+            (ErrorCode::KubeApi, format!("failed to {action}: {}", e.message))
+        }
+    };
+
+    let mut error =
+        KopsError::new(code, message).with_detail("status", e.code);
+    // Conflicts, throttling and server errors tend to go away.
+    error.retryable = matches!(e.code, 409 | 429 | 500..);
+    error
+}
+
+/// Exit code from the status reported by the kubelet at the end of exec.
+fn exit_code(status: Option<Status>) -> i32 {
+    let Some(status) = status else {
+        return 0;
+    };
+
+    if status.status.as_deref() == Some("Success") {
+        return 0;
+    }
+
+    status
+        .details
+        .and_then(|d| d.causes)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|c| c.reason.as_deref() == Some("ExitCode"))
+        .and_then(|c| c.message)
+        .and_then(|m| m.parse().ok())
+        .unwrap_or(1)
+}
+
+/// What a `PodsRequest` filters on, with its selectors parsed once.
+struct PodFilter {
+    namespace: Option<String>,
+    failed_only: bool,
+    labels: Option<LabelSelector>,
+    fields: Option<FieldSelector>,
+}
+
+impl PodFilter {
+    fn new(req: &PodsRequest) -> Result<Self, KopsError> {
+        let labels = req
+            .label_selector
+            .as_deref()
+            .map(LabelSelector::parse)
+            .transpose()
+            .map_err(KopsError::invalid)?;
+        let fields = req
+            .field_selector
+            .as_deref()
+            .map(FieldSelector::parse)
+            .transpose()
+            .map_err(KopsError::invalid)?;
+
+        Ok(Self {
+            namespace: req.namespace.clone(),
+            failed_only: req.failed_only,
+            labels,
+            fields,
+        })
+    }
+
+    /// Whether `pod`, summarized as `summary`, is part of the reply.
+    fn matches(&self, pod: &Pod, summary: &PodSummary) -> bool {
+        if let Some(ns) = &self.namespace
+            && &summary.namespace != ns
+        {
+            return false;
+        }
+
+        if self.failed_only && !summary.is_failing() {
+            return false;
+        }
+
+        self.labels.as_ref().is_none_or(|s| s.matches(pod.labels()))
+            && self.fields.as_ref().is_none_or(|s| s.matches(pod))
+    }
+}
+
+/// Pods of `cluster` matching `filter`, sorted.
+///
+/// Failing pods come with their latest warning event, which usually says
+/// why they fail, and with who to page about them.
+fn matching_pods(
+    cluster: &ClusterState,
+    filter: &PodFilter,
+) -> Vec<PodSummary> {
+    let mut warnings = if filter.failed_only {
+        latest_pod_warnings(cluster)
+    } else {
+        HashMap::new()
+    };
+
+    let mut pods: Vec<PodSummary> = cluster
+        .store()
+        .state()
+        .into_iter()
+        .filter_map(|p| {
+            let mut summary = PodSummary::from_pod(cluster.name(), &p)?;
+            if !filter.matches(&p, &summary) {
+                return None;
+            }
+
+            let key = (summary.namespace.clone(), summary.name.clone());
+            summary.last_warning = warnings.remove(&key);
+            if filter.failed_only {
+                summary.ownership = pod_ownership(cluster, &p);
+            }
+            Some(summary)
+        })
+        .collect();
+
+    pods.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+    pods
+}
+
+/// Who to page about `pod` of `cluster`, from its annotations, completed
+/// by those of its workload, then by those of its namespace.
+fn pod_ownership(cluster: &ClusterState, pod: &Pod) -> Option<Ownership> {
+    let namespace = pod.namespace().unwrap_or_default();
+    let mut ownership = Ownership::from_annotations(pod.annotations());
+
+    for owner in pod.owner_references() {
+        let workload = match owner.kind.as_str() {
+            // Named after their Deployment and pod template hash.
+            "ReplicaSet" => pod
+                .labels()
+                .get("pod-template-hash")
+                .and_then(|hash| owner.name.strip_suffix(&format!("-{hash}")))
+                .and_then(|name| {
+                    annotated(cluster.deployments(), &namespace, name)
+                }),
+            "StatefulSet" => {
+                annotated(cluster.stateful_sets(), &namespace, &owner.name)
+            }
+            "DaemonSet" => {
+                annotated(cluster.daemon_sets(), &namespace, &owner.name)
+            }
+            "Job" => annotated(cluster.jobs(), &namespace, &owner.name),
+            _ => None,
+        };
+        if let Some(workload) = workload {
+            ownership = ownership.or(workload);
+        }
+    }
+
+    if let Some(ns) = cluster.namespaces().get(&ObjectRef::new(&namespace)) {
+        ownership =
+            ownership.or(Ownership::from_annotations(ns.annotations()));
+    }
+
+    (!ownership.is_empty()).then_some(ownership)
+}
+
+/// Ownership annotations of object `name` of `store`, if it has it.
+fn annotated<K>(
+    store: &Store<K>,
+    namespace: &str,
+    name: &str,
+) -> Option<Ownership>
+where
+    K: Resource + Clone + 'static,
+    K::DynamicType: Default + Eq + std::hash::Hash + Clone,
+{
+    let obj = store.get(&ObjectRef::new(name).within(namespace))?;
+    Some(Ownership::from_annotations(obj.annotations()))
+}
+
+/// Message of the latest Warning event of each pod of `cluster`, by
+/// (namespace, name).
+fn latest_pod_warnings(
+    cluster: &ClusterState,
+) -> HashMap<(String, String), String> {
+    let mut latest: HashMap<(String, String), (Option<i64>, String)> =
+        HashMap::new();
+
+    for e in cluster.events().all() {
+        if e.type_ != "Warning" {
+            continue;
+        }
+        let Some(pod) = e.object.strip_prefix("Pod/") else {
+            continue;
+        };
+
+        let key = (e.namespace.clone(), pod.to_string());
+        let newer = latest
+            .get(&key)
+            .is_none_or(|(seen, _)| e.last_seen_epoch_ms >= *seen);
+        if newer {
+            latest.insert(key, (e.last_seen_epoch_ms, e.message));
+        }
+    }
+
+    latest.into_iter().map(|(key, (_, message))| (key, message)).collect()
+}
+
+/// Health of the reflectors of `cluster`, with the size of their stores.
+fn cluster_status(cluster: &ClusterState) -> ClusterStatus {
+    let sizes = cluster.store_sizes();
+
+    let reflectors = cluster
+        .health()
+        .snapshot()
+        .into_iter()
+        .map(|(kind, health)| {
+            let state = match health.error {
+                Some(err) => SyncState::Error(err),
+                None if health.synced => SyncState::Synced,
+                None => SyncState::Pending,
+            };
+
+            ReflectorStatus {
+                kind: kind.to_string(),
+                state,
+                objects: sizes.get(kind).copied().unwrap_or(0) as u64,
+                failures: health.failures,
+                last_event_epoch_ms: health
+                    .last_event
+                    .map(|t| t.timestamp_millis()),
+            }
+        })
+        .collect();
+
+    ClusterStatus {
+        name: cluster.name().to_string(),
+        degraded: cluster.degraded(),
+        credentials_expiring: cluster.credentials_expiring(),
+        reflectors,
+    }
+}
+
+/// Send the pods matching `filter` as a `Response::Pods` and return their
+/// (namespace, name) keys, or `None` if `tx` has no receiver.
+async fn send_pod_snapshot(
+    cluster: &ClusterState,
+    filter: &PodFilter,
+    tx: &mpsc::Sender<Response>,
+) -> Option<HashSet<(String, String)>> {
+    let pods = matching_pods(cluster, filter);
+    let known =
+        pods.iter().map(|p| (p.namespace.clone(), p.name.clone())).collect();
+
+    let resp = Response::Pods { pods, continue_token: None };
+    tx.send(resp).await.ok()?;
+
+    Some(known)
+}
+
+/// Whether an event `object` (`<Kind>/<name>`) matches a user filter given
+/// as `<name>` or `<kind>/<name>`; the kind is matched case-insensitively.
+fn involves(object: &str, filter: &str) -> bool {
+    let (kind, name) = object.split_once('/').unwrap_or(("", object));
+
+    match filter.split_once('/') {
+        Some((k, n)) => k.eq_ignore_ascii_case(kind) && n == name,
+        None => filter == name,
+    }
+}
+
+/// Classify where a container env var gets its value from.
+fn env_source(var: &EnvVar) -> EnvSource {
+    let Some(from) = &var.value_from else {
+        return EnvSource::Literal;
+    };
+
+    if let Some(r) = &from.config_map_key_ref {
+        EnvSource::ConfigMap { name: r.name.clone(), key: r.key.clone() }
+    } else if let Some(r) = &from.secret_key_ref {
+        EnvSource::Secret { name: r.name.clone(), key: r.key.clone() }
+    } else if let Some(r) = &from.field_ref {
+        EnvSource::DownwardApi { path: r.field_path.clone() }
+    } else if let Some(r) = &from.resource_field_ref {
+        EnvSource::DownwardApi { path: r.resource.clone() }
+    } else {
+        EnvSource::Literal
+    }
 }
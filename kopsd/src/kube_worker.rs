@@ -14,68 +14,707 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
-use std::sync::Arc;
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    hash::Hash,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use anyhow::Result;
-use futures::StreamExt;
-use k8s_openapi::api::core::v1::Pod;
-use kube::runtime::reflector::store::Writer;
+use anyhow::{Context, Result, bail};
+use aws_config::{Region, SdkConfig};
+use aws_credential_types::provider::SharedCredentialsProvider;
+use chrono::Utc;
+use futures::{StreamExt, stream};
+use k8s_openapi::NamespaceResourceScope;
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, StatefulSet};
+use k8s_openapi::api::autoscaling::v2::HorizontalPodAutoscaler;
+use k8s_openapi::api::batch::v1::{CronJob, Job};
+use k8s_openapi::api::core::v1::{
+    Event, Namespace, Node, PersistentVolumeClaim, Pod, Service,
+};
+use k8s_openapi::api::discovery::v1::EndpointSlice;
+use k8s_openapi::api::networking::v1::Ingress;
+use kops_aws_eks::KubeClientOptions;
+use kops_protocol::{EventSummary, Notification, PodSummary};
+use kube::runtime::reflector::{ObjectRef, store::Writer};
 use kube::{
-    Api, Client,
+    Api, Client, Resource,
     config::{KubeConfigOptions, Kubeconfig},
 };
 use kube_runtime::{
     reflector::{self, Store},
     watcher,
 };
-use tokio::task;
+use serde::de::DeserializeOwned;
+use tokio::{sync::broadcast, task, time::sleep};
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
-use crate::config::ClusterConfig;
-use crate::state::{ClusterName, ClusterState};
+use crate::bus::EventBus;
+use crate::compact::CompactPod;
+use crate::config::{ClusterAuth, ClusterConfig};
+use crate::event_log::EventLog;
+use crate::proxy;
+use crate::restarts::RestartHistory;
+use crate::state::{
+    AwsSession, ClusterHealth, ClusterName, ClusterState, ClusterStores,
+    EksCluster, PodChange, ProfileName, SessionCredentials,
+};
+
+/// Pod changes buffered per watcher before it is considered lagging.
+const POD_CHANGES_CAPACITY: usize = 1024;
 
-/// Initialize a ClusterState for a given cluster config and start
-/// a background reflector task to keep the Store<Pod> up-to-date.
+/// Bounds of the delay before a failed watcher is resumed.
+const BACKOFF_MIN: Duration = Duration::from_secs(1);
+const BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// Initialize a ClusterState for a given cluster and start the background
+/// reflector tasks that keep its stores up-to-date.
+///
+/// `profile` is the AWS profile `client` was built from. Pod and event
+/// changes are published on `bus`.
 pub async fn init_cluster_state(
     cluster_name: ClusterName,
+    profile: Option<ProfileName>,
+    options: WatchOptions,
     client: kube::Client,
-) -> Result<Arc<ClusterState>> {
+    bus: EventBus,
+) -> Result<ClusterState> {
     // let cluster_name: ClusterName = cfg.name.clone();
 
     // let client = build_client_for_cluster(&cfg).await?;
 
-    let pods_api: Api<Pod> = Api::all(client);
+    let restarts = Arc::new(RestartHistory::default());
+    let history = restarts.clone();
+    let health = Arc::new(ClusterHealth::default());
+    let stop = CancellationToken::new();
+
+    let reflectors = Reflectors {
+        client: client.clone(),
+        cluster_name: cluster_name.clone(),
+        health: health.clone(),
+        stop: stop.clone(),
+        namespaces: options.namespaces.filter(|ns| !ns.is_empty()),
+    };
+
+    let events = {
+        let cluster = cluster_name.clone();
+        let bus = bus.clone();
+        reflectors.spawn_event_log(move |event| {
+            if let watcher::Event::Apply(e) = event
+                && bus.has_subscribers()
+                && let Some(e) = EventSummary::from_event(&cluster, e)
+            {
+                bus.publish(Notification::Event(e));
+            }
+        })
+    };
+
+    let (pod_changes, _) = broadcast::channel(POD_CHANGES_CAPACITY);
+    let changes = pod_changes.clone();
+    let pod_bus = bus.clone();
+    let pod_cluster = cluster_name.clone();
+
+    let on_pod = move |event: &watcher::Event<Pod>| {
+        let (pod, deleted) = match event {
+            watcher::Event::Apply(pod) | watcher::Event::InitApply(pod) => {
+                history.observe(pod);
+                (pod, false)
+            }
+            watcher::Event::Delete(pod) => {
+                history.forget(pod);
+                (pod, true)
+            }
+            watcher::Event::Init | watcher::Event::InitDone => return,
+        };
+
+        // Only copy pods when someone is watching.
+        if changes.receiver_count() > 0 {
+            let pod = pod.clone();
+            let change = if deleted {
+                PodChange::Deleted(pod)
+            } else {
+                PodChange::Applied(pod)
+            };
+            let _ = changes.send(change);
+        }
+
+        // A relist replays every pod; subscribers want changes.
+        if !matches!(event, watcher::Event::InitApply(_))
+            && pod_bus.has_subscribers()
+            && let Some(p) = PodSummary::from_pod(&pod_cluster, pod)
+        {
+            pod_bus.publish(if deleted {
+                Notification::PodDeleted(p)
+            } else {
+                Notification::PodApplied(p)
+            });
+        }
+    };
+
+    let stores = ClusterStores {
+        // Compact pods are decoded as such, not trimmed once received.
+        pods: if options.compact_pods {
+            reflectors.spawn_converted("pod", CompactPod::into_pod, on_pod)
+        } else {
+            reflectors.spawn_with("pod", on_pod)
+        },
+        deployments: reflectors.spawn::<Deployment>("deployment"),
+        stateful_sets: reflectors.spawn::<StatefulSet>("statefulset"),
+        daemon_sets: reflectors.spawn::<DaemonSet>("daemonset"),
+        events,
+        nodes: reflectors.spawn_cluster_wide::<Node>("node"),
+        namespaces: reflectors.spawn_cluster_wide::<Namespace>("namespace"),
+        services: reflectors.spawn::<Service>("service"),
+        endpoint_slices: reflectors.spawn::<EndpointSlice>("endpointslice"),
+        ingresses: reflectors.spawn::<Ingress>("ingress"),
+        jobs: reflectors.spawn::<Job>("job"),
+        cron_jobs: reflectors.spawn::<CronJob>("cronjob"),
+        hpas: reflectors.spawn::<HorizontalPodAutoscaler>("hpa"),
+        pvcs: reflectors.spawn::<PersistentVolumeClaim>("pvc"),
+        compact_pods: options.compact_pods,
+        stop,
+    };
+
+    let state = ClusterState::new(
+        cluster_name,
+        profile,
+        client,
+        stores,
+        restarts,
+        pod_changes,
+        health,
+    );
+
+    Ok(state)
+}
+
+/// Start a cluster of the config file, reached through its kubeconfig,
+/// or as an EKS cluster with `session` when it names an AWS profile.
+pub async fn start_configured_cluster(
+    cfg: &ClusterConfig,
+    session: Option<&AwsSession>,
+    bus: EventBus,
+) -> Result<Arc<ClusterState>> {
+    let (client, credentials) = build_client(cfg, session).await?;
+
+    let eks = credentials.as_ref().map(|_| eks_cluster(cfg));
+
+    let profile = cfg.eks_profile().map(str::to_string);
+    let state =
+        init_cluster_state(cfg.name.clone(), profile, cfg.into(), client, bus)
+            .await?;
+
+    Ok(Arc::new(state.with_credentials(credentials).with_eks(eks)))
+}
+
+/// What a cluster watches, and how much of it is kept.
+#[derive(Clone, Debug, Default)]
+pub struct WatchOptions {
+    /// Namespaces the namespaced kinds are watched in; all when `None`.
+    pub namespaces: Option<Vec<String>>,
+
+    /// Watch pods as `CompactPod`s, decoded without what listings do not
+    /// read.
+    pub compact_pods: bool,
+}
+
+impl From<&ClusterConfig> for WatchOptions {
+    fn from(cfg: &ClusterConfig) -> Self {
+        Self {
+            namespaces: cfg.namespaces.clone(),
+            compact_pods: cfg.compact_pods.unwrap_or(false),
+        }
+    }
+}
+
+/// What the reflectors of one cluster share.
+struct Reflectors {
+    client: Client,
+    cluster_name: ClusterName,
+    health: Arc<ClusterHealth>,
+    stop: CancellationToken,
+
+    /// Namespaces the namespaced kinds are watched in; all when `None`.
+    namespaces: Option<Vec<String>>,
+}
 
-    let (store, writer): (Store<Pod>, Writer<Pod>) = reflector::store();
+impl Reflectors {
+    /// Start a reflector for the namespaced kind `K` and return its Store.
+    ///
+    /// The reflector reports its sync state to `health` under `kind`, and
+    /// runs until `stop` is cancelled.
+    fn spawn<K>(&self, kind: &'static str) -> Store<K>
+    where
+        K: Resource<Scope = NamespaceResourceScope>
+            + Clone
+            + DeserializeOwned
+            + Debug
+            + Send
+            + Sync
+            + 'static,
+        K::DynamicType: Default + Eq + Hash + Clone + Send + Sync,
+    {
+        self.spawn_with(kind, |_| {})
+    }
 
-    let watcher_cfg = watcher::Config::default();
+    /// Like `spawn`, also passing every watcher event to `on_event` after
+    /// the Store has applied it.
+    fn spawn_with<K, F>(&self, kind: &'static str, on_event: F) -> Store<K>
+    where
+        K: Resource<Scope = NamespaceResourceScope>
+            + Clone
+            + DeserializeOwned
+            + Debug
+            + Send
+            + Sync
+            + 'static,
+        K::DynamicType: Default + Eq + Hash + Clone + Send + Sync,
+        F: Fn(&watcher::Event<K>) + Send + Sync + 'static,
+    {
+        self.spawn_converted(kind, |obj| obj, on_event)
+    }
 
-    let rf = reflector::reflector(writer, watcher(pods_api, watcher_cfg));
+    /// Like `spawn_with`, watching the objects as `W` and converting them
+    /// with `convert` before they reach the Store.
+    fn spawn_converted<W, K, F>(
+        &self,
+        kind: &'static str,
+        convert: fn(W) -> K,
+        on_event: F,
+    ) -> Store<K>
+    where
+        W: Resource<Scope = NamespaceResourceScope>
+            + Clone
+            + DeserializeOwned
+            + Debug
+            + Send
+            + Sync
+            + 'static,
+        W::DynamicType: Default,
+        K: Resource + Clone + Debug + Send + Sync + 'static,
+        K::DynamicType: Default + Eq + Hash + Clone + Send + Sync,
+        F: Fn(&watcher::Event<K>) + Send + Sync + 'static,
+    {
+        let (store, writer) = reflector::store();
+        let apis = self.namespaced_apis();
+        let merge = Merge::new(writer, apis.len());
+        self.run(apis, kind, convert, merge, on_event);
+        store
+    }
 
-    let state = Arc::new(ClusterState::new(cluster_name.clone(), store));
+    /// Like `spawn`, for kinds that are not namespaced.
+    fn spawn_cluster_wide<K>(&self, kind: &'static str) -> Store<K>
+    where
+        K: Resource + Clone + DeserializeOwned + Debug + Send + Sync + 'static,
+        K::DynamicType: Default + Eq + Hash + Clone + Send + Sync,
+    {
+        let (store, writer) = reflector::store();
+        let merge = Merge::new(writer, 1);
+        self.run(
+            vec![Api::all(self.client.clone())],
+            kind,
+            |obj| obj,
+            merge,
+            |_| {},
+        );
+        store
+    }
 
-    task::spawn(async move {
-        info!(cluster = %cluster_name, "starting pod reflector");
+    /// Start the event reflector, coalescing what it sees into an
+    /// EventLog, and return the log.
+    ///
+    /// `on_event` gets the watcher events as `spawn_with` passes them,
+    /// except for deletions found by relists.
+    fn spawn_event_log<F>(&self, on_event: F) -> Arc<EventLog>
+    where
+        F: Fn(&watcher::Event<Event>) + Send + Sync + 'static,
+    {
+        let log = Arc::new(EventLog::new(self.cluster_name.to_string()));
+        let apis = self.namespaced_apis();
+        let feed =
+            EventFeed { log: log.clone(), relists: Relists::new(apis.len()) };
+        self.run(apis, "event", |obj| obj, feed, on_event);
+        log
+    }
 
-        // `for_each` consome o stream; não precisamos do valor em si,
-        // o objetivo é só manter o Store sincronizado.
-        rf.for_each(|event_result| {
-            if let Err(err) = &event_result {
-                warn!(cluster = %cluster_name, %err, "reflector event error");
+    /// An Api per watched namespace, or one for all of them.
+    fn namespaced_apis<K>(&self) -> Vec<Api<K>>
+    where
+        K: Resource<Scope = NamespaceResourceScope>,
+        K::DynamicType: Default,
+    {
+        match &self.namespaces {
+            Some(namespaces) => namespaces
+                .iter()
+                .map(|ns| Api::namespaced(self.client.clone(), ns))
+                .collect(),
+            None => vec![Api::all(self.client.clone())],
+        }
+    }
+
+    /// Feed `sink` from a watcher per Api, restarting the watchers with
+    /// a backoff when they fail.
+    fn run<W, K, S, F>(
+        &self,
+        apis: Vec<Api<W>>,
+        kind: &'static str,
+        convert: fn(W) -> K,
+        mut sink: S,
+        on_event: F,
+    ) where
+        W: Resource + Clone + DeserializeOwned + Debug + Send + Sync + 'static,
+        K: Send + 'static,
+        S: Sink<K> + Send + 'static,
+        F: Fn(&watcher::Event<K>) + Send + Sync + 'static,
+    {
+        // Listed as pending until the first listing completes.
+        self.health.update(kind, |h| h.running = true);
+
+        let cluster_name = self.cluster_name.clone();
+        let health = self.health.clone();
+        let stop = self.stop.clone();
+        task::spawn(async move {
+            info!(cluster = %cluster_name, "starting {kind} reflector");
+
+            let supervise = async {
+                // A watcher stream only ends on a bug; start new ones then.
+                while !stop.is_cancelled() {
+                    let mut events = stream::select_all(
+                        apis.iter().enumerate().map(|(i, api)| {
+                            watcher(api.clone(), watcher::Config::default())
+                                .map(move |event| (i, event))
+                                .boxed()
+                        }),
+                    );
+
+                    while let Some((i, event_result)) = events.next().await {
+                        match event_result {
+                            Ok(event) => {
+                                let event = convert_event(event, convert);
+                                for gone in sink.apply(i, &event) {
+                                    on_event(&watcher::Event::Delete(gone));
+                                }
+                                on_event(&event);
+
+                                let synced = sink.synced();
+                                let listed = synced
+                                    && matches!(
+                                        event,
+                                        watcher::Event::InitDone
+                                    );
+                                health.update(kind, |h| {
+                                    if listed {
+                                        h.synced_at = Some(Utc::now());
+                                    }
+                                    h.synced = synced;
+                                    h.error = None;
+                                    h.failures = 0;
+                                    h.last_event = Some(Utc::now());
+                                });
+                            }
+                            Err(err) => {
+                                let failures =
+                                    record_failure(&health, kind, &err);
+                                warn!(
+                                    cluster = %cluster_name,
+                                    %err,
+                                    failures,
+                                    "{kind} reflector event error"
+                                );
+                                // The watcher resumes on the next poll.
+                                sleep(backoff(failures)).await;
+                            }
+                        }
+                    }
+
+                    let failures =
+                        record_failure(&health, kind, "watch ended");
+                    warn!(
+                        cluster = %cluster_name,
+                        failures,
+                        "{kind} reflector ended, restarting"
+                    );
+                    sleep(backoff(failures)).await;
+                }
+            };
+
+            tokio::select! {
+                _ = supervise => {}
+                _ = stop.cancelled() => {
+                    info!(cluster = %cluster_name, "{kind} reflector stopped");
+                }
             }
-            futures::future::ready(())
-        })
-        .await;
 
-        // if let Err(err) = fut.await {
-        //     // Isso só acontece se o stream em si quebrar de forma grave
-        //     warn!(cluster = %cluster_name, "reflector stream ended: {err:?}");
-        // }
+            health.update(kind, |h| h.running = false);
+        });
+    }
+}
+
+/// What `Reflectors::run` feeds from several watchers.
+trait Sink<K> {
+    /// Apply `event` of watcher `i`; returns the objects this deleted
+    /// because the relist it completes no longer has them.
+    fn apply(&mut self, i: usize, event: &watcher::Event<K>) -> Vec<K>;
+
+    /// Every watcher completed a listing and none is relisting.
+    fn synced(&self) -> bool;
+}
+
+/// Objects of each of several watchers, to tell which ones a relist no
+/// longer has.
+///
+/// A watcher relists with `Init`, `InitApply`s and `InitDone`; with a
+/// watcher per namespace, what one relist lacks is only gone from that
+/// namespace.
+struct Relists<T> {
+    /// Objects of each watcher.
+    known: Vec<HashSet<T>>,
+
+    /// Objects seen so far by the relist of each watcher, while it runs.
+    relisting: Vec<Option<HashSet<T>>>,
+
+    /// Watchers that completed a listing.
+    listed: Vec<bool>,
+}
+
+impl<T> Relists<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn new(watchers: usize) -> Self {
+        Self {
+            known: vec![HashSet::new(); watchers],
+            relisting: vec![None; watchers],
+            listed: vec![false; watchers],
+        }
+    }
+
+    /// Track `event` of watcher `i`, whose objects are told apart by
+    /// `key`; returns the keys of the objects the relist it completes no
+    /// longer has.
+    fn track<K>(
+        &mut self,
+        i: usize,
+        event: &watcher::Event<K>,
+        key: impl Fn(&K) -> T,
+    ) -> Vec<T> {
+        match event {
+            watcher::Event::Init => {
+                self.relisting[i] = Some(HashSet::new());
+            }
+            watcher::Event::InitApply(obj) => {
+                if let Some(seen) = &mut self.relisting[i] {
+                    seen.insert(key(obj));
+                }
+            }
+            watcher::Event::InitDone => {
+                let seen = self.relisting[i].take().unwrap_or_default();
+                let known = std::mem::replace(&mut self.known[i], seen);
+                self.listed[i] = true;
 
-        info!(cluster = %cluster_name, "pod reflector finished");
+                return known.difference(&self.known[i]).cloned().collect();
+            }
+            watcher::Event::Apply(obj) => {
+                self.known[i].insert(key(obj));
+            }
+            watcher::Event::Delete(obj) => {
+                self.known[i].remove(&key(obj));
+            }
+        }
+
+        Vec::new()
+    }
+
+    fn synced(&self) -> bool {
+        self.listed.iter().all(|&l| l)
+            && self.relisting.iter().all(Option::is_none)
+    }
+}
+
+/// Feeds one Store from several watchers.
+///
+/// On a relist, a `Writer` swaps its whole content; with a watcher per
+/// namespace, that would drop the objects of the others. Relisted objects
+/// are applied one by one instead, and those a relist no longer has are
+/// deleted once it is done.
+struct Merge<K>
+where
+    K: Resource + Clone + 'static,
+    K::DynamicType: Eq + Hash + Clone,
+{
+    writer: Writer<K>,
+    store: Store<K>,
+    relists: Relists<ObjectRef<K>>,
+}
+
+impl<K> Merge<K>
+where
+    K: Resource + Clone + 'static,
+    K::DynamicType: Default + Eq + Hash + Clone,
+{
+    fn new(writer: Writer<K>, watchers: usize) -> Self {
+        Self {
+            store: writer.as_reader(),
+            writer,
+            relists: Relists::new(watchers),
+        }
+    }
+}
+
+impl<K> Sink<K> for Merge<K>
+where
+    K: Resource + Clone + 'static,
+    K::DynamicType: Default + Eq + Hash + Clone,
+{
+    fn apply(&mut self, i: usize, event: &watcher::Event<K>) -> Vec<K> {
+        let gone = self.relists.track(i, event, ObjectRef::from_obj);
+
+        match event {
+            watcher::Event::InitApply(obj) => {
+                let apply = watcher::Event::Apply(obj.clone());
+                self.writer.apply_watcher_event(&apply);
+            }
+            watcher::Event::Apply(_) | watcher::Event::Delete(_) => {
+                self.writer.apply_watcher_event(event);
+            }
+            watcher::Event::Init | watcher::Event::InitDone => {}
+        }
+
+        gone.iter()
+            .filter_map(|key| self.store.get(key))
+            .map(|obj| {
+                let obj = (*obj).clone();
+                let delete = watcher::Event::Delete(obj.clone());
+                self.writer.apply_watcher_event(&delete);
+                obj
+            })
+            .collect()
+    }
+
+    fn synced(&self) -> bool {
+        self.relists.synced()
+    }
+}
+
+/// Feeds an EventLog from several watchers.
+///
+/// The log keeps no Event objects, so the deletions found by relists are
+/// not returned.
+struct EventFeed {
+    log: Arc<EventLog>,
+    relists: Relists<ObjectRef<Event>>,
+}
+
+impl Sink<Event> for EventFeed {
+    fn apply(
+        &mut self,
+        i: usize,
+        event: &watcher::Event<Event>,
+    ) -> Vec<Event> {
+        let gone = self.relists.track(i, event, ObjectRef::from_obj);
+
+        match event {
+            watcher::Event::InitApply(obj) | watcher::Event::Apply(obj) => {
+                self.log.apply(obj);
+            }
+            watcher::Event::Delete(obj) => {
+                self.log.remove(&ObjectRef::from_obj(obj));
+            }
+            watcher::Event::Init | watcher::Event::InitDone => {}
+        }
+
+        for key in &gone {
+            self.log.remove(key);
+        }
+
+        Vec::new()
+    }
+
+    fn synced(&self) -> bool {
+        self.relists.synced()
+    }
+}
+
+/// `event` with its object converted by `convert`.
+fn convert_event<W, K>(
+    event: watcher::Event<W>,
+    convert: fn(W) -> K,
+) -> watcher::Event<K> {
+    match event {
+        watcher::Event::Init => watcher::Event::Init,
+        watcher::Event::InitApply(obj) => {
+            watcher::Event::InitApply(convert(obj))
+        }
+        watcher::Event::InitDone => watcher::Event::InitDone,
+        watcher::Event::Apply(obj) => watcher::Event::Apply(convert(obj)),
+        watcher::Event::Delete(obj) => watcher::Event::Delete(convert(obj)),
+    }
+}
+
+/// Count a failure of the `kind` reflector and return how many happened
+/// in a row.
+fn record_failure(
+    health: &ClusterHealth,
+    kind: &'static str,
+    err: impl ToString,
+) -> u32 {
+    let mut failures = 0;
+    health.update(kind, |h| {
+        h.failures += 1;
+        h.error = Some(err.to_string());
+        failures = h.failures;
     });
+    failures
+}
 
-    Ok(state)
+/// Delay before resuming a watcher after `failures` failures in a row:
+/// doubling from `BACKOFF_MIN` up to `BACKOFF_MAX`, then randomized over
+/// its upper half so that reflectors do not retry in lockstep.
+fn backoff(failures: u32) -> Duration {
+    let exp = failures.saturating_sub(1).min(16);
+    let delay = BACKOFF_MIN.saturating_mul(1 << exp).min(BACKOFF_MAX);
+
+    // Good enough as a jitter source; this is not about randomness.
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let half = delay / 2;
+    half + half.mul_f64(f64::from(nanos % 1000) / 1000.0)
+}
+
+/// Client of the cluster of `cfg` as its `auth` says, with the credentials
+/// it signs EKS tokens with.
+async fn build_client(
+    cfg: &ClusterConfig,
+    session: Option<&AwsSession>,
+) -> Result<(Client, Option<SessionCredentials>)> {
+    match cfg.auth() {
+        ClusterAuth::Kubeconfig => {
+            Ok((build_client_for_cluster(cfg).await?, None))
+        }
+        ClusterAuth::Eks => {
+            let Some(profile) = &cfg.profile else {
+                bail!("cluster {} uses EKS auth without a profile", cfg.name);
+            };
+            let Some(session) = session else {
+                bail!("no AWS session stored for profile {profile}");
+            };
+
+            let credentials = SessionCredentials::new(session);
+            let client = build_eks_client(
+                cfg,
+                &eks_cluster(cfg),
+                session,
+                &credentials,
+            )
+            .await?;
+            Ok((client, Some(credentials)))
+        }
+    }
 }
 
 /// Build a Kubernetes client using kubeconfig + context from ClusterConfig.
@@ -84,20 +723,146 @@ pub async fn init_cluster_state(
 ///   - $KUBECONFIG
 ///   - in-cluster config
 async fn build_client_for_cluster(cfg: &ClusterConfig) -> Result<Client> {
-    if let Some(path) = &cfg.kubeconfig {
+    let mut config = if let Some(path) = &cfg.kubeconfig {
         // Usa kubeconfig explícito + context opcional
         let kubeconfig = Kubeconfig::read_from(path)?;
         let options = KubeConfigOptions {
             context: cfg.context.clone(),
             ..KubeConfigOptions::default()
         };
-        let config =
-            kube::Config::from_custom_kubeconfig(kubeconfig, &options).await?;
-        Ok(Client::try_from(config)?)
+        kube::Config::from_custom_kubeconfig(kubeconfig, &options).await?
     } else {
         // Usa a detecção padrão (KUBECONFIG, in-cluster, etc.)
-        Ok(Client::try_default().await?)
+        kube::Config::infer().await?
+    };
+
+    let options = KubeClientOptions::from_config(&config);
+    client_options(cfg, options)?.apply(&mut config);
+
+    Ok(Client::try_from(config)?)
+}
+
+/// `options` with the client settings of `cfg` over them.
+fn client_options(
+    cfg: &ClusterConfig,
+    mut options: KubeClientOptions,
+) -> Result<KubeClientOptions> {
+    if let Some(namespace) = &cfg.default_namespace {
+        options.namespace = namespace.clone();
+    }
+    if let Some(secs) = cfg.connect_timeout_secs {
+        options.connect_timeout = Some(Duration::from_secs(secs));
+    }
+    if let Some(secs) = cfg.read_timeout_secs {
+        options.read_timeout = Some(Duration::from_secs(secs));
+    }
+    // the one of the daemon, unless the kubeconfig has its own
+    let proxy_url = match &cfg.proxy_url {
+        Some(url) => Some(url.as_str()),
+        None if options.proxy_url.is_none() => proxy::proxy_url(),
+        None => None,
+    };
+    if let Some(url) = proxy_url {
+        let url = url.parse().with_context(|| {
+            format!("invalid proxy_url of cluster {}: {url}", cfg.name)
+        })?;
+        options.proxy_url = Some(url);
+    }
+    if let Some(name) = &cfg.tls_server_name {
+        options.tls_server_name = Some(name.clone());
+    }
+    if let Some(endpoint) = &cfg.endpoint {
+        let endpoint = endpoint.parse().with_context(|| {
+            format!("invalid endpoint of cluster {}: {endpoint}", cfg.name)
+        })?;
+        options.endpoint = Some(endpoint);
+    }
+    for path in cfg.ca_bundles.iter().flatten() {
+        let certs = kops_aws_eks::read_ca_bundle(path).with_context(|| {
+            format!("invalid ca_bundles of cluster {}", cfg.name)
+        })?;
+        options.extra_root_certs.extend(certs);
     }
+
+    Ok(options)
+}
+
+/// Client of the EKS cluster of `cfg`, in the region of `session`, signing
+/// its tokens with `credentials`.
+async fn build_eks_client(
+    cfg: &ClusterConfig,
+    eks: &EksCluster,
+    session: &AwsSession,
+    credentials: &SessionCredentials,
+) -> Result<Client> {
+    let sdk_config =
+        sdk_config_from_session(eks, session, credentials.clone()).await?;
+    let options = client_options(cfg, KubeClientOptions::default())?;
+    let client = kops_aws_eks::create_kube_client(
+        &sdk_config,
+        cfg.eks_name(),
+        &options,
+    )
+    .await
+    .with_context(|| {
+        format!("failed to create kube client for cluster {}", cfg.name)
+    })?;
+
+    Ok(client)
+}
+
+/// SDK config reaching `eks` with the credentials of `session`.
+pub async fn eks_sdk_config(
+    eks: &EksCluster,
+    session: &AwsSession,
+) -> Result<SdkConfig> {
+    sdk_config_from_session(eks, session, SessionCredentials::new(session))
+        .await
+}
+
+/// EKS cluster of `cfg`, for clusters with EKS auth.
+fn eks_cluster(cfg: &ClusterConfig) -> EksCluster {
+    EksCluster {
+        name: cfg.eks_name().to_string(),
+        region: cfg.region.clone(),
+        use_fips: cfg.use_fips.unwrap_or(false),
+        use_dual_stack: cfg.use_dual_stack.unwrap_or(false),
+    }
+}
+
+async fn sdk_config_from_session(
+    eks: &EksCluster,
+    session: &AwsSession,
+    creds: SessionCredentials,
+) -> anyhow::Result<SdkConfig> {
+    let creds_provider = SharedCredentialsProvider::new(creds);
+
+    // 2. Resolve região
+    let region = eks
+        .region
+        .clone()
+        .or_else(|| session.region.clone())
+        .unwrap_or_else(|| "us-east-1".to_string());
+
+    let region = Region::new(region);
+
+    // 3. Monta o SdkConfig manualmente
+    let mut loader = aws_config::from_env()
+        .region(region)
+        .credentials_provider(creds_provider);
+    // unset, the AWS_USE_* variables apply
+    if eks.use_fips {
+        loader = loader.use_fips(true);
+    }
+    if eks.use_dual_stack {
+        loader = loader.use_dual_stack(true);
+    }
+    if let Some(http_client) = proxy::aws_http_client() {
+        loader = loader.http_client(http_client);
+    }
+    let sdk_config = loader.load().await;
+
+    Ok(sdk_config)
 }
 
 // use std::sync::Arc;
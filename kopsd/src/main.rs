@@ -15,14 +15,35 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
+use std::path::PathBuf;
+
 use anyhow::Result;
 use clap::{ArgAction, Parser};
 
+mod access;
+mod audit;
+mod bus;
+mod compact;
 mod config;
+mod discovery;
+mod env;
+mod event_log;
+mod expiry;
 mod handler;
 mod kube_worker;
+mod manifest;
+mod metrics;
+mod page;
+mod prometheus;
+mod proxy;
+mod reload;
+mod replication;
+mod restarts;
+mod selector;
 mod server;
+mod session_store;
 mod state;
+mod traffic;
 
 const VERSION: &str = concat!(
     env!("CARGO_PKG_VERSION"),
@@ -50,9 +71,15 @@ struct Args {
 
     /// Do not daemonize.
     ///
-    /// If this option is specified, kopsd will run in the foreground and log to stderr.
-    #[arg(short)]
-    daemon: bool,
+    /// If this option is specified, kopsd will run in the foreground and log
+    /// to stdout.
+    #[arg(short, long)]
+    foreground: bool,
+
+    /// Listen on this socket instead of the one of KOPS_SOCKET, the config
+    /// file or the default.
+    #[arg(long)]
+    socket: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
@@ -0,0 +1,94 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Full objects of the cached kinds, for `kopsctl get`.
+//!
+//! Objects are served from the reflector stores, without their managed
+//! fields as `kubectl get -o yaml` shows them. Pods are read from the API
+//! server when the store only keeps compacted ones.
+
+use kops_protocol::ResourceKind;
+use kube::Resource;
+use kube::runtime::reflector::{ObjectRef, Store};
+use serde::Serialize;
+
+use crate::state::ClusterState;
+
+/// `name` of `kind` as JSON, `None` when the cluster has no such object.
+///
+/// `namespace` is unset for nodes and namespaces.
+pub(crate) async fn get(
+    cluster: &ClusterState,
+    kind: ResourceKind,
+    namespace: Option<&str>,
+    name: &str,
+) -> Result<Option<String>, kube::Error> {
+    match kind {
+        ResourceKind::Pod => {
+            let Some(namespace) = namespace else {
+                return Ok(None);
+            };
+            cluster
+                .full_pod(namespace, name)
+                .await?
+                .map(|pod| to_json(&*pod))
+                .transpose()
+        }
+        ResourceKind::Deployment => {
+            cached(cluster.deployments(), namespace, name)
+        }
+        ResourceKind::StatefulSet => {
+            cached(cluster.stateful_sets(), namespace, name)
+        }
+        ResourceKind::DaemonSet => {
+            cached(cluster.daemon_sets(), namespace, name)
+        }
+        ResourceKind::Service => cached(cluster.services(), namespace, name),
+        ResourceKind::Ingress => cached(cluster.ingresses(), namespace, name),
+        ResourceKind::Job => cached(cluster.jobs(), namespace, name),
+        ResourceKind::CronJob => cached(cluster.cron_jobs(), namespace, name),
+        ResourceKind::Hpa => cached(cluster.hpas(), namespace, name),
+        ResourceKind::Pvc => cached(cluster.pvcs(), namespace, name),
+        ResourceKind::Node => cached(cluster.nodes(), None, name),
+        ResourceKind::Namespace => cached(cluster.namespaces(), None, name),
+    }
+}
+
+fn cached<K>(
+    store: &Store<K>,
+    namespace: Option<&str>,
+    name: &str,
+) -> Result<Option<String>, kube::Error>
+where
+    K: Resource<DynamicType = ()> + Clone + Serialize + 'static,
+{
+    let mut obj = ObjectRef::new(name);
+    if let Some(namespace) = namespace {
+        obj = obj.within(namespace);
+    }
+
+    store.get(&obj).map(|object| to_json(&*object)).transpose()
+}
+
+fn to_json<K>(object: &K) -> Result<String, kube::Error>
+where
+    K: Resource + Clone + Serialize,
+{
+    let mut object = object.clone();
+    object.meta_mut().managed_fields = None;
+
+    serde_json::to_string(&object).map_err(kube::Error::SerdeError)
+}
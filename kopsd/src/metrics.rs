@@ -0,0 +1,173 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Resource usage from the metrics.k8s.io API (metrics-server).
+//!
+//! The metrics types are not part of k8s-openapi, so objects are read as
+//! `DynamicObject`s and only the `usage` fields are picked out.
+
+use kops_protocol::ResourceUsage;
+use kube::{
+    Api, Client,
+    api::{ApiResource, DynamicObject, GroupVersionKind, ListParams},
+};
+use serde_json::Value;
+
+fn resource(kind: &str, plural: &str) -> ApiResource {
+    let gvk = GroupVersionKind::gvk("metrics.k8s.io", "v1beta1", kind);
+    ApiResource::from_gvk_with_plural(&gvk, plural)
+}
+
+/// Current usage of every pod, summed over its containers.
+pub(crate) async fn pod_usage(
+    client: Client,
+    cluster: &str,
+    namespace: Option<&str>,
+) -> Result<Vec<ResourceUsage>, kube::Error> {
+    let ar = resource("PodMetrics", "pods");
+    let api: Api<DynamicObject> = match namespace {
+        Some(ns) => Api::namespaced_with(client, ns, &ar),
+        None => Api::all_with(client, &ar),
+    };
+
+    let list = api.list(&ListParams::default()).await?;
+
+    Ok(list
+        .items
+        .iter()
+        .filter_map(|obj| {
+            let containers = obj.data.get("containers")?.as_array()?;
+            let (cpu, memory) = containers
+                .iter()
+                .filter_map(|c| c.get("usage"))
+                .map(usage)
+                .fold((0, 0), |(c, m), (cpu, mem)| (c + cpu, m + mem));
+
+            Some(ResourceUsage {
+                cluster: cluster.to_string(),
+                namespace: obj.metadata.namespace.clone(),
+                name: obj.metadata.name.clone()?,
+                cpu_millis: cpu,
+                memory_bytes: memory,
+            })
+        })
+        .collect())
+}
+
+/// Current usage of every node.
+pub(crate) async fn node_usage(
+    client: Client,
+    cluster: &str,
+) -> Result<Vec<ResourceUsage>, kube::Error> {
+    let ar = resource("NodeMetrics", "nodes");
+    let api: Api<DynamicObject> = Api::all_with(client, &ar);
+
+    let list = api.list(&ListParams::default()).await?;
+
+    Ok(list
+        .items
+        .iter()
+        .filter_map(|obj| {
+            let (cpu, memory) = usage(obj.data.get("usage")?);
+
+            Some(ResourceUsage {
+                cluster: cluster.to_string(),
+                namespace: None,
+                name: obj.metadata.name.clone()?,
+                cpu_millis: cpu,
+                memory_bytes: memory,
+            })
+        })
+        .collect())
+}
+
+/// CPU (millicores) and memory (bytes) of a `usage` object.
+fn usage(usage: &Value) -> (u64, u64) {
+    let cpu = usage
+        .get("cpu")
+        .and_then(Value::as_str)
+        .and_then(parse_cpu)
+        .unwrap_or(0);
+    let memory = usage
+        .get("memory")
+        .and_then(Value::as_str)
+        .and_then(parse_memory)
+        .unwrap_or(0);
+
+    (cpu, memory)
+}
+
+/// Parse a CPU quantity (`250m`, `1`, `123456n`) into millicores.
+fn parse_cpu(q: &str) -> Option<u64> {
+    let (value, scale) =
+        split_suffix(q, &[("n", 1e-6), ("u", 1e-3), ("m", 1.0), ("", 1e3)])?;
+
+    Some((value * scale).round() as u64)
+}
+
+/// Parse a memory quantity (`128Mi`, `1G`, `1048576`, `100m`) into bytes.
+fn parse_memory(q: &str) -> Option<u64> {
+    let (value, scale) = split_suffix(
+        q,
+        &[
+            ("Ki", 1024f64),
+            ("Mi", 1024f64.powi(2)),
+            ("Gi", 1024f64.powi(3)),
+            ("Ti", 1024f64.powi(4)),
+            ("Pi", 1024f64.powi(5)),
+            ("Ei", 1024f64.powi(6)),
+            ("k", 1e3),
+            ("M", 1e6),
+            ("G", 1e9),
+            ("T", 1e12),
+            ("P", 1e15),
+            ("E", 1e18),
+            ("m", 1e-3),
+            ("", 1.0),
+        ],
+    )?;
+
+    Some((value * scale).round() as u64)
+}
+
+/// Split a quantity into its number and the scale of its suffix.
+///
+/// `suffixes` is tried in order, so the empty suffix must come last.
+fn split_suffix(q: &str, suffixes: &[(&str, f64)]) -> Option<(f64, f64)> {
+    suffixes.iter().find_map(|(suffix, scale)| {
+        let number = q.strip_suffix(suffix)?;
+        number.parse::<f64>().ok().map(|v| (v, *scale))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantities_parse_with_their_suffix() {
+        assert_eq!(parse_cpu("250m"), Some(250));
+        assert_eq!(parse_cpu("2"), Some(2000));
+        assert_eq!(parse_cpu("1500000n"), Some(2));
+
+        assert_eq!(parse_memory("128Mi"), Some(128 * 1024 * 1024));
+        assert_eq!(parse_memory("1G"), Some(1_000_000_000));
+        assert_eq!(parse_memory("1048576"), Some(1_048_576));
+        assert_eq!(parse_memory("1500m"), Some(2));
+        assert_eq!(parse_memory("100m"), Some(0));
+        assert_eq!(parse_memory("12x"), None);
+    }
+}
@@ -0,0 +1,207 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Cursor-based pagination of sorted listings.
+//!
+//! A continue token names the last item of the previous page by its sort
+//! key, so a page resumes right after it even if items were added or
+//! removed in between: nothing already listed is repeated and nothing
+//! that existed throughout is skipped.
+
+use kops_protocol::KopsError;
+
+/// Keep the page of `items` following `token`, at most `limit` long.
+///
+/// `items` must be sorted by `key`, a `(cluster, namespace, name)` triple.
+/// Returns the token of the next page, if any item is left out.
+pub(crate) fn paginate<T>(
+    items: &mut Vec<T>,
+    key: impl Fn(&T) -> (&str, &str, &str),
+    limit: Option<u32>,
+    token: Option<&str>,
+) -> Result<Option<String>, KopsError> {
+    if let Some(token) = token {
+        let after = parse_token(token)?;
+        let start = items.partition_point(|item| key(item) <= after);
+        items.drain(..start);
+    }
+
+    let Some(limit) = limit else {
+        return Ok(None);
+    };
+    if limit == 0 {
+        return Err(KopsError::invalid("limit must be at least 1"));
+    }
+
+    let limit = limit as usize;
+    if items.len() <= limit {
+        return Ok(None);
+    }
+    items.truncate(limit);
+
+    Ok(items.last().map(|item| {
+        let (cluster, namespace, name) = key(item);
+        // Namespaces and names never hold a `/`, so the cluster goes last.
+        format!("{namespace}/{name}/{cluster}")
+    }))
+}
+
+fn parse_token(token: &str) -> Result<(&str, &str, &str), KopsError> {
+    let mut parts = token.splitn(3, '/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(namespace), Some(name), Some(cluster)) => {
+            Ok((cluster, namespace, name))
+        }
+        _ => {
+            Err(KopsError::invalid(format!("invalid continue token: {token}")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kops_protocol::ErrorCode;
+
+    use super::*;
+
+    type Item = (String, String, String);
+
+    fn items(keys: &[(&str, &str, &str)]) -> Vec<Item> {
+        keys.iter()
+            .map(|(c, ns, n)| (c.to_string(), ns.to_string(), n.to_string()))
+            .collect()
+    }
+
+    fn key(item: &Item) -> (&str, &str, &str) {
+        (&item.0, &item.1, &item.2)
+    }
+
+    fn names(items: &[Item]) -> Vec<&str> {
+        items.iter().map(|item| item.2.as_str()).collect()
+    }
+
+    fn pods() -> Vec<Item> {
+        items(&[
+            ("dev", "default", "a"),
+            ("dev", "default", "b"),
+            ("dev", "kube-system", "c"),
+            ("prod", "default", "d"),
+            ("prod", "default", "e"),
+        ])
+    }
+
+    #[test]
+    fn no_limit_keeps_everything() {
+        let mut page = pods();
+        assert_eq!(paginate(&mut page, key, None, None).unwrap(), None);
+        assert_eq!(page.len(), 5);
+    }
+
+    #[test]
+    fn limit_at_or_over_the_length_ends_the_listing() {
+        for limit in [5, 6, u32::MAX] {
+            let mut page = pods();
+            let next = paginate(&mut page, key, Some(limit), None).unwrap();
+            assert_eq!(next, None);
+            assert_eq!(page.len(), 5);
+        }
+    }
+
+    #[test]
+    fn limit_under_the_length_hands_out_a_token() {
+        let mut page = pods();
+        let next = paginate(&mut page, key, Some(4), None).unwrap();
+        assert_eq!(names(&page), ["a", "b", "c", "d"]);
+        assert_eq!(next.as_deref(), Some("default/d/prod"));
+
+        let mut page = pods();
+        let next = paginate(&mut page, key, Some(1), None).unwrap();
+        assert_eq!(names(&page), ["a"]);
+        assert_eq!(next.as_deref(), Some("default/a/dev"));
+    }
+
+    #[test]
+    fn pages_cover_the_listing_once() {
+        let mut seen = Vec::new();
+        let mut token = None;
+        loop {
+            let mut page = pods();
+            token =
+                paginate(&mut page, key, Some(2), token.as_deref()).unwrap();
+            seen.extend(page);
+            if token.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(seen, pods());
+    }
+
+    #[test]
+    fn token_survives_changes_between_pages() {
+        let mut page = pods();
+        let next = paginate(&mut page, key, Some(2), None).unwrap();
+        assert_eq!(next.as_deref(), Some("default/b/dev"));
+
+        // The last pod listed went away and one sorting before it came.
+        let mut page = items(&[
+            ("dev", "default", "a"),
+            ("dev", "default", "aa"),
+            ("dev", "kube-system", "c"),
+            ("prod", "default", "d"),
+        ]);
+        let next = paginate(&mut page, key, Some(2), next.as_deref()).unwrap();
+        assert_eq!(names(&page), ["c", "d"]);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn token_past_the_end_yields_an_empty_page() {
+        let mut page = pods();
+        let token = Some("zzz/zzz/zzz");
+        assert_eq!(paginate(&mut page, key, Some(2), token).unwrap(), None);
+        assert!(page.is_empty());
+    }
+
+    #[test]
+    fn cluster_names_may_hold_a_slash() {
+        let mut page = items(&[
+            ("arn:aws:eks:cluster/a", "default", "web"),
+            ("arn:aws:eks:cluster/b", "default", "web"),
+        ]);
+        let token = Some("default/web/arn:aws:eks:cluster/a");
+        paginate(&mut page, key, None, token).unwrap();
+        assert_eq!(
+            page,
+            items(&[("arn:aws:eks:cluster/b", "default", "web")])
+        );
+    }
+
+    #[test]
+    fn zero_limit_is_invalid() {
+        let err = paginate(&mut pods(), key, Some(0), None).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidRequest);
+    }
+
+    #[test]
+    fn malformed_token_is_invalid() {
+        for token in ["", "default", "default/web"] {
+            let err =
+                paginate(&mut pods(), key, Some(2), Some(token)).unwrap_err();
+            assert_eq!(err.code, ErrorCode::InvalidRequest);
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Opt-in Prometheus endpoint, so that the client traffic shown by
+//! `kopsctl status` can be scraped and graphed.
+//!
+//! A bare HTTP/1.1 listener: every request gets the metrics, in the text
+//! exposition format, and the connection is closed.
+
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use kops_protocol::ClientTraffic;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tracing::{debug, info};
+
+use crate::state::DaemonState;
+
+/// How long a scraper gets to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most of a request read; the rest is ignored.
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+
+/// A client traffic counter: its name, help, and how to read it.
+type Counter = (&'static str, &'static str, fn(&ClientTraffic) -> u64);
+
+const COUNTERS: [Counter; 3] = [
+    ("received_bytes", "Bytes of requests", |t| t.bytes_in),
+    ("sent_bytes", "Bytes of responses", |t| t.bytes_out),
+    ("stalled_writes", "Responses that waited on a slow reader", |t| {
+        t.stalled_writes
+    }),
+];
+
+/// Listen for scrapers on `addr`, e.g. `127.0.0.1:9464`.
+pub async fn bind(addr: &str) -> Result<TcpListener> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to listen for metrics on {addr}"))?;
+    info!("serving metrics on http://{addr}/metrics");
+    Ok(listener)
+}
+
+/// Answer the scrapers of `listener` with the metrics of `state`.
+pub async fn serve(listener: TcpListener, state: Arc<DaemonState>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let state = state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = answer(stream, &state).await {
+                        debug!("metrics request failed: {e}");
+                    }
+                });
+            }
+            Err(e) => debug!("failed to accept metrics connection: {e}"),
+        }
+    }
+}
+
+async fn answer(mut stream: TcpStream, state: &DaemonState) -> Result<()> {
+    // The request only has to end its head; what it asks is not read.
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    timeout(REQUEST_TIMEOUT, async {
+        while !request.windows(4).any(|w| w == b"\r\n\r\n")
+            && request.len() < MAX_REQUEST_LEN
+        {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+        }
+        Ok::<_, std::io::Error>(())
+    })
+    .await
+    .context("request timed out")??;
+
+    let body = render(state);
+    let head = format!(
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Write the `# HELP` and `# TYPE` lines of metric `name`.
+fn header(out: &mut String, name: &str, type_: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {type_}");
+}
+
+/// Metrics of `state` in the Prometheus text exposition format.
+fn render(state: &DaemonState) -> String {
+    let mut out = String::new();
+
+    header(&mut out, "kopsd_uptime_seconds", "gauge", "Time since start.");
+    let uptime = state.started_at.elapsed().as_secs();
+    let _ = writeln!(out, "kopsd_uptime_seconds {uptime}");
+
+    header(
+        &mut out,
+        "kopsd_client_connections",
+        "gauge",
+        "Client connections currently open.",
+    );
+    let connections = state.connections.load(Ordering::Relaxed);
+    let _ = writeln!(out, "kopsd_client_connections {connections}");
+
+    let mut users: Vec<_> =
+        state.user_traffic.iter().map(|u| (*u.key(), u.snapshot())).collect();
+    users.sort_by_key(|(uid, _)| *uid);
+
+    let mut clients: Vec<_> = state
+        .clients
+        .iter()
+        .map(|c| (*c.key(), c.uid, c.traffic.snapshot()))
+        .collect();
+    clients.sort_by_key(|(id, _, _)| *id);
+
+    for (name, help, pick) in COUNTERS {
+        let metric = format!("kopsd_user_{name}_total");
+        header(&mut out, &metric, "counter", &format!("{help}, by user."));
+        for (uid, traffic) in &users {
+            let value = pick(traffic);
+            let _ = writeln!(out, "{metric}{{uid=\"{uid}\"}} {value}");
+        }
+
+        let metric = format!("kopsd_connection_{name}_total");
+        header(
+            &mut out,
+            &metric,
+            "counter",
+            &format!("{help}, by open connection."),
+        );
+        for (id, uid, traffic) in &clients {
+            let value = pick(traffic);
+            let _ = writeln!(
+                out,
+                "{metric}{{connection=\"{id}\",uid=\"{uid}\"}} {value}"
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "kopsd_cluster_objects",
+        "gauge",
+        "Objects held by the reflectors of each running cluster.",
+    );
+    let mut clusters = state.clusters();
+    clusters.sort_by(|a, b| a.name().cmp(b.name()));
+    for cluster in clusters {
+        let mut sizes: Vec<_> = cluster.store_sizes().into_iter().collect();
+        sizes.sort();
+        for (kind, objects) in sizes {
+            let _ = writeln!(
+                out,
+                "kopsd_cluster_objects{{cluster=\"{}\",kind=\"{kind}\"}} \
+                 {objects}",
+                escape(cluster.name())
+            );
+        }
+    }
+
+    out
+}
+
+/// `value` escaped for a label value.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_the_traffic_of_each_user() {
+        let state =
+            DaemonState::new(Some("dev".to_string()), Vec::new(), None);
+        state.user_traffic.insert(1000, Arc::default());
+
+        let metrics = render(&state);
+        assert!(metrics.contains("kopsd_client_connections 0\n"));
+        assert!(
+            metrics
+                .contains("# TYPE kopsd_user_received_bytes_total counter\n")
+        );
+        assert!(
+            metrics.contains("kopsd_user_sent_bytes_total{uid=\"1000\"} 0\n")
+        );
+        assert!(
+            metrics
+                .contains("kopsd_user_stalled_writes_total{uid=\"1000\"} 0\n")
+        );
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -0,0 +1,68 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! HTTP proxy of the daemon, `proxy_url` of `[kops]`, read at startup.
+//!
+//! The AWS API calls and the clusters without a proxy of their own (in
+//! their config or kubeconfig) go through it. Without it, the AWS SDK
+//! honors HTTPS_PROXY, HTTP_PROXY and NO_PROXY.
+
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use aws_smithy_http_client::{
+    Builder, Connector,
+    proxy::ProxyConfig,
+    tls::{self, rustls_provider::CryptoMode},
+};
+use aws_smithy_runtime_api::client::http::SharedHttpClient;
+
+static PROXY_URL: OnceLock<String> = OnceLock::new();
+static AWS_HTTP_CLIENT: OnceLock<SharedHttpClient> = OnceLock::new();
+
+/// Send the AWS calls and the cluster requests through `url`.
+pub(crate) fn init(url: Option<&str>) -> Result<()> {
+    let Some(url) = url else {
+        return Ok(());
+    };
+
+    let proxy = ProxyConfig::all(url)
+        .with_context(|| format!("invalid proxy_url {url}"))?;
+    // Only the connector takes a proxy; the client builds one per set of
+    // connector settings (timeouts) the SDK asks for.
+    let client = Builder::new().build_with_connector_fn(
+        move |settings, _components| {
+            Connector::builder()
+                .connector_settings(settings.cloned().unwrap_or_default())
+                .proxy_config(proxy.clone())
+                .tls_provider(tls::Provider::Rustls(CryptoMode::AwsLc))
+                .build()
+        },
+    );
+
+    let _ = PROXY_URL.set(url.to_string());
+    let _ = AWS_HTTP_CLIENT.set(client);
+    Ok(())
+}
+
+pub(crate) fn proxy_url() -> Option<&'static str> {
+    PROXY_URL.get().map(String::as_str)
+}
+
+/// HTTP client of the AWS SDK configs, the default one when `None`.
+pub(crate) fn aws_http_client() -> Option<SharedHttpClient> {
+    AWS_HTTP_CLIENT.get().cloned()
+}
@@ -0,0 +1,147 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Configuration reload, on SIGHUP or `Request::Reload`.
+//!
+//! Only the cluster and profile lists are applied: clusters added to the
+//! config file are started, removed ones are stopped and changed ones
+//! restarted; profile changes apply to the next login.
+//! Client connections and clusters added with `kopsctl cluster add` are
+//! left alone, unless the config file names them.
+
+use anyhow::Result;
+use tracing::{error, info};
+
+use crate::config::{self, ClusterConfig};
+use crate::discovery;
+use crate::kube_worker;
+use crate::state::DaemonState;
+
+/// Clusters a reload touched, sorted by name.
+#[derive(Debug, Default)]
+pub(crate) struct Reloaded {
+    pub started: Vec<String>,
+    pub stopped: Vec<String>,
+
+    /// Clusters that failed to start, as `name: error`.
+    pub failed: Vec<String>,
+}
+
+/// Re-read the config file and apply its cluster list.
+pub(crate) async fn reload(state: &DaemonState) -> Result<Reloaded> {
+    let config = config::load()?;
+
+    // Swapped first, so that a concurrent reload diffs against it.
+    let previous = state.replace_configured(config.cluster.clone());
+    // Discovered clusters are left running; a login finds new ones.
+    state.replace_profiles(config.profile.clone());
+
+    let mut reloaded = Reloaded::default();
+
+    for cfg in previous.iter().filter(|c| !config.cluster.contains(c)) {
+        if stop_configured(state, &cfg.name) {
+            info!("stopped cluster '{}' removed from config", cfg.name);
+            reloaded.stopped.push(cfg.name.clone());
+        }
+    }
+
+    for cfg in config.cluster.iter().filter(|c| !previous.contains(c)) {
+        match start_configured(state, cfg).await {
+            Ok(true) => {
+                info!("started cluster '{}' added to config", cfg.name);
+                reloaded.started.push(cfg.name.clone());
+            }
+            Ok(false) => {
+                info!("cluster '{}' waits for a login", cfg.name);
+            }
+            Err(err) => {
+                error!("failed to start cluster '{}': {err:#}", cfg.name);
+                reloaded.failed.push(format!("{}: {err:#}", cfg.name));
+            }
+        }
+    }
+
+    reloaded.started.sort();
+    reloaded.stopped.sort();
+    reloaded.failed.sort();
+
+    Ok(reloaded)
+}
+
+/// Start the clusters of the config file at daemon startup, and those
+/// discovered for the profiles with a restored session; the ones of an
+/// AWS profile wait for its login.
+pub(crate) async fn start(state: &DaemonState) {
+    let configured = state.configured();
+
+    // with those found for earlier profiles, whose names are taken too
+    let mut clusters = configured.clone();
+    let running = state.clusters();
+    for (profile, session) in state.sessions() {
+        if let Some(discovery) = state.profile_config(&profile) {
+            let found =
+                discovery::discover(&discovery, &session, &clusters, &running)
+                    .await;
+            clusters.extend(found);
+        }
+    }
+
+    for cfg in clusters {
+        match start_configured(state, &cfg).await {
+            Ok(true) => info!("started cluster '{}'", cfg.name),
+            Ok(false) => info!("cluster '{}' waits for a login", cfg.name),
+            Err(err) => {
+                error!("failed to start cluster '{}': {err:#}", cfg.name);
+            }
+        }
+    }
+}
+
+/// Stop cluster `name`; returns whether it was running.
+fn stop_configured(state: &DaemonState, name: &str) -> bool {
+    let Some(cluster) = state.remove_cluster(name) else {
+        return false;
+    };
+
+    cluster.stop();
+    true
+}
+
+/// Start the cluster of `cfg`, replacing a running one from an older
+/// config; returns false if its profile has no session yet.
+async fn start_configured(
+    state: &DaemonState,
+    cfg: &ClusterConfig,
+) -> Result<bool> {
+    let session = match cfg.eks_profile() {
+        Some(profile) => match state.session(profile) {
+            Some(session) => Some(session),
+            None => return Ok(false),
+        },
+        None => None,
+    };
+
+    let bus = state.bus.clone();
+    let cluster =
+        kube_worker::start_configured_cluster(cfg, session.as_ref(), bus)
+            .await?;
+
+    if let Some(old) = state.insert_cluster(cluster) {
+        old.stop();
+    }
+
+    Ok(true)
+}
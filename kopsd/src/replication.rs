@@ -0,0 +1,567 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Experimental failover pair, for teams sharing one daemon.
+//!
+//! The primary serves clients and sends its changes, as JSON lines of
+//! `Delta`, to a secondary over mutual TLS. The secondary runs the
+//! clusters of the same config file, so that its reflectors are warm,
+//! but refuses clients while the primary is up. Once the primary stops
+//! answering, the secondary takes over and serves reads only, until the
+//! primary is back.
+//!
+//! AWS sessions are replicated only when `sessions` is set, and never
+//! with their SSO refresh token: the secondary does not renew them, so
+//! it serves the clusters of a profile until its session expires.
+//! Clusters added with `kopsctl cluster add` are not replicated.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use kops_protocol::wire::{HEARTBEAT_INTERVAL, HEARTBEAT_TIMEOUT};
+use kops_protocol::{
+    KopsError, LoginRequest, Notification, Request, Response,
+};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{ClientConfig, RootCertStore, ServerConfig};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{interval, sleep, timeout};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tracing::{info, warn};
+
+use crate::config::{ReplicationConfig, ReplicationRole};
+use crate::handler::Handler;
+use crate::state::{AwsSession, DaemonState, ProfileName};
+
+/// How long the secondary waits before connecting to the primary again.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+const ACTIVE: u8 = 0;
+const STANDBY: u8 = 1;
+const TAKEN_OVER: u8 = 2;
+
+/// Whether the daemon serves its clients: always, unless it is the
+/// secondary of a failover pair.
+#[derive(Debug, Default)]
+pub struct Role(AtomicU8);
+
+impl Role {
+    /// Why `req` is refused, if it is.
+    pub fn refusal(&self, req: &Request) -> Option<KopsError> {
+        if always_served(req) {
+            return None;
+        }
+        match self.0.load(Ordering::Relaxed) {
+            STANDBY => Some(
+                KopsError::invalid(
+                    "this kopsd is the secondary of a failover pair, \
+                     use its primary",
+                )
+                .retryable(),
+            ),
+            TAKEN_OVER if !is_read(req) => Some(KopsError::invalid(
+                "the primary of this failover pair is down, its secondary \
+                 only serves reads",
+            )),
+            _ => None,
+        }
+    }
+
+    /// Refuse clients, the primary being up; returns whether the daemon
+    /// served them until now.
+    fn stand_by(&self) -> bool {
+        self.0.swap(STANDBY, Ordering::Relaxed) != STANDBY
+    }
+
+    /// Serve reads, the primary being down; returns whether the daemon
+    /// stood by until now.
+    fn take_over(&self) -> bool {
+        self.0
+            .compare_exchange(
+                STANDBY,
+                TAKEN_OVER,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+    }
+}
+
+/// Requests about the connection or the daemon itself, served whatever
+/// the role.
+fn always_served(req: &Request) -> bool {
+    matches!(
+        req,
+        Request::Ping
+            | Request::Version
+            | Request::Status
+            | Request::Heartbeat
+            | Request::Stream { .. }
+            | Request::Cancel { .. }
+            | Request::Reload
+            | Request::Shutdown
+    )
+}
+
+/// Requests that change nothing and hand out no credentials, served by
+/// a secondary that took over.
+fn is_read(req: &Request) -> bool {
+    matches!(
+        req,
+        Request::Sessions
+            | Request::SessionHealth
+            | Request::Clusters
+            | Request::Pods(_)
+            | Request::WatchPods(_)
+            | Request::Subscribe { .. }
+            | Request::DescribePod(_)
+            | Request::Get(_)
+            | Request::Deployments(_)
+            | Request::StatefulSets(_)
+            | Request::DaemonSets(_)
+            | Request::Events(_)
+            | Request::Restarts(_)
+            | Request::RestartHistory(_)
+            | Request::Nodes(_)
+            | Request::Services(_)
+            | Request::Ingresses(_)
+            | Request::Jobs(_)
+            | Request::Hpas(_)
+            | Request::Pvcs(_)
+            | Request::Images(_)
+            | Request::CronJobs(_)
+            | Request::Namespaces(_)
+            | Request::ConfigMaps(_)
+            | Request::ConfigMapData(_)
+            | Request::Secrets(_)
+            | Request::SecretValue(_)
+            | Request::Metrics(_)
+            | Request::Env(_)
+            | Request::EnvGrep(_)
+            | Request::Logs(_)
+    )
+}
+
+/// What the primary sends the secondary, one JSON line each.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Delta {
+    /// State of the primary when the secondary connects, or after the
+    /// primary missed some of its changes; no sessions when they are not
+    /// replicated.
+    Snapshot { sessions: Option<Vec<(ProfileName, AwsSession)>> },
+
+    /// Session of `profile` stored by a login or a renewal.
+    Session { profile: ProfileName, session: Box<AwsSession> },
+
+    /// Session of `profile` removed by a logout.
+    Logout { profile: ProfileName },
+
+    /// Sent every `HEARTBEAT_INTERVAL`.
+    Heartbeat,
+}
+
+/// Take the role of `config` in its failover pair.
+pub async fn start(
+    config: &ReplicationConfig,
+    handler: &Arc<Handler>,
+) -> Result<()> {
+    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+    match config.role {
+        ReplicationRole::Primary => {
+            let Some(addr) = &config.listen else {
+                bail!("the primary of a failover pair needs `listen`");
+            };
+            let acceptor = TlsAcceptor::from(Arc::new(server_config(config)?));
+            let listener =
+                TcpListener::bind(addr).await.with_context(|| {
+                    format!("failed to listen for the secondary on {addr}")
+                })?;
+            info!("accepting the secondary on {addr}");
+            let state = handler.state().clone();
+            tokio::spawn(lead(listener, acceptor, state, config.sessions));
+        }
+        ReplicationRole::Secondary => {
+            let Some(primary) = config.primary.clone() else {
+                bail!("the secondary of a failover pair needs `primary`");
+            };
+            let name = match &config.server_name {
+                Some(name) => name.clone(),
+                None => host(&primary).to_string(),
+            };
+            let name = ServerName::try_from(name)
+                .context("invalid server name of the primary")?;
+            let connector =
+                TlsConnector::from(Arc::new(client_config(config)?));
+            handler.state().role.stand_by();
+            tokio::spawn(follow(primary, name, connector, handler.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Host of `addr`, e.g. `kopsd-1` of `kopsd-1:7443`.
+fn host(addr: &str) -> &str {
+    let host = addr.rsplit_once(':').map_or(addr, |(host, _)| host);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+fn certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect())
+        .with_context(|| {
+            format!("failed to read certificates from {}", path.display())
+        })
+}
+
+fn key(path: &Path) -> Result<PrivateKeyDer<'static>> {
+    PrivateKeyDer::from_pem_file(path).with_context(|| {
+        format!("failed to read private key from {}", path.display())
+    })
+}
+
+fn roots(path: &Path) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for cert in certs(path)? {
+        roots.add(cert).with_context(|| {
+            format!("invalid CA certificate in {}", path.display())
+        })?;
+    }
+    Ok(roots)
+}
+
+fn server_config(config: &ReplicationConfig) -> Result<ServerConfig> {
+    let verifier =
+        WebPkiClientVerifier::builder(Arc::new(roots(&config.ca)?)).build()?;
+    ServerConfig::builder()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certs(&config.cert)?, key(&config.key)?)
+        .context("invalid certificate or key")
+}
+
+fn client_config(config: &ReplicationConfig) -> Result<ClientConfig> {
+    ClientConfig::builder()
+        .with_root_certificates(roots(&config.ca)?)
+        .with_client_auth_cert(certs(&config.cert)?, key(&config.key)?)
+        .context("invalid certificate or key")
+}
+
+/// Send the changes of `state` to each secondary accepted on `listener`.
+async fn lead(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    state: Arc<DaemonState>,
+    sessions: bool,
+) {
+    loop {
+        let (stream, addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("failed to accept the secondary: {e}");
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let state = state.clone();
+        tokio::spawn(async move {
+            let res = match acceptor.accept(stream).await {
+                Ok(stream) => {
+                    info!(%addr, "secondary connected");
+                    feed(stream, &state, sessions).await
+                }
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = res {
+                warn!(%addr, "secondary disconnected: {e:#}");
+            }
+        });
+    }
+}
+
+/// Send the state of the primary, then its changes, to `stream` until
+/// the secondary goes away.
+async fn feed<S: AsyncWrite + Unpin>(
+    mut stream: S,
+    state: &DaemonState,
+    sessions: bool,
+) -> Result<()> {
+    // before the snapshot, so that no change is missed in between
+    let mut changes = state.bus.subscribe();
+    send(&mut stream, &snapshot(state, sessions)).await?;
+
+    let mut heartbeat = interval(HEARTBEAT_INTERVAL);
+    loop {
+        let delta = tokio::select! {
+            _ = heartbeat.tick() => Delta::Heartbeat,
+            change = changes.recv() => match change {
+                Ok(notification) => {
+                    match delta(state, sessions, notification) {
+                        Some(delta) => delta,
+                        None => continue,
+                    }
+                }
+                Err(RecvError::Lagged(_)) => snapshot(state, sessions),
+                Err(RecvError::Closed) => return Ok(()),
+            },
+        };
+        send(&mut stream, &delta).await?;
+    }
+}
+
+fn snapshot(state: &DaemonState, sessions: bool) -> Delta {
+    let sessions = sessions.then(|| {
+        state
+            .sessions()
+            .into_iter()
+            .map(|(profile, session)| (profile, replicated(session)))
+            .collect()
+    });
+    Delta::Snapshot { sessions }
+}
+
+/// The change `notification` tells of, if it is replicated. Expired
+/// sessions are not: the secondary expires them itself.
+fn delta(
+    state: &DaemonState,
+    sessions: bool,
+    notification: Notification,
+) -> Option<Delta> {
+    if !sessions {
+        return None;
+    }
+    match notification {
+        Notification::SessionStored { profile, .. } => {
+            let session = replicated(state.session(&profile)?);
+            Some(Delta::Session { profile, session: Box::new(session) })
+        }
+        Notification::SessionRemoved { profile } => {
+            Some(Delta::Logout { profile })
+        }
+        _ => None,
+    }
+}
+
+/// `session` without its refresh token, which stays on the primary.
+fn replicated(session: AwsSession) -> AwsSession {
+    AwsSession { refresh: None, ..session }
+}
+
+async fn send<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    delta: &Delta,
+) -> Result<()> {
+    let mut line = serde_json::to_vec(delta)?;
+    line.push(b'\n');
+    stream.write_all(&line).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Apply the changes of `primary` to the state of `handler`, taking over
+/// whenever the primary is unreachable.
+async fn follow(
+    primary: String,
+    name: ServerName<'static>,
+    connector: TlsConnector,
+    handler: Arc<Handler>,
+) {
+    let state = handler.state().clone();
+    loop {
+        tokio::select! {
+            res = replicate(&primary, &name, &connector, &handler) => {
+                if let Err(e) = res {
+                    warn!("lost the primary {primary}: {e:#}");
+                }
+            }
+            () = state.shutdown.cancelled() => return,
+        }
+        if state.role.take_over() {
+            warn!("primary {primary} is down, serving reads");
+        }
+        tokio::select! {
+            () = sleep(RECONNECT_DELAY) => {}
+            () = state.shutdown.cancelled() => return,
+        }
+    }
+}
+
+async fn replicate(
+    primary: &str,
+    name: &ServerName<'static>,
+    connector: &TlsConnector,
+    handler: &Handler,
+) -> Result<()> {
+    let stream = timeout(HEARTBEAT_TIMEOUT, TcpStream::connect(primary))
+        .await
+        .context("timed out connecting")??;
+    let stream = connector.connect(name.clone(), stream).await?;
+
+    let mut lines = BufReader::new(stream).lines();
+    loop {
+        let Some(line) = timeout(HEARTBEAT_TIMEOUT, lines.next_line())
+            .await
+            .context("no heartbeat")??
+        else {
+            bail!("connection closed");
+        };
+        let delta: Delta =
+            serde_json::from_str(&line).context("invalid delta")?;
+        if matches!(delta, Delta::Snapshot { .. })
+            && handler.state().role.stand_by()
+        {
+            info!("following primary {primary}");
+        }
+        apply(handler, delta).await;
+    }
+}
+
+async fn apply(handler: &Handler, delta: Delta) {
+    match delta {
+        Delta::Snapshot { sessions: Some(sessions) } => {
+            let state = handler.state();
+            for (profile, _) in state.sessions() {
+                if !sessions.iter().any(|(p, _)| *p == profile) {
+                    logout(handler, profile).await;
+                }
+            }
+            for (profile, session) in sessions {
+                let stored = state
+                    .session(&profile)
+                    .is_some_and(|s| s.access_key_id == session.access_key_id);
+                if !stored {
+                    login(handler, profile, session).await;
+                }
+            }
+        }
+        Delta::Snapshot { sessions: None } | Delta::Heartbeat => {}
+        Delta::Session { profile, session } => {
+            login(handler, profile, *session).await;
+        }
+        Delta::Logout { profile } => logout(handler, profile).await,
+    }
+}
+
+/// Store `session` as a login of `profile` would, starting its clusters.
+async fn login(handler: &Handler, profile: ProfileName, session: AwsSession) {
+    let req = LoginRequest {
+        name: profile.clone(),
+        region: session.region,
+        account_id: session.account_id,
+        role_name: session.role_name,
+        access_key_id: session.access_key_id,
+        secret_access_key: session.secret_access_key,
+        session_token: session.session_token,
+        expires_at_epoch_ms: session.expires_at.timestamp_millis(),
+        refresh: None,
+    };
+    match handler.handle(Request::Login(req)).await {
+        Response::Error(e) => {
+            warn!("failed to replicate session of profile '{profile}': {e}");
+        }
+        _ => info!("replicated session of profile '{profile}'"),
+    }
+}
+
+async fn logout(handler: &Handler, profile: ProfileName) {
+    let req = Request::Logout { profile: profile.clone() };
+    if let Response::Error(e) = handler.handle(req).await {
+        warn!("failed to replicate logout of profile '{profile}': {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use kops_protocol::SsoRefresh;
+
+    use super::*;
+
+    #[test]
+    fn secondary_serves_reads_only_after_taking_over() {
+        let role = Role::default();
+        let clusters = Request::Clusters;
+        let logout = Request::Logout { profile: "dev".to_string() };
+        assert!(role.refusal(&clusters).is_none());
+        assert!(role.refusal(&logout).is_none());
+
+        assert!(role.stand_by());
+        assert!(role.refusal(&clusters).is_some_and(|e| e.retryable));
+        assert!(role.refusal(&logout).is_some());
+        assert!(role.refusal(&Request::Ping).is_none());
+
+        assert!(role.take_over());
+        assert!(role.refusal(&clusters).is_none());
+        assert!(role.refusal(&logout).is_some());
+        assert!(!role.take_over());
+    }
+
+    #[tokio::test]
+    async fn replicated_sessions_leave_out_their_refresh_token() {
+        let state =
+            DaemonState::new(Some("dev".to_string()), Vec::new(), None);
+        state.store_session(
+            "dev".to_string(),
+            AwsSession {
+                account_id: "123456789012".to_string(),
+                role_name: "Admin".to_string(),
+                region: None,
+                access_key_id: "ASIAEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: "token".to_string(),
+                expires_at: Utc::now(),
+                refresh: Some(SsoRefresh {
+                    region: "eu-west-1".to_string(),
+                    start_url: "https://example.awsapps.com/start".into(),
+                    client_id: "client".to_string(),
+                    client_secret: "client-secret".to_string(),
+                    refresh_token: "refresh-token".to_string(),
+                }),
+            },
+        );
+
+        let (rd, wr) = tokio::io::duplex(64 * 1024);
+        let feeding = async {
+            let _ = feed(wr, &state, true).await;
+        };
+        let reading = async {
+            let mut lines = BufReader::new(rd).lines();
+            lines.next_line().await.unwrap().unwrap()
+        };
+        let line = tokio::select! {
+            () = feeding => unreachable!("the feed stopped"),
+            line = reading => line,
+        };
+
+        let Ok(Delta::Snapshot { sessions: Some(sessions) }) =
+            serde_json::from_str(&line)
+        else {
+            panic!("not a snapshot: {line}");
+        };
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].1.access_key_id, "ASIAEXAMPLE");
+        assert!(sessions[0].1.refresh.is_none());
+        assert!(!line.contains("refresh-token"));
+    }
+}
@@ -0,0 +1,295 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use k8s_openapi::api::core::v1::Pod;
+use kops_protocol::EventSummary;
+use kube::ResourceExt;
+
+/// How long restart records are kept in memory.
+const RETENTION_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// How far before a restart Warning events are taken as its cause.
+const CAUSE_WINDOW_MS: i64 = 10 * 60 * 1000;
+
+/// Most causes attached to one restart.
+const MAX_CAUSES: usize = 3;
+
+/// One observed increase of a container restart count.
+#[derive(Clone, Debug)]
+pub struct RestartRecord {
+    /// When the container last terminated, or when the increase was seen
+    /// if the status does not tell.
+    pub at_epoch_ms: i64,
+    pub namespace: String,
+    pub pod: String,
+    pub container: String,
+
+    /// Restarts since the previous observation of the container.
+    pub count: u32,
+    pub reason: Option<String>,
+    pub exit_code: Option<i32>,
+
+    /// Node the pod ran on, whose Warning events may explain the restart.
+    pub node: Option<String>,
+}
+
+/// (namespace, pod, container)
+type ContainerKey = (String, String, String);
+
+#[derive(Default)]
+struct Inner {
+    last_counts: HashMap<ContainerKey, i32>,
+    records: Vec<RestartRecord>,
+}
+
+/// Restart history of a cluster, fed by the pod reflector.
+#[derive(Default)]
+pub struct RestartHistory {
+    inner: Mutex<Inner>,
+}
+
+impl RestartHistory {
+    /// Record the restart-count deltas of `pod` since it was last seen.
+    ///
+    /// The first sighting of a container only sets its baseline, so
+    /// restarts from before kopsd started are not reported.
+    pub fn observe(&self, pod: &Pod) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+
+        let now = Utc::now().timestamp_millis();
+        let namespace = pod.namespace().unwrap_or_default();
+        let name = pod.name_any();
+        let node = pod.spec.as_ref().and_then(|s| s.node_name.clone());
+
+        let statuses = pod.status.iter().flat_map(|s| {
+            s.init_container_statuses
+                .iter()
+                .flatten()
+                .chain(s.container_statuses.iter().flatten())
+        });
+
+        for cs in statuses {
+            let key = (namespace.clone(), name.clone(), cs.name.clone());
+            let Some(previous) =
+                inner.last_counts.insert(key, cs.restart_count)
+            else {
+                continue;
+            };
+            if cs.restart_count <= previous {
+                continue;
+            }
+
+            let terminated =
+                cs.last_state.as_ref().and_then(|s| s.terminated.as_ref());
+            let finished_at = terminated
+                .and_then(|t| t.finished_at.as_ref())
+                .map(|t| t.0.timestamp_millis());
+            let at_epoch_ms = finished_at.unwrap_or(now);
+
+            inner.records.push(RestartRecord {
+                at_epoch_ms,
+                namespace: namespace.clone(),
+                pod: name.clone(),
+                container: cs.name.clone(),
+                count: (cs.restart_count - previous) as u32,
+                reason: terminated.and_then(|t| t.reason.clone()),
+                exit_code: terminated.map(|t| t.exit_code),
+                node: node.clone(),
+            });
+        }
+
+        inner.records.retain(|r| r.at_epoch_ms >= now - RETENTION_MS);
+    }
+
+    /// Drop the baselines of a deleted pod. Its records are kept until
+    /// they expire.
+    pub fn forget(&self, pod: &Pod) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+
+        let namespace = pod.namespace().unwrap_or_default();
+        let name = pod.name_any();
+        inner
+            .last_counts
+            .retain(|(ns, p, _), _| *ns != namespace || *p != name);
+    }
+
+    /// Records of the restarts of one pod, oldest first.
+    pub fn of_pod(&self, namespace: &str, pod: &str) -> Vec<RestartRecord> {
+        let Ok(inner) = self.inner.lock() else {
+            return Vec::new();
+        };
+
+        inner
+            .records
+            .iter()
+            .filter(|r| r.namespace == namespace && r.pod == pod)
+            .cloned()
+            .collect()
+    }
+
+    /// Records of restarts at or after `since_epoch_ms`.
+    pub fn since(&self, since_epoch_ms: i64) -> Vec<RestartRecord> {
+        let Ok(inner) = self.inner.lock() else {
+            return Vec::new();
+        };
+
+        inner
+            .records
+            .iter()
+            .filter(|r| r.at_epoch_ms >= since_epoch_ms)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Warning events of `events` about the pod of `record`, or about its
+/// node (e.g. `OOMKilling`), seen within `CAUSE_WINDOW_MS` before the
+/// restart; the closest `MAX_CAUSES` of them, oldest first.
+///
+/// An event repeated over several restarts is coalesced into one whose
+/// `last_seen` is its latest repetition, so it counts for every restart
+/// between its first sighting and the window after its last one.
+///
+/// `BackOff` events are left out: they follow restarts rather than cause
+/// them.
+pub fn causes<'a>(
+    record: &RestartRecord,
+    events: impl IntoIterator<Item = &'a EventSummary>,
+) -> Vec<EventSummary> {
+    let pod_object = format!("Pod/{}", record.pod);
+    let node_object = record.node.as_ref().map(|node| format!("Node/{node}"));
+
+    let mut causes: Vec<EventSummary> = events
+        .into_iter()
+        .filter(|e| e.type_ == "Warning" && e.reason != "BackOff")
+        .filter(|e| {
+            (e.object == pod_object && e.namespace == record.namespace)
+                || node_object.as_ref() == Some(&e.object)
+        })
+        .filter(|e| {
+            let Some(last_seen) = e.last_seen_epoch_ms else {
+                return false;
+            };
+            let first_seen = e.first_seen_epoch_ms.unwrap_or(last_seen);
+            first_seen <= record.at_epoch_ms
+                && last_seen >= record.at_epoch_ms - CAUSE_WINDOW_MS
+        })
+        .cloned()
+        .collect();
+
+    causes.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+    causes.split_off(causes.len().saturating_sub(MAX_CAUSES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AT: i64 = 1_000_000_000;
+
+    fn record() -> RestartRecord {
+        RestartRecord {
+            at_epoch_ms: AT,
+            namespace: "default".to_string(),
+            pod: "api".to_string(),
+            container: "app".to_string(),
+            count: 1,
+            reason: None,
+            exit_code: None,
+            node: Some("node-1".to_string()),
+        }
+    }
+
+    fn warning(
+        namespace: &str,
+        object: &str,
+        reason: &str,
+        seen: i64,
+    ) -> EventSummary {
+        EventSummary {
+            cluster: "dev".to_string(),
+            namespace: namespace.to_string(),
+            name: format!("{object}.{reason}"),
+            type_: "Warning".to_string(),
+            reason: reason.to_string(),
+            object: object.to_string(),
+            message: String::new(),
+            count: 1,
+            first_seen_epoch_ms: Some(seen),
+            last_seen_epoch_ms: Some(seen),
+        }
+    }
+
+    fn reasons(causes: &[EventSummary]) -> Vec<&str> {
+        causes.iter().map(|c| c.reason.as_str()).collect()
+    }
+
+    #[test]
+    fn causes_are_warnings_about_the_pod_or_its_node() {
+        let events = [
+            warning("default", "Pod/api", "Unhealthy", AT - 1),
+            warning("", "Node/node-1", "OOMKilling", AT - 2),
+            warning("other", "Pod/api", "Unhealthy", AT - 3),
+            warning("default", "Pod/web", "Unhealthy", AT - 4),
+            warning("", "Node/node-2", "OOMKilling", AT - 5),
+            warning("default", "Pod/api", "BackOff", AT - 6),
+        ];
+
+        let causes = causes(&record(), &events);
+        assert_eq!(reasons(&causes), ["OOMKilling", "Unhealthy"]);
+    }
+
+    #[test]
+    fn causes_are_the_closest_within_the_window() {
+        let events = [
+            warning("default", "Pod/api", "Late", AT + 1),
+            warning("default", "Pod/api", "Old", AT - CAUSE_WINDOW_MS - 1),
+            warning("default", "Pod/api", "A", AT - 4),
+            warning("default", "Pod/api", "B", AT - 3),
+            warning("default", "Pod/api", "C", AT - 2),
+            warning("default", "Pod/api", "D", AT - 1),
+        ];
+
+        let causes = causes(&record(), &events);
+        assert_eq!(reasons(&causes), ["B", "C", "D"]);
+    }
+
+    #[test]
+    fn coalesced_warning_is_a_cause_of_every_restart_it_spans() {
+        // Seen before the first restart, repeated until after the last.
+        let mut unhealthy = warning("default", "Pod/api", "Unhealthy", AT);
+        unhealthy.first_seen_epoch_ms = Some(AT - 60_000);
+        unhealthy.last_seen_epoch_ms = Some(AT + 3 * CAUSE_WINDOW_MS);
+        unhealthy.count = 40;
+
+        for at in [AT, AT + CAUSE_WINDOW_MS, AT + 2 * CAUSE_WINDOW_MS] {
+            let restart = RestartRecord { at_epoch_ms: at, ..record() };
+            let causes = causes(&restart, [&unhealthy]);
+            assert_eq!(reasons(&causes), ["Unhealthy"], "restart at {at}");
+        }
+
+        let before = RestartRecord { at_epoch_ms: AT - 120_000, ..record() };
+        assert!(causes(&before, [&unhealthy]).is_empty());
+    }
+}
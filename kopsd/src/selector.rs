@@ -0,0 +1,348 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Kubernetes label and field selectors, matched against cached objects.
+
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::Pod;
+
+/// One requirement of a label selector.
+#[derive(Debug)]
+enum Requirement {
+    Exists(String),
+    NotExists(String),
+    Equals(String, String),
+    NotEquals(String, String),
+    In(String, Vec<String>),
+    NotIn(String, Vec<String>),
+}
+
+/// Label selector such as `app=payments,tier in (web,api),!canary`.
+#[derive(Debug)]
+pub(crate) struct LabelSelector {
+    requirements: Vec<Requirement>,
+}
+
+impl LabelSelector {
+    /// Parse the selector syntax accepted by `kubectl -l`.
+    pub(crate) fn parse(s: &str) -> Result<Self, String> {
+        let requirements = split_terms(s)
+            .into_iter()
+            .map(parse_requirement)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { requirements })
+    }
+
+    /// Whether `labels` satisfy every requirement.
+    pub(crate) fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.requirements.iter().all(|r| match r {
+            Requirement::Exists(k) => labels.contains_key(k),
+            Requirement::NotExists(k) => !labels.contains_key(k),
+            Requirement::Equals(k, v) => labels.get(k) == Some(v),
+            // Like Kubernetes, a missing label satisfies != and notin.
+            Requirement::NotEquals(k, v) => labels.get(k) != Some(v),
+            Requirement::In(k, vs) => {
+                labels.get(k).is_some_and(|v| vs.contains(v))
+            }
+            Requirement::NotIn(k, vs) => {
+                labels.get(k).is_none_or(|v| !vs.contains(v))
+            }
+        })
+    }
+}
+
+/// Split on commas that are not inside a `(...)` value set.
+fn split_terms(s: &str) -> Vec<&str> {
+    let mut terms = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                terms.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    terms.push(&s[start..]);
+
+    terms.into_iter().map(str::trim).filter(|t| !t.is_empty()).collect()
+}
+
+fn parse_requirement(term: &str) -> Result<Requirement, String> {
+    if let Some(key) = term.strip_prefix('!') {
+        return Ok(Requirement::NotExists(parse_key(key)?));
+    }
+
+    if let Some((key, value)) = term.split_once("!=") {
+        return Ok(Requirement::NotEquals(
+            parse_key(key)?,
+            parse_value(value),
+        ));
+    }
+
+    if let Some((key, value)) =
+        term.split_once("==").or_else(|| term.split_once('='))
+    {
+        return Ok(Requirement::Equals(parse_key(key)?, parse_value(value)));
+    }
+
+    if let Some(open) = term.find('(') {
+        let mut words = term[..open].split_whitespace();
+        let (Some(key), Some(op), None) =
+            (words.next(), words.next(), words.next())
+        else {
+            return Err(format!("invalid label selector term: {term}"));
+        };
+        let Some(set) = term[open + 1..].strip_suffix(')') else {
+            return Err(format!("unterminated value set in: {term}"));
+        };
+
+        let key = parse_key(key)?;
+        let values: Vec<String> = set.split(',').map(parse_value).collect();
+
+        return match op {
+            "in" => Ok(Requirement::In(key, values)),
+            "notin" => Ok(Requirement::NotIn(key, values)),
+            _ => Err(format!("unknown label selector operator: {op}")),
+        };
+    }
+
+    Ok(Requirement::Exists(parse_key(term)?))
+}
+
+fn parse_key(key: &str) -> Result<String, String> {
+    let key = key.trim();
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return Err(format!("invalid label key: {key:?}"));
+    }
+
+    Ok(key.to_string())
+}
+
+fn parse_value(value: &str) -> String {
+    value.trim().to_string()
+}
+
+/// Field selector such as `status.phase=Running,spec.nodeName!=node-1`.
+///
+/// Only the pod fields the API server itself supports are accepted.
+#[derive(Debug)]
+pub(crate) struct FieldSelector {
+    /// (field, value, negated)
+    terms: Vec<(String, String, bool)>,
+}
+
+/// Pod fields accepted in a field selector.
+const POD_FIELDS: &[&str] = &[
+    "metadata.name",
+    "metadata.namespace",
+    "spec.nodeName",
+    "spec.restartPolicy",
+    "spec.schedulerName",
+    "spec.serviceAccountName",
+    "status.phase",
+    "status.podIP",
+    "status.nominatedNodeName",
+];
+
+impl FieldSelector {
+    pub(crate) fn parse(s: &str) -> Result<Self, String> {
+        let mut terms = Vec::new();
+
+        for term in split_terms(s) {
+            let (field, value, negated) = if let Some((f, v)) =
+                term.split_once("!=")
+            {
+                (f, v, true)
+            } else if let Some((f, v)) =
+                term.split_once("==").or_else(|| term.split_once('='))
+            {
+                (f, v, false)
+            } else {
+                return Err(format!("invalid field selector term: {term}"));
+            };
+
+            let field = field.trim();
+            if !POD_FIELDS.contains(&field) {
+                return Err(format!("field not supported for pods: {field}"));
+            }
+
+            terms.push((field.to_string(), parse_value(value), negated));
+        }
+
+        Ok(Self { terms })
+    }
+
+    /// Whether `pod` satisfies every term; unset fields compare as "".
+    pub(crate) fn matches(&self, pod: &Pod) -> bool {
+        self.terms.iter().all(|(field, value, negated)| {
+            let actual = pod_field(pod, field).unwrap_or_default();
+            (actual == value.as_str()) != *negated
+        })
+    }
+}
+
+pub(crate) fn pod_field<'a>(pod: &'a Pod, field: &str) -> Option<&'a str> {
+    let spec = pod.spec.as_ref();
+    let status = pod.status.as_ref();
+
+    match field {
+        "metadata.name" => pod.metadata.name.as_deref(),
+        "metadata.namespace" => pod.metadata.namespace.as_deref(),
+        "spec.nodeName" => spec?.node_name.as_deref(),
+        "spec.restartPolicy" => spec?.restart_policy.as_deref(),
+        "spec.schedulerName" => spec?.scheduler_name.as_deref(),
+        "spec.serviceAccountName" => spec?.service_account_name.as_deref(),
+        "status.phase" => status?.phase.as_deref(),
+        "status.podIP" => status?.pod_ip.as_deref(),
+        "status.nominatedNodeName" => status?.nominated_node_name.as_deref(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::api::core::v1::{PodSpec, PodStatus};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn selects(selector: &str, pairs: &[(&str, &str)]) -> bool {
+        LabelSelector::parse(selector).unwrap().matches(&labels(pairs))
+    }
+
+    #[test]
+    fn label_equality() {
+        assert!(selects("app=web", &[("app", "web")]));
+        assert!(selects("app == web", &[("app", "web"), ("tier", "x")]));
+        assert!(!selects("app=web", &[("app", "api")]));
+        assert!(!selects("app=web", &[]));
+
+        assert!(selects("app!=web", &[("app", "api")]));
+        assert!(!selects("app!=web", &[("app", "web")]));
+        // Like Kubernetes, a missing label satisfies !=.
+        assert!(selects("app!=web", &[]));
+    }
+
+    #[test]
+    fn label_existence() {
+        assert!(selects("canary", &[("canary", "")]));
+        assert!(!selects("canary", &[]));
+        assert!(selects("!canary", &[("app", "web")]));
+        assert!(!selects("!canary", &[("canary", "true")]));
+    }
+
+    #[test]
+    fn label_sets() {
+        let sel = "tier in (web, api)";
+        assert!(selects(sel, &[("tier", "web")]));
+        assert!(selects(sel, &[("tier", "api")]));
+        assert!(!selects(sel, &[("tier", "db")]));
+        assert!(!selects(sel, &[]));
+
+        let sel = "tier notin (web,api)";
+        assert!(selects(sel, &[("tier", "db")]));
+        assert!(selects(sel, &[]));
+        assert!(!selects(sel, &[("tier", "web")]));
+    }
+
+    #[test]
+    fn every_term_must_match() {
+        let sel = "app=payments, tier in (web,api), !canary";
+        assert!(selects(sel, &[("app", "payments"), ("tier", "api")]));
+        assert!(!selects(sel, &[("app", "payments"), ("tier", "db")]));
+        assert!(!selects(
+            sel,
+            &[("app", "payments"), ("tier", "web"), ("canary", "1")]
+        ));
+    }
+
+    #[test]
+    fn empty_selector_matches_everything() {
+        assert!(selects("", &[]));
+        assert!(selects(" , ", &[("app", "web")]));
+    }
+
+    #[test]
+    fn malformed_label_selectors_are_rejected() {
+        for sel in [
+            "=web",
+            "!",
+            "my app=web",
+            "tier in (web,api",
+            "tier (web)",
+            "tier in x (web)",
+            "tier is (web)",
+        ] {
+            assert!(LabelSelector::parse(sel).is_err(), "accepted {sel:?}");
+        }
+    }
+
+    fn pod() -> Pod {
+        Pod {
+            metadata: ObjectMeta {
+                name: Some("web-1".to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
+            },
+            spec: Some(PodSpec {
+                node_name: Some("node-1".to_string()),
+                ..Default::default()
+            }),
+            status: Some(PodStatus {
+                phase: Some("Running".to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn fields(selector: &str) -> bool {
+        FieldSelector::parse(selector).unwrap().matches(&pod())
+    }
+
+    #[test]
+    fn field_terms() {
+        assert!(fields("status.phase=Running"));
+        assert!(fields("metadata.name==web-1,metadata.namespace=default"));
+        assert!(fields("spec.nodeName!=node-2"));
+        assert!(!fields("spec.nodeName!=node-1"));
+        assert!(!fields("status.phase=Pending,metadata.name=web-1"));
+    }
+
+    #[test]
+    fn unset_fields_compare_as_empty() {
+        assert!(fields("status.podIP="));
+        assert!(fields("spec.serviceAccountName!=default"));
+    }
+
+    #[test]
+    fn malformed_field_selectors_are_rejected() {
+        for sel in ["status.phase", "metadata.labels=x", "spec.nodename=a"] {
+            assert!(FieldSelector::parse(sel).is_err(), "accepted {sel:?}");
+        }
+    }
+}
@@ -18,99 +18,74 @@
 use std::{
     collections::HashMap,
     os::unix::fs::PermissionsExt,
-    sync::{Arc, Mutex},
+    path::Path,
+    sync::{Arc, atomic::Ordering},
+    time::Duration,
 };
 
 use anyhow::{Context, Result};
 use daemonize::Daemonize;
 use tokio::{
-    fs::remove_file,
-    net::{UnixListener, UnixStream},
-    signal, task,
+    fs::{create_dir_all, remove_file},
+    io::AsyncReadExt,
+    net::{UnixListener, UnixStream, unix::UCred},
+    signal::{self, unix::SignalKind},
+    sync::mpsc,
+    task::JoinHandle,
+    time::{interval, sleep, timeout},
 };
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 use kops_protocol::{
-    Request,
-    wire::{read_message, write_message},
+    ErrorCode, KopsError, Request, Response, socket,
+    wire::{
+        Codec, Encoding, Frame, HEARTBEAT_INTERVAL, WireError,
+        accept_handshake, read_message_as, write_message_as,
+    },
 };
 
 use crate::{
+    access::AccessPolicy,
+    audit::{self, Audit, AuditLog, Entry},
     config::{self, KopsdConfig},
+    expiry,
     handler::Handler,
-    // kube_worker::start_cluster_worker,
-    kube_worker::init_cluster_state,
-    state::{ClusterState, DaemonState},
+    prometheus, proxy, reload, replication,
+    session_store::SessionStore,
+    state::DaemonState,
+    traffic::Metered,
 };
 
-const SOCKET_PATH: &str = "/var/run/kopsd/kopsd.sock";
+/// A client connection, counting its traffic.
+type ClientStream = Metered<UnixStream>;
+
+/// Responses a streamed request may produce ahead of the socket.
+const STREAM_BUFFER: usize = 64;
+
+/// How long open connections may keep the daemon up once it stops.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
 
 pub(crate) fn run(args: &crate::Args) -> Result<()> {
     kops_log::init(args.verbose);
 
     let config = config::load()?;
 
-    if args.daemon {
-        run_fg(&config)?;
-    }
-    // } else {
-    //     run_bg(&config, handler)?;
-    // }
+    let socket =
+        socket::resolve(args.socket.clone(), config.kops.socket.clone());
 
-    Ok(())
+    if args.foreground {
+        run_fg(&config, &socket)
+    } else {
+        run_bg(&config, &socket)
+    }
 }
 
-fn run_fg(config: &KopsdConfig) -> Result<()> {
-    let rt = tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()
-        .context("failed to build tokio runtime")?;
-
-    rt.block_on(async move {
-        // for c in &config.cluster {
-        //     let cs = init_cluster_state(c.clone()).await.unwrap();
-        //     // clusters_map.insert(c.name.clone(), cs);
-        // }
-
-        let default_cluster = config
-            .kops
-            .default_cluster
-            .clone()
-            .unwrap_or_else(|| config.cluster[0].name.clone());
-
-        // let state =
-        //     Arc::new(DaemonState { clusters: clusters_map, default_cluster });
-        let state = Arc::new(DaemonState {
-            clusters: Mutex::new(HashMap::new()),
-            default_cluster,
-            aws_sessions: Mutex::new(HashMap::new()),
-        });
-
-        // for c in config.cluster.clone() {
-        //     let cluster_name = c.name.clone();
-        //     let cluster_state = state
-        //         .clusters
-        //         .get(&cluster_name)
-        //         .cloned()
-        //         .expect("cluster state must exist");
-
-        //     task::spawn(async move {
-        //         if let Err(err) =
-        //             start_cluster_worker(c, cluster_state, cluster_name.clone())
-        //                 .await
-        //         {
-        //             error!(cluster = %cluster_name, "cluster worker failed: {err:?}");
-        //         }
-        //     });
-        // }
-
-        let handler = Arc::new(Handler::new(state.clone()));
-
-        _run(config, handler).await
-    })
+fn run_fg(config: &KopsdConfig, socket: &Path) -> Result<()> {
+    runtime()?.block_on(serve(config, socket))
 }
 
-fn run_bg(config: &KopsdConfig, handler: Arc<Handler>) -> Result<()> {
+fn run_bg(config: &KopsdConfig, socket: &Path) -> Result<()> {
     let daemon_cfg = config.daemon.clone().unwrap_or_default();
 
     let stdout = if let Some(ref path) = daemon_cfg.stdout {
@@ -129,7 +104,10 @@ fn run_bg(config: &KopsdConfig, handler: Arc<Handler>) -> Result<()> {
         None
     };
 
-    let mut daemon = Daemonize::new();
+    // config::load resolves the config file against it, also on reload.
+    let cwd = std::env::current_dir()
+        .context("failed to get the current directory")?;
+    let mut daemon = Daemonize::new().working_directory(cwd);
 
     if let Some(ref user) = daemon_cfg.user {
         daemon = daemon.user(user.as_str());
@@ -151,44 +129,162 @@ fn run_bg(config: &KopsdConfig, handler: Arc<Handler>) -> Result<()> {
         daemon = daemon.stderr(stderr);
     }
 
-    // Fork and detach
+    // Fork and detach; the runtime and its threads only exist after it.
     daemon.start().context("failed to daemonize kopsd process")?;
 
-    let rt = tokio::runtime::Builder::new_multi_thread()
+    let res = runtime()?.block_on(serve(config, socket));
+
+    if let Some(ref pid_file) = daemon_cfg.pid_file
+        && let Err(e) = std::fs::remove_file(pid_file)
+    {
+        error!("failed to remove pid file {pid_file}: {e:?}");
+    }
+
+    res
+}
+
+fn runtime() -> Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()
-        .context("failed to build tokio runtime")?;
+        .context("failed to build tokio runtime")
+}
+
+/// Build the daemon state and serve clients until shutdown.
+async fn serve(config: &KopsdConfig, socket: &Path) -> Result<()> {
+    proxy::init(config.kops.proxy_url.as_deref())?;
+
+    // none with clusters only added at runtime or discovered
+    let default_cluster = config
+        .kops
+        .default_cluster
+        .clone()
+        .or_else(|| config.cluster.first().map(|c| c.name.clone()));
+
+    let session_store = SessionStore::new(
+        config.kops.state_dir.clone(),
+        config.kops.keyring.unwrap_or(true),
+    );
+    let session_store = if config.kops.persist_sessions.unwrap_or(true) {
+        Some(session_store)
+    } else {
+        // no session of a run that persisted them is left behind
+        if let Err(e) = session_store.clear() {
+            error!("failed to remove the stored AWS sessions: {e:#}");
+        }
+        None
+    };
+    let state = Arc::new(DaemonState::new(
+        default_cluster,
+        config.cluster.clone(),
+        session_store,
+    ));
+    state.replace_profiles(config.profile.clone());
+    // before the clusters start, those of restored profiles included
+    state.restore_sessions();
+
+    // In the background, so that clients are served meanwhile.
+    let starting = state.clone();
+    tokio::spawn(async move { reload::start(&starting).await });
+    tokio::spawn(expiry::monitor(state.clone()));
+    if let Some(notify) = config.notify.clone() {
+        let clusters = state.clone();
+        let running = move || {
+            clusters.clusters().iter().map(|c| c.name().to_string()).collect()
+        };
+        tokio::spawn(kops_notify::run(notify, state.bus.subscribe(), running));
+    }
+    let metrics =
+        config.daemon.as_ref().and_then(|d| d.metrics_listen.as_deref());
+    if let Some(addr) = metrics {
+        let listener = prometheus::bind(addr).await?;
+        tokio::spawn(prometheus::serve(listener, state.clone()));
+    }
 
-    rt.block_on(async move { _run(config, handler).await })
+    let handler = Arc::new(Handler::new(state.clone()));
+    if let Some(replication) = &config.replication {
+        replication::start(replication, &handler).await?;
+    }
+
+    _run(config, handler, socket).await
 }
 
-async fn _run(_config: &KopsdConfig, handler: Arc<Handler>) -> Result<()> {
+async fn _run(
+    config: &KopsdConfig,
+    handler: Arc<Handler>,
+    socket: &Path,
+) -> Result<()> {
     info!("starting kopsd");
 
+    let heartbeat_timeout = config.kops.heartbeat_timeout();
+    let access = Arc::new(AccessPolicy::from_config(config.daemon.as_ref())?);
+    let audit_log = AuditLog::open(config.daemon.as_ref())?;
+
+    // e.g. the kopsd directory under XDG_RUNTIME_DIR
+    if let Some(dir) = socket.parent() {
+        create_dir_all(dir).await.with_context(|| {
+            format!("failed to create socket directory {}", dir.display())
+        })?;
+    }
+
     // try to remove a stale socket if it exists
-    let _ = remove_file(SOCKET_PATH).await;
+    let _ = remove_file(socket).await;
 
-    let listener = UnixListener::bind(SOCKET_PATH).with_context(|| {
-        format!("failed to create socket path {SOCKET_PATH}")
+    let listener = UnixListener::bind(socket).with_context(|| {
+        format!("failed to create socket path {}", socket.display())
     })?;
-    info!("listening on unix socket {}", SOCKET_PATH);
+    info!("listening on unix socket {}", socket.display());
 
     if let Err(e) = std::fs::set_permissions(
-        SOCKET_PATH,
+        socket,
         std::fs::Permissions::from_mode(0o660),
     ) {
         error!("failed to set socket permissions: {e:?}");
     }
 
+    let mut hangup = signal::unix::signal(SignalKind::hangup())
+        .context("failed to listen for SIGHUP")?;
+    let mut terminate = signal::unix::signal(SignalKind::terminate())
+        .context("failed to listen for SIGTERM")?;
+
     loop {
         tokio::select! {
             res = listener.accept() => {
                 match res {
                     Ok((stream, _addr)) => {
+                        let cred = match stream.peer_cred() {
+                            Ok(cred) => cred,
+                            Err(e) => {
+                                error!("failed to get peer credentials: {e}");
+                                continue;
+                            }
+                        };
+                        let audit = audit_log.client(cred.uid());
+                        if !access.allows(&cred) {
+                            warn!(
+                                uid = cred.uid(),
+                                gid = cred.gid(),
+                                pid = ?cred.pid(),
+                                "refused client connection"
+                            );
+                            tokio::spawn(refuse_client(stream, cred, audit));
+                            continue;
+                        }
+
                         let handler = handler.clone();
+                        let (connection, meter) =
+                            handler.state().track_connection(&cred);
+                        let stream = Metered::new(stream, meter);
                         debug!("new client connection");
                         tokio::spawn(async move {
-                            if let Err(e) = handle_client(stream, handler).await {
+                            let _connection = connection;
+                            let res = handle_client(
+                                stream,
+                                handler,
+                                heartbeat_timeout,
+                                audit,
+                            );
+                            if let Err(e) = res.await {
                                 error!("client handler error: {e:?}");
                             }
                         });
@@ -199,23 +295,54 @@ async fn _run(_config: &KopsdConfig, handler: Arc<Handler>) -> Result<()> {
                 }
             }
 
+            // reload the config in the background, keeping clients
+            _ = hangup.recv() => {
+                info!("SIGHUP received, reloading config");
+                let state = handler.state().clone();
+                tokio::spawn(async move {
+                    if let Err(e) = reload::reload(&state).await {
+                        error!("config reload failed: {e:#}");
+                    }
+                });
+            }
+
             // handle ctrl+c sigint
             _ = signal::ctrl_c() => {
                 warn!("CTRL+C received, shutting down gracefully...");
                 break;
             }
+
+            // how the daemon is usually stopped
+            _ = terminate.recv() => {
+                warn!("SIGTERM received, shutting down gracefully...");
+                break;
+            }
+
+            // Request::Shutdown
+            _ = handler.state().shutdown.cancelled() => break,
         }
     }
 
     // Dropping the listener closes the socket
     drop(listener);
 
-    if let Err(e) = remove_file(SOCKET_PATH).await {
+    // Let replies in flight, such as the one to Request::Shutdown, reach
+    // their clients.
+    let drained = timeout(SHUTDOWN_GRACE, async {
+        while handler.state().connections.load(Ordering::Relaxed) > 0 {
+            sleep(Duration::from_millis(50)).await;
+        }
+    });
+    if drained.await.is_err() {
+        warn!("closing client connections still open");
+    }
+
+    if let Err(e) = remove_file(socket).await {
         if e.kind() != std::io::ErrorKind::NotFound {
             error!("failed to remove socket file on shutdown: {e:?}");
         }
     } else {
-        info!("removed socket file {}", SOCKET_PATH);
+        info!("removed socket file {}", socket.display());
     }
 
     info!("kopsd server stopped");
@@ -223,15 +350,68 @@ async fn _run(_config: &KopsdConfig, handler: Arc<Handler>) -> Result<()> {
     Ok(())
 }
 
+/// Answer the first request of a client the access policy refuses with a
+/// permission error, then drop the connection.
+async fn refuse_client(mut stream: UnixStream, cred: UCred, audit: Audit) {
+    let Ok((_, codec)) = accept_handshake(&mut stream).await else {
+        return;
+    };
+
+    let error = KopsError::new(
+        ErrorCode::PermissionDenied,
+        format!("uid {} is not allowed to use kopsd", cred.uid()),
+    )
+    .with_detail("uid", cred.uid())
+    .with_detail("gid", cred.gid());
+
+    let req = match read_message_as::<_, Request>(&mut stream, codec).await {
+        Ok(Some(req)) => req,
+        Ok(None) | Err(_) => return,
+    };
+    audit.begin(&req).error(&error);
+
+    let res = match req {
+        Request::Stream { id, .. } => {
+            write_message_as(&mut stream, &Frame::Error { id, error }, codec)
+                .await
+        }
+        _ => {
+            let resp = Response::Error(error);
+            write_message_as(&mut stream, &resp, codec).await
+        }
+    };
+    if let Err(e) = res {
+        debug!("failed to refuse client: {e:?}");
+    }
+}
+
 /// Handle a single client connection
 ///
-/// Read `kops_protocol::Request` and write `kops_protocol::Response`.
+/// Negotiate the protocol version and encoding, then read
+/// `kops_protocol::Request` and write `kops_protocol::Response`.
+///
+/// Multiplexed connections and exec sessions silent for `heartbeat_timeout`
+/// are dropped.
 async fn handle_client(
-    mut stream: UnixStream,
+    mut stream: ClientStream,
     handler: Arc<Handler>,
+    heartbeat_timeout: Duration,
+    audit: Audit,
 ) -> Result<()> {
+    let codec = match accept_handshake(&mut stream).await {
+        Ok((version, codec)) => {
+            debug!(version, ?codec, "client handshake done");
+            codec
+        }
+        Err(e) => {
+            warn!("client handshake failed: {e}");
+            return Ok(());
+        }
+    };
+
+    let mut first = true;
     loop {
-        let req: Request = match read_message(&mut stream).await {
+        let req: Request = match read_message_as(&mut stream, codec).await {
             Ok(Some(msg)) => msg,
             Ok(None) => {
                 debug!("client closed connection");
@@ -245,13 +425,309 @@ async fn handle_client(
 
         debug!("received request: {:?}", req);
 
-        let resp = handler.handle(req).await;
+        if first && matches!(req, Request::Heartbeat) {
+            // a heartbeat says nothing of the connection yet, the request
+            // after it may still make it multiplexed
+            continue;
+        }
 
-        if let Err(e) = write_message(&mut stream, &resp).await {
-            error!("failed to write response: {e:?}");
-            break;
+        let entry = audit.begin(&req);
+        if let Some(error) = handler.state().role.refusal(&req) {
+            let resp = Response::Error(error);
+            entry.response(&resp);
+            first = false;
+            if let Err(e) = write_message_as(&mut stream, &resp, codec).await {
+                error!("failed to write response: {e:?}");
+                break;
+            }
+            continue;
+        }
+        let res = match req {
+            Request::Stream { id, request } if first => {
+                // the connection is multiplexed from now on, its requests
+                // are audited one by one
+                entry.discard();
+                serve_multiplexed(
+                    stream,
+                    handler,
+                    codec,
+                    heartbeat_timeout,
+                    audit,
+                    id,
+                    *request,
+                )
+                .await;
+                break;
+            }
+            Request::Stream { .. } => {
+                warn!("stream request on a plain connection");
+                entry.discard();
+                break;
+            }
+            req @ (Request::Logs(_)
+            | Request::WatchPods(_)
+            | Request::Subscribe { .. }) => {
+                relay(&handler, req, &mut stream, codec, entry).await
+            }
+            Request::Exec(_) | Request::PortForward(_)
+                if codec.encoding != Encoding::Bincode =>
+            {
+                // sessions exchange raw byte frames, only bincode fits
+                let message =
+                    "exec and port-forward need the bincode encoding";
+                let resp = Response::Error(KopsError::invalid(message));
+                entry.response(&resp);
+                write_message_as(&mut stream, &resp, codec)
+                    .await
+                    .map(|()| true)
+            }
+            Request::Exec(r) => {
+                // the connection is consumed by the exec session
+                let res =
+                    handler.exec(r, &mut stream, heartbeat_timeout).await;
+                match res {
+                    Ok(()) => entry.ok(),
+                    Err(e) => {
+                        error!("exec session error: {e:?}");
+                        entry.failed(format!("{e:#}"));
+                    }
+                }
+                break;
+            }
+            Request::PortForward(r) => {
+                // the connection is consumed by the port-forward session
+                let res = handler.port_forward(r, &mut stream).await;
+                match res {
+                    Ok(()) => entry.ok(),
+                    Err(e) => {
+                        error!("port-forward session error: {e:?}");
+                        entry.failed(format!("{e:#}"));
+                    }
+                }
+                break;
+            }
+            req => {
+                let resp = handler.handle(req).await;
+                entry.response(&resp);
+                write_message_as(&mut stream, &resp, codec)
+                    .await
+                    .map(|()| true)
+            }
+        };
+
+        first = false;
+        match res {
+            Ok(true) => {}
+            Ok(false) => {
+                debug!("client cancelled stream");
+                break;
+            }
+            Err(e) => {
+                error!("failed to write response: {e:?}");
+                break;
+            }
         }
     }
 
     Ok(())
 }
+
+/// Serve a streamed request on a plain connection, relaying the
+/// responses to `stream` as they are produced.
+///
+/// Anything sent by the client meanwhile, or closing the connection,
+/// cancels the request. Returns whether the connection is still usable.
+async fn relay(
+    handler: &Arc<Handler>,
+    req: Request,
+    stream: &mut ClientStream,
+    codec: Codec,
+    entry: Entry,
+) -> Result<bool, WireError> {
+    let (tx, mut rx) = mpsc::channel(STREAM_BUFFER);
+    let cancel = CancellationToken::new();
+    {
+        let handler = handler.clone();
+        let cancel = cancel.clone();
+        tokio::spawn(async move { handler.stream(req, tx, cancel).await });
+    }
+
+    let (mut rd, mut wr) = tokio::io::split(stream);
+    let mut buf = [0u8; 1];
+    let mut failed = None;
+    loop {
+        let resp = tokio::select! {
+            _ = rd.read(&mut buf) => {
+                cancel.cancel();
+                return Ok(false);
+            }
+            resp = rx.recv() => resp,
+        };
+
+        match resp {
+            Some(resp) => {
+                if let Response::Error(error) = &resp {
+                    failed.get_or_insert_with(|| error.clone());
+                }
+                write_message_as(&mut wr, &resp, codec).await?;
+            }
+            None => {
+                match failed {
+                    Some(error) => entry.error(&error),
+                    None => entry.ok(),
+                }
+                return Ok(true);
+            }
+        }
+    }
+}
+
+/// Serve a multiplexed connection: every request is a `Request::Stream`,
+/// they run concurrently and their frames interleave on the socket.
+///
+/// `Request::Cancel` stops one of them; closing the connection, or
+/// staying silent for `heartbeat_timeout`, cancels the requests still
+/// running. A `Frame::Heartbeat` is sent every `HEARTBEAT_INTERVAL`.
+async fn serve_multiplexed(
+    stream: ClientStream,
+    handler: Arc<Handler>,
+    codec: Codec,
+    heartbeat_timeout: Duration,
+    audit: Audit,
+    id: u32,
+    req: Request,
+) {
+    let (mut rd, mut wr) = tokio::io::split(stream);
+
+    let (frames, mut rx) = mpsc::channel::<Frame>(STREAM_BUFFER);
+    let writer = tokio::spawn(async move {
+        while let Some(frame) = rx.recv().await {
+            if let Err(e) = write_message_as(&mut wr, &frame, codec).await {
+                debug!("failed to write frame: {e:?}");
+                break;
+            }
+        }
+    });
+
+    let heartbeat = {
+        let frames = frames.clone();
+        tokio::spawn(async move {
+            let mut ticks = interval(HEARTBEAT_INTERVAL);
+            loop {
+                ticks.tick().await;
+                if frames.send(Frame::Heartbeat).await.is_err() {
+                    break;
+                }
+            }
+        })
+    };
+
+    let mut running: HashMap<u32, (JoinHandle<()>, CancellationToken)> =
+        HashMap::new();
+    let mut next = Some(Request::Stream { id, request: Box::new(req) });
+    while let Some(req) = next.take() {
+        running.retain(|_, (task, _)| !task.is_finished());
+        match req {
+            Request::Stream { id, .. } if running.contains_key(&id) => {
+                let message = format!("request id {id} is already in use");
+                let error = KopsError::invalid(message);
+                let _ = frames.send(Frame::Error { id, error }).await;
+            }
+            Request::Stream { id, request } => {
+                let entry = audit.begin(&request);
+                if let Some(error) = handler.state().role.refusal(&request) {
+                    entry.error(&error);
+                    let _ = frames.send(Frame::Error { id, error }).await;
+                } else {
+                    let cancel = CancellationToken::new();
+                    let task = run_stream(
+                        handler.clone(),
+                        id,
+                        entry,
+                        *request,
+                        cancel.clone(),
+                        frames.clone(),
+                    );
+                    running.insert(id, (tokio::spawn(task), cancel));
+                }
+            }
+            Request::Cancel { request_id } => {
+                if let Some((_, cancel)) = running.get(&request_id) {
+                    debug!(request_id, "cancelling request");
+                    cancel.cancel();
+                }
+            }
+            Request::Heartbeat => {}
+            req => {
+                let request = audit::kind(&req);
+                warn!(request, "plain request on a multiplexed connection");
+                break;
+            }
+        }
+
+        let read = read_message_as(&mut rd, codec);
+        next = match timeout(heartbeat_timeout, read).await {
+            Ok(Ok(Some(req))) => Some(req),
+            Ok(Ok(None)) => {
+                debug!("client closed connection");
+                None
+            }
+            Ok(Err(e)) => {
+                error!("failed to read message: {e:?}");
+                None
+            }
+            Err(_) => {
+                warn!("client missed its heartbeats, closing connection");
+                None
+            }
+        };
+    }
+
+    for (task, _) in running.into_values() {
+        task.abort();
+    }
+    heartbeat.abort();
+    writer.abort();
+}
+
+/// Serve one request of a multiplexed connection, sending its responses
+/// as `Frame`s tagged with `id`.
+async fn run_stream(
+    handler: Arc<Handler>,
+    id: u32,
+    entry: Entry,
+    req: Request,
+    cancel: CancellationToken,
+    frames: mpsc::Sender<Frame>,
+) {
+    let (tx, mut rx) = mpsc::channel(STREAM_BUFFER);
+    let cancelled = cancel.clone();
+    let mut failed = None;
+    let failure = &mut failed;
+
+    // Dropping `rx` once done stops the handler at its next response.
+    let forward = async move {
+        frames.send(Frame::Start { id }).await.ok()?;
+        while let Some(resp) = rx.recv().await {
+            let frame = match resp {
+                Response::Error(error) => {
+                    *failure = Some(error.clone());
+                    frames.send(Frame::Error { id, error }).await.ok()?;
+                    return None;
+                }
+                response => Frame::Chunk { id, response: Box::new(response) },
+            };
+            frames.send(frame).await.ok()?;
+        }
+        frames.send(Frame::End { id }).await.ok()
+    };
+
+    tokio::join!(handler.stream(req, tx, cancel), forward);
+
+    match failed {
+        Some(error) => entry.error(&error),
+        // dropping the entry records the request as cancelled
+        None if cancelled.is_cancelled() => drop(entry),
+        None => entry.ok(),
+    }
+}
@@ -0,0 +1,322 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! AWS sessions kept across restarts, so that restarting the daemon does
+//! not send every profile through the SSO device flow again.
+//!
+//! Sessions are kept as JSON in the OS keyring (macOS Keychain, Secret
+//! Service) when there is one. Otherwise, e.g. when they do not fit in it,
+//! they are encrypted with age to `sessions.age` in the state directory,
+//! under a key generated on first use and kept in the keyring.
+//!
+//! Without a keyring, or when it is disabled, the key is kept next to the
+//! sessions in `sessions.key`. Both are readable by the daemon user only,
+//! which is then all that protects the sessions: the encryption merely
+//! keeps them from being read by accident, e.g. in a backup or a grep.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use age::{secrecy::ExposeSecret, x25519::Identity};
+use anyhow::{Context, Result, anyhow};
+use chrono::Utc;
+use tracing::warn;
+
+use crate::state::{AwsSession, ProfileName};
+
+const SESSIONS_FILE: &str = "sessions.age";
+const KEY_FILE: &str = "sessions.key";
+
+/// Service and user of the keyring entry holding the sessions.
+const KEYRING_SERVICE: &str = "kopsd";
+const KEYRING_USER: &str = "aws-sessions";
+const KEYRING_KEY_USER: &str = "aws-sessions-key";
+
+/// Keyring entry or encrypted file holding the AWS sessions of the daemon.
+pub struct SessionStore {
+    dir: PathBuf,
+
+    /// Whether the OS keyring is tried before the file.
+    keyring: bool,
+
+    /// Serializes writes, so that the last one holds the latest sessions.
+    writing: Mutex<()>,
+}
+
+impl SessionStore {
+    /// Store in the OS keyring when `keyring` is set and one is reachable,
+    /// else in `dir`, else `kopsd` under `XDG_STATE_HOME`, else
+    /// `~/.local/state/kopsd`, else `/var/lib/kopsd`.
+    pub fn new(dir: Option<PathBuf>, keyring: bool) -> Self {
+        let dir = dir.unwrap_or_else(default_dir);
+        Self { dir, keyring, writing: Mutex::new(()) }
+    }
+
+    /// Unexpired sessions of the store, none when there is no store yet.
+    pub fn load(&self) -> Result<Vec<(ProfileName, AwsSession)>> {
+        // the file is only left when the last save missed the keyring, so
+        // it is the newest copy
+        let plain = match self.load_file()? {
+            Some(plain) => plain,
+            None => match self.load_keyring() {
+                Some(plain) => plain,
+                None => return Ok(Vec::new()),
+            },
+        };
+
+        let sessions: BTreeMap<ProfileName, AwsSession> =
+            serde_json::from_slice(&plain)
+                .context("invalid stored sessions")?;
+
+        let now = Utc::now();
+        Ok(sessions.into_iter().filter(|(_, s)| s.expires_at > now).collect())
+    }
+
+    /// Replace the stored sessions with `sessions`.
+    pub fn save(
+        &self,
+        sessions: Vec<(ProfileName, AwsSession)>,
+    ) -> Result<()> {
+        let _writing =
+            self.writing.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let sessions: BTreeMap<_, _> = sessions.into_iter().collect();
+        let plain = serde_json::to_vec(&sessions)?;
+
+        if self.save_keyring(&plain) {
+            // no copy left behind on disk, e.g. from before the keyring
+            return match fs::remove_file(self.dir.join(SESSIONS_FILE)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                    Err(e).context("failed to remove the session file")
+                }
+                _ => Ok(()),
+            };
+        }
+        self.save_file(&plain)?;
+
+        // an older copy in the keyring would hide the file on restart
+        if let Some(entry) = self.keyring_entry(KEYRING_USER) {
+            match entry.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => {}
+                Err(e) => warn!("failed to clear the OS keyring: {e}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the stored sessions and their key, e.g. once sessions are no
+    /// longer persisted.
+    pub fn clear(&self) -> Result<()> {
+        let _writing =
+            self.writing.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for user in [KEYRING_USER, KEYRING_KEY_USER] {
+            let Some(entry) = self.keyring_entry(user) else {
+                break;
+            };
+            match entry.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => {}
+                Err(e) => warn!("failed to clear the OS keyring: {e}"),
+            }
+        }
+        for file in [SESSIONS_FILE, KEY_FILE] {
+            let path = self.dir.join(file);
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                    return Err(e).with_context(|| {
+                        format!("failed to remove {}", path.display())
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn keyring_entry(&self, user: &str) -> Option<keyring::Entry> {
+        if !self.keyring {
+            return None;
+        }
+        match keyring::Entry::new(KEYRING_SERVICE, user) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("OS keyring unavailable, using the session file: {e}");
+                None
+            }
+        }
+    }
+
+    /// Sessions of the keyring, `None` when it has none or is unavailable.
+    fn load_keyring(&self) -> Option<Vec<u8>> {
+        match self.keyring_entry(KEYRING_USER)?.get_secret() {
+            Ok(plain) => Some(plain),
+            Err(keyring::Error::NoEntry) => None,
+            Err(e) => {
+                warn!("failed to read the OS keyring: {e}");
+                None
+            }
+        }
+    }
+
+    /// Whether the sessions made it to the keyring.
+    fn save_keyring(&self, plain: &[u8]) -> bool {
+        let Some(entry) = self.keyring_entry(KEYRING_USER) else {
+            return false;
+        };
+        match entry.set_secret(plain) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("failed to write the OS keyring: {e}");
+                false
+            }
+        }
+    }
+
+    /// Decrypted content of the session file, `None` when there is none.
+    fn load_file(&self) -> Result<Option<Vec<u8>>> {
+        let path = self.dir.join(SESSIONS_FILE);
+        let encrypted = match fs::read(&path) {
+            Ok(encrypted) => encrypted,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("failed to read {}", path.display())
+                });
+            }
+        };
+
+        let identity = self.identity()?;
+        let plain = age::decrypt(&identity, &encrypted).map_err(|e| {
+            anyhow!("failed to decrypt {}: {e}", path.display())
+        })?;
+        Ok(Some(plain))
+    }
+
+    fn save_file(&self, plain: &[u8]) -> Result<()> {
+        let identity = self.identity()?;
+        let encrypted = age::encrypt(&identity.to_public(), plain)
+            .map_err(|e| anyhow!("failed to encrypt sessions: {e}"))?;
+
+        // written aside then renamed, never leaving a truncated store
+        let path = self.dir.join(SESSIONS_FILE);
+        let tmp = path.with_extension("age.tmp");
+        write_private(&tmp, &encrypted)?;
+        fs::rename(&tmp, &path)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Key of the store, generated on first use: from the keyring, else
+    /// from the key file, which an older store may have left even with a
+    /// keyring.
+    ///
+    /// No key is generated while the keyring cannot be read and a session
+    /// file exists: it would never decrypt again.
+    fn identity(&self) -> Result<Identity> {
+        let entry = self.keyring_entry(KEYRING_KEY_USER);
+        let mut unreadable = None;
+        if let Some(entry) = &entry {
+            match entry.get_password() {
+                Ok(key) => {
+                    return key.trim().parse().map_err(|e| {
+                        anyhow!("invalid key in the OS keyring: {e}")
+                    });
+                }
+                Err(keyring::Error::NoEntry) => {}
+                Err(e) => {
+                    warn!("failed to read the OS keyring: {e}");
+                    unreadable = Some(e);
+                }
+            }
+        }
+
+        let path = self.dir.join(KEY_FILE);
+        match fs::read_to_string(&path) {
+            Ok(key) => key.trim().parse().map_err(|e| {
+                anyhow!("invalid key in {}: {e}", path.display())
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                if let Some(e) = unreadable
+                    && self.dir.join(SESSIONS_FILE).exists()
+                {
+                    return Err(anyhow!(
+                        "failed to read the session key from the OS \
+                         keyring: {e}"
+                    ));
+                }
+
+                let identity = Identity::generate();
+                let key = identity.to_string();
+                let key = key.expose_secret();
+                match entry.map(|entry| entry.set_password(key)) {
+                    Some(Ok(())) => {}
+                    Some(Err(e)) => {
+                        warn!("failed to write the OS keyring: {e}");
+                        write_private(&path, key.as_bytes())?;
+                    }
+                    None => write_private(&path, key.as_bytes())?,
+                }
+                Ok(identity)
+            }
+            Err(e) => Err(e)
+                .with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+}
+
+fn default_dir() -> PathBuf {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .filter(|d| !d.is_empty())
+                .map(|home| Path::new(&home).join(".local/state"))
+        });
+    match state_home {
+        Some(dir) => dir.join("kopsd"),
+        None => PathBuf::from("/var/lib/kopsd"),
+    }
+}
+
+/// Write `data` to `path`, readable by the daemon user only, creating its
+/// directory as needed.
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("failed to create {}", path.display()))?;
+    file.write_all(data)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
@@ -14,15 +14,42 @@
 // OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 //
 
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+use std::time::Instant;
 
+use aws_credential_types::Credentials;
+use aws_credential_types::provider::{ProvideCredentials, future};
 use chrono::{DateTime, Utc};
-use k8s_openapi::api::core::v1::Pod;
-use kube::runtime::reflector::Store;
+use dashmap::DashMap;
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, StatefulSet};
+use k8s_openapi::api::autoscaling::v2::HorizontalPodAutoscaler;
+use k8s_openapi::api::batch::v1::{CronJob, Job};
+use k8s_openapi::api::core::v1::{
+    Namespace, Node, PersistentVolumeClaim, Pod, Service,
+};
+use k8s_openapi::api::discovery::v1::EndpointSlice;
+use k8s_openapi::api::networking::v1::Ingress;
+use kops_protocol::SsoRefresh;
+use kube::runtime::reflector::{ObjectRef, Store};
+use kube::{Api, Client, api::ListParams};
+use serde::{Deserialize, Serialize};
+use tokio::net::unix::UCred;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
 
-/// AWS session stored in daemon memory.
-#[derive(Clone)]
+use crate::bus::EventBus;
+use crate::config::{ClusterConfig, ProfileConfig};
+use crate::event_log::EventLog;
+use crate::replication::Role;
+use crate::restarts::RestartHistory;
+use crate::session_store::SessionStore;
+use crate::traffic::{ClientConnection, Meter, Traffic};
+
+/// AWS session stored in daemon memory, and on disk unless disabled.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AwsSession {
     pub account_id: String,
     pub role_name: String,
@@ -31,6 +58,51 @@ pub struct AwsSession {
     pub secret_access_key: String,
     pub session_token: String,
     pub expires_at: DateTime<Utc>,
+
+    /// Renews the session before it expires, see `expiry`.
+    #[serde(default)]
+    pub refresh: Option<SsoRefresh>,
+}
+
+impl AwsSession {
+    pub fn credentials(&self) -> Credentials {
+        // the account keys the cached EKS endpoints, see kops_aws_eks
+        Credentials::builder()
+            .access_key_id(self.access_key_id.clone())
+            .secret_access_key(self.secret_access_key.clone())
+            .session_token(self.session_token.clone())
+            .expiry(self.expires_at.into())
+            .account_id(self.account_id.as_str())
+            .provider_name("kops-sso-session")
+            .build()
+    }
+}
+
+/// AWS credentials of a running EKS cluster, replaced by those of each new
+/// login of its profile so the cluster keeps running across logins.
+#[derive(Clone, Debug)]
+pub struct SessionCredentials(Arc<RwLock<Credentials>>);
+
+impl SessionCredentials {
+    pub fn new(session: &AwsSession) -> Self {
+        Self(Arc::new(RwLock::new(session.credentials())))
+    }
+
+    pub fn update(&self, session: &AwsSession) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) =
+            session.credentials();
+    }
+}
+
+impl ProvideCredentials for SessionCredentials {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        let credentials =
+            self.0.read().unwrap_or_else(PoisonError::into_inner).clone();
+        future::ProvideCredentials::ready(Ok(credentials))
+    }
 }
 
 /// Logical name of the cluster (from config).
@@ -38,39 +110,444 @@ pub type ClusterName = String;
 pub type ProfileName = String;
 
 /// Global daemon state shared by handlers.
+///
+/// Shared maps are only reached through the methods below, which never
+/// hold an entry across an `.await` nor hand out a guard.
 pub struct DaemonState {
-    pub clusters: Mutex<HashMap<ClusterName, Arc<ClusterState>>>,
-    pub default_cluster: ClusterName,
+    clusters: DashMap<ClusterName, Arc<ClusterState>>,
+
+    /// Cluster of requests naming none; requests must name one without.
+    default_cluster: Option<ClusterName>,
+
+    /// Clusters of the config file, running or not; replaced on reload.
+    configured: Mutex<Vec<ClusterConfig>>,
+
+    /// Profiles of the config file whose clusters are discovered.
+    profiles: Mutex<Vec<ProfileConfig>>,
 
     /// AWS sessions keyed by logical profile name ("dev", "prod", ...).
-    pub aws_sessions: Mutex<HashMap<ProfileName, AwsSession>>,
+    aws_sessions: DashMap<ProfileName, AwsSession>,
+
+    /// Where sessions are persisted; `None` keeps them in memory only.
+    session_store: Option<SessionStore>,
+
+    /// Clusters stopped because the session of their profile expired,
+    /// with that profile; cleared when the cluster starts again.
+    expired: DashMap<ClusterName, ProfileName>,
+
+    /// Notifications for `Request::Subscribe` clients.
+    pub bus: EventBus,
+
+    pub started_at: Instant,
+
+    /// Client connections currently open.
+    pub connections: AtomicUsize,
+
+    /// The open client connections, by connection id.
+    pub clients: DashMap<u64, Arc<ClientConnection>>,
+
+    /// Traffic of each user since the daemon started, by uid.
+    pub user_traffic: DashMap<u32, Arc<Traffic>>,
+
+    next_connection_id: AtomicU64,
+
+    /// Cancelled to stop the daemon.
+    pub shutdown: CancellationToken,
+
+    /// Whether clients are served, see `replication`.
+    pub role: Role,
 }
 
 impl DaemonState {
-    pub fn default_cluster(&self) -> &str {
-        &self.default_cluster
+    pub fn new(
+        default_cluster: Option<ClusterName>,
+        configured: Vec<ClusterConfig>,
+        session_store: Option<SessionStore>,
+    ) -> Self {
+        Self {
+            clusters: DashMap::new(),
+            default_cluster,
+            configured: Mutex::new(configured),
+            profiles: Mutex::default(),
+            aws_sessions: DashMap::new(),
+            session_store,
+            expired: DashMap::new(),
+            bus: EventBus::default(),
+            started_at: Instant::now(),
+            connections: AtomicUsize::new(0),
+            clients: DashMap::new(),
+            user_traffic: DashMap::new(),
+            next_connection_id: AtomicU64::new(1),
+            shutdown: CancellationToken::new(),
+            role: Role::default(),
+        }
+    }
+
+    pub fn default_cluster(&self) -> Option<&str> {
+        self.default_cluster.as_deref()
+    }
+
+    /// Count a client connection of the peer `cred` until the returned
+    /// guard is dropped, and where its traffic goes.
+    pub fn track_connection(
+        self: &Arc<Self>,
+        cred: &UCred,
+    ) -> (ConnectionGuard, Meter) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+
+        let id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        let connection = Arc::new(ClientConnection::new(cred));
+        let meter = Meter {
+            connection: connection.traffic.clone(),
+            user: self.user_traffic.entry(cred.uid()).or_default().clone(),
+        };
+        self.clients.insert(id, connection);
+
+        (ConnectionGuard { state: self.clone(), id }, meter)
+    }
+
+    /// Running cluster `name`.
+    pub fn cluster(&self, name: &str) -> Option<Arc<ClusterState>> {
+        self.clusters.get(name).map(|c| c.value().clone())
+    }
+
+    /// Every running cluster, in no particular order.
+    pub fn clusters(&self) -> Vec<Arc<ClusterState>> {
+        self.clusters.iter().map(|c| c.value().clone()).collect()
+    }
+
+    /// Register a started cluster under its name; returns the cluster it
+    /// replaces, which the caller should stop.
+    pub fn insert_cluster(
+        &self,
+        cluster: Arc<ClusterState>,
+    ) -> Option<Arc<ClusterState>> {
+        self.expired.remove(cluster.name());
+        self.clusters.insert(cluster.name().to_string(), cluster)
+    }
+
+    /// Unregister cluster `name`.
+    pub fn remove_cluster(&self, name: &str) -> Option<Arc<ClusterState>> {
+        self.clusters.remove(name).map(|(_, c)| c)
+    }
+
+    /// Unregister cluster `name` if `pred` holds for it.
+    pub fn remove_cluster_if(
+        &self,
+        name: &str,
+        pred: impl FnOnce(&ClusterState) -> bool,
+    ) -> Option<Arc<ClusterState>> {
+        self.clusters.remove_if(name, |_, c| pred(c)).map(|(_, c)| c)
+    }
+
+    /// Unregister the clusters started with the session of `profile`.
+    pub fn remove_profile_clusters(
+        &self,
+        profile: &str,
+    ) -> Vec<Arc<ClusterState>> {
+        let names: Vec<ClusterName> = self
+            .clusters
+            .iter()
+            .filter(|c| c.profile() == Some(profile))
+            .map(|c| c.key().clone())
+            .collect();
+
+        names
+            .iter()
+            .filter_map(|name| {
+                self.remove_cluster_if(name, |c| c.profile() == Some(profile))
+            })
+            .collect()
+    }
+
+    /// Clusters of the config file.
+    pub fn configured(&self) -> Vec<ClusterConfig> {
+        self.configured_lock().clone()
+    }
+
+    /// Replace the clusters of the config file; returns the previous ones.
+    pub fn replace_configured(
+        &self,
+        configured: Vec<ClusterConfig>,
+    ) -> Vec<ClusterConfig> {
+        std::mem::replace(&mut *self.configured_lock(), configured)
+    }
+
+    // The list is only ever replaced whole, so a panic while the lock was
+    // held cannot have left it half-updated.
+    fn configured_lock(&self) -> MutexGuard<'_, Vec<ClusterConfig>> {
+        self.configured.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Discovery settings of `profile`, if the config file has any.
+    pub fn profile_config(&self, profile: &str) -> Option<ProfileConfig> {
+        self.profiles
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|p| p.name == profile)
+            .cloned()
+    }
+
+    /// Replace the profiles of the config file.
+    pub fn replace_profiles(&self, profiles: Vec<ProfileConfig>) {
+        *self.profiles.lock().unwrap_or_else(PoisonError::into_inner) =
+            profiles;
+    }
+
+    pub fn session(&self, profile: &str) -> Option<AwsSession> {
+        self.aws_sessions.get(profile).map(|s| s.value().clone())
+    }
+
+    /// Every stored session with its profile, in no particular order.
+    pub fn sessions(&self) -> Vec<(ProfileName, AwsSession)> {
+        self.aws_sessions
+            .iter()
+            .map(|s| (s.key().clone(), s.value().clone()))
+            .collect()
+    }
+
+    pub fn store_session(&self, profile: ProfileName, session: AwsSession) {
+        self.aws_sessions.insert(profile, session);
+        self.persist_sessions();
+    }
+
+    pub fn remove_session(&self, profile: &str) -> Option<AwsSession> {
+        let removed = self.aws_sessions.remove(profile).map(|(_, s)| s);
+        if removed.is_some() {
+            self.persist_sessions();
+        }
+        removed
+    }
+
+    /// Remove the session of `profile` if it expired by `now`; a session
+    /// stored by a new login in the meantime is kept.
+    pub fn remove_expired_session(
+        &self,
+        profile: &str,
+        now: DateTime<Utc>,
+    ) -> Option<AwsSession> {
+        let removed = self
+            .aws_sessions
+            .remove_if(profile, |_, s| s.expires_at <= now)
+            .map(|(_, s)| s);
+        if removed.is_some() {
+            self.persist_sessions();
+        }
+        removed
+    }
+
+    /// Load the unexpired sessions persisted by a previous run.
+    pub fn restore_sessions(&self) {
+        let Some(store) = &self.session_store else {
+            return;
+        };
+        match store.load() {
+            Ok(sessions) => {
+                for (profile, session) in sessions {
+                    info!("restored AWS session for profile '{profile}'");
+                    self.aws_sessions.insert(profile, session);
+                }
+            }
+            Err(e) => error!("failed to restore AWS sessions: {e:#}"),
+        }
+    }
+
+    /// Write the stored sessions to the session store; a failure only
+    /// costs a login after the next restart.
+    fn persist_sessions(&self) {
+        if let Some(store) = &self.session_store
+            && let Err(e) = store.save(self.sessions())
+        {
+            error!("failed to persist AWS sessions: {e:#}");
+        }
+    }
+
+    /// Record that cluster `name` was stopped because the session of
+    /// `profile` expired.
+    pub fn mark_expired(&self, name: &str, profile: &str) {
+        self.expired.insert(name.to_string(), profile.to_string());
+    }
+
+    /// Profile whose expired session stopped cluster `name`, if any.
+    pub fn expired_profile(&self, name: &str) -> Option<ProfileName> {
+        self.expired.get(name).map(|p| p.value().clone())
+    }
+}
+
+/// Keeps a client connection counted in `DaemonState::connections` and
+/// listed in `DaemonState::clients`.
+pub struct ConnectionGuard {
+    state: Arc<DaemonState>,
+    id: u64,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.state.clients.remove(&self.id);
+        self.state.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Failures in a row after which a reflector makes its cluster degraded.
+pub const DEGRADED_AFTER_FAILURES: u32 = 5;
+
+/// Sync state of one reflector, as last reported by its watcher.
+#[derive(Clone, Debug, Default)]
+pub struct ReflectorHealth {
+    /// The initial listing, or the last relist, completed.
+    pub synced: bool,
+
+    /// Last watcher error, cleared by the next event.
+    pub error: Option<String>,
+    pub last_event: Option<DateTime<Utc>>,
+
+    /// When the last listing completed.
+    pub synced_at: Option<DateTime<Utc>>,
+
+    /// Watcher failures since the last successful event.
+    pub failures: u32,
+
+    /// The reflector task has not finished (or been stopped) yet.
+    pub running: bool,
+}
+
+/// Health of the reflectors of a cluster, keyed by kind.
+#[derive(Default)]
+pub struct ClusterHealth {
+    reflectors: Mutex<BTreeMap<&'static str, ReflectorHealth>>,
+}
+
+impl ClusterHealth {
+    /// Apply `update` to the health of the `kind` reflector, registering
+    /// it on first use.
+    pub fn update(
+        &self,
+        kind: &'static str,
+        update: impl FnOnce(&mut ReflectorHealth),
+    ) {
+        if let Ok(mut reflectors) = self.reflectors.lock() {
+            update(reflectors.entry(kind).or_default());
+        }
     }
 
-    #[allow(dead_code)]
-    pub fn get_session(&self, name: &str) -> Option<AwsSession> {
-        let sessions = self.aws_sessions.lock().ok()?;
-        sessions.get(name).cloned()
+    /// Health of every reflector, sorted by kind.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, ReflectorHealth> {
+        self.reflectors.lock().map(|r| r.clone()).unwrap_or_default()
     }
 }
 
-/// Per-cluster in-memory state backed by a reflector Store.
+/// Reflector stores of a cluster, kept up-to-date by kube_worker.
+pub struct ClusterStores {
+    pub pods: Store<Pod>,
+
+    /// `pods` only keeps what listings need of each pod.
+    pub compact_pods: bool,
+
+    pub deployments: Store<Deployment>,
+    pub stateful_sets: Store<StatefulSet>,
+    pub daemon_sets: Store<DaemonSet>,
+    pub events: Arc<EventLog>,
+    pub nodes: Store<Node>,
+    pub namespaces: Store<Namespace>,
+    pub services: Store<Service>,
+    pub endpoint_slices: Store<EndpointSlice>,
+    pub ingresses: Store<Ingress>,
+    pub jobs: Store<Job>,
+    pub cron_jobs: Store<CronJob>,
+    pub hpas: Store<HorizontalPodAutoscaler>,
+    pub pvcs: Store<PersistentVolumeClaim>,
+
+    /// Stops the reflectors feeding these Stores once cancelled.
+    pub stop: CancellationToken,
+}
+
+/// Change seen by the pod reflector, broadcast to pod watchers.
+#[derive(Clone, Debug)]
+pub enum PodChange {
+    Applied(Pod),
+    Deleted(Pod),
+}
+
+/// Per-cluster in-memory state backed by reflector Stores.
 ///
-/// The Store is automatically kept up-to-date by the kube_worker
-/// background task (reflector + watcher).
+/// The Stores are automatically kept up-to-date by the kube_worker
+/// background tasks (reflector + watcher).
 pub struct ClusterState {
     name: ClusterName,
-    store: Store<Pod>,
+    profile: Option<ProfileName>,
+    client: Client,
+    stores: ClusterStores,
+    restarts: Arc<RestartHistory>,
+    pod_changes: broadcast::Sender<PodChange>,
+    health: Arc<ClusterHealth>,
+    started_at: DateTime<Utc>,
+
+    /// The session of `profile` is about to expire.
+    credentials_expiring: AtomicBool,
+
+    /// Credentials the client of an EKS cluster signs its tokens with.
+    credentials: Option<SessionCredentials>,
+
+    /// EKS cluster behind this cluster, if any.
+    eks: Option<EksCluster>,
+}
+
+/// EKS cluster reached with the session of a profile.
+#[derive(Clone, Debug)]
+pub struct EksCluster {
+    pub name: String,
+
+    /// Region of the cluster; the region of the session when `None`.
+    pub region: Option<String>,
+
+    /// FIPS and dual-stack endpoints for the EKS and STS calls.
+    pub use_fips: bool,
+    pub use_dual_stack: bool,
 }
 
 impl ClusterState {
-    /// Create a new ClusterState from a cluster name and a reflector Store.
-    pub fn new(name: ClusterName, store: Store<Pod>) -> Self {
-        Self { name, store }
+    /// Create a new ClusterState from a cluster name, the AWS profile its
+    /// client was built from (if any), the client used by its watchers,
+    /// its reflector Stores, the restart history and pod change channel
+    /// fed by the pod reflector, and the health its reflectors report.
+    pub fn new(
+        name: ClusterName,
+        profile: Option<ProfileName>,
+        client: Client,
+        stores: ClusterStores,
+        restarts: Arc<RestartHistory>,
+        pod_changes: broadcast::Sender<PodChange>,
+        health: Arc<ClusterHealth>,
+    ) -> Self {
+        Self {
+            name,
+            profile,
+            client,
+            stores,
+            restarts,
+            pod_changes,
+            health,
+            started_at: Utc::now(),
+            credentials_expiring: AtomicBool::new(false),
+            credentials: None,
+            eks: None,
+        }
+    }
+
+    pub fn with_eks(mut self, eks: Option<EksCluster>) -> Self {
+        self.eks = eks;
+        self
+    }
+
+    /// Sign the tokens of the client with `credentials`, kept up-to-date
+    /// by `update_credentials`.
+    pub fn with_credentials(
+        mut self,
+        credentials: Option<SessionCredentials>,
+    ) -> Self {
+        self.credentials = credentials;
+        self
     }
 
     /// Name of this cluster (as in config).
@@ -78,13 +555,228 @@ impl ClusterState {
         &self.name
     }
 
-    /// Access the underlying Store for this cluster.
+    /// AWS profile whose session the client uses, if any.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    pub fn eks(&self) -> Option<&EksCluster> {
+        self.eks.as_ref()
+    }
+
+    /// Stop the reflectors; the Stores keep their last content.
+    pub fn stop(&self) {
+        self.stores.stop.cancel();
+    }
+
+    /// When the cluster was started in the daemon.
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    /// Whether the session of the profile of this cluster is about to
+    /// expire, as last seen by the expiry monitor.
+    pub fn credentials_expiring(&self) -> bool {
+        self.credentials_expiring.load(Ordering::Relaxed)
+    }
+
+    pub fn set_credentials_expiring(&self, expiring: bool) {
+        self.credentials_expiring.store(expiring, Ordering::Relaxed);
+    }
+
+    /// Switch the client to the credentials of a new login of `profile`;
+    /// returns false if the client does not use AWS credentials.
+    pub fn update_credentials(&self, session: &AwsSession) -> bool {
+        let Some(credentials) = &self.credentials else {
+            return false;
+        };
+
+        credentials.update(session);
+        self.set_credentials_expiring(false);
+        true
+    }
+
+    /// Whether every reflector is still running.
+    pub fn running(&self) -> bool {
+        !self.stores.stop.is_cancelled()
+            && self.health.snapshot().values().all(|h| h.running)
+    }
+
+    /// Whether a reflector failed `DEGRADED_AFTER_FAILURES` times in a
+    /// row; the Stores may be stale.
+    pub fn degraded(&self) -> bool {
+        self.health
+            .snapshot()
+            .values()
+            .any(|h| h.failures >= DEGRADED_AFTER_FAILURES)
+    }
+
+    /// When every Store last held a complete listing, i.e. the latest
+    /// listing completion; `None` until every reflector has listed once.
+    pub fn last_sync(&self) -> Option<DateTime<Utc>> {
+        self.health
+            .snapshot()
+            .values()
+            .map(|h| h.synced_at)
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max()
+    }
+
+    /// Pod `namespace/name` with its whole spec: from the Store, or from
+    /// the API server when the Store only keeps compact pods.
+    pub async fn full_pod(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<Arc<Pod>>, kube::Error> {
+        if !self.stores.compact_pods {
+            let obj = ObjectRef::new(name).within(namespace);
+            return Ok(self.stores.pods.get(&obj));
+        }
+
+        let api: Api<Pod> = Api::namespaced(self.client(), namespace);
+        Ok(api.get_opt(name).await?.map(Arc::new))
+    }
+
+    /// Pods of `namespace`, or of every namespace, with their whole spec:
+    /// from the Store, or listed from the API server when the Store only
+    /// keeps compact pods.
+    pub async fn full_pods(
+        &self,
+        namespace: Option<&str>,
+    ) -> Result<Vec<Arc<Pod>>, kube::Error> {
+        if !self.stores.compact_pods {
+            let mut pods = self.stores.pods.state();
+            if let Some(ns) = namespace {
+                pods.retain(|p| p.metadata.namespace.as_deref() == Some(ns));
+            }
+            return Ok(pods);
+        }
+
+        let api: Api<Pod> = match namespace {
+            Some(ns) => Api::namespaced(self.client(), ns),
+            None => Api::all(self.client()),
+        };
+        let pods = api.list(&ListParams::default()).await?;
+        Ok(pods.items.into_iter().map(Arc::new).collect())
+    }
+
+    /// Kubernetes client for requests that are not served from the store
+    /// (logs, exec, writes...).
+    pub fn client(&self) -> Client {
+        self.client.clone()
+    }
+
+    /// Access the underlying Pod Store for this cluster.
     ///
     /// You can call:
     ///   - `store.state()` para snapshot
     ///   - `store.get(ObjectRef)` para um Pod específico
     ///   - `store.len()`, etc.
     pub fn store(&self) -> &Store<Pod> {
-        &self.store
+        &self.stores.pods
+    }
+
+    /// Deployment Store for this cluster.
+    pub fn deployments(&self) -> &Store<Deployment> {
+        &self.stores.deployments
+    }
+
+    /// StatefulSet Store for this cluster.
+    pub fn stateful_sets(&self) -> &Store<StatefulSet> {
+        &self.stores.stateful_sets
+    }
+
+    /// DaemonSet Store for this cluster.
+    pub fn daemon_sets(&self) -> &Store<DaemonSet> {
+        &self.stores.daemon_sets
+    }
+
+    /// Events of this cluster, coalesced by occurrence.
+    pub fn events(&self) -> &EventLog {
+        &self.stores.events
+    }
+
+    /// Node Store for this cluster.
+    pub fn nodes(&self) -> &Store<Node> {
+        &self.stores.nodes
+    }
+
+    /// Namespace Store for this cluster.
+    pub fn namespaces(&self) -> &Store<Namespace> {
+        &self.stores.namespaces
+    }
+
+    /// Service Store for this cluster.
+    pub fn services(&self) -> &Store<Service> {
+        &self.stores.services
+    }
+
+    /// EndpointSlice Store for this cluster.
+    pub fn endpoint_slices(&self) -> &Store<EndpointSlice> {
+        &self.stores.endpoint_slices
+    }
+
+    /// Ingress Store for this cluster.
+    pub fn ingresses(&self) -> &Store<Ingress> {
+        &self.stores.ingresses
+    }
+
+    /// Job Store for this cluster.
+    pub fn jobs(&self) -> &Store<Job> {
+        &self.stores.jobs
+    }
+
+    /// CronJob Store for this cluster.
+    pub fn cron_jobs(&self) -> &Store<CronJob> {
+        &self.stores.cron_jobs
+    }
+
+    /// HorizontalPodAutoscaler Store for this cluster.
+    pub fn hpas(&self) -> &Store<HorizontalPodAutoscaler> {
+        &self.stores.hpas
+    }
+
+    /// PersistentVolumeClaim Store for this cluster.
+    pub fn pvcs(&self) -> &Store<PersistentVolumeClaim> {
+        &self.stores.pvcs
+    }
+
+    /// Container restarts observed since the daemon started.
+    pub fn restarts(&self) -> &RestartHistory {
+        &self.restarts
+    }
+
+    /// Receive pod changes from now on.
+    pub fn subscribe_pods(&self) -> broadcast::Receiver<PodChange> {
+        self.pod_changes.subscribe()
+    }
+
+    /// Sync state of the reflectors.
+    pub fn health(&self) -> &ClusterHealth {
+        &self.health
+    }
+
+    /// Objects held by each Store, keyed by the kind its reflector
+    /// reports health under.
+    pub fn store_sizes(&self) -> HashMap<&'static str, usize> {
+        let s = &self.stores;
+        HashMap::from([
+            ("pod", s.pods.state().len()),
+            ("deployment", s.deployments.state().len()),
+            ("statefulset", s.stateful_sets.state().len()),
+            ("daemonset", s.daemon_sets.state().len()),
+            ("event", s.events.len()),
+            ("node", s.nodes.state().len()),
+            ("namespace", s.namespaces.state().len()),
+            ("service", s.services.state().len()),
+            ("endpointslice", s.endpoint_slices.state().len()),
+            ("ingress", s.ingresses.state().len()),
+            ("job", s.jobs.state().len()),
+            ("cronjob", s.cron_jobs.state().len()),
+            ("hpa", s.hpas.state().len()),
+            ("pvc", s.pvcs.state().len()),
+        ])
     }
 }
@@ -0,0 +1,157 @@
+//
+// Copyright (c) 2025 murilo ijanc' <murilo@ijanc.org>
+//
+// Permission to use, copy, modify, and distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+//
+
+//! Bytes exchanged with each client, so that the operators of a shared
+//! daemon can tell which user or automation loads it.
+//!
+//! Every connection is wrapped in a `Metered` stream counting into the
+//! `Traffic` of the connection and into the one of its user.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use kops_protocol::ClientTraffic;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::unix::UCred;
+
+/// Counters of one connection, or of every connection of a user.
+#[derive(Debug, Default)]
+pub struct Traffic {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+
+    /// Writes that waited for the client to read: its socket buffer was
+    /// full.
+    stalled_writes: AtomicU64,
+}
+
+impl Traffic {
+    pub fn snapshot(&self) -> ClientTraffic {
+        ClientTraffic {
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            stalled_writes: self.stalled_writes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// An open client connection.
+#[derive(Debug)]
+pub struct ClientConnection {
+    pub uid: u32,
+    pub pid: Option<i32>,
+    pub since: Instant,
+    pub traffic: Arc<Traffic>,
+}
+
+impl ClientConnection {
+    pub fn new(cred: &UCred) -> Self {
+        Self {
+            uid: cred.uid(),
+            pid: cred.pid(),
+            since: Instant::now(),
+            traffic: Arc::default(),
+        }
+    }
+}
+
+/// Where the traffic of one connection is counted.
+#[derive(Clone, Debug)]
+pub struct Meter {
+    pub connection: Arc<Traffic>,
+    pub user: Arc<Traffic>,
+}
+
+impl Meter {
+    fn add(&self, counter: fn(&Traffic) -> &AtomicU64, n: u64) {
+        counter(&self.connection).fetch_add(n, Ordering::Relaxed);
+        counter(&self.user).fetch_add(n, Ordering::Relaxed);
+    }
+}
+
+/// A client stream counting what goes through it.
+pub struct Metered<S> {
+    inner: S,
+    meter: Meter,
+
+    /// The last write is waiting on the client.
+    stalled: bool,
+}
+
+impl<S> Metered<S> {
+    pub fn new(inner: S, meter: Meter) -> Self {
+        Self { inner, meter, stalled: false }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Metered<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let res = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = res {
+            let n = (buf.filled().len() - before) as u64;
+            self.meter.add(|t| &t.bytes_in, n);
+        }
+        res
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Metered<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let res = Pin::new(&mut self.inner).poll_write(cx, buf);
+        match &res {
+            // once per stall, however often the write is polled
+            Poll::Pending if !self.stalled => {
+                self.stalled = true;
+                self.meter.add(|t| &t.stalled_writes, 1);
+            }
+            Poll::Pending => {}
+            Poll::Ready(res) => {
+                self.stalled = false;
+                if let Ok(n) = res {
+                    self.meter.add(|t| &t.bytes_out, *n as u64);
+                }
+            }
+        }
+        res
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}